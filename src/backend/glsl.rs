@@ -0,0 +1,412 @@
+use super::*;
+
+const KEYWORDS: &[&str] = &["attribute", "const", "uniform", "varying", "buffer", "shared",
+                            "coherent", "volatile", "restrict", "readonly", "writeonly",
+                            "atomic_uint", "layout", "centroid", "flat", "smooth",
+                            "noperspective", "patch", "sample", "break", "continue", "do",
+                            "for", "while", "switch", "case", "default", "if", "else",
+                            "subroutine", "in", "out", "inout", "float", "double", "int",
+                            "void", "bool", "true", "false", "invariant", "precise",
+                            "discard", "return", "mat2", "mat3", "mat4", "dmat2", "dmat3",
+                            "dmat4", "vec2", "vec3", "vec4", "ivec2", "ivec3", "ivec4",
+                            "bvec2", "bvec3", "bvec4", "dvec2", "dvec3", "dvec4", "uint",
+                            "uvec2", "uvec3", "uvec4", "lowp", "mediump", "highp",
+                            "precision", "sampler2D", "image2D", "struct", "common",
+                            "partition", "active", "asm", "class", "union", "enum",
+                            "typedef", "template", "this", "resource", "goto", "inline",
+                            "noinline", "public", "static", "extern", "external",
+                            "interface", "long", "short", "half", "fixed", "unsigned",
+                            "superp", "input", "output", "filter", "sizeof", "cast",
+                            "namespace", "using", "main", "sin", "cos", "floor", "trunc",
+                            "mod", "not", "equal", "notEqual", "lessThan", "greaterThan",
+                            "lessThanEqual", "greaterThanEqual", "floatBitsToInt",
+                            "floatBitsToUint", "intBitsToFloat", "uintBitsToFloat"];
+
+/// The syntax of GLSL 4.50
+struct Glsl;
+
+impl Dialect for Glsl {
+    fn is_reserved(&self, name: &str) -> bool {
+        name.starts_with("gl_") || name.contains("__") || KEYWORDS.contains(&name)
+    }
+
+    fn entry_point_name(&self, _: &str) -> String {
+        "main".into()
+    }
+
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String> {
+        let name = match *ty {
+            Type::Void => "void".into(),
+            Type::Bool => "bool".into(),
+            Type::Int(64, false) => "uint64_t".into(),
+            Type::Int(64, true) => "int64_t".into(),
+            Type::Int(32, false) => "uint".into(),
+            Type::Int(32, true) => "int".into(),
+            Type::Float(32) => "float".into(),
+            Type::Float(64) => "double".into(),
+            Type::Vector(ref component, count) => {
+                let prefix = match **component {
+                    Type::Bool => "b",
+                    Type::Int(64, false) => "u64",
+                    Type::Int(64, true) => "i64",
+                    Type::Int(32, false) => "u",
+                    Type::Int(32, true) => "i",
+                    Type::Float(32) => "",
+                    Type::Float(64) => "d",
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                };
+                format!("{}vec{}", prefix, count)
+            }
+            Type::Matrix(ref column, columns) => {
+                let (prefix, rows) = match **column {
+                    Type::Vector(ref component, rows) if **component == Type::Float(32) => {
+                        ("", rows)
+                    }
+                    Type::Vector(ref component, rows) if **component == Type::Float(64) => {
+                        ("d", rows)
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                };
+                if rows == columns {
+                    format!("{}mat{}", prefix, columns)
+                } else {
+                    format!("{}mat{}x{}", prefix, columns, rows)
+                }
+            }
+            Type::Array(ref element, length) => {
+                format!("{}[{}]", try!(self.type_name(program, element)), length)
+            }
+            Type::Struct(id) => program.name(id),
+            _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+        };
+        Ok(name)
+    }
+
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(match *ty {
+                Type::Pointer(_, ref pointed) => {
+                    format!("inout {}", try!(self.declaration(program, pointed, name)))
+                }
+                _ => try!(self.declaration(program, ty, name)),
+            });
+        }
+        Ok(format!("{} {}({})",
+                   try!(self.type_name(program, return_type)),
+                   name,
+                   params.join(", ")))
+    }
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String {
+        match (width, signed) {
+            (64, false) => format!("{}ul", value),
+            (64, true) if value as i64 == i64::MIN => "(-9223372036854775807l - 1l)".into(),
+            (64, true) => format!("{}l", value as i64),
+            (_, false) => format!("{}u", value as u32),
+            (_, true) if value as i32 == i32::MIN => "(-2147483647 - 1)".into(),
+            (_, true) => format!("{}", value as i32),
+        }
+    }
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String> {
+        Ok(match (format_float(bits, width), width) {
+            (Some(value), 64) => format!("{}lf", value),
+            (Some(value), _) => value,
+            (None, 64) => format!("uint64BitsToDouble({}ul)", bits),
+            (None, _) => format!("uintBitsToFloat(0x{:08x}u)", bits as u32),
+        })
+    }
+
+    fn bitcast(&self, program: &Program, from: &Type, to: &Type, expr: &str)
+               -> BackendResult<String> {
+        let function = match (from.scalar(), to.scalar()) {
+            (&Type::Float(32), &Type::Int(32, true)) => "floatBitsToInt",
+            (&Type::Float(32), &Type::Int(32, false)) => "floatBitsToUint",
+            (&Type::Int(32, true), &Type::Float(32)) => "intBitsToFloat",
+            (&Type::Int(32, false), &Type::Float(32)) => "uintBitsToFloat",
+            (&Type::Float(64), &Type::Int(64, true)) => "doubleBitsToInt64",
+            (&Type::Float(64), &Type::Int(64, false)) => "doubleBitsToUint64",
+            (&Type::Int(64, true), &Type::Float(64)) => "int64BitsToDouble",
+            (&Type::Int(64, false), &Type::Float(64)) => "uint64BitsToDouble",
+            (&Type::Int(from_width, _), &Type::Int(to_width, _)) if from_width == to_width => {
+                return self.cast(program, to, expr);
+            }
+            _ if from == to => return Ok(expr.into()),
+            _ => return Err(BackendError::UnrepresentableType(to.clone())),
+        };
+        Ok(format!("{}({})", function, expr))
+    }
+
+    fn compare(&self, operator: &str, function: &str, a: &str, b: &str, vector: bool) -> String {
+        if vector {
+            format!("{}({}, {})", function, a, b)
+        } else {
+            format!("{} {} {}", a, operator, b)
+        }
+    }
+
+    fn not(&self, expr: &str, vector: bool) -> String {
+        if vector {
+            format!("not({})", expr)
+        } else {
+            format!("!({})", expr)
+        }
+    }
+
+    fn ordered_not_equal(&self, a: &str, b: &str, vector: bool) -> String {
+        if vector {
+            format!("not({})", self.unordered_equal(a, b, vector))
+        } else {
+            format!("!isnan({}) && !isnan({}) && {} != {}", a, b, a, b)
+        }
+    }
+
+    fn unordered_equal(&self, a: &str, b: &str, vector: bool) -> String {
+        if vector {
+            // Boolean vectors have no logical operators, so each or is a mix
+            // that selects true where the selector is true
+            format!("mix(mix(equal({}, {}), isnan({}), isnan({})), isnan({}), isnan({}))",
+                    a,
+                    b,
+                    b,
+                    b,
+                    a,
+                    a)
+        } else {
+            format!("isnan({}) || isnan({}) || {} == {}", a, b, a, b)
+        }
+    }
+
+    // The result of % is undefined in GLSL if either operand is negative
+    fn srem(&self, a: &str, b: &str) -> String {
+        format!("{} - {} * ({} / {})", a, b, a, b)
+    }
+
+    fn frem(&self, a: &str, b: &str) -> String {
+        format!("{} - {} * trunc({} / {})", a, b, a, b)
+    }
+
+    fn fmod(&self, a: &str, b: &str) -> String {
+        format!("mod({}, {})", a, b)
+    }
+
+    fn builtin(&self, builtin: &BuiltIn) -> Option<String> {
+        let name = match *builtin {
+            BuiltIn::Position => "gl_Position",
+            BuiltIn::PointSize => "gl_PointSize",
+            BuiltIn::ClipDistance => "gl_ClipDistance",
+            BuiltIn::CullDistance => "gl_CullDistance",
+            BuiltIn::VertexId | BuiltIn::VertexIndex => "gl_VertexID",
+            BuiltIn::InstanceId | BuiltIn::InstanceIndex => "gl_InstanceID",
+            BuiltIn::PrimitiveId => "gl_PrimitiveID",
+            BuiltIn::InvocationId => "gl_InvocationID",
+            BuiltIn::Layer => "gl_Layer",
+            BuiltIn::ViewportIndex => "gl_ViewportIndex",
+            BuiltIn::TessLevelOuter => "gl_TessLevelOuter",
+            BuiltIn::TessLevelInner => "gl_TessLevelInner",
+            BuiltIn::TessCoord => "gl_TessCoord",
+            BuiltIn::PatchVerticies => "gl_PatchVerticesIn",
+            BuiltIn::FragCoord => "gl_FragCoord",
+            BuiltIn::PointCoord => "gl_PointCoord",
+            BuiltIn::FrontFacing => "gl_FrontFacing",
+            BuiltIn::SampleId => "gl_SampleID",
+            BuiltIn::SamplePosition => "gl_SamplePosition",
+            BuiltIn::SampleMask => "gl_SampleMask",
+            BuiltIn::FragDepth => "gl_FragDepth",
+            BuiltIn::HelperInvocation => "gl_HelperInvocation",
+            BuiltIn::NumWorkgroups => "gl_NumWorkGroups",
+            BuiltIn::WorkgroupSize => "gl_WorkGroupSize",
+            BuiltIn::WorkgroupId => "gl_WorkGroupID",
+            BuiltIn::LocalInvocationId => "gl_LocalInvocationID",
+            BuiltIn::GlobalInvocationId => "gl_GlobalInvocationID",
+            BuiltIn::LocalInvocationIndex => "gl_LocalInvocationIndex",
+            _ => return None,
+        };
+        Some(name.into())
+    }
+}
+
+/// Generates GLSL 4.50 source code for an entry point of a module
+///
+/// Storage buffers are declared as `buffer` blocks with the std430 layout
+/// and uniform buffers as `uniform` blocks with the std140 layout, with the
+/// Binding and Offset decorations of the module as layout qualifiers. The
+/// DescriptorSet decoration is not used, as OpenGL has a single set of
+/// bindings. Inputs and outputs are declared with their Location, and
+/// builtin variables are replaced by the matching `gl_` variable.
+///
+/// The entry point function is named `main`, and only the functions and
+/// global variables it uses are written. Specialization constants are
+/// written with their default values.
+pub fn generate_glsl(module: &LogicalModule, entry_point: &str) -> BackendResult<String> {
+    let dialect = Glsl;
+    let program = try!(Program::new(module, entry_point, &dialect));
+    let mut output = String::from("#version 450\n");
+    if module.capabilities.contains(&Capability::Int64) {
+        output.push_str("#extension GL_ARB_gpu_shader_int64 : require\n");
+    }
+    for mode in program.execution_modes() {
+        if let ExecutionMode::LocalSize(x, y, z) = *mode {
+            output.push_str(&format!("layout(local_size_x = {}, local_size_y = {}, \
+                                      local_size_z = {}) in;\n",
+                                     x,
+                                     y,
+                                     z));
+        }
+    }
+    output.push('\n');
+
+    for id in program.struct_ids() {
+        if program.has_decoration(id, Decoration::Block) ||
+           program.has_decoration(id, Decoration::BufferBlock) ||
+           program.is_builtin_block(id) {
+            continue;
+        }
+        output.push_str(&format!("struct {} {{\n", program.name(id)));
+        for (member, member_type) in program.struct_members(id).iter().enumerate() {
+            let ty = try!(program.get_type(member_type));
+            let name = program.member_name(id, member as u32);
+            output.push_str(&format!("    {};\n", try!(dialect.declaration(&program, ty, &name))));
+        }
+        output.push_str("};\n\n");
+    }
+
+    let mut declared = false;
+    for var in program.used_variables() {
+        declared |= try!(write_variable(&program, &dialect, var, &mut output));
+    }
+    if declared {
+        output.push('\n');
+    }
+
+    let functions = program.reachable_functions();
+    for (index, function) in functions.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str(&try!(FunctionWriter::write(&program, &dialect, function)));
+    }
+    Ok(output)
+}
+
+/// Declares a global variable, returning false for builtin variables that
+/// are not declared
+fn write_variable(program: &Program,
+                  dialect: &Glsl,
+                  var: &OpVariable,
+                  output: &mut String)
+                  -> BackendResult<bool> {
+    let id = var.result_id.0;
+    let (storage_class, ty) = match *try!(program.get_type(&var.result_type)) {
+        Type::Pointer(ref storage_class, ref pointed) => (storage_class.clone(), &**pointed),
+        _ => return Err(BackendError::InvalidId(OpId(id))),
+    };
+    let unsupported = || BackendError::UnsupportedStorageClass(OpId(id), storage_class.clone());
+    let name = program.name(id);
+    let binding = program.decoration_value(id, |d| match *d {
+        Decoration::Binding(binding) => Some(binding),
+        _ => None,
+    });
+    let location = program.decoration_value(id, |d| match *d {
+        Decoration::Location(location) => Some(location),
+        _ => None,
+    });
+
+    let declaration = match storage_class {
+        StorageClass::Uniform |
+        StorageClass::PushConstant => {
+            let struct_id = match *ty {
+                Type::Struct(struct_id) => struct_id,
+                _ => return Err(unsupported()),
+            };
+            let (layout, kind) = if program.has_decoration(struct_id, Decoration::BufferBlock) {
+                ("std430", "buffer")
+            } else if program.has_decoration(struct_id, Decoration::Block) {
+                ("std140", "uniform")
+            } else {
+                return Err(unsupported());
+            };
+            let layout = match binding {
+                Some(binding) => format!("{}, binding = {}", layout, binding),
+                None => layout.into(),
+            };
+            let mut block = format!("layout({}) {} {} {{\n", layout, kind, program.name(struct_id));
+            for (member, member_type) in program.struct_members(struct_id).iter().enumerate() {
+                let member = member as u32;
+                let mut qualifiers = Vec::new();
+                for decoration in program.member_decorations(struct_id, member) {
+                    match **decoration {
+                        Decoration::Offset(offset) => qualifiers.push(format!("offset = {}", offset)),
+                        Decoration::RowMajor => qualifiers.push("row_major".into()),
+                        _ => {}
+                    }
+                }
+                let declaration = try!(dialect.declaration(program,
+                                                           try!(program.get_type(member_type)),
+                                                           &program.member_name(struct_id,
+                                                                                member)));
+                if qualifiers.is_empty() {
+                    block.push_str(&format!("    {};\n", declaration));
+                } else {
+                    block.push_str(&format!("    layout({}) {};\n",
+                                            qualifiers.join(", "),
+                                            declaration));
+                }
+            }
+            block.push_str(&format!("}} {}", name));
+            block
+        }
+        StorageClass::Input |
+        StorageClass::Output => {
+            let is_builtin_block = match *ty {
+                Type::Struct(struct_id) => program.is_builtin_block(struct_id),
+                _ => false,
+            };
+            if program.builtin(id).is_some() || is_builtin_block {
+                return Ok(false);
+            }
+            let direction = match storage_class {
+                StorageClass::Input => "in",
+                _ => "out",
+            };
+            let mut qualifiers = String::new();
+            if let Some(location) = location {
+                qualifiers.push_str(&format!("layout(location = {}) ", location));
+            }
+            if program.has_decoration(id, Decoration::Flat) {
+                qualifiers.push_str("flat ");
+            }
+            if program.has_decoration(id, Decoration::NoPerspective) {
+                qualifiers.push_str("noperspective ");
+            }
+            format!("{}{} {}",
+                    qualifiers,
+                    direction,
+                    try!(dialect.declaration(program, ty, &name)))
+        }
+        StorageClass::Workgroup => {
+            format!("shared {}", try!(dialect.declaration(program, ty, &name)))
+        }
+        StorageClass::Private => {
+            let declaration = try!(dialect.declaration(program, ty, &name));
+            match var.initializer {
+                Some(ref initializer) => {
+                    format!("{} = {}",
+                            declaration,
+                            try!(program.constant_expr(dialect, initializer)))
+                }
+                None => declaration,
+            }
+        }
+        _ => return Err(unsupported()),
+    };
+    output.push_str(&declaration);
+    output.push_str(";\n");
+    Ok(true)
+}
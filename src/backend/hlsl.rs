@@ -0,0 +1,511 @@
+use super::*;
+
+const KEYWORDS: &[&str] = &["AppendStructuredBuffer", "asm", "asm_fragment", "BlendState",
+                            "bool", "break", "Buffer", "ByteAddressBuffer", "case", "cbuffer",
+                            "centroid", "class", "column_major", "compile", "compile_fragment",
+                            "CompileShader", "const", "continue", "ComputeShader",
+                            "ConsumeStructuredBuffer", "default", "DepthStencilState",
+                            "DepthStencilView", "discard", "do", "double", "DomainShader",
+                            "dword", "else", "export", "extern", "false", "float", "for",
+                            "fxgroup", "GeometryShader", "groupshared", "half", "Hullshader",
+                            "if", "in", "inline", "inout", "InputPatch", "int", "interface",
+                            "line", "lineadj", "linear", "LineStream", "matrix", "min16float",
+                            "min10float", "min16int", "min12int", "min16uint", "namespace",
+                            "nointerpolation", "noperspective", "NULL", "out", "OutputPatch",
+                            "packoffset", "pass", "pixelfragment", "PixelShader", "point",
+                            "PointStream", "precise", "RasterizerState", "RenderTargetView",
+                            "return", "register", "row_major", "RWBuffer",
+                            "RWByteAddressBuffer", "RWStructuredBuffer", "RWTexture1D",
+                            "RWTexture2D", "RWTexture3D", "sample", "sampler", "SamplerState",
+                            "SamplerComparisonState", "shared", "snorm", "stateblock",
+                            "stateblock_state", "static", "string", "struct", "switch",
+                            "StructuredBuffer", "tbuffer", "technique", "technique10",
+                            "technique11", "texture", "Texture1D", "Texture2D", "Texture3D",
+                            "TextureCube", "triangle", "triangleadj", "TriangleStream", "true",
+                            "typedef", "uniform", "unorm", "uint", "unsigned", "vector",
+                            "vertexfragment", "VertexShader", "void", "volatile", "while",
+                            "asfloat", "asint", "asuint", "fmod", "floor", "sin", "cos",
+                            "main"];
+
+/// The syntax of HLSL for shader model 5.1 and later
+struct Hlsl;
+
+/// Returns the system value semantic of a builtin variable
+fn semantic(builtin: &BuiltIn) -> Option<&'static str> {
+    let semantic = match *builtin {
+        BuiltIn::Position |
+        BuiltIn::FragCoord => "SV_Position",
+        BuiltIn::VertexId |
+        BuiltIn::VertexIndex => "SV_VertexID",
+        BuiltIn::InstanceId |
+        BuiltIn::InstanceIndex => "SV_InstanceID",
+        BuiltIn::PrimitiveId => "SV_PrimitiveID",
+        BuiltIn::FrontFacing => "SV_IsFrontFace",
+        BuiltIn::SampleId => "SV_SampleIndex",
+        BuiltIn::FragDepth => "SV_Depth",
+        BuiltIn::WorkgroupId => "SV_GroupID",
+        BuiltIn::LocalInvocationId => "SV_GroupThreadID",
+        BuiltIn::GlobalInvocationId => "SV_DispatchThreadID",
+        BuiltIn::LocalInvocationIndex => "SV_GroupIndex",
+        _ => return None,
+    };
+    Some(semantic)
+}
+
+/// Returns the element type of a storage block that can be declared as a
+/// structured buffer, which is a block with a single runtime array
+fn structured_buffer_element<'a>(program: &'a Program, struct_id: u32) -> Option<&'a Type> {
+    if !program.has_decoration(struct_id, Decoration::BufferBlock) {
+        return None;
+    }
+    match program.struct_members(struct_id) {
+        [member] => {
+            match program.get_type(member) {
+                Ok(Type::RuntimeArray(element)) => Some(element),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Dialect for Hlsl {
+    fn is_reserved(&self, name: &str) -> bool {
+        name.starts_with("gl_") || name.starts_with("SV_") || name.contains("__") ||
+        KEYWORDS.contains(&name)
+    }
+
+    /// The entry point function is called by a wrapper with the name of the
+    /// entry point, which copies the stage inputs and outputs
+    fn entry_point_name(&self, name: &str) -> String {
+        format!("{}_inner", sanitize(name))
+    }
+
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String> {
+        let name = match *ty {
+            Type::Void => "void".into(),
+            Type::Bool => "bool".into(),
+            Type::Int(64, false) => "uint64_t".into(),
+            Type::Int(64, true) => "int64_t".into(),
+            Type::Int(32, false) => "uint".into(),
+            Type::Int(32, true) => "int".into(),
+            Type::Float(16) => "half".into(),
+            Type::Float(32) => "float".into(),
+            Type::Float(64) => "double".into(),
+            Type::Vector(ref component, count) => {
+                match **component {
+                    Type::Vector(_, _) => {
+                        return Err(BackendError::UnrepresentableType(ty.clone()))
+                    }
+                    _ => format!("{}{}", try!(self.type_name(program, component)), count),
+                }
+            }
+            Type::Matrix(ref column, columns) => {
+                match **column {
+                    Type::Vector(ref component, rows) => {
+                        format!("{}{}x{}", try!(self.type_name(program, component)), columns, rows)
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                }
+            }
+            Type::Struct(id) => program.name(id),
+            _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+        };
+        Ok(name)
+    }
+
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(match *ty {
+                Type::Pointer(_, ref pointed) => {
+                    format!("inout {}", try!(self.declaration(program, pointed, name)))
+                }
+                _ => try!(self.declaration(program, ty, name)),
+            });
+        }
+        Ok(format!("{} {}({})",
+                   try!(self.type_name(program, return_type)),
+                   name,
+                   params.join(", ")))
+    }
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String {
+        match (width, signed) {
+            (64, false) => format!("{}ull", value),
+            (64, true) if value as i64 == i64::MIN => "(-9223372036854775807ll - 1ll)".into(),
+            (64, true) => format!("{}ll", value as i64),
+            (_, false) => format!("{}u", value as u32),
+            (_, true) if value as i32 == i32::MIN => "(-2147483647 - 1)".into(),
+            (_, true) => format!("{}", value as i32),
+        }
+    }
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String> {
+        match (format_float(bits, width), width) {
+            (Some(value), 64) => Ok(format!("{}L", value)),
+            (Some(value), _) => Ok(value),
+            (None, 64) => Err(BackendError::UnrepresentableType(Type::Float(64))),
+            (None, _) => Ok(format!("asfloat(0x{:08x}u)", bits as u32)),
+        }
+    }
+
+    /// Arrays and structs can only be built in initializers in HLSL
+    fn construct(&self, program: &Program, ty: &Type, args: &[String]) -> BackendResult<String> {
+        match *ty {
+            Type::Vector(_, _) |
+            Type::Matrix(_, _) => {
+                Ok(format!("{}({})", try!(self.type_name(program, ty)), args.join(", ")))
+            }
+            _ => Err(BackendError::UnrepresentableType(ty.clone())),
+        }
+    }
+
+    fn bitcast(&self, program: &Program, from: &Type, to: &Type, expr: &str)
+               -> BackendResult<String> {
+        let function = match (from.scalar(), to.scalar()) {
+            (&Type::Float(32), &Type::Int(32, true)) |
+            (&Type::Int(32, _), &Type::Int(32, true)) => "asint",
+            (&Type::Float(32), &Type::Int(32, false)) |
+            (&Type::Int(32, _), &Type::Int(32, false)) => "asuint",
+            (&Type::Int(32, _), &Type::Float(32)) => "asfloat",
+            (&Type::Int(64, _), &Type::Int(64, _)) => return self.cast(program, to, expr),
+            _ if from == to => return Ok(expr.into()),
+            _ => return Err(BackendError::UnrepresentableType(to.clone())),
+        };
+        Ok(format!("{}({})", function, expr))
+    }
+
+    fn frem(&self, a: &str, b: &str) -> String {
+        format!("fmod({}, {})", a, b)
+    }
+
+    fn builtin(&self, builtin: &BuiltIn) -> Option<String> {
+        let name = match *builtin {
+            BuiltIn::Position => "gl_Position",
+            BuiltIn::PointSize => "gl_PointSize",
+            BuiltIn::ClipDistance => "gl_ClipDistance",
+            BuiltIn::CullDistance => "gl_CullDistance",
+            BuiltIn::VertexId | BuiltIn::VertexIndex => "gl_VertexIndex",
+            BuiltIn::InstanceId | BuiltIn::InstanceIndex => "gl_InstanceIndex",
+            BuiltIn::PrimitiveId => "gl_PrimitiveID",
+            BuiltIn::FragCoord => "gl_FragCoord",
+            BuiltIn::FrontFacing => "gl_FrontFacing",
+            BuiltIn::SampleId => "gl_SampleID",
+            BuiltIn::FragDepth => "gl_FragDepth",
+            BuiltIn::WorkgroupId => "gl_WorkGroupID",
+            BuiltIn::LocalInvocationId => "gl_LocalInvocationID",
+            BuiltIn::GlobalInvocationId => "gl_GlobalInvocationID",
+            BuiltIn::LocalInvocationIndex => "gl_LocalInvocationIndex",
+            _ => return None,
+        };
+        Some(name.into())
+    }
+
+    /// Structured buffers are indexed directly, and the members of constant
+    /// buffers are global names
+    fn block_member(&self, program: &Program, variable: u32, struct_id: u32, member: u32)
+                    -> String {
+        if structured_buffer_element(program, struct_id).is_some() {
+            program.name(variable)
+        } else {
+            format!("{}_{}", program.name(variable), program.member_name(struct_id, member))
+        }
+    }
+
+    fn loop_attribute(&self, control: &LoopControl) -> Option<&'static str> {
+        if control.unroll {
+            Some("[unroll]")
+        } else if control.dont_unroll {
+            Some("[loop]")
+        } else {
+            None
+        }
+    }
+
+    fn selection_attribute(&self, control: &SelectionControl) -> Option<&'static str> {
+        if control.flatten {
+            Some("[flatten]")
+        } else if control.dont_flatten {
+            Some("[branch]")
+        } else {
+            None
+        }
+    }
+}
+
+/// The parameters of the entry point wrapper and the statements that copy
+/// them to and from the global variables used by the shader
+#[derive(Default)]
+struct Stage {
+    parameters: Vec<String>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl Stage {
+    /// Passes a global variable in or out of the entry point with a semantic
+    fn add(&mut self, declaration: &str, name: &str, semantic: &str, output: bool) {
+        if output {
+            self.parameters.push(format!("out {}_out : {}", declaration, semantic));
+            self.outputs.push(format!("{}_out = {};", name, name));
+        } else {
+            self.parameters.push(format!("{}_in : {}", declaration, semantic));
+            self.inputs.push(format!("{} = {}_in;", name, name));
+        }
+    }
+}
+
+/// Generates HLSL source code for shader model 5.1 for an entry point of a
+/// module
+///
+/// Storage blocks that hold a single runtime array are declared as
+/// `RWStructuredBuffer`, or `StructuredBuffer` if they are NonWritable, and
+/// uniform blocks as `cbuffer` with a `packoffset` for each member. Bindings
+/// become `register` slots, with the DescriptorSet as the register space.
+///
+/// Inputs, outputs and builtin variables are static globals. The entry point
+/// is a wrapper function that copies them from and to its parameters, which
+/// have the semantics of the builtins or `TEXCOORD` and `SV_Target` semantics
+/// from the Location. The LocalSize execution mode becomes the `numthreads`
+/// attribute of the wrapper. Loop and selection controls become the
+/// `[unroll]`, `[loop]`, `[flatten]` and `[branch]` attributes.
+pub fn generate_hlsl(module: &LogicalModule, entry_point: &str) -> BackendResult<String> {
+    let dialect = Hlsl;
+    let program = try!(Program::new(module, entry_point, &dialect));
+    let mut output = String::new();
+
+    for id in program.struct_ids() {
+        if program.has_decoration(id, Decoration::Block) ||
+           program.has_decoration(id, Decoration::BufferBlock) ||
+           program.is_builtin_block(id) {
+            continue;
+        }
+        output.push_str(&format!("struct {} {{\n", program.name(id)));
+        for (member, member_type) in program.struct_members(id).iter().enumerate() {
+            let ty = try!(program.get_type(member_type));
+            let name = program.member_name(id, member as u32);
+            output.push_str(&format!("    {};\n", try!(dialect.declaration(&program, ty, &name))));
+        }
+        output.push_str("};\n\n");
+    }
+
+    let mut stage = Stage::default();
+    let mut declared = false;
+    for var in program.used_variables() {
+        try!(write_variable(&program, &dialect, var, &mut stage, &mut output));
+        declared = true;
+    }
+    if declared {
+        output.push('\n');
+    }
+
+    for function in program.reachable_functions() {
+        output.push_str(&try!(FunctionWriter::write(&program, &dialect, function)));
+        output.push('\n');
+    }
+
+    for mode in program.execution_modes() {
+        if let ExecutionMode::LocalSize(x, y, z) = *mode {
+            output.push_str(&format!("[numthreads({}, {}, {})]\n", x, y, z));
+        }
+    }
+    output.push_str(&format!("void {}({}) {{\n",
+                             sanitize(&program.entry_point.name),
+                             stage.parameters.join(", ")));
+    for input in &stage.inputs {
+        output.push_str(&format!("    {}\n", input));
+    }
+    output.push_str(&format!("    {}();\n", dialect.entry_point_name(&program.entry_point.name)));
+    for stage_output in &stage.outputs {
+        output.push_str(&format!("    {}\n", stage_output));
+    }
+    output.push_str("}\n");
+    Ok(output)
+}
+
+/// Returns the register of a resource from its Binding and DescriptorSet
+fn register(program: &Program, id: u32, kind: char) -> String {
+    let binding = program.decoration_value(id, |d| match *d {
+        Decoration::Binding(binding) => Some(binding),
+        _ => None,
+    });
+    let set = program.decoration_value(id, |d| match *d {
+        Decoration::DescriptorSet(set) => Some(set),
+        _ => None,
+    });
+    match (binding, set) {
+        (Some(binding), Some(set)) if set != 0 => {
+            format!(" : register({}{}, space{})", kind, binding, set)
+        }
+        (Some(binding), _) => format!(" : register({}{})", kind, binding),
+        (None, _) => String::new(),
+    }
+}
+
+/// Declares a builtin variable as a static global, and passes it in or out
+/// of the entry point if it has a semantic
+fn write_builtin(dialect: &Hlsl,
+                 program: &Program,
+                 builtin: &BuiltIn,
+                 ty: &Type,
+                 output: bool,
+                 stage: &mut Stage)
+                 -> BackendResult<String> {
+    let name = match dialect.builtin(builtin) {
+        Some(name) => name,
+        None => return Err(BackendError::UnsupportedBuiltIn(builtin.clone())),
+    };
+    let declaration = try!(dialect.declaration(program, ty, &name));
+    match semantic(builtin) {
+        Some(semantic) => stage.add(&declaration, &name, semantic, output),
+        // Outputs without a semantic, such as gl_PointSize, are not used
+        None if output => {}
+        None => return Err(BackendError::UnsupportedBuiltIn(builtin.clone())),
+    }
+    Ok(format!("static {};\n", declaration))
+}
+
+fn write_variable(program: &Program,
+                  dialect: &Hlsl,
+                  var: &OpVariable,
+                  stage: &mut Stage,
+                  output: &mut String)
+                  -> BackendResult<()> {
+    let id = var.result_id.0;
+    let (storage_class, ty) = match *try!(program.get_type(&var.result_type)) {
+        Type::Pointer(ref storage_class, ref pointed) => (storage_class.clone(), &**pointed),
+        _ => return Err(BackendError::InvalidId(OpId(id))),
+    };
+    let unsupported = || BackendError::UnsupportedStorageClass(OpId(id), storage_class.clone());
+    let name = program.name(id);
+
+    let declaration = match storage_class {
+        StorageClass::Uniform |
+        StorageClass::PushConstant => {
+            let struct_id = match *ty {
+                Type::Struct(struct_id) => struct_id,
+                _ => return Err(unsupported()),
+            };
+            if let Some(element) = structured_buffer_element(program, struct_id) {
+                let read_only = program.has_decoration(id, Decoration::NonWritable) ||
+                                program.member_decorations(struct_id, 0)
+                    .iter()
+                    .any(|d| **d == Decoration::NonWritable);
+                let (buffer, kind) = if read_only {
+                    ("StructuredBuffer", 't')
+                } else {
+                    ("RWStructuredBuffer", 'u')
+                };
+                format!("{}<{}> {}{};\n",
+                        buffer,
+                        try!(dialect.type_name(program, element)),
+                        name,
+                        register(program, id, kind))
+            } else if program.has_decoration(struct_id, Decoration::Block) {
+                let mut block = format!("cbuffer {}{} {{\n",
+                                        program.name(struct_id),
+                                        register(program, id, 'b'));
+                for (member, member_type) in program.struct_members(struct_id).iter().enumerate() {
+                    let member = member as u32;
+                    let member_name = dialect.block_member(program, id, struct_id, member);
+                    let declaration = try!(dialect.declaration(program,
+                                                               try!(program.get_type(member_type)),
+                                                               &member_name));
+                    let offset = program.member_decorations(struct_id, member)
+                        .iter()
+                        .filter_map(|d| match **d {
+                            Decoration::Offset(offset) => Some(offset),
+                            _ => None,
+                        })
+                        .next();
+                    match offset {
+                        Some(offset) if offset % 16 == 0 => {
+                            block.push_str(&format!("    {} : packoffset(c{});\n",
+                                                    declaration,
+                                                    offset / 16));
+                        }
+                        Some(offset) => {
+                            let component = ["x", "y", "z", "w"][(offset % 16 / 4) as usize];
+                            block.push_str(&format!("    {} : packoffset(c{}.{});\n",
+                                                    declaration,
+                                                    offset / 16,
+                                                    component));
+                        }
+                        None => block.push_str(&format!("    {};\n", declaration)),
+                    }
+                }
+                block.push_str("};\n");
+                block
+            } else {
+                return Err(unsupported());
+            }
+        }
+        StorageClass::Input |
+        StorageClass::Output => {
+            let is_output = storage_class == StorageClass::Output;
+            if let Some(builtin) = program.builtin(id) {
+                try!(write_builtin(dialect, program, builtin, ty, is_output, stage))
+            } else if let Type::Struct(struct_id) = *ty {
+                if !program.is_builtin_block(struct_id) {
+                    return Err(unsupported());
+                }
+                let mut statics = String::new();
+                for (member, member_type) in program.struct_members(struct_id).iter().enumerate() {
+                    if let Some(builtin) = program.member_builtin(struct_id, member as u32) {
+                        let member_type = try!(program.get_type(member_type));
+                        statics.push_str(&try!(write_builtin(dialect,
+                                                             program,
+                                                             builtin,
+                                                             member_type,
+                                                             is_output,
+                                                             stage)));
+                    }
+                }
+                statics
+            } else {
+                let location = match program.decoration_value(id, |d| match *d {
+                    Decoration::Location(location) => Some(location),
+                    _ => None,
+                }) {
+                    Some(location) => location,
+                    None => return Err(unsupported()),
+                };
+                let semantic = match (&program.entry_point.execution_model, is_output) {
+                    (&ExecutionModel::Fragment, true) => format!("SV_Target{}", location),
+                    _ => format!("TEXCOORD{}", location),
+                };
+                let mut declaration = try!(dialect.declaration(program, ty, &name));
+                if program.has_decoration(id, Decoration::Flat) {
+                    declaration = format!("nointerpolation {}", declaration);
+                } else if program.has_decoration(id, Decoration::NoPerspective) {
+                    declaration = format!("noperspective {}", declaration);
+                }
+                stage.add(&declaration, &name, &semantic, is_output);
+                format!("static {};\n", try!(dialect.declaration(program, ty, &name)))
+            }
+        }
+        StorageClass::Workgroup => {
+            format!("groupshared {};\n", try!(dialect.declaration(program, ty, &name)))
+        }
+        StorageClass::Private => {
+            let declaration = try!(dialect.declaration(program, ty, &name));
+            match var.initializer {
+                Some(ref initializer) => {
+                    format!("static {} = {};\n",
+                            declaration,
+                            try!(program.constant_expr(dialect, initializer)))
+                }
+                None => format!("static {};\n", declaration),
+            }
+        }
+        _ => return Err(unsupported()),
+    };
+    output.push_str(&declaration);
+    Ok(())
+}
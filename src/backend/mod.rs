@@ -0,0 +1,1551 @@
+//! Generation of shader source code from logical modules
+
+use std::collections::{HashMap, HashSet};
+use glsl450;
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use spv::logical::*;
+
+mod glsl;
+pub use self::glsl::generate_glsl;
+mod hlsl;
+pub use self::hlsl::generate_hlsl;
+mod msl;
+pub use self::msl::{generate_msl, MslArgument, ResourceBinding};
+mod pseudo;
+pub use self::pseudo::generate_pseudo_code;
+mod wgsl;
+pub use self::wgsl::generate_wgsl;
+
+#[derive(Debug)]
+pub enum BackendError {
+    /// The module has no entry point with the given name
+    UnknownEntryPoint(String),
+    /// The structured control flow of a function could not be found
+    ControlFlow(OpId, ControlFlowError),
+    /// A type definition can not be represented in the target language
+    UnsupportedType(OpId),
+    /// A type can not be written in the target language
+    UnrepresentableType(Type),
+    /// A global variable has a storage class that can not be represented
+    UnsupportedStorageClass(OpId, StorageClass),
+    /// A builtin variable has no equivalent in the target language
+    UnsupportedBuiltIn(BuiltIn),
+    /// An instruction can not be translated
+    UnsupportedInstruction(String),
+    /// An id is used without a definition that can be referenced
+    InvalidId(OpId),
+    /// The entry point is for a kind of shader the target language can not
+    /// express
+    UnsupportedExecutionModel(ExecutionModel),
+}
+
+pub type BackendResult<T> = Result<T, BackendError>;
+
+/// The structure of a type that can be written in source code
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Void,
+    Bool,
+    /// An integer with a width and signedness
+    Int(u32, bool),
+    Float(u32),
+    /// A vector with a component type and count
+    Vector(Box<Type>, u32),
+    /// A matrix with a column type and count
+    Matrix(Box<Type>, u32),
+    Array(Box<Type>, u32),
+    RuntimeArray(Box<Type>),
+    /// A struct type, with the id of its definition
+    Struct(u32),
+    Pointer(StorageClass, Box<Type>),
+    /// An image type, with the id of its definition
+    Image(u32),
+    Sampler,
+}
+
+impl Type {
+    /// Returns the type with integer components changed to a signedness
+    pub fn with_signedness(&self, signed: bool) -> Type {
+        match *self {
+            Type::Int(width, _) => Type::Int(width, signed),
+            Type::Vector(ref component, count) => {
+                Type::Vector(Box::new(component.with_signedness(signed)), count)
+            }
+            ref ty => ty.clone(),
+        }
+    }
+
+    /// Returns the type of the components of a scalar or vector
+    pub fn scalar(&self) -> &Type {
+        match *self {
+            Type::Vector(ref component, _) => component,
+            ref ty => ty,
+        }
+    }
+
+    pub fn is_vector(&self) -> bool {
+        match *self {
+            Type::Vector(_, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Formats a float constant so that it is read back as the same value
+///
+/// Returns `None` for infinities and NaNs, which have no literal form.
+pub fn format_float(bits: u64, width: u32) -> Option<String> {
+    let value = if width == 64 {
+        f64::from_bits(bits)
+    } else {
+        f32::from_bits(bits as u32) as f64
+    };
+    if !value.is_finite() {
+        return None;
+    }
+    let text = if width == 64 {
+        format!("{:?}", value)
+    } else {
+        format!("{:?}", value as f32)
+    };
+    Some(text)
+}
+
+/// The syntax of a target language
+///
+/// Statements and declarations are written by the shared function writer
+/// using these methods. Each backend also writes the global declarations and
+/// entry point of its language.
+pub trait Dialect {
+    /// Returns if a name can not be used as an identifier
+    fn is_reserved(&self, name: &str) -> bool;
+
+    /// Returns the name the entry point function must have
+    fn entry_point_name(&self, name: &str) -> String {
+        sanitize(name)
+    }
+
+    /// Returns the name of a type, as used in casts and constructors
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String>;
+
+    /// Declares a variable, member or parameter with a type
+    ///
+    /// Array sizes come after the name, as in C.
+    fn declaration(&self, program: &Program, ty: &Type, name: &str) -> BackendResult<String> {
+        let mut sizes = String::new();
+        let mut ty = ty;
+        loop {
+            match *ty {
+                Type::Array(ref element, length) => {
+                    sizes.push_str(&format!("[{}]", length));
+                    ty = element;
+                }
+                Type::RuntimeArray(ref element) => {
+                    sizes.push_str("[]");
+                    ty = element;
+                }
+                _ => break,
+            }
+        }
+        Ok(format!("{} {}{}", try!(self.type_name(program, ty)), name, sizes))
+    }
+
+    /// Declares a function local variable, without the ending semicolon
+    fn local(&self,
+             program: &Program,
+             ty: &Type,
+             name: &str,
+             initializer: Option<&str>)
+             -> BackendResult<String> {
+        let declaration = try!(self.declaration(program, ty, name));
+        Ok(match initializer {
+            Some(initializer) => format!("{} = {}", declaration, initializer),
+            None => declaration,
+        })
+    }
+
+    /// Writes the signature of a function
+    ///
+    /// Parameters that are pointers are passed so that the callee can write
+    /// through them.
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String>;
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String;
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String>;
+
+    /// Builds a vector, matrix, array or struct from its constituents
+    fn construct(&self, program: &Program, ty: &Type, args: &[String]) -> BackendResult<String> {
+        Ok(format!("{}({})", try!(self.type_name(program, ty)), args.join(", ")))
+    }
+
+    /// Converts a value to a type
+    fn cast(&self, program: &Program, ty: &Type, expr: &str) -> BackendResult<String> {
+        Ok(format!("{}({})", try!(self.type_name(program, ty)), expr))
+    }
+
+    /// Reinterprets the bits of a value as another type
+    fn bitcast(&self, program: &Program, from: &Type, to: &Type, expr: &str)
+               -> BackendResult<String>;
+
+    /// Compares two values with an infix operator such as `<`
+    ///
+    /// `function` is the name of the GLSL function that compares vectors
+    /// component-wise, such as `lessThan`.
+    fn compare(&self, operator: &str, _function: &str, a: &str, b: &str, _vector: bool)
+               -> String {
+        format!("{} {} {}", a, operator, b)
+    }
+
+    /// Negates a boolean value or vector
+    fn not(&self, expr: &str, _vector: bool) -> String {
+        format!("!({})", expr)
+    }
+
+    /// Compares two floats with `!=`, with a false result if either is NaN
+    fn ordered_not_equal(&self, a: &str, b: &str, _vector: bool) -> String {
+        format!("!isnan({}) && !isnan({}) && {} != {}", a, b, a, b)
+    }
+
+    /// Compares two floats with `==`, with a true result if either is NaN
+    fn unordered_equal(&self, a: &str, b: &str, _vector: bool) -> String {
+        format!("isnan({}) || isnan({}) || {} == {}", a, b, a, b)
+    }
+
+    /// The remainder of a signed integer division with the sign of `a`
+    fn srem(&self, a: &str, b: &str) -> String {
+        format!("{} % {}", a, b)
+    }
+
+    /// The remainder of a float division with the sign of `a`
+    fn frem(&self, a: &str, b: &str) -> String;
+
+    /// The remainder of a float division with the sign of `b`
+    fn fmod(&self, a: &str, b: &str) -> String {
+        format!("{} - {} * floor({} / {})", a, b, a, b)
+    }
+
+    /// Returns the expression for a builtin variable
+    fn builtin(&self, builtin: &BuiltIn) -> Option<String>;
+
+    /// Returns the expression for a member of a uniform or storage block
+    /// variable
+    fn block_member(&self, program: &Program, variable: u32, struct_id: u32, member: u32)
+                    -> String {
+        format!("{}.{}", program.name(variable), program.member_name(struct_id, member))
+    }
+
+    /// Returns the expression that starts an infinite loop
+    fn loop_start(&self) -> &'static str {
+        "while (true)"
+    }
+
+    /// Returns the keyword of the block at the end of a loop that `continue`
+    /// statements jump to, if the language has one
+    ///
+    /// The continue target of a loop is written in this block when every
+    /// iteration ends by running it.
+    fn continuing(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the attribute written before a loop
+    fn loop_attribute(&self, _control: &LoopControl) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the attribute written before an if statement
+    fn selection_attribute(&self, _control: &SelectionControl) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns if global variables must be passed to the functions that use
+    /// them, for languages without mutable variables at global scope
+    fn passes_globals(&self) -> bool {
+        false
+    }
+
+    /// Writes the signature of the entry point function
+    fn entry_point_header(&self,
+                          program: &Program,
+                          name: &str,
+                          return_type: &Type,
+                          parameters: &[(Type, String)])
+                          -> BackendResult<String> {
+        self.function_header(program, name, return_type, parameters)
+    }
+
+    /// Returns the statements at the start of the entry point function
+    fn entry_point_prologue(&self, _program: &Program) -> BackendResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Reads or writes through a pointer parameter
+    fn deref(&self, name: &str) -> String {
+        name.into()
+    }
+
+    /// Passes a variable to a pointer parameter
+    fn address_of(&self, expr: &str) -> String {
+        expr.into()
+    }
+}
+
+/// Information about a module that is shared by every backend
+pub struct Program<'a> {
+    pub module: &'a LogicalModule,
+    pub entry_point: &'a OpEntryPoint,
+    types: HashMap<u32, Type>,
+    structs: HashMap<u32, &'a OpTypeStruct>,
+    images: HashMap<u32, &'a OpTypeImage>,
+    constants: HashMap<u32, &'a GroupConstant>,
+    variables: HashMap<u32, &'a OpVariable>,
+    functions: HashMap<u32, &'a FunctionDefinition>,
+    ext_inst_imports: HashMap<u32, &'a str>,
+    decorations: HashMap<u32, Vec<&'a Decoration>>,
+    member_decorations: HashMap<(u32, u32), Vec<&'a Decoration>>,
+    names: HashMap<u32, String>,
+    member_names: HashMap<(u32, u32), String>,
+}
+
+fn sanitize(name: &str) -> String {
+    let mut name = name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Returns if a name has the same form as the names given to unnamed ids
+fn is_generated(name: &str) -> bool {
+    name.starts_with('_') && name.len() > 1 && name[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+impl<'a> Program<'a> {
+    /// Gathers the types, constants, decorations and unique names of a
+    /// module for generating code for one entry point
+    pub fn new(module: &'a LogicalModule,
+               entry_point: &str,
+               dialect: &Dialect)
+               -> BackendResult<Program<'a>> {
+        let entry_point = match module.entry_points.iter().find(|op| op.name == entry_point) {
+            Some(op) => op,
+            None => return Err(BackendError::UnknownEntryPoint(entry_point.into())),
+        };
+        let mut program = Program {
+            module,
+            entry_point,
+            types: HashMap::new(),
+            structs: HashMap::new(),
+            images: HashMap::new(),
+            constants: HashMap::new(),
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+            ext_inst_imports: HashMap::new(),
+            decorations: HashMap::new(),
+            member_decorations: HashMap::new(),
+            names: HashMap::new(),
+            member_names: HashMap::new(),
+        };
+
+        for op in &module.ext_inst_imports {
+            program.ext_inst_imports.insert(op.result_id.0, &op.name);
+        }
+        for op in &module.annotations {
+            match *op {
+                GroupAnnotation::OpDecorate(ref op) => {
+                    program.decorations.entry(op.target.0).or_default().push(&op.decoration);
+                }
+                GroupAnnotation::OpMemberDecorate(ref op) => {
+                    program.member_decorations
+                        .entry((op.structure_type.0, op.member.0))
+                        .or_default()
+                        .push(&op.decoration);
+                }
+            }
+        }
+        for op in &module.globals {
+            match *op {
+                GroupGlobal::GroupType(ref op) => program.add_type(op),
+                GroupGlobal::GroupConstant(ref constant) => {
+                    if let Some(result_id) = constant.result_id() {
+                        program.constants.insert(result_id.0, constant);
+                    }
+                }
+                GroupGlobal::OpVariable(ref var) => {
+                    program.variables.insert(var.result_id.0, var);
+                }
+                GroupGlobal::OpLine(_) |
+                GroupGlobal::OpNoLine(_) => {}
+            }
+        }
+        for function in &module.function_definitions {
+            program.functions.insert(function.function.result_id.0, function);
+        }
+
+        // The name of the entry point is kept free for backends that wrap
+        // the entry point function
+        let mut taken = HashSet::new();
+        let entry_name = dialect.entry_point_name(&entry_point.name);
+        taken.insert(entry_name.clone());
+        taken.insert(sanitize(&entry_point.name));
+        program.names.insert(entry_point.entry_point.0, entry_name);
+        for op in &module.debug {
+            match *op {
+                GroupDebug::OpName(ref op) if !program.names.contains_key(&op.target.0) => {
+                    let mut name = sanitize(&op.name);
+                    if name.is_empty() {
+                        continue;
+                    }
+                    if is_generated(&name) || dialect.is_reserved(&name) || taken.contains(&name) {
+                        name = format!("{}_{}", name, op.target.0);
+                    }
+                    while taken.contains(&name) {
+                        name.push('_');
+                    }
+                    taken.insert(name.clone());
+                    program.names.insert(op.target.0, name);
+                }
+                GroupDebug::OpMemberName(ref op) => {
+                    let mut name = sanitize(&op.name);
+                    if name.is_empty() {
+                        continue;
+                    }
+                    if dialect.is_reserved(&name) || name.starts_with("_m") {
+                        name = format!("{}_m{}", name, op.member.0);
+                    }
+                    program.member_names.insert((op.struct_type.0, op.member.0), name);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(program)
+    }
+
+    fn add_type(&mut self, op: &'a GroupType) {
+        let ty = match *op {
+            GroupType::OpTypeVoid(_) => Type::Void,
+            GroupType::OpTypeBool(_) => Type::Bool,
+            GroupType::OpTypeInt(ref op) => {
+                Type::Int(op.width, op.signedness == Signedness::Signed)
+            }
+            GroupType::OpTypeFloat(ref op) => Type::Float(op.width),
+            GroupType::OpTypeVector(ref op) => {
+                match self.types.get(&op.component_type.0) {
+                    Some(component) => Type::Vector(Box::new(component.clone()), op.component_count),
+                    None => return,
+                }
+            }
+            GroupType::OpTypeMatrix(ref op) => {
+                match self.types.get(&op.column_type.0) {
+                    Some(column) => Type::Matrix(Box::new(column.clone()), op.column_count),
+                    None => return,
+                }
+            }
+            GroupType::OpTypeArray(ref op) => {
+                match (self.types.get(&op.element_type.0), self.constant_int(&op.length)) {
+                    (Some(element), Some(length)) => {
+                        Type::Array(Box::new(element.clone()), length as u32)
+                    }
+                    _ => return,
+                }
+            }
+            GroupType::OpTypeRuntimeArray(ref op) => {
+                match self.types.get(&op.element_type.0) {
+                    Some(element) => Type::RuntimeArray(Box::new(element.clone())),
+                    None => return,
+                }
+            }
+            GroupType::OpTypeStruct(ref op) => {
+                if op.member_types.iter().any(|id| !self.types.contains_key(&id.0)) {
+                    return;
+                }
+                self.structs.insert(op.result_id.0, op);
+                Type::Struct(op.result_id.0)
+            }
+            GroupType::OpTypeImage(ref op) => {
+                if !self.types.contains_key(&op.sampled_type.0) {
+                    return;
+                }
+                self.images.insert(op.result_id.0, op);
+                Type::Image(op.result_id.0)
+            }
+            GroupType::OpTypeSampler(_) => Type::Sampler,
+            GroupType::OpTypePointer(ref op) => {
+                match self.types.get(&op.pointed_type.0) {
+                    Some(pointed) => {
+                        Type::Pointer(op.storage_class.clone(), Box::new(pointed.clone()))
+                    }
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let result_id = op.result_id().expect("type without a result id").0;
+        self.types.insert(result_id, ty);
+    }
+
+    /// Returns the type with an id
+    pub fn get_type(&self, id: &OpId) -> BackendResult<&Type> {
+        self.types.get(&id.0).ok_or_else(|| BackendError::UnsupportedType(id.clone()))
+    }
+
+    /// Returns the member types of a struct
+    pub fn struct_members(&self, id: u32) -> &[OpId] {
+        &self.structs[&id].member_types
+    }
+
+    /// Returns the struct types in the order they are defined
+    pub fn struct_ids(&self) -> Vec<u32> {
+        self.module
+            .globals
+            .iter()
+            .filter_map(|op| match *op {
+                GroupGlobal::GroupType(GroupType::OpTypeStruct(ref op)) => Some(op.result_id.0),
+                _ => None,
+            })
+            .filter(|id| self.structs.contains_key(id))
+            .collect()
+    }
+
+    pub fn get_image(&self, id: u32) -> &'a OpTypeImage {
+        self.images[&id]
+    }
+
+    pub fn get_variable(&self, id: u32) -> Option<&'a OpVariable> {
+        self.variables.get(&id).cloned()
+    }
+
+    pub fn get_constant(&self, id: u32) -> Option<&'a GroupConstant> {
+        self.constants.get(&id).cloned()
+    }
+
+    pub fn get_function(&self, id: u32) -> Option<&'a FunctionDefinition> {
+        self.functions.get(&id).cloned()
+    }
+
+    /// Returns the value of an integer constant
+    pub fn constant_int(&self, id: &OpId) -> Option<u64> {
+        let value = match self.constants.get(&id.0) {
+            Some(GroupConstant::OpConstant(op)) => &op.value,
+            Some(GroupConstant::OpSpecConstant(op)) => &op.value,
+            _ => return None,
+        };
+        let low = *value.first().unwrap_or(&0) as u64;
+        let high = *value.get(1).unwrap_or(&0) as u64;
+        Some(low | (high << 32))
+    }
+
+    /// Returns the unique name of an id
+    pub fn name(&self, id: u32) -> String {
+        match self.names.get(&id) {
+            Some(name) => name.clone(),
+            None => format!("_{}", id),
+        }
+    }
+
+    /// Returns the unique name of a struct member within its struct
+    pub fn member_name(&self, struct_id: u32, member: u32) -> String {
+        match self.member_names.get(&(struct_id, member)) {
+            Some(name) => name.clone(),
+            None => format!("_m{}", member),
+        }
+    }
+
+    pub fn decorations(&self, id: u32) -> &[&'a Decoration] {
+        match self.decorations.get(&id) {
+            Some(decorations) => decorations,
+            None => &[],
+        }
+    }
+
+    pub fn member_decorations(&self, id: u32, member: u32) -> &[&'a Decoration] {
+        match self.member_decorations.get(&(id, member)) {
+            Some(decorations) => decorations,
+            None => &[],
+        }
+    }
+
+    pub fn has_decoration(&self, id: u32, decoration: Decoration) -> bool {
+        self.decorations(id).iter().any(|d| **d == decoration)
+    }
+
+    /// Returns the value of a decoration with a single number, such as
+    /// Binding or Location
+    pub fn decoration_value<F>(&self, id: u32, get: F) -> Option<u32>
+        where F: Fn(&Decoration) -> Option<u32>
+    {
+        self.decorations(id).iter().filter_map(|d| get(d)).next()
+    }
+
+    pub fn builtin(&self, id: u32) -> Option<&'a BuiltIn> {
+        self.decorations(id)
+            .iter()
+            .filter_map(|decoration| match **decoration {
+                Decoration::BuiltIn(ref builtin) => Some(builtin),
+                _ => None,
+            })
+            .next()
+    }
+
+    pub fn member_builtin(&self, id: u32, member: u32) -> Option<&'a BuiltIn> {
+        self.member_decorations(id, member)
+            .iter()
+            .filter_map(|decoration| match **decoration {
+                Decoration::BuiltIn(ref builtin) => Some(builtin),
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Returns if a struct is a block of builtin variables, such as
+    /// gl_PerVertex
+    pub fn is_builtin_block(&self, id: u32) -> bool {
+        self.structs.contains_key(&id) &&
+        (0..self.struct_members(id).len() as u32).any(|member| {
+            self.member_builtin(id, member).is_some()
+        })
+    }
+
+    /// Returns the execution modes of the entry point
+    pub fn execution_modes(&self) -> Vec<&'a ExecutionMode> {
+        self.module
+            .execution_modes
+            .iter()
+            .filter(|op| op.entry_point == self.entry_point.entry_point)
+            .map(|op| &op.mode)
+            .collect()
+    }
+
+    /// Returns the local size of a compute entry point
+    ///
+    /// A constant decorated with the WorkgroupSize builtin takes precedence
+    /// over the LocalSize execution mode, and specialization constants use
+    /// their default values.
+    pub fn workgroup_size(&self) -> Option<(u32, u32, u32)> {
+        for (id, constant) in &self.constants {
+            if self.builtin(*id) != Some(&BuiltIn::WorkgroupSize) {
+                continue;
+            }
+            let constituents = match **constant {
+                GroupConstant::OpConstantComposite(ref op) => &op.constituents,
+                GroupConstant::OpSpecConstantComposite(ref op) => &op.constituents,
+                _ => continue,
+            };
+            let size = constituents.iter()
+                .map(|id| self.constant_int(id).map(|value| value as u32))
+                .collect::<Option<Vec<_>>>();
+            if let Some(size) = size {
+                if size.len() == 3 {
+                    return Some((size[0], size[1], size[2]));
+                }
+            }
+        }
+        self.execution_modes()
+            .into_iter()
+            .filter_map(|mode| match *mode {
+                ExecutionMode::LocalSize(x, y, z) => Some((x, y, z)),
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Returns the functions called from the entry point, with callees
+    /// before their callers and the entry point last
+    pub fn reachable_functions(&self) -> Vec<&'a FunctionDefinition> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+        self.visit_function(self.entry_point.entry_point.0, &mut visited, &mut order);
+        order
+    }
+
+    fn visit_function(&self,
+                      id: u32,
+                      visited: &mut HashSet<u32>,
+                      order: &mut Vec<&'a FunctionDefinition>) {
+        if !visited.insert(id) {
+            return;
+        }
+        let function = match self.functions.get(&id) {
+            Some(function) => *function,
+            None => return,
+        };
+        for block in &function.blocks {
+            for op in &block.code {
+                if let GroupCode::OpFunctionCall(ref call) = *op {
+                    self.visit_function(call.function.0, visited, order);
+                }
+            }
+        }
+        order.push(function);
+    }
+
+    /// Returns the global variables used by a function and the functions it
+    /// calls, in the order they are defined
+    pub fn function_globals(&self, function: &FunctionDefinition) -> Vec<&'a OpVariable> {
+        let mut used = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![function];
+        while let Some(function) = pending.pop() {
+            if !visited.insert(function.function.result_id.0) {
+                continue;
+            }
+            for block in &function.blocks {
+                for op in &block.code {
+                    used.extend(op.operands().into_iter().map(|id| id.0));
+                    if let GroupCode::OpFunctionCall(ref call) = *op {
+                        if let Some(callee) = self.functions.get(&call.function.0) {
+                            pending.push(callee);
+                        }
+                    }
+                }
+                used.extend(block.branch.operands().into_iter().map(|id| id.0));
+            }
+        }
+        self.module
+            .globals
+            .iter()
+            .filter_map(|op| match *op {
+                GroupGlobal::OpVariable(ref var) if used.contains(&var.result_id.0) => Some(var),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the global variables used by the functions called from the
+    /// entry point, in the order they are defined
+    pub fn used_variables(&self) -> Vec<&'a OpVariable> {
+        let mut used = HashSet::new();
+        for function in self.reachable_functions() {
+            for block in &function.blocks {
+                for op in &block.code {
+                    used.extend(op.operands().into_iter().map(|id| id.0));
+                }
+                used.extend(block.branch.operands().into_iter().map(|id| id.0));
+            }
+        }
+        used.extend(self.entry_point.interface.iter().map(|id| id.0));
+        self.module
+            .globals
+            .iter()
+            .filter_map(|op| match *op {
+                GroupGlobal::OpVariable(ref var) if used.contains(&var.result_id.0) => Some(var),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Writes a constant as an expression
+    pub fn constant_expr(&self, dialect: &Dialect, id: &OpId) -> BackendResult<String> {
+        if let Some(builtin) = self.builtin(id.0) {
+            if let Some(expr) = dialect.builtin(builtin) {
+                return Ok(expr);
+            }
+        }
+        let constant = match self.constants.get(&id.0) {
+            Some(constant) => *constant,
+            None => return Err(BackendError::InvalidId(id.clone())),
+        };
+        match *constant {
+            GroupConstant::OpConstantTrue(_) |
+            GroupConstant::OpSpecConstantTrue(_) => Ok("true".into()),
+            GroupConstant::OpConstantFalse(_) |
+            GroupConstant::OpSpecConstantFalse(_) => Ok("false".into()),
+            GroupConstant::OpConstant(OpConstant { ref result_type, ref value, .. }) |
+            GroupConstant::OpSpecConstant(OpSpecConstant { ref result_type, ref value, .. }) => {
+                let low = *value.first().unwrap_or(&0) as u64;
+                let high = *value.get(1).unwrap_or(&0) as u64;
+                let bits = low | (high << 32);
+                match *try!(self.get_type(result_type)) {
+                    Type::Int(width, signed) => Ok(dialect.int_literal(bits, width, signed)),
+                    Type::Float(width) => dialect.float_literal(bits, width),
+                    _ => Err(BackendError::UnsupportedType(result_type.clone())),
+                }
+            }
+            GroupConstant::OpConstantComposite(OpConstantComposite { ref result_type,
+                                                                     ref constituents,
+                                                                     .. }) |
+            GroupConstant::OpSpecConstantComposite(OpSpecConstantComposite { ref result_type,
+                                                                             ref constituents,
+                                                                             .. }) => {
+                let mut args = Vec::new();
+                for constituent in constituents {
+                    args.push(try!(self.constant_expr(dialect, constituent)));
+                }
+                dialect.construct(self, try!(self.get_type(result_type)), &args)
+            }
+            GroupConstant::OpSpecConstantOp(ref op) => {
+                Err(BackendError::UnsupportedInstruction(format!("{}", op)))
+            }
+        }
+    }
+}
+
+/// Returns the block that runs last in a chain, as written by the function
+/// writer
+fn tail_block(chain: &ControlFlowChain) -> Option<u32> {
+    match *chain {
+        ControlFlowChain::Atom(id) => Some(OpId::from(id).0),
+        ControlFlowChain::Block(ref chains) => chains.last().and_then(tail_block),
+        ControlFlowChain::Selection(_, ref true_chain, ref false_chain, _, _) => {
+            match **false_chain {
+                ControlFlowChain::Break |
+                ControlFlowChain::Continue => tail_block(true_chain),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns if a chain continues the loop it is in, outside of nested loops
+fn has_continue(chain: &ControlFlowChain) -> bool {
+    match *chain {
+        ControlFlowChain::Continue => true,
+        ControlFlowChain::Block(ref chains) => chains.iter().any(has_continue),
+        ControlFlowChain::Selection(_, ref true_chain, ref false_chain, _, _) => {
+            has_continue(true_chain) || has_continue(false_chain)
+        }
+        _ => false,
+    }
+}
+
+/// Writes the statements of functions using the syntax of a dialect
+pub struct FunctionWriter<'a: 'b, 'b> {
+    program: &'b Program<'a>,
+    dialect: &'b Dialect,
+    function: &'a FunctionDefinition,
+    blocks: HashMap<u32, &'a BasicBlock>,
+    /// The types of the values defined in the function
+    value_types: HashMap<u32, &'a OpId>,
+    /// Pointers that are written directly into the instructions using them
+    pointers: HashMap<u32, String>,
+    /// The block that is written in the continuing block of the current loop
+    continuing: Option<u32>,
+    output: String,
+    indent: usize,
+}
+
+impl<'a: 'b, 'b> FunctionWriter<'a, 'b> {
+    /// Writes a function definition
+    pub fn write(program: &'b Program<'a>,
+                 dialect: &'b Dialect,
+                 function: &'a FunctionDefinition)
+                 -> BackendResult<String> {
+        let mut writer = FunctionWriter {
+            program,
+            dialect,
+            function,
+            blocks: HashMap::new(),
+            value_types: HashMap::new(),
+            pointers: HashMap::new(),
+            continuing: None,
+            output: String::new(),
+            indent: 0,
+        };
+        for param in &function.parameters {
+            writer.value_types.insert(param.result_id.0, &param.result_type);
+        }
+        for block in &function.blocks {
+            writer.blocks.insert(block.label.result_id.0, block);
+            for op in &block.code {
+                if let (Some(result_id), Some(result_type)) = (op.result_id(), op.result_type()) {
+                    writer.value_types.insert(result_id.0, result_type);
+                }
+            }
+        }
+        try!(writer.write_function());
+        Ok(writer.output)
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    fn write_function(&mut self) -> BackendResult<()> {
+        let id = OpId(self.function.function.result_id.0);
+        let chain = match find_control_flow(self.function.clone()) {
+            Ok(chain) => chain,
+            Err(err) => return Err(BackendError::ControlFlow(id, err)),
+        };
+
+        let mut parameters = Vec::new();
+        for param in &self.function.parameters {
+            let ty = try!(self.program.get_type(&param.result_type)).clone();
+            let name = self.program.name(param.result_id.0);
+            if let Type::Pointer(_, _) = ty {
+                let deref = self.dialect.deref(&name);
+                self.pointers.insert(param.result_id.0, deref);
+            }
+            parameters.push((ty, name));
+        }
+        let is_entry_point = id == self.program.entry_point.entry_point;
+        if self.dialect.passes_globals() && !is_entry_point {
+            for var in self.program.function_globals(self.function) {
+                parameters.push(try!(self.global_parameter(var)));
+            }
+        }
+        let name = self.program.name(id.0);
+        let return_type = try!(self.program.get_type(&self.function.function.result_type));
+        let header = if is_entry_point {
+            try!(self.dialect.entry_point_header(self.program, &name, return_type, &parameters))
+        } else {
+            try!(self.dialect.function_header(self.program, &name, return_type, &parameters))
+        };
+        self.line(&format!("{} {{", header));
+        self.indent += 1;
+        if is_entry_point {
+            for statement in try!(self.dialect.entry_point_prologue(self.program)) {
+                self.line(&statement);
+            }
+        }
+        try!(self.write_declarations());
+        try!(self.write_chain(&chain));
+        self.indent -= 1;
+        self.line("}");
+        Ok(())
+    }
+
+    /// Returns the type and name of the parameter that passes a global
+    /// variable to a function, which is the value of builtin variables and a
+    /// pointer to other variables
+    fn global_parameter(&self, var: &OpVariable) -> BackendResult<(Type, String)> {
+        let ty = try!(self.program.get_type(&var.result_type));
+        match (self.program.builtin(var.result_id.0), ty) {
+            (Some(builtin), Type::Pointer(_, pointed)) => {
+                let name = try!(self.dialect
+                    .builtin(builtin)
+                    .ok_or_else(|| BackendError::UnsupportedBuiltIn(builtin.clone())));
+                Ok(((**pointed).clone(), name))
+            }
+            _ => Ok((ty.clone(), self.program.name(var.result_id.0))),
+        }
+    }
+
+    /// Declares every value of the function at its start, as a value may be
+    /// used outside of the scope of the block that defines it
+    fn write_declarations(&mut self) -> BackendResult<()> {
+        let function = self.function;
+        for block in &function.blocks {
+            for op in &block.code {
+                let (result_id, result_type) = match (op.result_id(), op.result_type()) {
+                    (Some(result_id), Some(result_type)) => (result_id.0, result_type),
+                    _ => continue,
+                };
+                let name = self.program.name(result_id);
+                match *op {
+                    GroupCode::OpAccessChain(_) => continue,
+                    GroupCode::OpVariable(ref var) => {
+                        let initializer = match var.initializer {
+                            Some(ref id) => Some(try!(self.expr(id))),
+                            None => None,
+                        };
+                        let ty = match *try!(self.program.get_type(result_type)) {
+                            Type::Pointer(_, ref pointed) => (**pointed).clone(),
+                            _ => return Err(BackendError::UnsupportedType(result_type.clone())),
+                        };
+                        let local = try!(self.dialect.local(self.program,
+                                                            &ty,
+                                                            &name,
+                                                            initializer.as_deref()));
+                        self.line(&format!("{};", local));
+                        continue;
+                    }
+                    _ => {}
+                }
+                let ty = try!(self.program.get_type(result_type)).clone();
+                match ty {
+                    Type::Void => continue,
+                    Type::Pointer(_, _) => {
+                        return Err(BackendError::UnsupportedType(result_type.clone()))
+                    }
+                    _ => {}
+                }
+                let local = try!(self.dialect.local(self.program, &ty, &name, None));
+                self.line(&format!("{};", local));
+                if let GroupCode::OpPhi(_) = *op {
+                    let phi = try!(self.dialect
+                        .local(self.program, &ty, &format!("{}_phi", name), None));
+                    self.line(&format!("{};", phi));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&self, id: u32) -> BackendResult<&'a BasicBlock> {
+        self.blocks.get(&id).cloned().ok_or(BackendError::InvalidId(OpId(id)))
+    }
+
+    fn write_chain(&mut self, chain: &ControlFlowChain) -> BackendResult<()> {
+        match *chain {
+            ControlFlowChain::Atom(id) if self.continuing == Some(OpId::from(id).0) => {}
+            ControlFlowChain::Atom(id) => try!(self.write_block(OpId::from(id).0)),
+            ControlFlowChain::Block(ref chains) => {
+                for chain in chains {
+                    try!(self.write_chain(chain));
+                }
+            }
+            ControlFlowChain::Selection(id, ref true_chain, ref false_chain, ref control, _) => {
+                let block = try!(self.block(OpId::from(id).0));
+                try!(self.write_code(block));
+                if let Some(attribute) = self.dialect.selection_attribute(control) {
+                    self.line(attribute);
+                }
+                let op = match block.branch {
+                    GroupBranch::OpBranchConditional(ref op) => op,
+                    _ => return Err(BackendError::InvalidId(OpId::from(id))),
+                };
+                let condition = try!(self.expr(&op.condition));
+                let leaves = match **false_chain {
+                    ControlFlowChain::Break |
+                    ControlFlowChain::Continue => true,
+                    _ => false,
+                };
+                if leaves {
+                    // Leave the loop early, then carry on with the rest of
+                    // the loop outside of the selection
+                    let not = self.dialect.not(&condition, false);
+                    self.line(&format!("if ({}) {{", not));
+                    self.indent += 1;
+                    try!(self.write_phi_copies(block, &op.false_label));
+                    try!(self.write_chain(false_chain));
+                    self.indent -= 1;
+                    self.line("}");
+                    try!(self.write_phi_copies(block, &op.true_label));
+                    try!(self.write_chain(true_chain));
+                } else {
+                    self.line(&format!("if ({}) {{", condition));
+                    self.indent += 1;
+                    try!(self.write_phi_copies(block, &op.true_label));
+                    try!(self.write_chain(true_chain));
+                    self.indent -= 1;
+                    let mark = self.output.len();
+                    self.line("} else {");
+                    self.indent += 1;
+                    let start = self.output.len();
+                    try!(self.write_phi_copies(block, &op.false_label));
+                    try!(self.write_chain(false_chain));
+                    self.indent -= 1;
+                    if self.output.len() == start {
+                        self.output.truncate(mark);
+                    }
+                    self.line("}");
+                }
+            }
+            ControlFlowChain::Loop(id, ref inner, ref control, _) => {
+                let block = try!(self.block(OpId::from(id).0));
+                if let Some(attribute) = self.dialect.loop_attribute(control) {
+                    self.line(attribute);
+                }
+                let continue_target = match block.merge {
+                    Some(GroupMerge::OpLoopMerge(ref op)) => Some(op.continue_target.0),
+                    _ => None,
+                };
+                let continuing = match (self.dialect.continuing(), continue_target) {
+                    (Some(keyword), Some(target)) if target != OpId::from(id).0 &&
+                                                     tail_block(inner) == Some(target) &&
+                                                     !has_continue(inner) => {
+                        Some((keyword, target))
+                    }
+                    _ => None,
+                };
+                let outer_continuing = self.continuing;
+                self.continuing = continuing.map(|(_, target)| target);
+
+                let start = self.dialect.loop_start();
+                self.line(&format!("{} {{", start));
+                self.indent += 1;
+                try!(self.write_code(block));
+                if let GroupBranch::OpBranch(ref op) = block.branch {
+                    try!(self.write_phi_copies(block, &op.target_label));
+                }
+                try!(self.write_chain(inner));
+                self.continuing = outer_continuing;
+                if let Some((keyword, target)) = continuing {
+                    self.line(&format!("{} {{", keyword));
+                    self.indent += 1;
+                    try!(self.write_block(target));
+                    self.indent -= 1;
+                    self.line("}");
+                }
+                self.indent -= 1;
+                self.line("}");
+            }
+            ControlFlowChain::Break => self.line("break;"),
+            ControlFlowChain::Continue => self.line("continue;"),
+        }
+        Ok(())
+    }
+
+    /// Writes a block that does not start a selection or loop
+    fn write_block(&mut self, id: u32) -> BackendResult<()> {
+        let block = try!(self.block(id));
+        try!(self.write_code(block));
+        match block.branch {
+            GroupBranch::OpBranch(ref op) => {
+                try!(self.write_phi_copies(block, &op.target_label));
+            }
+            GroupBranch::OpReturn(_) => self.line("return;"),
+            GroupBranch::OpReturnValue(ref op) => {
+                let value = try!(self.expr(&op.value));
+                self.line(&format!("return {};", value));
+            }
+            GroupBranch::OpBranchConditional(_) => return Err(BackendError::InvalidId(OpId(id))),
+        }
+        Ok(())
+    }
+
+    /// Assigns the values for the phis of a block when it is branched to
+    /// from another block
+    fn write_phi_copies(&mut self, from: &BasicBlock, to: &OpId) -> BackendResult<()> {
+        let target = try!(self.block(to.0));
+        for op in &target.code {
+            if let GroupCode::OpPhi(ref phi) = *op {
+                let parent = OpId(from.label.result_id.0);
+                if let Some(arg) = phi.variables.iter().find(|arg| arg.parent == parent) {
+                    let value = try!(self.expr(&arg.variable));
+                    let name = self.program.name(phi.result_id.0);
+                    self.line(&format!("{}_phi = {};", name, value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_code(&mut self, block: &'a BasicBlock) -> BackendResult<()> {
+        for op in &block.code {
+            try!(self.write_op(op));
+        }
+        Ok(())
+    }
+
+    fn value_type(&self, id: &OpId) -> BackendResult<Type> {
+        let type_id = match self.value_types.get(&id.0) {
+            Some(type_id) => *type_id,
+            None => {
+                if let Some(constant) = self.program.get_constant(id.0) {
+                    constant.result_type().expect("constant without a type")
+                } else if let Some(var) = self.program.get_variable(id.0) {
+                    &var.result_type
+                } else {
+                    return Err(BackendError::InvalidId(id.clone()));
+                }
+            }
+        };
+        self.program.get_type(type_id).cloned()
+    }
+
+    /// Writes the expression for an id
+    fn expr(&self, id: &OpId) -> BackendResult<String> {
+        if let Some(pointer) = self.pointers.get(&id.0) {
+            return Ok(pointer.clone());
+        }
+        if self.program.get_constant(id.0).is_some() {
+            return self.program.constant_expr(self.dialect, id);
+        }
+        if let Some(builtin) = self.program.builtin(id.0) {
+            return self.dialect
+                .builtin(builtin)
+                .ok_or_else(|| BackendError::UnsupportedBuiltIn(builtin.clone()));
+        }
+        Ok(self.program.name(id.0))
+    }
+
+    /// Writes an expression converted to a type if it has a different type
+    fn expr_as(&self, id: &OpId, ty: &Type) -> BackendResult<String> {
+        let expr = try!(self.expr(id));
+        if try!(self.value_type(id)) == *ty {
+            Ok(expr)
+        } else {
+            self.dialect.cast(self.program, ty, &expr)
+        }
+    }
+
+    fn assign(&mut self, result_id: &ResultId, value: &str) {
+        let name = self.program.name(result_id.0);
+        self.line(&format!("{} = {};", name, value));
+    }
+
+    /// Writes an access chain as the expression for the value it points to
+    fn access_chain(&self, op: &OpAccessChain) -> BackendResult<String> {
+        let mut ty = match try!(self.value_type(&op.base)) {
+            Type::Pointer(_, pointed) => *pointed,
+            _ => return Err(BackendError::InvalidId(op.base.clone())),
+        };
+        let mut expr = try!(self.expr(&op.base));
+        for (position, index) in op.indexes.iter().enumerate() {
+            ty = match ty {
+                Type::Struct(id) => {
+                    let member = match self.program.constant_int(index) {
+                        Some(member) => member as u32,
+                        None => return Err(BackendError::InvalidId(index.clone())),
+                    };
+                    let builtin = self.program.member_builtin(id, member);
+                    let is_block = self.program.get_variable(op.base.0).is_some() &&
+                                   (self.program.has_decoration(id, Decoration::Block) ||
+                                    self.program.has_decoration(id, Decoration::BufferBlock));
+                    match builtin {
+                        Some(builtin) if position == 0 && self.program.is_builtin_block(id) => {
+                            expr = try!(self.dialect
+                                .builtin(builtin)
+                                .ok_or_else(|| BackendError::UnsupportedBuiltIn(builtin.clone())));
+                        }
+                        _ if position == 0 && is_block => {
+                            expr = self.dialect.block_member(self.program, op.base.0, id, member);
+                        }
+                        _ => expr = format!("{}.{}", expr, self.program.member_name(id, member)),
+                    }
+                    let members = self.program.struct_members(id);
+                    match members.get(member as usize) {
+                        Some(member_type) => try!(self.program.get_type(member_type)).clone(),
+                        None => return Err(BackendError::InvalidId(index.clone())),
+                    }
+                }
+                Type::Vector(component, _) => {
+                    match self.program.constant_int(index) {
+                        Some(component) if component < 4 => {
+                            expr = format!("{}.{}", expr, ["x", "y", "z", "w"][component as usize]);
+                        }
+                        _ => expr = format!("{}[{}]", expr, try!(self.expr(index))),
+                    }
+                    *component
+                }
+                Type::Matrix(column, _) => {
+                    expr = format!("{}[{}]", expr, try!(self.expr(index)));
+                    *column
+                }
+                Type::Array(element, _) |
+                Type::RuntimeArray(element) => {
+                    expr = format!("{}[{}]", expr, try!(self.expr(index)));
+                    *element
+                }
+                _ => return Err(BackendError::InvalidId(index.clone())),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn binary(&mut self,
+              result_type: &OpId,
+              result_id: &ResultId,
+              operand1: &OpId,
+              operand2: &OpId,
+              operator: &str,
+              signed: Option<bool>)
+              -> BackendResult<()> {
+        let ty = try!(self.program.get_type(result_type)).clone();
+        let operand_type = match signed {
+            Some(signed) => ty.with_signedness(signed),
+            None => ty.clone(),
+        };
+        let a = try!(self.expr_as(operand1, &operand_type));
+        let b = try!(self.expr_as(operand2, &operand_type));
+        let mut value = format!("{} {} {}", a, operator, b);
+        if operand_type != ty {
+            value = try!(self.dialect.cast(self.program, &ty, &value));
+        }
+        self.assign(result_id, &value);
+        Ok(())
+    }
+
+    fn float_binary(&mut self,
+                    result_id: &ResultId,
+                    operand1: &OpId,
+                    operand2: &OpId,
+                    f: &Fn(&str, &str) -> String)
+                    -> BackendResult<()> {
+        let a = try!(self.expr(operand1));
+        let b = try!(self.expr(operand2));
+        let value = f(&a, &b);
+        self.assign(result_id, &value);
+        Ok(())
+    }
+
+    /// Writes an operation on signed integers, converting the result back to
+    /// the result type if it is unsigned
+    fn signed_binary(&mut self,
+                     result_type: &OpId,
+                     result_id: &ResultId,
+                     operand1: &OpId,
+                     operand2: &OpId,
+                     f: &Fn(&str, &str) -> String)
+                     -> BackendResult<()> {
+        let result_type = try!(self.program.get_type(result_type)).clone();
+        let ty = result_type.with_signedness(true);
+        let a = try!(self.expr_as(operand1, &ty));
+        let b = try!(self.expr_as(operand2, &ty));
+        let mut value = f(&a, &b);
+        if result_type != ty {
+            value = try!(self.dialect.cast(self.program, &result_type, &value));
+        }
+        self.assign(result_id, &value);
+        Ok(())
+    }
+
+    fn compare(&mut self,
+               result_type: &OpId,
+               result_id: &ResultId,
+               (operand1, operand2): (&OpId, &OpId),
+               operator: &str,
+               function: &str,
+               signed: Option<bool>)
+               -> BackendResult<()> {
+        let vector = try!(self.program.get_type(result_type)).is_vector();
+        let mut operand_type = try!(self.value_type(operand1));
+        if let Some(signed) = signed {
+            operand_type = operand_type.with_signedness(signed);
+        }
+        let a = try!(self.expr_as(operand1, &operand_type));
+        let b = try!(self.expr_as(operand2, &operand_type));
+        let value = self.dialect.compare(operator, function, &a, &b, vector);
+        self.assign(result_id, &value);
+        Ok(())
+    }
+
+    /// Compares floats so that the result is true if either is NaN, by
+    /// negating the ordered comparison with the opposite result
+    fn compare_unordered(&mut self,
+                         result_type: &OpId,
+                         result_id: &ResultId,
+                         operand1: &OpId,
+                         operand2: &OpId,
+                         operator: &str,
+                         function: &str)
+                         -> BackendResult<()> {
+        let vector = try!(self.program.get_type(result_type)).is_vector();
+        let a = try!(self.expr(operand1));
+        let b = try!(self.expr(operand2));
+        let ordered = self.dialect.compare(operator, function, &a, &b, vector);
+        let value = self.dialect.not(&ordered, vector);
+        self.assign(result_id, &value);
+        Ok(())
+    }
+
+    fn write_op(&mut self, op: &'a GroupCode) -> BackendResult<()> {
+        macro_rules! binary {
+            ($op: ident, $operator: expr, $signed: expr) => {
+                self.binary(&$op.result_type,
+                            &$op.result_id,
+                            &$op.operand1,
+                            &$op.operand2,
+                            $operator,
+                            $signed)
+            }
+        }
+        macro_rules! compare {
+            ($op: ident, $operator: expr, $function: expr, $signed: expr) => {
+                self.compare(&$op.result_type,
+                             &$op.result_id,
+                             (&$op.operand1, &$op.operand2),
+                             $operator,
+                             $function,
+                             $signed)
+            }
+        }
+        macro_rules! unordered {
+            ($op: ident, $operator: expr, $function: expr) => {
+                self.compare_unordered(&$op.result_type,
+                                       &$op.result_id,
+                                       &$op.operand1,
+                                       &$op.operand2,
+                                       $operator,
+                                       $function)
+            }
+        }
+
+        match *op {
+            GroupCode::OpNop(_) |
+            GroupCode::OpUndef(_) |
+            GroupCode::OpLine(_) |
+            GroupCode::OpNoLine(_) |
+            GroupCode::OpVariable(_) => Ok(()),
+            GroupCode::OpPhi(ref op) => {
+                let value = format!("{}_phi", self.program.name(op.result_id.0));
+                self.assign(&op.result_id, &value);
+                Ok(())
+            }
+            GroupCode::OpAccessChain(ref op) => {
+                let expr = try!(self.access_chain(op));
+                self.pointers.insert(op.result_id.0, expr);
+                Ok(())
+            }
+            GroupCode::OpLoad(ref op) => {
+                let pointer = try!(self.expr(&op.pointer));
+                self.assign(&op.result_id, &pointer);
+                Ok(())
+            }
+            GroupCode::OpStore(ref op) => {
+                let pointer = try!(self.expr(&op.pointer));
+                let object = try!(self.expr(&op.object));
+                self.line(&format!("{} = {};", pointer, object));
+                Ok(())
+            }
+            GroupCode::OpFunctionCall(ref op) => {
+                let mut args = Vec::new();
+                for arg in &op.arguments {
+                    let expr = try!(self.expr(arg));
+                    match try!(self.value_type(arg)) {
+                        Type::Pointer(_, _) => args.push(self.dialect.address_of(&expr)),
+                        _ => args.push(expr),
+                    }
+                }
+                if self.dialect.passes_globals() {
+                    if let Some(callee) = self.program.get_function(op.function.0) {
+                        for var in self.program.function_globals(callee) {
+                            let (ty, name) = try!(self.global_parameter(var));
+                            match ty {
+                                Type::Pointer(_, _) => args.push(self.dialect.address_of(&name)),
+                                _ => args.push(name),
+                            }
+                        }
+                    }
+                }
+                let call = format!("{}({})", self.program.name(op.function.0), args.join(", "));
+                if let Type::Void = *try!(self.program.get_type(&op.result_type)) {
+                    self.line(&format!("{};", call));
+                } else {
+                    self.assign(&op.result_id, &call);
+                }
+                Ok(())
+            }
+            GroupCode::OpExtInst(ref op) => {
+                let set = self.program.ext_inst_imports.get(&op.set.0).cloned();
+                let inst = op.instruction.as_ref().as_any().downcast_ref::<glsl450::Inst>();
+                let value = match (set, inst) {
+                    (Some("GLSL.std.450"), Some(glsl450::Inst::Sin(inst))) => {
+                        format!("sin({})", try!(self.expr(&inst.x)))
+                    }
+                    (Some("GLSL.std.450"), Some(glsl450::Inst::Cos(inst))) => {
+                        format!("cos({})", try!(self.expr(&inst.x)))
+                    }
+                    _ => return Err(BackendError::UnsupportedInstruction(format!("{}", op))),
+                };
+                self.assign(&op.result_id, &value);
+                Ok(())
+            }
+            GroupCode::OpConvertUToF(ref op) => {
+                let from = try!(self.value_type(&op.unsigned_value)).with_signedness(false);
+                let value = try!(self.expr_as(&op.unsigned_value, &from));
+                let ty = try!(self.program.get_type(&op.result_type)).clone();
+                let value = try!(self.dialect.cast(self.program, &ty, &value));
+                self.assign(&op.result_id, &value);
+                Ok(())
+            }
+            GroupCode::OpBitcast(ref op) => {
+                let from = try!(self.value_type(&op.operand));
+                let to = try!(self.program.get_type(&op.result_type)).clone();
+                let value = try!(self.expr(&op.operand));
+                let value = try!(self.dialect.bitcast(self.program, &from, &to, &value));
+                self.assign(&op.result_id, &value);
+                Ok(())
+            }
+            GroupCode::OpIAdd(ref op) => binary!(op, "+", None),
+            GroupCode::OpISub(ref op) => binary!(op, "-", None),
+            GroupCode::OpIMul(ref op) => binary!(op, "*", None),
+            GroupCode::OpUDiv(ref op) => binary!(op, "/", Some(false)),
+            GroupCode::OpSDiv(ref op) => binary!(op, "/", Some(true)),
+            GroupCode::OpUMod(ref op) => binary!(op, "%", Some(false)),
+            GroupCode::OpSRem(ref op) => {
+                let dialect = self.dialect;
+                self.signed_binary(&op.result_type,
+                                   &op.result_id,
+                                   &op.operand1,
+                                   &op.operand2,
+                                   &|a, b| dialect.srem(a, b))
+            }
+            GroupCode::OpSMod(ref op) => {
+                // Adding b to the remainder and taking the remainder again
+                // moves a result with the wrong sign into the range of b
+                let dialect = self.dialect;
+                self.signed_binary(&op.result_type,
+                                   &op.result_id,
+                                   &op.operand1,
+                                   &op.operand2,
+                                   &|a, b| {
+                                       let rem = dialect.srem(a, b);
+                                       dialect.srem(&format!("(({}) + {})", rem, b), b)
+                                   })
+            }
+            GroupCode::OpFAdd(ref op) => binary!(op, "+", None),
+            GroupCode::OpFSub(ref op) => binary!(op, "-", None),
+            GroupCode::OpFMul(ref op) => binary!(op, "*", None),
+            GroupCode::OpFDiv(ref op) => binary!(op, "/", None),
+            GroupCode::OpFRem(ref op) => {
+                let dialect = self.dialect;
+                self.float_binary(&op.result_id, &op.operand1, &op.operand2, &|a, b| {
+                    dialect.frem(a, b)
+                })
+            }
+            GroupCode::OpFMod(ref op) => {
+                let dialect = self.dialect;
+                self.float_binary(&op.result_id, &op.operand1, &op.operand2, &|a, b| {
+                    dialect.fmod(a, b)
+                })
+            }
+            GroupCode::OpBitwiseOr(ref op) => binary!(op, "|", None),
+            GroupCode::OpBitwiseXor(ref op) => binary!(op, "^", None),
+            GroupCode::OpBitwiseAnd(ref op) => binary!(op, "&", None),
+            GroupCode::OpIEqual(ref op) => compare!(op, "==", "equal", None),
+            GroupCode::OpINotEqual(ref op) => compare!(op, "!=", "notEqual", None),
+            GroupCode::OpUGreaterThan(ref op) => compare!(op, ">", "greaterThan", Some(false)),
+            GroupCode::OpSGreaterThan(ref op) => compare!(op, ">", "greaterThan", Some(true)),
+            GroupCode::OpUGreaterThanEqual(ref op) => {
+                compare!(op, ">=", "greaterThanEqual", Some(false))
+            }
+            GroupCode::OpSGreaterThanEqual(ref op) => {
+                compare!(op, ">=", "greaterThanEqual", Some(true))
+            }
+            GroupCode::OpULessThan(ref op) => compare!(op, "<", "lessThan", Some(false)),
+            GroupCode::OpSLessThan(ref op) => compare!(op, "<", "lessThan", Some(true)),
+            GroupCode::OpULessThanEqual(ref op) => compare!(op, "<=", "lessThanEqual", Some(false)),
+            GroupCode::OpSLessThanEqual(ref op) => compare!(op, "<=", "lessThanEqual", Some(true)),
+            GroupCode::OpFOrdEqual(ref op) => compare!(op, "==", "equal", None),
+            GroupCode::OpFUnordEqual(ref op) => {
+                let vector = try!(self.program.get_type(&op.result_type)).is_vector();
+                let dialect = self.dialect;
+                self.float_binary(&op.result_id, &op.operand1, &op.operand2, &|a, b| {
+                    dialect.unordered_equal(a, b, vector)
+                })
+            }
+            GroupCode::OpFOrdNotEqual(ref op) => {
+                let vector = try!(self.program.get_type(&op.result_type)).is_vector();
+                let dialect = self.dialect;
+                self.float_binary(&op.result_id, &op.operand1, &op.operand2, &|a, b| {
+                    dialect.ordered_not_equal(a, b, vector)
+                })
+            }
+            GroupCode::OpFUnordNotEqual(ref op) => compare!(op, "!=", "notEqual", None),
+            GroupCode::OpFOrdLessThan(ref op) => compare!(op, "<", "lessThan", None),
+            GroupCode::OpFOrdGreaterThan(ref op) => compare!(op, ">", "greaterThan", None),
+            GroupCode::OpFOrdLessThanEqual(ref op) => compare!(op, "<=", "lessThanEqual", None),
+            GroupCode::OpFOrdGreaterThanEqual(ref op) => {
+                compare!(op, ">=", "greaterThanEqual", None)
+            }
+            GroupCode::OpFUnordLessThan(ref op) => unordered!(op, ">=", "greaterThanEqual"),
+            GroupCode::OpFUnordGreaterThan(ref op) => unordered!(op, "<=", "lessThanEqual"),
+            GroupCode::OpFUnordLessThanEqual(ref op) => unordered!(op, ">", "greaterThan"),
+            GroupCode::OpFUnordGreaterThanEqual(ref op) => unordered!(op, "<", "lessThan"),
+            GroupCode::OpIAddCarry(_) |
+            GroupCode::OpISubBorrow(_) |
+            GroupCode::OpUMulExtended(_) |
+            GroupCode::OpSMulExtended(_) => {
+                Err(BackendError::UnsupportedInstruction(format!("{}", op)))
+            }
+        }
+    }
+}
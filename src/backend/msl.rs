@@ -0,0 +1,464 @@
+use std::collections::{HashMap, HashSet};
+use super::*;
+
+const KEYWORDS: &[&str] = &["alignas", "alignof", "and", "asm", "auto", "bool", "break", "case",
+                            "catch", "char", "class", "const", "constexpr", "const_cast",
+                            "constant", "continue", "decltype", "default", "delete", "device",
+                            "do", "double", "dynamic_cast", "else", "enum", "explicit",
+                            "export", "extern", "false", "float", "for", "fragment", "friend",
+                            "goto", "half", "if", "inline", "int", "kernel", "long", "main",
+                            "mutable", "namespace", "new", "noexcept", "not", "nullptr",
+                            "operator", "or", "private", "protected", "public", "register",
+                            "reinterpret_cast", "return", "sampler", "short", "signed",
+                            "sizeof", "static", "static_assert", "static_cast", "struct",
+                            "switch", "template", "texture", "this", "thread", "threadgroup",
+                            "throw", "true", "try", "typedef", "typeid", "typename", "uint",
+                            "union", "unsigned", "using", "vertex", "virtual", "void",
+                            "volatile", "while", "xor", "metal", "as_type", "fmod", "floor",
+                            "sin", "cos", "float2", "float3", "float4", "int2", "int3",
+                            "int4", "uint2", "uint3", "uint4", "bool2", "bool3", "bool4"];
+
+/// A resource binding in a descriptor set
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceBinding {
+    pub set: u32,
+    pub binding: u32,
+}
+
+/// The Metal argument table and index a resource is passed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MslArgument {
+    Buffer(u32),
+    Texture(u32),
+    Sampler(u32),
+}
+
+/// The syntax of the Metal Shading Language
+struct Msl {
+    /// The argument of each resource variable used by the entry point
+    arguments: HashMap<u32, MslArgument>,
+}
+
+/// Returns the attribute of a builtin kernel argument
+fn builtin_attribute(builtin: &BuiltIn) -> Option<&'static str> {
+    let attribute = match *builtin {
+        BuiltIn::GlobalInvocationId => "thread_position_in_grid",
+        BuiltIn::LocalInvocationId => "thread_position_in_threadgroup",
+        BuiltIn::WorkgroupId => "threadgroup_position_in_grid",
+        BuiltIn::NumWorkgroups => "threadgroups_per_grid",
+        BuiltIn::LocalInvocationIndex => "thread_index_in_threadgroup",
+        _ => return None,
+    };
+    Some(attribute)
+}
+
+/// Returns the address space of the value a pointer points to
+fn address_space(program: &Program, storage_class: &StorageClass, pointed: &Type)
+                 -> Option<&'static str> {
+    match *storage_class {
+        StorageClass::Function |
+        StorageClass::Private => Some("thread"),
+        StorageClass::Workgroup => Some("threadgroup"),
+        StorageClass::PushConstant => Some("constant"),
+        StorageClass::Uniform => {
+            match *pointed {
+                Type::Struct(id) if program.has_decoration(id, Decoration::Block) => {
+                    Some("constant")
+                }
+                _ => Some("device"),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the kind of argument a resource variable is passed as, with an
+/// index of zero
+fn resource_kind(storage_class: &StorageClass, pointed: &Type) -> Option<MslArgument> {
+    match (storage_class, pointed) {
+        (&StorageClass::Uniform, &Type::Struct(_)) |
+        (&StorageClass::PushConstant, &Type::Struct(_)) => Some(MslArgument::Buffer(0)),
+        (&StorageClass::UniformConstant, &Type::Image(_)) => Some(MslArgument::Texture(0)),
+        (&StorageClass::UniformConstant, &Type::Sampler) => Some(MslArgument::Sampler(0)),
+        _ => None,
+    }
+}
+
+impl Msl {
+    /// Declares a parameter that refers to a value in an address space
+    fn reference(&self, program: &Program, space: &str, ty: &Type, name: &str)
+                 -> BackendResult<String> {
+        let name = match *ty {
+            Type::Array(_, _) => format!("(&{})", name),
+            _ => format!("&{}", name),
+        };
+        Ok(format!("{} {}", space, try!(self.declaration(program, ty, &name))))
+    }
+
+    fn parameter(&self, program: &Program, ty: &Type, name: &str) -> BackendResult<String> {
+        match *ty {
+            Type::Pointer(ref storage_class, ref pointed) => {
+                match **pointed {
+                    Type::Image(_) |
+                    Type::Sampler => self.declaration(program, pointed, name),
+                    _ => {
+                        match address_space(program, storage_class, pointed) {
+                            Some(space) => self.reference(program, space, pointed, name),
+                            None => Err(BackendError::UnrepresentableType(ty.clone())),
+                        }
+                    }
+                }
+            }
+            _ => self.declaration(program, ty, name),
+        }
+    }
+}
+
+impl Dialect for Msl {
+    fn is_reserved(&self, name: &str) -> bool {
+        name.starts_with("gl_") || name.contains("__") || KEYWORDS.contains(&name)
+    }
+
+    /// Metal kernels can not be named `main`
+    fn entry_point_name(&self, name: &str) -> String {
+        format!("{}0", sanitize(name))
+    }
+
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String> {
+        let name = match *ty {
+            Type::Void => "void".into(),
+            Type::Bool => "bool".into(),
+            Type::Int(64, false) => "ulong".into(),
+            Type::Int(64, true) => "long".into(),
+            Type::Int(32, false) => "uint".into(),
+            Type::Int(32, true) => "int".into(),
+            Type::Int(16, false) => "ushort".into(),
+            Type::Int(16, true) => "short".into(),
+            Type::Float(16) => "half".into(),
+            Type::Float(32) => "float".into(),
+            Type::Vector(ref component, count) => {
+                match **component {
+                    Type::Vector(_, _) => {
+                        return Err(BackendError::UnrepresentableType(ty.clone()))
+                    }
+                    _ => format!("{}{}", try!(self.type_name(program, component)), count),
+                }
+            }
+            Type::Matrix(ref column, columns) => {
+                match **column {
+                    Type::Vector(ref component, rows) => {
+                        format!("{}{}x{}", try!(self.type_name(program, component)), columns, rows)
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                }
+            }
+            Type::Struct(id) => program.name(id),
+            Type::Image(id) => {
+                let image = program.get_image(id);
+                let sampled = try!(program.get_type(&image.sampled_type));
+                let depth = image.depth == DepthStatus::Depth;
+                let kind = match image.dim {
+                    Dim::Tex1D => "1d",
+                    Dim::Tex2D => "2d",
+                    Dim::Tex3D => "3d",
+                    Dim::Cube => "cube",
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                };
+                let mut name = format!("{}{}", if depth { "depth" } else { "texture" }, kind);
+                if image.arrayed == Arrayed::True {
+                    name.push_str("_array");
+                }
+                if image.ms == MS::Multi {
+                    name.push_str("_ms");
+                }
+                let access = match image.sampled {
+                    SampledStatus::WithoutSampler => ", access::read_write",
+                    _ => "",
+                };
+                format!("{}<{}{}>", name, try!(self.type_name(program, sampled)), access)
+            }
+            Type::Sampler => "sampler".into(),
+            _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+        };
+        Ok(name)
+    }
+
+    /// Runtime arrays are declared with a single element, and accessed past
+    /// the end of the declaration
+    fn declaration(&self, program: &Program, ty: &Type, name: &str) -> BackendResult<String> {
+        let mut sizes = String::new();
+        let mut ty = ty;
+        loop {
+            match *ty {
+                Type::Array(ref element, length) => {
+                    sizes.push_str(&format!("[{}]", length));
+                    ty = element;
+                }
+                Type::RuntimeArray(ref element) => {
+                    sizes.push_str("[1]");
+                    ty = element;
+                }
+                _ => break,
+            }
+        }
+        Ok(format!("{} {}{}", try!(self.type_name(program, ty)), name, sizes))
+    }
+
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(try!(self.parameter(program, ty, name)));
+        }
+        Ok(format!("{} {}({})",
+                   try!(self.type_name(program, return_type)),
+                   name,
+                   params.join(", ")))
+    }
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String {
+        match (width, signed) {
+            (64, false) => format!("{}ul", value),
+            (64, true) if value as i64 == i64::MIN => "(-9223372036854775807l - 1l)".into(),
+            (64, true) => format!("{}l", value as i64),
+            (_, false) => format!("{}u", value as u32),
+            (_, true) if value as i32 == i32::MIN => "(-2147483647 - 1)".into(),
+            (_, true) => format!("{}", value as i32),
+        }
+    }
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String> {
+        match (format_float(bits, width), width) {
+            (_, 64) => Err(BackendError::UnrepresentableType(Type::Float(64))),
+            (Some(value), 16) => Ok(format!("{}h", value)),
+            (Some(value), _) => Ok(format!("{}f", value)),
+            (None, _) => Ok(format!("as_type<float>(0x{:08x}u)", bits as u32)),
+        }
+    }
+
+    /// Structs are built with aggregate initialization
+    fn construct(&self, program: &Program, ty: &Type, args: &[String]) -> BackendResult<String> {
+        match *ty {
+            Type::Struct(_) => {
+                Ok(format!("{}{{{}}}", try!(self.type_name(program, ty)), args.join(", ")))
+            }
+            Type::Array(_, _) => Err(BackendError::UnrepresentableType(ty.clone())),
+            _ => Ok(format!("{}({})", try!(self.type_name(program, ty)), args.join(", "))),
+        }
+    }
+
+    fn bitcast(&self, program: &Program, _: &Type, to: &Type, expr: &str)
+               -> BackendResult<String> {
+        Ok(format!("as_type<{}>({})", try!(self.type_name(program, to)), expr))
+    }
+
+    fn frem(&self, a: &str, b: &str) -> String {
+        format!("fmod({}, {})", a, b)
+    }
+
+    fn builtin(&self, builtin: &BuiltIn) -> Option<String> {
+        let name = match *builtin {
+            BuiltIn::GlobalInvocationId => "gl_GlobalInvocationID",
+            BuiltIn::LocalInvocationId => "gl_LocalInvocationID",
+            BuiltIn::WorkgroupId => "gl_WorkGroupID",
+            BuiltIn::NumWorkgroups => "gl_NumWorkGroups",
+            BuiltIn::LocalInvocationIndex => "gl_LocalInvocationIndex",
+            _ => return None,
+        };
+        Some(name.into())
+    }
+
+    fn passes_globals(&self) -> bool {
+        true
+    }
+
+    /// Resources and builtin variables are arguments of the kernel
+    fn entry_point_header(&self,
+                          program: &Program,
+                          name: &str,
+                          return_type: &Type,
+                          parameters: &[(Type, String)])
+                          -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(try!(self.parameter(program, ty, name)));
+        }
+        for var in program.used_variables() {
+            let id = var.result_id.0;
+            let ty = try!(program.get_type(&var.result_type));
+            if let Some(argument) = self.arguments.get(&id) {
+                let declaration = try!(self.parameter(program, ty, &program.name(id)));
+                params.push(match *argument {
+                    MslArgument::Buffer(index) => format!("{} [[buffer({})]]", declaration, index),
+                    MslArgument::Texture(index) => {
+                        format!("{} [[texture({})]]", declaration, index)
+                    }
+                    MslArgument::Sampler(index) => {
+                        format!("{} [[sampler({})]]", declaration, index)
+                    }
+                });
+            } else if let Some(builtin) = program.builtin(id) {
+                let (attribute, name) = match (builtin_attribute(builtin), self.builtin(builtin)) {
+                    (Some(attribute), Some(name)) => (attribute, name),
+                    _ => return Err(BackendError::UnsupportedBuiltIn(builtin.clone())),
+                };
+                let pointed = match *ty {
+                    Type::Pointer(_, ref pointed) => pointed,
+                    _ => return Err(BackendError::InvalidId(OpId(id))),
+                };
+                params.push(format!("{} [[{}]]",
+                                    try!(self.declaration(program, pointed, &name)),
+                                    attribute));
+            }
+        }
+        Ok(format!("kernel {} {}({})",
+                   try!(self.type_name(program, return_type)),
+                   name,
+                   params.join(", ")))
+    }
+
+    /// Workgroup and private variables are declared in the kernel, and
+    /// passed to the functions that use them
+    fn entry_point_prologue(&self, program: &Program) -> BackendResult<Vec<String>> {
+        let mut statements = Vec::new();
+        for var in program.used_variables() {
+            let id = var.result_id.0;
+            let (storage_class, pointed) = match *try!(program.get_type(&var.result_type)) {
+                Type::Pointer(ref storage_class, ref pointed) => (storage_class.clone(), pointed),
+                _ => return Err(BackendError::InvalidId(OpId(id))),
+            };
+            let declaration = try!(self.declaration(program, pointed, &program.name(id)));
+            match storage_class {
+                StorageClass::Workgroup => statements.push(format!("threadgroup {};", declaration)),
+                StorageClass::Private => {
+                    statements.push(match var.initializer {
+                        Some(ref initializer) => {
+                            format!("{} = {};",
+                                    declaration,
+                                    try!(program.constant_expr(self, initializer)))
+                        }
+                        None => format!("{};", declaration),
+                    })
+                }
+                _ if self.arguments.contains_key(&id) || program.builtin(id).is_some() => {}
+                _ => return Err(BackendError::UnsupportedStorageClass(OpId(id), storage_class)),
+            }
+        }
+        Ok(statements)
+    }
+}
+
+/// Finds the argument index of each resource used by the entry point
+///
+/// Resources with a binding in the table use the argument it is mapped to.
+/// Other resources use the lowest index of their kind that is not used by
+/// another resource, in the order they are defined.
+fn assign_arguments(program: &Program,
+                    bindings: &HashMap<ResourceBinding, MslArgument>)
+                    -> BackendResult<HashMap<u32, MslArgument>> {
+    let mut arguments = HashMap::new();
+    let mut unmapped = Vec::new();
+    for var in program.used_variables() {
+        let id = var.result_id.0;
+        let kind = match *try!(program.get_type(&var.result_type)) {
+            Type::Pointer(ref storage_class, ref pointed) => resource_kind(storage_class, pointed),
+            _ => return Err(BackendError::InvalidId(OpId(id))),
+        };
+        let kind = match kind {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let set = program.decoration_value(id, |d| match *d {
+            Decoration::DescriptorSet(set) => Some(set),
+            _ => None,
+        });
+        let binding = program.decoration_value(id, |d| match *d {
+            Decoration::Binding(binding) => Some(binding),
+            _ => None,
+        });
+        let binding = binding.map(|binding| {
+            ResourceBinding {
+                set: set.unwrap_or(0),
+                binding,
+            }
+        });
+        match binding.and_then(|binding| bindings.get(&binding)) {
+            Some(argument) => {
+                arguments.insert(id, *argument);
+            }
+            None => unmapped.push((id, kind)),
+        }
+    }
+
+    let mut used = arguments.values().cloned().collect::<HashSet<_>>();
+    for (id, kind) in unmapped {
+        let argument = (0..)
+            .map(|index| match kind {
+                MslArgument::Buffer(_) => MslArgument::Buffer(index),
+                MslArgument::Texture(_) => MslArgument::Texture(index),
+                MslArgument::Sampler(_) => MslArgument::Sampler(index),
+            })
+            .find(|argument| !used.contains(argument))
+            .expect("ran out of argument indexes");
+        used.insert(argument);
+        arguments.insert(id, argument);
+    }
+    Ok(arguments)
+}
+
+/// Generates Metal Shading Language source code for a compute entry point
+/// of a module
+///
+/// The entry point becomes a `kernel` function. Uniform and storage blocks
+/// are passed to it as `constant` and `device` references, and images and
+/// samplers as textures and samplers. The argument index of each resource is
+/// looked up by its DescriptorSet and Binding in `bindings`, and resources
+/// that are not in the table take the next free index. Compute builtins such
+/// as GlobalInvocationId are arguments with attributes such as
+/// `[[thread_position_in_grid]]`.
+///
+/// Metal has no mutable variables at global scope, so workgroup and private
+/// variables are declared in the kernel and passed by reference to the
+/// functions that use them, along with the resources and builtins. The
+/// threadgroup size is chosen when the kernel is dispatched, so the
+/// LocalSize execution mode is not used.
+pub fn generate_msl(module: &LogicalModule,
+                    entry_point: &str,
+                    bindings: &HashMap<ResourceBinding, MslArgument>)
+                    -> BackendResult<String> {
+    let mut dialect = Msl { arguments: HashMap::new() };
+    let program = try!(Program::new(module, entry_point, &dialect));
+    if program.entry_point.execution_model != ExecutionModel::GlCompute {
+        let model = program.entry_point.execution_model.clone();
+        return Err(BackendError::UnsupportedExecutionModel(model));
+    }
+    dialect.arguments = try!(assign_arguments(&program, bindings));
+
+    let mut output = String::from("#include <metal_stdlib>\n#include <simd/simd.h>\n\n\
+                                   using namespace metal;\n\n");
+    for id in program.struct_ids() {
+        if program.is_builtin_block(id) {
+            continue;
+        }
+        output.push_str(&format!("struct {} {{\n", program.name(id)));
+        for (member, member_type) in program.struct_members(id).iter().enumerate() {
+            let ty = try!(program.get_type(member_type));
+            let name = program.member_name(id, member as u32);
+            output.push_str(&format!("    {};\n", try!(dialect.declaration(&program, ty, &name))));
+        }
+        output.push_str("};\n\n");
+    }
+
+    let functions = program.reachable_functions();
+    for (index, function) in functions.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str(&try!(FunctionWriter::write(&program, &dialect, function)));
+    }
+    Ok(output)
+}
@@ -0,0 +1,939 @@
+use std::mem;
+use super::*;
+
+const KEYWORDS: &[&str] = &["if", "else", "while", "break", "continue", "return", "true",
+                            "false", "void", "bool", "int", "uint", "float", "double"];
+
+/// Binding strength of expressions, from the loosest to the tightest
+const BIT_OR: u8 = 1;
+const BIT_XOR: u8 = 2;
+const BIT_AND: u8 = 3;
+const EQUALITY: u8 = 4;
+const RELATIONAL: u8 = 5;
+const ADDITIVE: u8 = 6;
+const MULTIPLICATIVE: u8 = 7;
+const UNARY: u8 = 8;
+const ATOM: u8 = 9;
+
+/// The syntax of pseudo-code, which is C with the type names of HLSL and
+/// plain literals
+struct Pseudo;
+
+impl Dialect for Pseudo {
+    fn is_reserved(&self, name: &str) -> bool {
+        KEYWORDS.contains(&name)
+    }
+
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String> {
+        let name = match *ty {
+            Type::Void => "void".into(),
+            Type::Bool => "bool".into(),
+            Type::Int(32, false) => "uint".into(),
+            Type::Int(32, true) => "int".into(),
+            Type::Int(width, false) => format!("uint{}", width),
+            Type::Int(width, true) => format!("int{}", width),
+            Type::Float(16) => "half".into(),
+            Type::Float(32) => "float".into(),
+            Type::Float(64) => "double".into(),
+            Type::Float(width) => format!("float{}", width),
+            Type::Vector(ref component, count) => {
+                format!("{}{}", try!(self.type_name(program, component)), count)
+            }
+            Type::Matrix(ref column, columns) => {
+                match **column {
+                    Type::Vector(ref component, rows) => {
+                        format!("{}{}x{}", try!(self.type_name(program, component)), columns, rows)
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                }
+            }
+            Type::Array(ref element, length) => {
+                format!("{}[{}]", try!(self.type_name(program, element)), length)
+            }
+            Type::RuntimeArray(ref element) => {
+                format!("{}[]", try!(self.type_name(program, element)))
+            }
+            Type::Struct(id) |
+            Type::Image(id) => program.name(id),
+            Type::Pointer(_, ref pointed) => format!("{}*", try!(self.type_name(program, pointed))),
+            Type::Sampler => "sampler".into(),
+        };
+        Ok(name)
+    }
+
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(match *ty {
+                Type::Pointer(_, ref pointed) => {
+                    format!("inout {}", try!(self.declaration(program, pointed, name)))
+                }
+                _ => try!(self.declaration(program, ty, name)),
+            });
+        }
+        Ok(format!("{} {}({})",
+                   try!(self.type_name(program, return_type)),
+                   name,
+                   params.join(", ")))
+    }
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String {
+        let shift = 64 - width.clamp(1, 64);
+        if signed {
+            format!("{}", ((value << shift) as i64) >> shift)
+        } else {
+            format!("{}", (value << shift) >> shift)
+        }
+    }
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String> {
+        Ok(match format_float(bits, width) {
+            Some(value) => value,
+            None => format!("bitcast<float{}>(0x{:x})", width, bits),
+        })
+    }
+
+    fn bitcast(&self, program: &Program, _: &Type, to: &Type, expr: &str)
+               -> BackendResult<String> {
+        Ok(format!("bitcast<{}>({})", try!(self.type_name(program, to)), expr))
+    }
+
+    fn frem(&self, a: &str, b: &str) -> String {
+        format!("frem({}, {})", a, b)
+    }
+
+    fn fmod(&self, a: &str, b: &str) -> String {
+        format!("fmod({}, {})", a, b)
+    }
+
+    fn builtin(&self, _: &BuiltIn) -> Option<String> {
+        None
+    }
+
+    /// Members of blocks without a name are written on their own, as in
+    /// GLSL
+    fn block_member(&self, program: &Program, variable: u32, struct_id: u32, member: u32)
+                    -> String {
+        let name = program.name(variable);
+        if is_generated(&name) {
+            program.member_name(struct_id, member)
+        } else {
+            format!("{}.{}", name, program.member_name(struct_id, member))
+        }
+    }
+}
+
+/// Generates structured pseudo-code for the functions called from an entry
+/// point, for reading what a module does
+///
+/// The loops and selections found by `find_control_flow` are written as
+/// `while` and `if` statements, and values that are used once in the block
+/// that defines them are folded into the expression using them, so a loop
+/// reads like `while (i < n) { x = x * y; i += 1; }`. Values and variables
+/// are named by their OpName.
+///
+/// The result is not meant to be compiled: integer signedness is only shown
+/// in declarations, ordered and unordered float comparisons are written
+/// alike, and phis are assigned in the blocks that branch to them.
+pub fn generate_pseudo_code(module: &LogicalModule, entry_point: &str) -> BackendResult<String> {
+    let dialect = Pseudo;
+    let program = try!(Program::new(module, entry_point, &dialect));
+    let mut output = String::new();
+    for (index, function) in program.reachable_functions().iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str(&try!(PseudoWriter::write(&program, function)));
+    }
+    Ok(output)
+}
+
+/// An expression with the binding strength of its outermost operator
+struct Expr {
+    text: String,
+    precedence: u8,
+    /// If the expression reads memory, so it can not be moved past a store
+    loads: bool,
+}
+
+impl Expr {
+    fn atom(text: String) -> Expr {
+        Expr {
+            text,
+            precedence: ATOM,
+            loads: false,
+        }
+    }
+
+    /// Writes the expression as an operand of an operator, in parentheses if
+    /// it binds looser than `precedence`
+    fn operand(&self, precedence: u8) -> String {
+        if self.precedence < precedence {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    fn binary(self, operator: &str, other: Expr, precedence: u8) -> Expr {
+        Expr {
+            text: format!("{} {} {}",
+                          self.operand(precedence),
+                          operator,
+                          other.operand(precedence + 1)),
+            precedence,
+            loads: self.loads || other.loads,
+        }
+    }
+
+    fn not(self) -> Expr {
+        Expr {
+            text: format!("!{}", self.operand(UNARY)),
+            precedence: UNARY,
+            loads: self.loads,
+        }
+    }
+
+    fn call(function: &str, args: Vec<Expr>) -> Expr {
+        let loads = args.iter().any(|arg| arg.loads);
+        let args = args.into_iter().map(|arg| arg.text).collect::<Vec<_>>();
+        Expr {
+            text: format!("{}({})", function, args.join(", ")),
+            precedence: ATOM,
+            loads,
+        }
+    }
+}
+
+/// Returns if text is a number, for writing `x += 1`
+fn is_literal(text: &str) -> bool {
+    let digits = text.trim_start_matches('-');
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// A phi with its type and the value it is assigned
+type PhiCopy<'a> = (u32, &'a OpId, Expr);
+
+/// Writes a function as pseudo-code with folded expressions
+struct PseudoWriter<'a: 'b, 'b> {
+    program: &'b Program<'a>,
+    function: &'a FunctionDefinition,
+    blocks: HashMap<u32, &'a BasicBlock>,
+    /// The types of the values defined in the function
+    value_types: HashMap<u32, &'a OpId>,
+    /// Access chains, which are written into the instructions using them
+    chains: HashMap<u32, &'a OpAccessChain>,
+    /// The values written into the expression of their only use
+    folded: HashSet<u32>,
+    /// The values declared at the start of the function, as they are used
+    /// outside of the block that defines them
+    declared: HashSet<u32>,
+    /// The expressions of folded values of the current block that are not
+    /// used yet, in the order they are defined
+    pending: Vec<(u32, Expr)>,
+    output: String,
+    indent: usize,
+}
+
+impl<'a: 'b, 'b> PseudoWriter<'a, 'b> {
+    fn write(program: &'b Program<'a>, function: &'a FunctionDefinition) -> BackendResult<String> {
+        let mut writer = PseudoWriter {
+            program,
+            function,
+            blocks: HashMap::new(),
+            value_types: HashMap::new(),
+            chains: HashMap::new(),
+            folded: HashSet::new(),
+            declared: HashSet::new(),
+            pending: Vec::new(),
+            output: String::new(),
+            indent: 0,
+        };
+        for param in &function.parameters {
+            writer.value_types.insert(param.result_id.0, &param.result_type);
+        }
+        for block in &function.blocks {
+            writer.blocks.insert(block.label.result_id.0, block);
+            for op in &block.code {
+                if let (Some(result_id), Some(result_type)) = (op.result_id(), op.result_type()) {
+                    writer.value_types.insert(result_id.0, result_type);
+                }
+                if let GroupCode::OpAccessChain(ref op) = *op {
+                    writer.chains.insert(op.result_id.0, op);
+                }
+            }
+        }
+        writer.find_folded();
+        try!(writer.write_function());
+        Ok(writer.output)
+    }
+
+    /// Finds the values to fold and the values to declare from the blocks
+    /// that use them
+    ///
+    /// A value is folded when it has no side effects and is used once in
+    /// the block that defines it. The indexes of an access chain are only
+    /// folded when the chain itself is, as the chain is written again at
+    /// every use.
+    fn find_folded(&mut self) {
+        let mut uses = HashMap::<u32, Vec<u32>>::new();
+        for block in &self.function.blocks {
+            let label = block.label.result_id.0;
+            for op in &block.code {
+                match *op {
+                    GroupCode::OpPhi(ref phi) => {
+                        for arg in &phi.variables {
+                            uses.entry(arg.variable.0).or_default().push(arg.parent.0);
+                        }
+                    }
+                    _ => {
+                        for id in op.operands() {
+                            uses.entry(id.0).or_default().push(label);
+                        }
+                    }
+                }
+            }
+            for id in block.branch.operands() {
+                uses.entry(id.0).or_default().push(label);
+            }
+        }
+        let local = |uses: Option<&Vec<u32>>, label: u32| match uses {
+            Some(uses) => uses.iter().all(|block| *block == label),
+            None => true,
+        };
+
+        let mut pinned = HashSet::new();
+        for block in &self.function.blocks {
+            let label = block.label.result_id.0;
+            for op in &block.code {
+                if let GroupCode::OpAccessChain(ref chain) = *op {
+                    let chain_uses = uses.get(&chain.result_id.0);
+                    if chain_uses.map_or(0, |uses| uses.len()) > 1 || !local(chain_uses, label) {
+                        pinned.extend(chain.indexes.iter().map(|id| id.0));
+                    }
+                }
+            }
+        }
+        for block in &self.function.blocks {
+            let label = block.label.result_id.0;
+            for op in &block.code {
+                let id = match op.result_id() {
+                    Some(id) => id.0,
+                    None => continue,
+                };
+                let value_uses = uses.get(&id);
+                let foldable = match *op {
+                    GroupCode::OpNop(_) |
+                    GroupCode::OpVariable(_) |
+                    GroupCode::OpAccessChain(_) |
+                    GroupCode::OpFunctionCall(_) |
+                    GroupCode::OpPhi(_) |
+                    GroupCode::OpStore(_) => false,
+                    _ => !pinned.contains(&id),
+                };
+                if foldable && value_uses.map_or(0, |uses| uses.len()) <= 1 &&
+                   local(value_uses, label) {
+                    self.folded.insert(id);
+                } else {
+                    let phi = match *op {
+                        GroupCode::OpPhi(_) => true,
+                        _ => false,
+                    };
+                    if phi || !local(value_uses, label) {
+                        self.declared.insert(id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    /// Writes statements one level deeper into a separate string
+    fn nested<F>(&mut self, write: F) -> BackendResult<String>
+        where F: FnOnce(&mut Self) -> BackendResult<()>
+    {
+        let outer = mem::take(&mut self.output);
+        self.indent += 1;
+        let result = write(self);
+        self.indent -= 1;
+        let inner = mem::replace(&mut self.output, outer);
+        result.map(|()| inner)
+    }
+
+    fn write_function(&mut self) -> BackendResult<()> {
+        let id = OpId(self.function.function.result_id.0);
+        let chain = match find_control_flow(self.function.clone()) {
+            Ok(chain) => chain,
+            Err(err) => return Err(BackendError::ControlFlow(id, err)),
+        };
+
+        let mut parameters = Vec::new();
+        for param in &self.function.parameters {
+            let ty = try!(self.program.get_type(&param.result_type)).clone();
+            parameters.push((ty, self.program.name(param.result_id.0)));
+        }
+        let return_type = try!(self.program.get_type(&self.function.function.result_type));
+        let header = try!(Pseudo.function_header(self.program,
+                                                 &self.program.name(id.0),
+                                                 return_type,
+                                                 &parameters));
+        self.line(&format!("{} {{", header));
+        self.indent += 1;
+        try!(self.write_declarations());
+        try!(self.write_chain(&chain));
+        self.indent -= 1;
+        if *return_type == Type::Void && self.output.ends_with("\n    return;\n") {
+            let end = self.output.len() - "    return;\n".len();
+            self.output.truncate(end);
+        }
+        self.line("}");
+        Ok(())
+    }
+
+    /// Declares the variables of the function and the values that are used
+    /// outside of the block that defines them
+    fn write_declarations(&mut self) -> BackendResult<()> {
+        let function = self.function;
+        for block in &function.blocks {
+            for op in &block.code {
+                let (result_id, result_type) = match (op.result_id(), op.result_type()) {
+                    (Some(result_id), Some(result_type)) => (result_id.0, result_type),
+                    _ => continue,
+                };
+                let name = self.program.name(result_id);
+                let (ty, initializer) = match *op {
+                    GroupCode::OpVariable(ref var) => {
+                        let ty = match *try!(self.program.get_type(result_type)) {
+                            Type::Pointer(_, ref pointed) => (**pointed).clone(),
+                            _ => return Err(BackendError::UnsupportedType(result_type.clone())),
+                        };
+                        let initializer = match var.initializer {
+                            Some(ref id) => Some(try!(self.take(id)).text),
+                            None => None,
+                        };
+                        (ty, initializer)
+                    }
+                    _ if self.declared.contains(&result_id) => {
+                        (try!(self.program.get_type(result_type)).clone(), None)
+                    }
+                    _ => continue,
+                };
+                let local = try!(Pseudo.local(self.program, &ty, &name, initializer.as_deref()));
+                self.line(&format!("{};", local));
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&self, id: u32) -> BackendResult<&'a BasicBlock> {
+        self.blocks.get(&id).cloned().ok_or(BackendError::InvalidId(OpId(id)))
+    }
+
+    fn conditional(&self, block: &'a BasicBlock) -> BackendResult<&'a OpBranchConditional> {
+        match block.branch {
+            GroupBranch::OpBranchConditional(ref op) => Ok(op),
+            _ => Err(BackendError::InvalidId(OpId(block.label.result_id.0))),
+        }
+    }
+
+    fn write_chain(&mut self, chain: &ControlFlowChain) -> BackendResult<()> {
+        match *chain {
+            ControlFlowChain::Atom(id) => try!(self.write_block(OpId::from(id).0)),
+            ControlFlowChain::Block(ref chains) => {
+                for chain in chains {
+                    try!(self.write_chain(chain));
+                }
+            }
+            ControlFlowChain::Selection(id, ref true_chain, ref false_chain, _, _) => {
+                let block = try!(self.block(OpId::from(id).0));
+                try!(self.write_code(block));
+                let op = try!(self.conditional(block));
+                let condition = try!(self.take(&op.condition));
+                let exits = match **false_chain {
+                    ControlFlowChain::Break |
+                    ControlFlowChain::Continue => true,
+                    _ => false,
+                };
+                if exits {
+                    try!(self.write_exit(condition, block, op, false_chain));
+                    try!(self.write_phi_copies(block, &op.true_label));
+                    try!(self.write_chain(true_chain));
+                } else {
+                    // The values of both edges are taken before either arm is
+                    // written, as writing a block drops the folded values left
+                    // over from this one
+                    let true_copies = try!(self.phi_copies(block, &op.true_label));
+                    let false_copies = try!(self.phi_copies(block, &op.false_label));
+                    let true_arm = try!(self.nested(|w| {
+                        try!(w.write_copies(true_copies));
+                        w.write_chain(true_chain)
+                    }));
+                    let false_arm = try!(self.nested(|w| {
+                        try!(w.write_copies(false_copies));
+                        w.write_chain(false_chain)
+                    }));
+                    if true_arm.is_empty() && false_arm.is_empty() {
+                        return Ok(());
+                    }
+                    if true_arm.is_empty() {
+                        self.line(&format!("if ({}) {{", condition.not().text));
+                        self.output.push_str(&false_arm);
+                    } else {
+                        self.line(&format!("if ({}) {{", condition.text));
+                        self.output.push_str(&true_arm);
+                        if !false_arm.is_empty() {
+                            self.line("} else {");
+                            self.output.push_str(&false_arm);
+                        }
+                    }
+                    self.line("}");
+                }
+            }
+            ControlFlowChain::Loop(id, ref inner, _, _) => {
+                let block = try!(self.block(OpId::from(id).0));
+                let start = self.output.len();
+                self.line(&format!("{} {{", Pseudo.loop_start()));
+                self.indent += 1;
+                let body = self.output.len();
+                try!(self.write_code(block));
+                if let GroupBranch::OpBranch(ref op) = block.branch {
+                    try!(self.write_phi_copies(block, &op.target_label));
+                }
+                match **inner {
+                    // A loop that starts by leaving when a condition is false
+                    // is written with the condition in the while statement
+                    ControlFlowChain::Selection(id, ref true_chain, ref false_chain, _, _)
+                        if **false_chain == ControlFlowChain::Break => {
+                        let block = try!(self.block(OpId::from(id).0));
+                        try!(self.write_code(block));
+                        let op = try!(self.conditional(block));
+                        let condition = try!(self.take(&op.condition));
+                        if self.output.len() == body &&
+                           !self.has_phi_copies(block, &op.false_label) {
+                            self.output.truncate(start);
+                            self.indent -= 1;
+                            self.line(&format!("while ({}) {{", condition.text));
+                            self.indent += 1;
+                        } else {
+                            try!(self.write_exit(condition, block, op, false_chain));
+                        }
+                        try!(self.write_phi_copies(block, &op.true_label));
+                        try!(self.write_chain(true_chain));
+                    }
+                    _ => try!(self.write_chain(inner)),
+                }
+                self.indent -= 1;
+                self.line("}");
+            }
+            ControlFlowChain::Break => self.line("break;"),
+            ControlFlowChain::Continue => self.line("continue;"),
+        }
+        Ok(())
+    }
+
+    /// Writes the selection that leaves a loop early when a condition is
+    /// false
+    fn write_exit(&mut self,
+                  condition: Expr,
+                  block: &'a BasicBlock,
+                  op: &OpBranchConditional,
+                  exit: &ControlFlowChain)
+                  -> BackendResult<()> {
+        self.line(&format!("if ({}) {{", condition.not().text));
+        self.indent += 1;
+        try!(self.write_phi_copies(block, &op.false_label));
+        try!(self.write_chain(exit));
+        self.indent -= 1;
+        self.line("}");
+        Ok(())
+    }
+
+    /// Writes a block that does not start a selection or loop
+    fn write_block(&mut self, id: u32) -> BackendResult<()> {
+        let block = try!(self.block(id));
+        try!(self.write_code(block));
+        match block.branch {
+            GroupBranch::OpBranch(ref op) => {
+                try!(self.write_phi_copies(block, &op.target_label));
+            }
+            GroupBranch::OpReturn(_) => self.line("return;"),
+            GroupBranch::OpReturnValue(ref op) => {
+                let value = try!(self.take(&op.value));
+                self.line(&format!("return {};", value.text));
+            }
+            GroupBranch::OpBranchConditional(_) => return Err(BackendError::InvalidId(OpId(id))),
+        }
+        Ok(())
+    }
+
+    fn has_phi_copies(&self, from: &BasicBlock, to: &OpId) -> bool {
+        let parent = OpId(from.label.result_id.0);
+        match self.blocks.get(&to.0) {
+            Some(target) => {
+                target.code.iter().any(|op| match *op {
+                    GroupCode::OpPhi(ref phi) => {
+                        phi.variables.iter().any(|arg| arg.parent == parent)
+                    }
+                    _ => false,
+                })
+            }
+            None => false,
+        }
+    }
+
+    /// Assigns the phis of a block when it is branched to from another
+    /// block
+    fn write_phi_copies(&mut self, from: &BasicBlock, to: &OpId) -> BackendResult<()> {
+        let copies = try!(self.phi_copies(from, to));
+        self.write_copies(copies)
+    }
+
+    /// Returns the phis of a block with the values they take when it is
+    /// branched to from another block
+    fn phi_copies(&mut self, from: &BasicBlock, to: &OpId) -> BackendResult<Vec<PhiCopy<'a>>> {
+        let target = try!(self.block(to.0));
+        let parent = OpId(from.label.result_id.0);
+        let mut copies = Vec::new();
+        for op in &target.code {
+            if let GroupCode::OpPhi(ref phi) = *op {
+                if let Some(arg) = phi.variables.iter().find(|arg| arg.parent == parent) {
+                    let value = try!(self.take(&arg.variable));
+                    copies.push((phi.result_id.0, &phi.result_type, value));
+                }
+            }
+        }
+        Ok(copies)
+    }
+
+    /// Assigns phis their values
+    ///
+    /// Every value is read before any phi is assigned, through temporaries
+    /// if a value reads a phi assigned before it.
+    fn write_copies(&mut self, copies: Vec<PhiCopy<'a>>) -> BackendResult<()> {
+        let overlaps = copies.iter().enumerate().any(|(index, (_, _, value))| {
+            copies[..index].iter().any(|&(phi, _, _)| {
+                value.text.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .any(|word| word == self.program.name(phi))
+            })
+        });
+        if overlaps {
+            let mut temporaries = Vec::new();
+            for (phi, result_type, value) in copies {
+                let name = format!("{}_next", self.program.name(phi));
+                let ty = try!(self.program.get_type(result_type));
+                let local = try!(Pseudo.local(self.program, ty, &name, Some(&value.text)));
+                self.line(&format!("{};", local));
+                temporaries.push((phi, Expr::atom(name)));
+            }
+            for (phi, value) in temporaries {
+                let name = self.program.name(phi);
+                self.assign(&name, value);
+            }
+        } else {
+            for (phi, _, value) in copies {
+                let name = self.program.name(phi);
+                self.assign(&name, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_code(&mut self, block: &'a BasicBlock) -> BackendResult<()> {
+        // Folded values left over from the previous block are never used
+        self.pending.clear();
+        for op in &block.code {
+            try!(self.write_op(op));
+        }
+        Ok(())
+    }
+
+    /// Writes an assignment, as `x += 1` when adding a number to itself
+    fn assign(&mut self, target: &str, value: Expr) {
+        if value.precedence == ADDITIVE {
+            for operator in &["+", "-"] {
+                let prefix = format!("{} {} ", target, operator);
+                if value.text.starts_with(&prefix) && is_literal(&value.text[prefix.len()..]) {
+                    let amount = &value.text[prefix.len()..];
+                    self.line(&format!("{} {}= {};", target, operator, amount));
+                    return;
+                }
+            }
+        }
+        self.line(&format!("{} = {};", target, value.text));
+    }
+
+    /// Returns the expression for an id, using up the expression of a folded
+    /// value
+    fn take(&mut self, id: &OpId) -> BackendResult<Expr> {
+        if let Some(position) = self.pending.iter().position(|pending| pending.0 == id.0) {
+            return Ok(self.pending.remove(position).1);
+        }
+        if let Some(chain) = self.chains.get(&id.0).cloned() {
+            return self.access_chain(chain);
+        }
+        if self.program.get_constant(id.0).is_some() {
+            let text = try!(self.program.constant_expr(&Pseudo, id));
+            let precedence = if text.starts_with('-') { UNARY } else { ATOM };
+            return Ok(Expr {
+                text,
+                precedence,
+                loads: false,
+            });
+        }
+        let name = self.program.name(id.0);
+        match self.program.builtin(id.0) {
+            Some(builtin) if is_generated(&name) => Ok(Expr::atom(format!("{:?}", builtin))),
+            _ => Ok(Expr::atom(name)),
+        }
+    }
+
+    /// Gives a value its expression, which is written at its use if the
+    /// value is folded and assigned to the name of the value otherwise
+    fn define(&mut self, id: &ResultId, value: Expr) -> BackendResult<()> {
+        if self.folded.contains(&id.0) {
+            self.pending.push((id.0, value));
+            Ok(())
+        } else {
+            self.assign_value(id.0, value)
+        }
+    }
+
+    fn assign_value(&mut self, id: u32, value: Expr) -> BackendResult<()> {
+        let name = self.program.name(id);
+        if self.declared.contains(&id) {
+            self.assign(&name, value);
+        } else {
+            let ty = match self.value_types.get(&id) {
+                Some(ty) => try!(self.program.get_type(ty)),
+                None => return Err(BackendError::InvalidId(OpId(id))),
+            };
+            let local = try!(Pseudo.local(self.program, ty, &name, Some(&value.text)));
+            self.line(&format!("{};", local));
+        }
+        Ok(())
+    }
+
+    /// Assigns the folded values that read memory to their names, before a
+    /// statement that may write to it
+    fn write_pending_loads(&mut self) -> BackendResult<()> {
+        for (id, value) in mem::take(&mut self.pending) {
+            if value.loads {
+                try!(self.assign_value(id, value));
+            } else {
+                self.pending.push((id, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an access chain as the expression for the value it points to
+    fn access_chain(&mut self, op: &OpAccessChain) -> BackendResult<Expr> {
+        let base_type = match self.value_types.get(&op.base.0) {
+            Some(ty) => *ty,
+            None => {
+                match self.program.get_variable(op.base.0) {
+                    Some(var) => &var.result_type,
+                    None => return Err(BackendError::InvalidId(op.base.clone())),
+                }
+            }
+        };
+        let mut ty = match try!(self.program.get_type(base_type)) {
+            Type::Pointer(_, pointed) => (**pointed).clone(),
+            _ => return Err(BackendError::InvalidId(op.base.clone())),
+        };
+        let mut expr = try!(self.take(&op.base));
+        for (position, index) in op.indexes.iter().enumerate() {
+            ty = match ty {
+                Type::Struct(id) => {
+                    let member = match self.program.constant_int(index) {
+                        Some(member) => member as u32,
+                        None => return Err(BackendError::InvalidId(index.clone())),
+                    };
+                    let is_block = self.program.get_variable(op.base.0).is_some() &&
+                                   (self.program.has_decoration(id, Decoration::Block) ||
+                                    self.program.has_decoration(id, Decoration::BufferBlock));
+                    expr.text = if position == 0 && is_block {
+                        Pseudo.block_member(self.program, op.base.0, id, member)
+                    } else {
+                        format!("{}.{}", expr.text, self.program.member_name(id, member))
+                    };
+                    let members = self.program.struct_members(id);
+                    match members.get(member as usize) {
+                        Some(member_type) => try!(self.program.get_type(member_type)).clone(),
+                        None => return Err(BackendError::InvalidId(index.clone())),
+                    }
+                }
+                Type::Vector(component, _) => {
+                    match self.program.constant_int(index) {
+                        Some(component) if component < 4 => {
+                            let name = ["x", "y", "z", "w"][component as usize];
+                            expr.text = format!("{}.{}", expr.text, name);
+                        }
+                        _ => expr = try!(self.index(expr, index)),
+                    }
+                    *component
+                }
+                Type::Matrix(element, _) |
+                Type::Array(element, _) |
+                Type::RuntimeArray(element) => {
+                    expr = try!(self.index(expr, index));
+                    *element
+                }
+                _ => return Err(BackendError::InvalidId(index.clone())),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn index(&mut self, expr: Expr, index: &OpId) -> BackendResult<Expr> {
+        let index = try!(self.take(index));
+        Ok(Expr {
+            text: format!("{}[{}]", expr.text, index.text),
+            precedence: ATOM,
+            loads: expr.loads || index.loads,
+        })
+    }
+
+    fn binary(&mut self,
+              result_id: &ResultId,
+              operand1: &OpId,
+              operand2: &OpId,
+              operator: &str,
+              precedence: u8)
+              -> BackendResult<()> {
+        let a = try!(self.take(operand1));
+        let b = try!(self.take(operand2));
+        self.define(result_id, a.binary(operator, b, precedence))
+    }
+
+    fn call(&mut self, result_id: &ResultId, function: &str, args: &[&OpId]) -> BackendResult<()> {
+        let mut exprs = Vec::new();
+        for arg in args {
+            exprs.push(try!(self.take(arg)));
+        }
+        self.define(result_id, Expr::call(function, exprs))
+    }
+
+    fn write_op(&mut self, op: &'a GroupCode) -> BackendResult<()> {
+        macro_rules! binary {
+            ($op: ident, $operator: expr, $precedence: expr) => {
+                self.binary(&$op.result_id, &$op.operand1, &$op.operand2, $operator, $precedence)
+            }
+        }
+        macro_rules! call {
+            ($op: ident, $function: expr) => {
+                self.call(&$op.result_id, $function, &[&$op.operand1, &$op.operand2])
+            }
+        }
+
+        match *op {
+            GroupCode::OpNop(_) |
+            GroupCode::OpLine(_) |
+            GroupCode::OpNoLine(_) |
+            GroupCode::OpVariable(_) |
+            GroupCode::OpPhi(_) |
+            GroupCode::OpAccessChain(_) => Ok(()),
+            GroupCode::OpUndef(ref op) => self.define(&op.result_id, Expr::atom("undef".into())),
+            GroupCode::OpLoad(ref op) => {
+                let mut value = try!(self.take(&op.pointer));
+                value.loads = true;
+                self.define(&op.result_id, value)
+            }
+            GroupCode::OpStore(ref op) => {
+                let pointer = try!(self.take(&op.pointer));
+                let object = try!(self.take(&op.object));
+                try!(self.write_pending_loads());
+                self.assign(&pointer.text, object);
+                Ok(())
+            }
+            GroupCode::OpFunctionCall(ref op) => {
+                let mut args = Vec::new();
+                for arg in &op.arguments {
+                    args.push(try!(self.take(arg)));
+                }
+                try!(self.write_pending_loads());
+                let call = Expr::call(&self.program.name(op.function.0), args);
+                if let Type::Void = *try!(self.program.get_type(&op.result_type)) {
+                    self.line(&format!("{};", call.text));
+                    Ok(())
+                } else {
+                    self.define(&op.result_id, call)
+                }
+            }
+            GroupCode::OpExtInst(ref op) => {
+                let inst = op.instruction.as_ref();
+                let function = inst.get_op().get_name().to_lowercase();
+                let mut args = Vec::new();
+                for arg in inst.operands() {
+                    args.push(try!(self.take(arg)));
+                }
+                self.define(&op.result_id, Expr::call(&function, args))
+            }
+            GroupCode::OpConvertUToF(ref op) => {
+                let ty = try!(self.program.get_type(&op.result_type)).clone();
+                let name = try!(Pseudo.type_name(self.program, &ty));
+                self.call(&op.result_id, &name, &[&op.unsigned_value])
+            }
+            GroupCode::OpBitcast(ref op) => {
+                let ty = try!(self.program.get_type(&op.result_type)).clone();
+                let name = format!("bitcast<{}>", try!(Pseudo.type_name(self.program, &ty)));
+                self.call(&op.result_id, &name, &[&op.operand])
+            }
+            GroupCode::OpIAdd(ref op) => binary!(op, "+", ADDITIVE),
+            GroupCode::OpISub(ref op) => binary!(op, "-", ADDITIVE),
+            GroupCode::OpIMul(ref op) => binary!(op, "*", MULTIPLICATIVE),
+            GroupCode::OpUDiv(ref op) => binary!(op, "/", MULTIPLICATIVE),
+            GroupCode::OpSDiv(ref op) => binary!(op, "/", MULTIPLICATIVE),
+            GroupCode::OpUMod(ref op) => binary!(op, "%", MULTIPLICATIVE),
+            GroupCode::OpSRem(ref op) => binary!(op, "%", MULTIPLICATIVE),
+            GroupCode::OpSMod(ref op) => call!(op, "smod"),
+            GroupCode::OpFAdd(ref op) => binary!(op, "+", ADDITIVE),
+            GroupCode::OpFSub(ref op) => binary!(op, "-", ADDITIVE),
+            GroupCode::OpFMul(ref op) => binary!(op, "*", MULTIPLICATIVE),
+            GroupCode::OpFDiv(ref op) => binary!(op, "/", MULTIPLICATIVE),
+            GroupCode::OpFRem(ref op) => call!(op, "frem"),
+            GroupCode::OpFMod(ref op) => call!(op, "fmod"),
+            GroupCode::OpBitwiseOr(ref op) => binary!(op, "|", BIT_OR),
+            GroupCode::OpBitwiseXor(ref op) => binary!(op, "^", BIT_XOR),
+            GroupCode::OpBitwiseAnd(ref op) => binary!(op, "&", BIT_AND),
+            GroupCode::OpIEqual(ref op) => binary!(op, "==", EQUALITY),
+            GroupCode::OpFOrdEqual(ref op) => binary!(op, "==", EQUALITY),
+            GroupCode::OpFUnordEqual(ref op) => binary!(op, "==", EQUALITY),
+            GroupCode::OpINotEqual(ref op) => binary!(op, "!=", EQUALITY),
+            GroupCode::OpFOrdNotEqual(ref op) => binary!(op, "!=", EQUALITY),
+            GroupCode::OpFUnordNotEqual(ref op) => binary!(op, "!=", EQUALITY),
+            GroupCode::OpUGreaterThan(ref op) => binary!(op, ">", RELATIONAL),
+            GroupCode::OpSGreaterThan(ref op) => binary!(op, ">", RELATIONAL),
+            GroupCode::OpFOrdGreaterThan(ref op) => binary!(op, ">", RELATIONAL),
+            GroupCode::OpFUnordGreaterThan(ref op) => binary!(op, ">", RELATIONAL),
+            GroupCode::OpUGreaterThanEqual(ref op) => binary!(op, ">=", RELATIONAL),
+            GroupCode::OpSGreaterThanEqual(ref op) => binary!(op, ">=", RELATIONAL),
+            GroupCode::OpFOrdGreaterThanEqual(ref op) => binary!(op, ">=", RELATIONAL),
+            GroupCode::OpFUnordGreaterThanEqual(ref op) => binary!(op, ">=", RELATIONAL),
+            GroupCode::OpULessThan(ref op) => binary!(op, "<", RELATIONAL),
+            GroupCode::OpSLessThan(ref op) => binary!(op, "<", RELATIONAL),
+            GroupCode::OpFOrdLessThan(ref op) => binary!(op, "<", RELATIONAL),
+            GroupCode::OpFUnordLessThan(ref op) => binary!(op, "<", RELATIONAL),
+            GroupCode::OpULessThanEqual(ref op) => binary!(op, "<=", RELATIONAL),
+            GroupCode::OpSLessThanEqual(ref op) => binary!(op, "<=", RELATIONAL),
+            GroupCode::OpFOrdLessThanEqual(ref op) => binary!(op, "<=", RELATIONAL),
+            GroupCode::OpFUnordLessThanEqual(ref op) => binary!(op, "<=", RELATIONAL),
+            GroupCode::OpIAddCarry(ref op) => call!(op, "addCarry"),
+            GroupCode::OpISubBorrow(ref op) => call!(op, "subBorrow"),
+            GroupCode::OpUMulExtended(ref op) => call!(op, "umulExtended"),
+            GroupCode::OpSMulExtended(ref op) => call!(op, "imulExtended"),
+        }
+    }
+}
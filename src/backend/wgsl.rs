@@ -0,0 +1,502 @@
+use super::*;
+
+const KEYWORDS: &[&str] = &["alias", "break", "case", "const", "const_assert", "continue",
+                            "continuing", "default", "diagnostic", "discard", "else", "enable",
+                            "false", "fn", "for", "if", "let", "loop", "override", "requires",
+                            "return", "struct", "switch", "true", "var", "while", "array",
+                            "atomic", "bool", "f16", "f32", "i32", "mat2x2", "mat2x3",
+                            "mat2x4", "mat3x2", "mat3x3", "mat3x4", "mat4x2", "mat4x3",
+                            "mat4x4", "ptr", "sampler", "u32", "vec2", "vec3", "vec4",
+                            "NULL", "Self", "abstract", "active", "alignas", "alignof", "as",
+                            "asm", "async", "attribute", "auto", "await", "become", "cast",
+                            "catch", "class", "coherent", "column_major", "common", "compile",
+                            "concept", "const_cast", "consteval", "constexpr", "constinit",
+                            "crate", "debugger", "decltype", "delete", "demote", "do",
+                            "dynamic_cast", "enum", "explicit", "export", "extends", "extern",
+                            "external", "filter", "final", "finally", "friend", "from", "fxgroup",
+                            "get", "goto", "groupshared", "highp", "impl", "implements",
+                            "import", "inline", "instanceof", "interface", "layout", "lowp",
+                            "macro", "match", "mediump", "meta", "mod", "module", "move",
+                            "mut", "mutable", "namespace", "new", "nil", "noexcept",
+                            "noinline", "nointerpolation", "non_coherent", "noncoherent",
+                            "noperspective", "null", "nullptr", "of", "operator", "package",
+                            "packoffset", "partition", "pass", "patch", "pixelfragment",
+                            "precise", "precision", "premerge", "priv", "protected", "pub",
+                            "public", "readonly", "ref", "regardless", "register",
+                            "reinterpret_cast", "require", "resource", "restrict", "self",
+                            "set", "shared", "sizeof", "smooth", "snorm", "static",
+                            "static_assert", "static_cast", "std", "subroutine", "super",
+                            "target", "template", "this", "thread_local", "throw", "trait",
+                            "try", "type", "typedef", "typeid", "typename", "typeof",
+                            "union", "unless", "unorm", "unsafe", "unsized", "use", "using",
+                            "varying", "virtual", "volatile", "wgsl", "where", "with",
+                            "writeonly", "yield", "bitcast", "floor", "sin", "cos"];
+
+/// The syntax of the WebGPU Shading Language
+struct Wgsl {
+    /// If the `f16` extension is enabled
+    f16: bool,
+}
+
+/// Returns the name and type of a builtin entry point input or output
+fn builtin_value(builtin: &BuiltIn) -> Option<(&'static str, Type)> {
+    let uint = Type::Int(32, false);
+    let uvec3 = Type::Vector(Box::new(uint.clone()), 3);
+    let value = match *builtin {
+        BuiltIn::GlobalInvocationId => ("global_invocation_id", uvec3),
+        BuiltIn::LocalInvocationId => ("local_invocation_id", uvec3),
+        BuiltIn::WorkgroupId => ("workgroup_id", uvec3),
+        BuiltIn::NumWorkgroups => ("num_workgroups", uvec3),
+        BuiltIn::LocalInvocationIndex => ("local_invocation_index", uint),
+        BuiltIn::VertexId |
+        BuiltIn::VertexIndex => ("vertex_index", uint),
+        BuiltIn::InstanceId |
+        BuiltIn::InstanceIndex => ("instance_index", uint),
+        BuiltIn::Position |
+        BuiltIn::FragCoord => ("position", Type::Vector(Box::new(Type::Float(32)), 4)),
+        BuiltIn::FrontFacing => ("front_facing", Type::Bool),
+        BuiltIn::FragDepth => ("frag_depth", Type::Float(32)),
+        BuiltIn::SampleId => ("sample_index", uint),
+        _ => return None,
+    };
+    Some(value)
+}
+
+impl Dialect for Wgsl {
+    fn is_reserved(&self, name: &str) -> bool {
+        name.starts_with("__") || KEYWORDS.contains(&name)
+    }
+
+    /// The entry point function is called by a wrapper with the name of the
+    /// entry point, which copies the stage inputs and outputs
+    fn entry_point_name(&self, name: &str) -> String {
+        format!("{}_inner", sanitize(name))
+    }
+
+    fn type_name(&self, program: &Program, ty: &Type) -> BackendResult<String> {
+        let name = match *ty {
+            Type::Bool => "bool".into(),
+            Type::Int(32, false) => "u32".into(),
+            Type::Int(32, true) => "i32".into(),
+            Type::Float(16) if self.f16 => "f16".into(),
+            Type::Float(32) => "f32".into(),
+            Type::Vector(ref component, count) => {
+                format!("vec{}<{}>", count, try!(self.type_name(program, component)))
+            }
+            Type::Matrix(ref column, columns) => {
+                match **column {
+                    Type::Vector(ref component, rows) => {
+                        format!("mat{}x{}<{}>",
+                                columns,
+                                rows,
+                                try!(self.type_name(program, component)))
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                }
+            }
+            Type::Array(ref element, length) => {
+                format!("array<{}, {}>", try!(self.type_name(program, element)), length)
+            }
+            Type::RuntimeArray(ref element) => {
+                format!("array<{}>", try!(self.type_name(program, element)))
+            }
+            Type::Struct(id) => program.name(id),
+            Type::Pointer(ref storage_class, ref pointed) => {
+                let space = match *storage_class {
+                    StorageClass::Function => "function",
+                    StorageClass::Private => "private",
+                    StorageClass::Workgroup => "workgroup",
+                    StorageClass::Uniform => {
+                        match **pointed {
+                            Type::Struct(id) if program.has_decoration(id, Decoration::Block) => {
+                                "uniform"
+                            }
+                            _ => "storage, read_write",
+                        }
+                    }
+                    _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+                };
+                format!("ptr<{}, {}>", space, try!(self.type_name(program, pointed)))
+            }
+            _ => return Err(BackendError::UnrepresentableType(ty.clone())),
+        };
+        Ok(name)
+    }
+
+    fn declaration(&self, program: &Program, ty: &Type, name: &str) -> BackendResult<String> {
+        Ok(format!("{}: {}", name, try!(self.type_name(program, ty))))
+    }
+
+    fn local(&self,
+             program: &Program,
+             ty: &Type,
+             name: &str,
+             initializer: Option<&str>)
+             -> BackendResult<String> {
+        let declaration = try!(self.declaration(program, ty, name));
+        Ok(match initializer {
+            Some(initializer) => format!("var {} = {}", declaration, initializer),
+            None => format!("var {}", declaration),
+        })
+    }
+
+    fn function_header(&self,
+                       program: &Program,
+                       name: &str,
+                       return_type: &Type,
+                       parameters: &[(Type, String)])
+                       -> BackendResult<String> {
+        let mut params = Vec::new();
+        for (ty, name) in parameters {
+            params.push(try!(self.declaration(program, ty, name)));
+        }
+        let header = format!("fn {}({})", name, params.join(", "));
+        match *return_type {
+            Type::Void => Ok(header),
+            _ => Ok(format!("{} -> {}", header, try!(self.type_name(program, return_type)))),
+        }
+    }
+
+    fn int_literal(&self, value: u64, width: u32, signed: bool) -> String {
+        match (width, signed) {
+            (32, false) => format!("{}u", value as u32),
+            (32, true) if value as i32 == i32::MIN => "(-2147483647i - 1i)".into(),
+            (32, true) => format!("{}i", value as i32),
+            (_, _) => format!("{}", value),
+        }
+    }
+
+    fn float_literal(&self, bits: u64, width: u32) -> BackendResult<String> {
+        match (format_float(bits, width), width) {
+            (Some(value), 16) if self.f16 => Ok(format!("{}h", value)),
+            (Some(value), 32) => Ok(format!("{}f", value)),
+            (None, 32) => Ok(format!("bitcast<f32>(0x{:08x}u)", bits as u32)),
+            _ => Err(BackendError::UnrepresentableType(Type::Float(width))),
+        }
+    }
+
+    fn bitcast(&self, program: &Program, _: &Type, to: &Type, expr: &str)
+               -> BackendResult<String> {
+        Ok(format!("bitcast<{}>({})", try!(self.type_name(program, to)), expr))
+    }
+
+    // WGSL has no isnan, but NaN is the only value not equal to itself
+    fn ordered_not_equal(&self, a: &str, b: &str, vector: bool) -> String {
+        if vector {
+            format!("({} == {}) & ({} == {}) & ({} != {})", a, a, b, b, a, b)
+        } else {
+            format!("{} == {} && {} == {} && {} != {}", a, a, b, b, a, b)
+        }
+    }
+
+    fn unordered_equal(&self, a: &str, b: &str, vector: bool) -> String {
+        if vector {
+            format!("({} != {}) | ({} != {}) | ({} == {})", a, a, b, b, a, b)
+        } else {
+            format!("{} != {} || {} != {} || {} == {}", a, a, b, b, a, b)
+        }
+    }
+
+    fn frem(&self, a: &str, b: &str) -> String {
+        format!("{} % {}", a, b)
+    }
+
+    fn builtin(&self, builtin: &BuiltIn) -> Option<String> {
+        let name = match *builtin {
+            BuiltIn::Position => "gl_Position",
+            BuiltIn::PointSize => "gl_PointSize",
+            BuiltIn::VertexId | BuiltIn::VertexIndex => "gl_VertexIndex",
+            BuiltIn::InstanceId | BuiltIn::InstanceIndex => "gl_InstanceIndex",
+            BuiltIn::FragCoord => "gl_FragCoord",
+            BuiltIn::FrontFacing => "gl_FrontFacing",
+            BuiltIn::SampleId => "gl_SampleID",
+            BuiltIn::FragDepth => "gl_FragDepth",
+            BuiltIn::NumWorkgroups => "gl_NumWorkGroups",
+            BuiltIn::WorkgroupId => "gl_WorkGroupID",
+            BuiltIn::LocalInvocationId => "gl_LocalInvocationID",
+            BuiltIn::GlobalInvocationId => "gl_GlobalInvocationID",
+            BuiltIn::LocalInvocationIndex => "gl_LocalInvocationIndex",
+            _ => return None,
+        };
+        Some(name.into())
+    }
+
+    fn loop_start(&self) -> &'static str {
+        "loop"
+    }
+
+    fn continuing(&self) -> Option<&'static str> {
+        Some("continuing")
+    }
+
+    fn deref(&self, name: &str) -> String {
+        format!("(*{})", name)
+    }
+
+    fn address_of(&self, expr: &str) -> String {
+        format!("&{}", expr)
+    }
+}
+
+/// The parameters and results of the entry point wrapper and the statements
+/// that copy them to and from the private variables used by the shader
+#[derive(Default)]
+struct Stage {
+    parameters: Vec<String>,
+    inputs: Vec<String>,
+    /// The members of the struct returned by the wrapper, and the variables
+    /// they are set from
+    outputs: Vec<(String, String)>,
+}
+
+/// Generates WGSL source code for an entry point of a module
+///
+/// Storage blocks are declared as `var<storage, read_write>`, or
+/// `var<storage, read>` if they are NonWritable, and uniform blocks as
+/// `var<uniform>`, with `@group` and `@binding` from the DescriptorSet and
+/// Binding decorations. Struct members are laid out by the rules of WGSL, so
+/// the Offset decorations are not written.
+///
+/// Inputs, outputs and builtin variables are private variables. The entry
+/// point is a wrapper function that copies them from its parameters and
+/// returns the outputs in a struct. Compute entry points use the local size
+/// of `Program::workgroup_size` for `@workgroup_size`.
+///
+/// Loops are written as `loop` statements, with the continue target of the
+/// loop in the `continuing` block when every iteration ends with it. WGSL has
+/// no 64-bit types, so these are reported as unrepresentable, and 16-bit
+/// floats are only written when the module has the Float16 capability.
+pub fn generate_wgsl(module: &LogicalModule, entry_point: &str) -> BackendResult<String> {
+    let dialect = Wgsl { f16: module.capabilities.contains(&Capability::Float16) };
+    let program = try!(Program::new(module, entry_point, &dialect));
+    let entry_name = sanitize(&program.entry_point.name);
+    let attribute = match program.entry_point.execution_model {
+        ExecutionModel::GlCompute => {
+            let (x, y, z) = program.workgroup_size().unwrap_or((1, 1, 1));
+            format!("@compute @workgroup_size({}, {}, {})", x, y, z)
+        }
+        ExecutionModel::Vertex => "@vertex".into(),
+        ExecutionModel::Fragment => "@fragment".into(),
+        ref model => return Err(BackendError::UnsupportedExecutionModel(model.clone())),
+    };
+
+    let mut output = String::new();
+    if dialect.f16 {
+        output.push_str("enable f16;\n\n");
+    }
+    for id in program.struct_ids() {
+        if program.is_builtin_block(id) {
+            continue;
+        }
+        output.push_str(&format!("struct {} {{\n", program.name(id)));
+        for (member, member_type) in program.struct_members(id).iter().enumerate() {
+            let ty = try!(program.get_type(member_type));
+            let name = program.member_name(id, member as u32);
+            output.push_str(&format!("    {},\n", try!(dialect.declaration(&program, ty, &name))));
+        }
+        output.push_str("}\n\n");
+    }
+
+    let mut stage = Stage::default();
+    let mut declared = false;
+    for var in program.used_variables() {
+        try!(write_variable(&program, &dialect, var, &mut stage, &mut output));
+        declared = true;
+    }
+    if declared {
+        output.push('\n');
+    }
+
+    for function in program.reachable_functions() {
+        output.push_str(&try!(FunctionWriter::write(&program, &dialect, function)));
+        output.push('\n');
+    }
+
+    let outputs_name = format!("{}_outputs", entry_name);
+    if !stage.outputs.is_empty() {
+        output.push_str(&format!("struct {} {{\n", outputs_name));
+        for (member, _) in &stage.outputs {
+            output.push_str(&format!("    {},\n", member));
+        }
+        output.push_str("}\n\n");
+    }
+    output.push_str(&format!("{}\nfn {}({})", attribute, entry_name, stage.parameters.join(", ")));
+    if !stage.outputs.is_empty() {
+        output.push_str(&format!(" -> {}", outputs_name));
+    }
+    output.push_str(" {\n");
+    for input in &stage.inputs {
+        output.push_str(&format!("    {}\n", input));
+    }
+    output.push_str(&format!("    {}();\n", dialect.entry_point_name(&program.entry_point.name)));
+    if !stage.outputs.is_empty() {
+        let values = stage.outputs.iter().map(|output| output.1.as_str()).collect::<Vec<_>>();
+        output.push_str(&format!("    return {}({});\n", outputs_name, values.join(", ")));
+    }
+    output.push_str("}\n");
+    Ok(output)
+}
+
+/// Declares a builtin variable as a private variable, and passes it in or
+/// out of the entry point if WGSL has the builtin
+fn write_builtin(dialect: &Wgsl,
+                 program: &Program,
+                 builtin: &BuiltIn,
+                 ty: &Type,
+                 is_output: bool,
+                 stage: &mut Stage)
+                 -> BackendResult<String> {
+    let name = match dialect.builtin(builtin) {
+        Some(name) => name,
+        None => return Err(BackendError::UnsupportedBuiltIn(builtin.clone())),
+    };
+    match builtin_value(builtin) {
+        Some((value, ref value_type)) if is_output => {
+            let member = try!(dialect.declaration(program, value_type, &name));
+            let value_name = if value_type == ty {
+                name.clone()
+            } else {
+                try!(dialect.cast(program, value_type, &name))
+            };
+            stage.outputs.push((format!("@builtin({}) {}", value, member), value_name));
+        }
+        Some((value, ref value_type)) => {
+            let parameter = format!("{}_in", name);
+            stage.parameters.push(format!("@builtin({}) {}",
+                                          value,
+                                          try!(dialect.declaration(program,
+                                                                   value_type,
+                                                                   &parameter))));
+            let parameter = if value_type == ty {
+                parameter
+            } else {
+                try!(dialect.cast(program, ty, &parameter))
+            };
+            stage.inputs.push(format!("{} = {};", name, parameter));
+        }
+        // Outputs without a builtin, such as gl_PointSize, are not used
+        None if is_output => {}
+        None => return Err(BackendError::UnsupportedBuiltIn(builtin.clone())),
+    }
+    Ok(format!("var<private> {};\n", try!(dialect.declaration(program, ty, &name))))
+}
+
+fn write_variable(program: &Program,
+                  dialect: &Wgsl,
+                  var: &OpVariable,
+                  stage: &mut Stage,
+                  output: &mut String)
+                  -> BackendResult<()> {
+    let id = var.result_id.0;
+    let (storage_class, ty) = match *try!(program.get_type(&var.result_type)) {
+        Type::Pointer(ref storage_class, ref pointed) => (storage_class.clone(), &**pointed),
+        _ => return Err(BackendError::InvalidId(OpId(id))),
+    };
+    let unsupported = || BackendError::UnsupportedStorageClass(OpId(id), storage_class.clone());
+    let name = program.name(id);
+    let declaration = try!(dialect.declaration(program, ty, &name));
+
+    let declaration = match storage_class {
+        StorageClass::Uniform => {
+            let struct_id = match *ty {
+                Type::Struct(struct_id) => struct_id,
+                _ => return Err(unsupported()),
+            };
+            let address_space = if program.has_decoration(struct_id, Decoration::BufferBlock) {
+                let members = program.struct_members(struct_id).len() as u32;
+                let read_only = program.has_decoration(id, Decoration::NonWritable) ||
+                                (0..members).all(|member| {
+                    program.member_decorations(struct_id, member)
+                        .iter()
+                        .any(|d| **d == Decoration::NonWritable)
+                });
+                if read_only {
+                    "storage, read"
+                } else {
+                    "storage, read_write"
+                }
+            } else if program.has_decoration(struct_id, Decoration::Block) {
+                "uniform"
+            } else {
+                return Err(unsupported());
+            };
+            let group = program.decoration_value(id, |d| match *d {
+                Decoration::DescriptorSet(set) => Some(set),
+                _ => None,
+            });
+            let binding = program.decoration_value(id, |d| match *d {
+                Decoration::Binding(binding) => Some(binding),
+                _ => None,
+            });
+            format!("@group({}) @binding({}) var<{}> {};\n",
+                    group.unwrap_or(0),
+                    binding.unwrap_or(0),
+                    address_space,
+                    declaration)
+        }
+        StorageClass::Input |
+        StorageClass::Output => {
+            let is_output = storage_class == StorageClass::Output;
+            if let Some(builtin) = program.builtin(id) {
+                try!(write_builtin(dialect, program, builtin, ty, is_output, stage))
+            } else if let Type::Struct(struct_id) = *ty {
+                if !program.is_builtin_block(struct_id) {
+                    return Err(unsupported());
+                }
+                let mut privates = String::new();
+                for (member, member_type) in program.struct_members(struct_id).iter().enumerate() {
+                    if let Some(builtin) = program.member_builtin(struct_id, member as u32) {
+                        let member_type = try!(program.get_type(member_type));
+                        privates.push_str(&try!(write_builtin(dialect,
+                                                              program,
+                                                              builtin,
+                                                              member_type,
+                                                              is_output,
+                                                              stage)));
+                    }
+                }
+                privates
+            } else {
+                let location = match program.decoration_value(id, |d| match *d {
+                    Decoration::Location(location) => Some(location),
+                    _ => None,
+                }) {
+                    Some(location) => location,
+                    None => return Err(unsupported()),
+                };
+                let mut attributes = format!("@location({})", location);
+                if program.has_decoration(id, Decoration::Flat) {
+                    attributes.push_str(" @interpolate(flat)");
+                } else if program.has_decoration(id, Decoration::NoPerspective) {
+                    attributes.push_str(" @interpolate(linear)");
+                }
+                if is_output {
+                    stage.outputs.push((format!("{} {}", attributes, declaration), name.clone()));
+                } else {
+                    let parameter = format!("{}_in", name);
+                    stage.parameters.push(format!("{} {}",
+                                                  attributes,
+                                                  try!(dialect.declaration(program,
+                                                                           ty,
+                                                                           &parameter))));
+                    stage.inputs.push(format!("{} = {};", name, parameter));
+                }
+                format!("var<private> {};\n", declaration)
+            }
+        }
+        StorageClass::Workgroup => format!("var<workgroup> {};\n", declaration),
+        StorageClass::Private => {
+            match var.initializer {
+                Some(ref initializer) => {
+                    format!("var<private> {} = {};\n",
+                            declaration,
+                            try!(program.constant_expr(dialect, initializer)))
+                }
+                None => format!("var<private> {};\n", declaration),
+            }
+        }
+        _ => return Err(unsupported()),
+    };
+    output.push_str(&declaration);
+    Ok(())
+}
@@ -7,12 +7,16 @@ use std::fmt::{Display, Formatter};
 use spv::Op;
 use spv::ExtInst;
 use spv::ExtInstSet;
+use spv::Style;
+use spv::operands::Operands;
+use spv::types::{OpId, ResultId};
 use spv::raw::MemoryBlock;
 use spv::raw::MemoryBlockResult;
 use spv::raw::ReadError;
 use self::op::*;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Inst {
     Sin(Sin),
     Cos(Cos),
@@ -36,6 +40,47 @@ impl ExtInst for Inst {
             None => false,
         }
     }
+
+    fn disassemble(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.get_op().get_name()));
+        for operand in self.operands() {
+            try!(write!(f, " "));
+            try!(style.write_id(operand.0, f));
+        }
+        Ok(())
+    }
+}
+
+impl Operands for Inst {
+    fn result_id(&self) -> Option<&ResultId> {
+        None
+    }
+
+    fn result_type(&self) -> Option<&OpId> {
+        None
+    }
+
+    fn operands(&self) -> Vec<&OpId> {
+        match *self {
+            Inst::Sin(ref op) => op.operands(),
+            Inst::Cos(ref op) => op.operands(),
+        }
+    }
+
+    fn result_id_mut(&mut self) -> Option<&mut ResultId> {
+        None
+    }
+
+    fn result_type_mut(&mut self) -> Option<&mut OpId> {
+        None
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut OpId> {
+        match *self {
+            Inst::Sin(ref mut op) => op.operands_mut(),
+            Inst::Cos(ref mut op) => op.operands_mut(),
+        }
+    }
 }
 
 impl Display for Inst {
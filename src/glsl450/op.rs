@@ -2,9 +2,11 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use spv::Op;
-use spv::types::OpId;
+use spv::operands::Operands;
+use spv::types::{OpId, ResultId};
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Sin {
     pub x: OpId,
 }
@@ -15,6 +17,32 @@ impl Op for Sin {
     }
 }
 
+impl Operands for Sin {
+    fn result_id(&self) -> Option<&ResultId> {
+        None
+    }
+
+    fn result_type(&self) -> Option<&OpId> {
+        None
+    }
+
+    fn operands(&self) -> Vec<&OpId> {
+        vec![&self.x]
+    }
+
+    fn result_id_mut(&mut self) -> Option<&mut ResultId> {
+        None
+    }
+
+    fn result_type_mut(&mut self) -> Option<&mut OpId> {
+        None
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut OpId> {
+        vec![&mut self.x]
+    }
+}
+
 impl Display for Sin {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "Sin {}", self.x)
@@ -22,6 +50,7 @@ impl Display for Sin {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cos {
     pub x: OpId,
 }
@@ -32,6 +61,32 @@ impl Op for Cos {
     }
 }
 
+impl Operands for Cos {
+    fn result_id(&self) -> Option<&ResultId> {
+        None
+    }
+
+    fn result_type(&self) -> Option<&OpId> {
+        None
+    }
+
+    fn operands(&self) -> Vec<&OpId> {
+        vec![&self.x]
+    }
+
+    fn result_id_mut(&mut self) -> Option<&mut ResultId> {
+        None
+    }
+
+    fn result_type_mut(&mut self) -> Option<&mut OpId> {
+        None
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut OpId> {
+        vec![&mut self.x]
+    }
+}
+
 impl Display for Cos {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "Cos {}", self.x)
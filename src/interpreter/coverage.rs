@@ -0,0 +1,266 @@
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use super::*;
+
+/// An arm of a selection in the structured control flow of a function
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arm {
+    /// The arm taken when the condition of a header block is true
+    True(OpId),
+    /// The arm taken when the condition of a header block is false
+    False(OpId),
+}
+
+impl fmt::Display for Arm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Arm::True(ref header) => write!(f, "true arm of {}", header),
+            Arm::False(ref header) => write!(f, "false arm of {}", header),
+        }
+    }
+}
+
+/// The coverage of the blocks and branches of a function
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionCoverage {
+    pub function: OpId,
+    /// The label of every block, with the number of times it was entered
+    pub blocks: Vec<(OpId, u64)>,
+    /// The block of every conditional branch, with the number of times its
+    /// true and false labels were taken
+    pub branches: Vec<(OpId, u64, u64)>,
+    /// The arms of selections that were never taken, in the order of the
+    /// control flow of the function
+    pub untaken_arms: Vec<Arm>,
+}
+
+impl fmt::Display for FunctionCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let blocks = self.blocks.iter().filter(|block| block.1 > 0).count();
+        let directions = self.branches
+            .iter()
+            .map(|&(_, taken, not_taken)| (taken > 0) as usize + (not_taken > 0) as usize)
+            .sum::<usize>();
+        try!(writeln!(f,
+                      "{}: {}/{} blocks, {}/{} branch directions",
+                      self.function,
+                      blocks,
+                      self.blocks.len(),
+                      directions,
+                      self.branches.len() * 2));
+        for arm in &self.untaken_arms {
+            try!(writeln!(f, "    never took {}", arm));
+        }
+        Ok(())
+    }
+}
+
+/// Counts how often blocks are entered and which way conditional branches
+/// go
+///
+/// Coverage is passed as the observer of dispatches, and accumulates over
+/// every invocation of them. It is reported by block, or by source line as
+/// lcov when the module has line instructions.
+#[derive(Default)]
+pub struct Coverage {
+    blocks: HashMap<u32, u64>,
+    /// The number of times each branch from one block to another was taken
+    edges: HashMap<(u32, u32), u64>,
+}
+
+/// The source coverage of one file, as written to a record of lcov
+#[derive(Default)]
+struct FileCoverage {
+    /// The first line, name and call count of each function
+    functions: Vec<(u32, String, u64)>,
+    /// The highest count of the blocks each line appears in
+    lines: BTreeMap<u32, u64>,
+    /// The line, block label and true and false counts of each conditional
+    /// branch
+    branches: Vec<(u32, u32, u64, u64)>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    /// Returns the number of times the block with a label was entered
+    pub fn block_count(&self, label: &OpId) -> u64 {
+        self.blocks.get(&label.0).cloned().unwrap_or(0)
+    }
+
+    /// Returns the number of times the branch of a block went to its true and
+    /// false labels, if it is a conditional branch
+    pub fn branch_counts(&self, block: &BasicBlock) -> Option<(u64, u64)> {
+        match block.branch {
+            GroupBranch::OpBranchConditional(ref op) => {
+                let count = |label: &OpId| {
+                    self.edges.get(&(block.label.result_id.0, label.0)).cloned().unwrap_or(0)
+                };
+                Some((count(&op.true_label), count(&op.false_label)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the coverage of every function defined in a module
+    pub fn report(&self, module: &LogicalModule) -> ControlFlowResult<Vec<FunctionCoverage>> {
+        let mut report = Vec::new();
+        for function in &module.function_definitions {
+            let mut coverage = FunctionCoverage {
+                function: OpId(function.function.result_id.0),
+                blocks: Vec::new(),
+                branches: Vec::new(),
+                untaken_arms: Vec::new(),
+            };
+            let mut branches = HashMap::new();
+            for block in &function.blocks {
+                let label = OpId(block.label.result_id.0);
+                coverage.blocks.push((label.clone(), self.block_count(&label)));
+                if let Some((taken, not_taken)) = self.branch_counts(block) {
+                    coverage.branches.push((label.clone(), taken, not_taken));
+                    branches.insert(label.0, (taken, not_taken));
+                }
+            }
+            let chain = try!(find_control_flow(function.clone()));
+            untaken_arms(&chain, &branches, &mut coverage.untaken_arms);
+            report.push(coverage);
+        }
+        Ok(report)
+    }
+
+    /// Returns the coverage of every function defined in a module as lcov
+    /// tracefile text
+    ///
+    /// Blocks are mapped to source lines through their OpLine instructions,
+    /// so only modules with line information produce records. A line is
+    /// counted as often as the most entered block it appears in. Functions
+    /// start at the first line of their entry block, and a branch is on the
+    /// line in effect at the end of its block.
+    pub fn lcov(&self, module: &LogicalModule) -> String {
+        let mut files = HashMap::new();
+        let mut names = HashMap::new();
+        for op in &module.debug {
+            match *op {
+                GroupDebug::OpString(ref op) => {
+                    files.insert(op.result_id.0, op.string.as_str());
+                }
+                GroupDebug::OpName(ref op) => {
+                    names.insert(op.target.0, op.name.as_str());
+                }
+                _ => {}
+            }
+        }
+
+        let mut coverage = BTreeMap::<u32, FileCoverage>::new();
+        for function in &module.function_definitions {
+            let id = function.function.result_id.0;
+            let mut name = names.get(&id).map(|name| name.to_string());
+            for (index, block) in function.blocks.iter().enumerate() {
+                let count = self.block_count(&OpId(block.label.result_id.0));
+                let mut location = None;
+                for op in &block.code {
+                    match *op {
+                        GroupCode::OpLine(ref op) => {
+                            let file = coverage.entry(op.file.0).or_default();
+                            if index == 0 {
+                                if let Some(name) = name.take() {
+                                    file.functions.push((op.line.0, name, count));
+                                }
+                            }
+                            let line = file.lines.entry(op.line.0).or_insert(0);
+                            *line = cmp::max(*line, count);
+                            location = Some((op.file.0, op.line.0));
+                        }
+                        GroupCode::OpNoLine(_) => location = None,
+                        _ => {}
+                    }
+                }
+                if let (Some((file, line)), Some((taken, not_taken))) =
+                    (location, self.branch_counts(block)) {
+                    let file = coverage.entry(file).or_default();
+                    file.branches.push((line, block.label.result_id.0, taken, not_taken));
+                }
+            }
+        }
+
+        let mut output = String::new();
+        for (id, file) in &coverage {
+            output.push_str("TN:\n");
+            match files.get(id) {
+                Some(name) => output.push_str(&format!("SF:{}\n", name)),
+                None => output.push_str(&format!("SF:%{}\n", id)),
+            }
+            for &(line, ref name, _) in &file.functions {
+                output.push_str(&format!("FN:{},{}\n", line, name));
+            }
+            for &(_, ref name, count) in &file.functions {
+                output.push_str(&format!("FNDA:{},{}\n", count, name));
+            }
+            let hit = file.functions.iter().filter(|function| function.2 > 0).count();
+            output.push_str(&format!("FNF:{}\nFNH:{}\n", file.functions.len(), hit));
+            let mut hit = 0;
+            for &(line, label, taken, not_taken) in &file.branches {
+                // A branch whose block was never entered is written as
+                // never evaluated rather than as never taken
+                if taken + not_taken == 0 {
+                    output.push_str(&format!("BRDA:{},{},0,-\n", line, label));
+                    output.push_str(&format!("BRDA:{},{},1,-\n", line, label));
+                } else {
+                    output.push_str(&format!("BRDA:{},{},0,{}\n", line, label, taken));
+                    output.push_str(&format!("BRDA:{},{},1,{}\n", line, label, not_taken));
+                    hit += (taken > 0) as usize + (not_taken > 0) as usize;
+                }
+            }
+            output.push_str(&format!("BRF:{}\nBRH:{}\n", file.branches.len() * 2, hit));
+            for (line, count) in &file.lines {
+                output.push_str(&format!("DA:{},{}\n", line, count));
+            }
+            let hit = file.lines.values().filter(|count| **count > 0).count();
+            output.push_str(&format!("LF:{}\nLH:{}\n", file.lines.len(), hit));
+            output.push_str("end_of_record\n");
+        }
+        output
+    }
+}
+
+/// Finds the arms of selections whose header branch never went to them
+fn untaken_arms(chain: &ControlFlowChain,
+                branches: &HashMap<u32, (u64, u64)>,
+                arms: &mut Vec<Arm>) {
+    match *chain {
+        ControlFlowChain::Block(ref chains) => {
+            for chain in chains {
+                untaken_arms(chain, branches, arms);
+            }
+        }
+        ControlFlowChain::Selection(header, ref true_arm, ref false_arm, _, _) => {
+            let header = OpId::from(header);
+            if let Some(&(taken, not_taken)) = branches.get(&header.0) {
+                if taken == 0 {
+                    arms.push(Arm::True(header.clone()));
+                }
+                if not_taken == 0 {
+                    arms.push(Arm::False(header));
+                }
+            }
+            untaken_arms(true_arm, branches, arms);
+            untaken_arms(false_arm, branches, arms);
+        }
+        ControlFlowChain::Loop(_, ref inner, _, _) => untaken_arms(inner, branches, arms),
+        ControlFlowChain::Atom(_) |
+        ControlFlowChain::Break |
+        ControlFlowChain::Continue => {}
+    }
+}
+
+impl Observer for Coverage {
+    fn block(&mut self, from: Option<OpId>, to: OpId, _: &State) {
+        *self.blocks.entry(to.0).or_insert(0) += 1;
+        if let Some(from) = from {
+            *self.edges.entry((from.0, to.0)).or_insert(0) += 1;
+        }
+    }
+}
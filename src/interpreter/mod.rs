@@ -0,0 +1,1307 @@
+//! Execution of compute shaders on the CPU
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use byteorder::{LittleEndian, ByteOrder};
+use glsl450;
+use backend::ResourceBinding;
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use spv::logical::*;
+
+mod trace;
+pub use self::trace::{Trace, TraceEvent, InvocationTrace, Breakpoint, Debugger, Stop};
+mod coverage;
+pub use self::coverage::{Coverage, FunctionCoverage, Arm};
+
+#[derive(Debug)]
+pub enum InterpretError {
+    /// The module has no entry point with the given name
+    UnknownEntryPoint(String),
+    /// The entry point is not a compute shader
+    UnsupportedExecutionModel(ExecutionModel),
+    /// The entry point has no local size
+    MissingLocalSize,
+    /// A type is used that the interpreter can not represent
+    UnsupportedType(OpId),
+    /// A global variable has a storage class that can not be executed
+    UnsupportedStorageClass(OpId, StorageClass),
+    /// An input variable is not a builtin the interpreter provides
+    UnsupportedBuiltIn(OpId),
+    /// An instruction can not be executed
+    UnsupportedInstruction(String),
+    /// An id is used without a definition that can be referenced
+    InvalidId(OpId),
+    /// The operands of an instruction do not have the types it requires
+    InvalidOperand(OpId),
+    /// A resource variable is used without a buffer bound to it
+    UnboundResource(ResourceBinding),
+    /// A pointer is dereferenced outside of its variable or buffer
+    OutOfBounds(OpId),
+    /// An integer division or remainder has a divisor of zero
+    DivisionByZero(OpId),
+}
+
+pub type InterpretResult<T> = Result<T, InterpretError>;
+
+/// The value of an id during execution
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    /// The bits of an integer with its width, with any bits above the width
+    /// cleared
+    Int(u64, u32),
+    /// A float with its width
+    ///
+    /// The result of every operation is rounded to the width.
+    Float(f64, u32),
+    /// A vector, matrix, array or struct
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(bits, _) => write!(f, "{}", bits),
+            Value::Float(value, _) => write!(f, "{:?}", value),
+            Value::Composite(ref values) => {
+                try!(write!(f, "{{"));
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        try!(write!(f, ", "));
+                    }
+                    try!(write!(f, "{}", value));
+                }
+                write!(f, "}}")
+            }
+            Value::Pointer(_) => write!(f, "pointer"),
+        }
+    }
+}
+
+/// A reference to a variable, or to a member or element inside it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pointer {
+    root: Root,
+    /// The index at each level of the accessed member or element
+    path: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Root {
+    /// A private or workgroup variable, by its id
+    Global(u32),
+    /// The buffer bound to a resource variable, by the id of the variable
+    Buffer(u32),
+    /// A function variable, by its index in the stack of the invocation
+    Local(usize),
+}
+
+/// The structure of a type, along with its layout in buffers
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Void,
+    Bool,
+    Int(u32),
+    Float(u32),
+    Vector(Box<Type>, u32),
+    /// A matrix with a column type and count, and the stride of its columns
+    Matrix(Box<Type>, u32, u32),
+    /// An array with an element type, length and stride
+    Array(Box<Type>, u32, u32),
+    RuntimeArray(Box<Type>, u32),
+    /// A struct with the type and offset of each member
+    Struct(Vec<(Type, u32)>),
+    Pointer(Box<Type>),
+}
+
+impl Type {
+    /// Returns the size of the type in a buffer
+    fn size(&self) -> u32 {
+        match *self {
+            Type::Void | Type::Pointer(_) | Type::RuntimeArray(_, _) => 0,
+            Type::Bool => 4,
+            Type::Int(width) | Type::Float(width) => width / 8,
+            Type::Vector(ref component, count) => component.size() * count,
+            Type::Matrix(_, count, stride) |
+            Type::Array(_, count, stride) => count * stride,
+            Type::Struct(ref members) => {
+                members.iter().map(|&(ref ty, offset)| offset + ty.size()).max().unwrap_or(0)
+            }
+        }
+    }
+
+    /// Returns the type of the components of a scalar or vector
+    fn scalar(&self) -> &Type {
+        match *self {
+            Type::Vector(ref component, _) => component,
+            ref ty => ty,
+        }
+    }
+
+    /// Returns the value of the type with every scalar zero
+    fn zero(&self) -> Value {
+        match *self {
+            Type::Bool => Value::Bool(false),
+            Type::Int(width) => Value::Int(0, width),
+            Type::Float(width) => Value::Float(0.0, width),
+            Type::Vector(ref element, count) |
+            Type::Matrix(ref element, count, _) |
+            Type::Array(ref element, count, _) => {
+                Value::Composite(vec![element.zero(); count as usize])
+            }
+            Type::Struct(ref members) => {
+                Value::Composite(members.iter().map(|member| member.0.zero()).collect())
+            }
+            Type::Void | Type::Pointer(_) | Type::RuntimeArray(_, _) => Value::Composite(vec![]),
+        }
+    }
+}
+
+fn mask(bits: u64, width: u32) -> u64 {
+    if width >= 64 { bits } else { bits & ((1 << width) - 1) }
+}
+
+fn sign_extend(bits: u64, width: u32) -> i64 {
+    if width >= 64 {
+        bits as i64
+    } else {
+        let shift = 64 - width;
+        ((bits << shift) as i64) >> shift
+    }
+}
+
+fn round(value: f64, width: u32) -> f64 {
+    if width == 32 { value as f32 as f64 } else { value }
+}
+
+/// Reads a value of a type from a buffer
+fn read_value(bytes: &[u8], offset: u32, ty: &Type) -> Option<Value> {
+    let start = offset as usize;
+    if start + ty.size() as usize > bytes.len() {
+        return None;
+    }
+    let bytes = &bytes[start..];
+    let value = match *ty {
+        Type::Bool => Value::Bool(LittleEndian::read_u32(bytes) != 0),
+        Type::Int(width) => {
+            let bits = match width {
+                8 => bytes[0] as u64,
+                16 => LittleEndian::read_u16(bytes) as u64,
+                32 => LittleEndian::read_u32(bytes) as u64,
+                _ => LittleEndian::read_u64(bytes),
+            };
+            Value::Int(bits, width)
+        }
+        Type::Float(32) => Value::Float(LittleEndian::read_f32(bytes) as f64, 32),
+        Type::Float(width) => Value::Float(LittleEndian::read_f64(bytes), width),
+        Type::Vector(ref component, count) => {
+            let size = component.size();
+            let components = (0..count).map(|index| read_value(bytes, index * size, component));
+            return components.collect::<Option<_>>().map(Value::Composite);
+        }
+        Type::Matrix(ref element, count, stride) |
+        Type::Array(ref element, count, stride) => {
+            let elements = (0..count).map(|index| read_value(bytes, index * stride, element));
+            return elements.collect::<Option<_>>().map(Value::Composite);
+        }
+        Type::Struct(ref members) => {
+            let members = members.iter().map(|&(ref ty, offset)| read_value(bytes, offset, ty));
+            return members.collect::<Option<_>>().map(Value::Composite);
+        }
+        Type::Void | Type::Pointer(_) | Type::RuntimeArray(_, _) => return None,
+    };
+    Some(value)
+}
+
+/// Writes a value of a type to a buffer, returning if it fits
+fn write_value(bytes: &mut [u8], offset: u32, ty: &Type, value: &Value) -> bool {
+    let start = offset as usize;
+    if start + ty.size() as usize > bytes.len() {
+        return false;
+    }
+    match (ty, value) {
+        (&Type::Bool, &Value::Bool(value)) => {
+            LittleEndian::write_u32(&mut bytes[start..], value as u32);
+            true
+        }
+        (&Type::Int(width), &Value::Int(bits, _)) => {
+            match width {
+                8 => bytes[start] = bits as u8,
+                16 => LittleEndian::write_u16(&mut bytes[start..], bits as u16),
+                32 => LittleEndian::write_u32(&mut bytes[start..], bits as u32),
+                _ => LittleEndian::write_u64(&mut bytes[start..], bits),
+            }
+            true
+        }
+        (&Type::Float(32), &Value::Float(value, _)) => {
+            LittleEndian::write_f32(&mut bytes[start..], value as f32);
+            true
+        }
+        (&Type::Float(_), &Value::Float(value, _)) => {
+            LittleEndian::write_f64(&mut bytes[start..], value);
+            true
+        }
+        (Type::Vector(component, _), Value::Composite(values)) => {
+            let size = component.size();
+            values.iter().enumerate().all(|(index, value)| {
+                write_value(bytes, offset + index as u32 * size, component, value)
+            })
+        }
+        (Type::Matrix(element, _, stride), Value::Composite(values)) |
+        (Type::Array(element, _, stride), Value::Composite(values)) => {
+            values.iter().enumerate().all(|(index, value)| {
+                write_value(bytes, offset + index as u32 * *stride, element, value)
+            })
+        }
+        (Type::Struct(members), Value::Composite(values)) => {
+            members.iter().zip(values).all(|(&(ref ty, member_offset), value)| {
+                write_value(bytes, offset + member_offset, ty, value)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Applies an operation to two scalars, or to each pair of components of
+/// two vectors
+fn componentwise<F>(a: &Value, b: &Value, f: &F) -> Option<Value>
+    where F: Fn(&Value, &Value) -> Option<Value>
+{
+    match (a, b) {
+        (Value::Composite(a), Value::Composite(b)) if a.len() == b.len() => {
+            a.iter().zip(b).map(|(a, b)| componentwise(a, b, f)).collect::<Option<_>>()
+                .map(Value::Composite)
+        }
+        _ => f(a, b),
+    }
+}
+
+/// Applies an operation to a scalar, or to each component of a vector
+fn map_components<F>(value: &Value, f: &F) -> Option<Value>
+    where F: Fn(&Value) -> Option<Value>
+{
+    match *value {
+        Value::Composite(ref values) => {
+            values.iter().map(|value| map_components(value, f)).collect::<Option<_>>()
+                .map(Value::Composite)
+        }
+        ref value => f(value),
+    }
+}
+
+/// Receives the events of invocations as they are executed
+///
+/// Events are passed in the order they happen, so the events of an
+/// invocation follow its call to `invocation` until the next one.
+pub trait Observer {
+    /// Called before an invocation starts, with its global invocation id
+    fn invocation(&mut self, _global_id: [u32; 3]) {}
+
+    /// Called when execution enters a block, with the block it branched
+    /// from, or `None` at the start of a function
+    fn block(&mut self, _from: Option<OpId>, _to: OpId, _state: &State) {}
+
+    /// Called after an instruction is executed, with its result if it has
+    /// one and the state of the invocation
+    fn instruction(&mut self, _op: &GroupCode, _result: Option<&Value>, _state: &State) {}
+}
+
+struct Unobserved;
+
+impl Observer for Unobserved {}
+
+/// The state of an invocation, for inspecting values during execution
+pub struct State<'s, 'a: 's, 'b: 's> {
+    invocation: &'s Invocation<'a, 'b>,
+    values: &'s Values,
+}
+
+impl<'s, 'a, 'b> State<'s, 'a, 'b> {
+    /// Returns the global invocation id of the invocation
+    pub fn global_id(&self) -> [u32; 3] {
+        self.invocation.global_id
+    }
+
+    /// Returns the value of an id in the function being executed
+    ///
+    /// Constants and global variables can also be read, with variables
+    /// having pointer values.
+    pub fn value(&self, id: &OpId) -> Option<Value> {
+        self.invocation.value(self.values, id).ok()
+    }
+
+    /// Returns the value of an id with a debug name, loading it if it is
+    /// a variable
+    ///
+    /// If several ids have the name, the first with a value in the function
+    /// being executed is used.
+    pub fn named(&self, name: &str) -> Option<Value> {
+        let values = self.values;
+        let id = self.invocation.shader.names.get(name).and_then(|ids| {
+            ids.iter().find(|id| values.contains_key(id)).or_else(|| ids.first())
+        });
+        let id = match id {
+            Some(id) => OpId(*id),
+            None => return None,
+        };
+        match self.value(&id) {
+            Some(Value::Pointer(pointer)) => self.invocation.load(&pointer, &id).ok(),
+            value => value,
+        }
+    }
+
+    /// Returns the value of every id with a debug name that has a value, by
+    /// name
+    pub fn named_values(&self) -> HashMap<String, Value> {
+        self.invocation
+            .shader
+            .names
+            .keys()
+            .filter_map(|name| self.named(name).map(|value| (name.clone(), value)))
+            .collect()
+    }
+}
+
+/// The types, constants and functions of a module
+struct Shader<'a> {
+    entry_point: &'a OpEntryPoint,
+    local_size: (u32, u32, u32),
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, Value>,
+    variables: HashMap<u32, &'a OpVariable>,
+    functions: HashMap<u32, &'a FunctionDefinition>,
+    blocks: HashMap<u32, &'a BasicBlock>,
+    ext_inst_imports: HashMap<u32, &'a str>,
+    builtins: HashMap<u32, BuiltIn>,
+    bindings: HashMap<u32, ResourceBinding>,
+    /// The ids with each debug name
+    names: HashMap<String, Vec<u32>>,
+}
+
+/// Executes a compute entry point of a module over a grid of workgroups
+///
+/// Buffers are bound to the set and binding of their Uniform variables as
+/// bytes, using the Offset, ArrayStride and MatrixStride decorations of the
+/// module for their layout. Matrices in buffers are column major. The
+/// GlobalInvocationId, LocalInvocationId, WorkgroupId, NumWorkgroups and
+/// LocalInvocationIndex builtins are provided as inputs.
+///
+/// Invocations are executed one at a time, in order of their local
+/// invocation index within each workgroup. Private and function variables
+/// start zeroed unless they have an initializer, and workgroup variables are
+/// zeroed at the start of each workgroup. Specialization constants have
+/// their default values.
+pub struct Interpreter<'a> {
+    shader: Shader<'a>,
+    buffers: HashMap<ResourceBinding, Vec<u8>>,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Gathers the types, constants and functions of a module for executing
+    /// one of its entry points
+    pub fn new(module: &'a LogicalModule, entry_point: &str) -> InterpretResult<Interpreter<'a>> {
+        let entry_point = match module.entry_points.iter().find(|op| op.name == entry_point) {
+            Some(op) => op,
+            None => return Err(InterpretError::UnknownEntryPoint(entry_point.into())),
+        };
+        if entry_point.execution_model != ExecutionModel::GlCompute {
+            let model = entry_point.execution_model.clone();
+            return Err(InterpretError::UnsupportedExecutionModel(model));
+        }
+        let mut shader = Shader {
+            entry_point,
+            local_size: (0, 0, 0),
+            types: HashMap::new(),
+            constants: HashMap::new(),
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+            blocks: HashMap::new(),
+            ext_inst_imports: HashMap::new(),
+            builtins: HashMap::new(),
+            bindings: HashMap::new(),
+            names: HashMap::new(),
+        };
+
+        let mut decorations = HashMap::<u32, Vec<&Decoration>>::new();
+        let mut member_decorations = HashMap::<(u32, u32), Vec<&Decoration>>::new();
+        for op in &module.annotations {
+            match *op {
+                GroupAnnotation::OpDecorate(ref op) => {
+                    decorations.entry(op.target.0).or_default().push(&op.decoration);
+                }
+                GroupAnnotation::OpMemberDecorate(ref op) => {
+                    member_decorations.entry((op.structure_type.0, op.member.0))
+                        .or_default()
+                        .push(&op.decoration);
+                }
+            }
+        }
+        for (id, decorations) in &decorations {
+            for decoration in decorations {
+                if let Decoration::BuiltIn(ref builtin) = **decoration {
+                    shader.builtins.insert(*id, builtin.clone());
+                }
+            }
+        }
+        for op in &module.ext_inst_imports {
+            shader.ext_inst_imports.insert(op.result_id.0, &op.name);
+        }
+        for op in &module.debug {
+            if let GroupDebug::OpName(ref op) = *op {
+                shader.names.entry(op.name.clone()).or_default().push(op.target.0);
+            }
+        }
+
+        for op in &module.globals {
+            match *op {
+                GroupGlobal::GroupType(ref op) => {
+                    shader.add_type(op, &decorations, &member_decorations)
+                }
+                GroupGlobal::GroupConstant(ref op) => try!(shader.add_constant(op)),
+                GroupGlobal::OpVariable(ref var) => {
+                    let id = var.result_id.0;
+                    match var.storage_class {
+                        StorageClass::Uniform => {
+                            let decoration = |f: &Fn(&Decoration) -> Option<u32>| {
+                                decorations.get(&id)
+                                    .and_then(|decorations| {
+                                        decorations.iter().filter_map(|d| f(d)).next()
+                                    })
+                                    .unwrap_or(0)
+                            };
+                            let set = decoration(&|d| match *d {
+                                Decoration::DescriptorSet(set) => Some(set),
+                                _ => None,
+                            });
+                            let binding = decoration(&|d| match *d {
+                                Decoration::Binding(binding) => Some(binding),
+                                _ => None,
+                            });
+                            shader.bindings.insert(id, ResourceBinding { set, binding });
+                        }
+                        StorageClass::Input |
+                        StorageClass::Output |
+                        StorageClass::Private |
+                        StorageClass::Workgroup => {}
+                        ref storage_class => {
+                            let storage_class = storage_class.clone();
+                            return Err(InterpretError::UnsupportedStorageClass(OpId(id),
+                                                                               storage_class));
+                        }
+                    }
+                    shader.variables.insert(id, var);
+                }
+                GroupGlobal::OpLine(_) |
+                GroupGlobal::OpNoLine(_) => {}
+            }
+        }
+        for function in &module.function_definitions {
+            shader.functions.insert(function.function.result_id.0, function);
+            for block in &function.blocks {
+                shader.blocks.insert(block.label.result_id.0, block);
+            }
+        }
+
+        let size = shader.constants
+            .iter()
+            .filter(|&(id, _)| shader.builtins.get(id) == Some(&BuiltIn::WorkgroupSize))
+            .filter_map(|(_, value)| match *value {
+                Value::Composite(ref values) => {
+                    match values[..] {
+                        [Value::Int(x, _), Value::Int(y, _), Value::Int(z, _)] => {
+                            Some((x as u32, y as u32, z as u32))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .next();
+        let local_size = module.execution_modes
+            .iter()
+            .filter(|op| op.entry_point == entry_point.entry_point)
+            .filter_map(|op| match op.mode {
+                ExecutionMode::LocalSize(x, y, z) => Some((x, y, z)),
+                _ => None,
+            })
+            .next();
+        shader.local_size = match size.or(local_size) {
+            Some(size) => size,
+            None => return Err(InterpretError::MissingLocalSize),
+        };
+
+        Ok(Interpreter {
+            shader,
+            buffers: HashMap::new(),
+        })
+    }
+
+    /// Returns the number of invocations in each dimension of a workgroup
+    pub fn local_size(&self) -> (u32, u32, u32) {
+        self.shader.local_size
+    }
+
+    /// Binds the contents of a buffer to a set and binding, replacing any
+    /// buffer already bound to it
+    pub fn bind_buffer(&mut self, binding: ResourceBinding, bytes: Vec<u8>) {
+        self.buffers.insert(binding, bytes);
+    }
+
+    /// Returns the contents of the buffer bound to a set and binding
+    pub fn buffer(&self, binding: ResourceBinding) -> Option<&[u8]> {
+        self.buffers.get(&binding).map(|bytes| &bytes[..])
+    }
+
+    /// Executes every invocation of a grid of workgroups
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32) -> InterpretResult<()> {
+        self.dispatch_observed(x, y, z, &mut Unobserved)
+    }
+
+    /// Executes every invocation of a grid of workgroups, passing the
+    /// events of each invocation to an observer
+    pub fn dispatch_observed(&mut self,
+                             x: u32,
+                             y: u32,
+                             z: u32,
+                             observer: &mut Observer)
+                             -> InterpretResult<()> {
+        let (size_x, size_y, size_z) = self.shader.local_size;
+        for group_z in 0..z {
+            for group_y in 0..y {
+                for group_x in 0..x {
+                    let mut workgroup = HashMap::new();
+                    for (id, var) in &self.shader.variables {
+                        if var.storage_class == StorageClass::Workgroup {
+                            let ty = try!(self.shader.pointed_type(&var.result_type));
+                            workgroup.insert(*id, ty.zero());
+                        }
+                    }
+                    for local_z in 0..size_z {
+                        for local_y in 0..size_y {
+                            for local_x in 0..size_x {
+                                let builtins = Builtins {
+                                    workgroup_id: [group_x, group_y, group_z],
+                                    local_id: [local_x, local_y, local_z],
+                                    workgroup_count: [x, y, z],
+                                    local_size: [size_x, size_y, size_z],
+                                };
+                                let mut invocation = try!(Invocation::new(&self.shader,
+                                                                          &mut self.buffers,
+                                                                          &mut workgroup,
+                                                                          &builtins));
+                                observer.invocation(invocation.global_id);
+                                let entry_point = self.shader.entry_point.entry_point.clone();
+                                try!(invocation.call(observer, &entry_point, vec![]));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Shader<'a> {
+    fn add_type(&mut self,
+                op: &GroupType,
+                decorations: &HashMap<u32, Vec<&Decoration>>,
+                member_decorations: &HashMap<(u32, u32), Vec<&Decoration>>) {
+        let stride = |id: u32| {
+            decorations.get(&id).and_then(|decorations| {
+                decorations.iter()
+                    .filter_map(|d| match **d {
+                        Decoration::ArrayStride(stride) => Some(stride),
+                        _ => None,
+                    })
+                    .next()
+            })
+        };
+        let ty = match *op {
+            GroupType::OpTypeVoid(_) => Type::Void,
+            GroupType::OpTypeBool(_) => Type::Bool,
+            GroupType::OpTypeInt(ref op) => Type::Int(op.width),
+            GroupType::OpTypeFloat(ref op) if op.width == 32 || op.width == 64 => {
+                Type::Float(op.width)
+            }
+            GroupType::OpTypeVector(ref op) => {
+                match self.types.get(&op.component_type.0) {
+                    Some(component) => {
+                        Type::Vector(Box::new(component.clone()), op.component_count)
+                    }
+                    None => return,
+                }
+            }
+            GroupType::OpTypeMatrix(ref op) => {
+                match self.types.get(&op.column_type.0) {
+                    Some(column) => {
+                        let stride = column.size();
+                        Type::Matrix(Box::new(column.clone()), op.column_count, stride)
+                    }
+                    None => return,
+                }
+            }
+            GroupType::OpTypeArray(ref op) => {
+                let length = match self.constants.get(&op.length.0) {
+                    Some(&Value::Int(length, _)) => length as u32,
+                    _ => return,
+                };
+                match self.types.get(&op.element_type.0) {
+                    Some(element) => {
+                        let stride = stride(op.result_id.0).unwrap_or_else(|| element.size());
+                        Type::Array(Box::new(element.clone()), length, stride)
+                    }
+                    None => return,
+                }
+            }
+            GroupType::OpTypeRuntimeArray(ref op) => {
+                match self.types.get(&op.element_type.0) {
+                    Some(element) => {
+                        let stride = stride(op.result_id.0).unwrap_or_else(|| element.size());
+                        Type::RuntimeArray(Box::new(element.clone()), stride)
+                    }
+                    None => return,
+                }
+            }
+            GroupType::OpTypeStruct(ref op) => {
+                let mut members = Vec::new();
+                let mut end = 0;
+                for (index, id) in op.member_types.iter().enumerate() {
+                    let mut ty = match self.types.get(&id.0) {
+                        Some(ty) => ty.clone(),
+                        None => return,
+                    };
+                    let mut offset = end;
+                    let key = (op.result_id.0, index as u32);
+                    for decoration in member_decorations.get(&key).into_iter().flatten() {
+                        match **decoration {
+                            Decoration::Offset(value) => offset = value,
+                            Decoration::MatrixStride(value) => {
+                                if let Type::Matrix(_, _, ref mut stride) = ty {
+                                    *stride = value;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    end = offset + ty.size();
+                    members.push((ty, offset));
+                }
+                Type::Struct(members)
+            }
+            GroupType::OpTypePointer(ref op) => {
+                match self.types.get(&op.pointed_type.0) {
+                    Some(pointed) => Type::Pointer(Box::new(pointed.clone())),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        self.types.insert(op.result_id().unwrap().0, ty);
+    }
+
+    fn add_constant(&mut self, op: &GroupConstant) -> InterpretResult<()> {
+        let value = match *op {
+            GroupConstant::OpConstantTrue(_) |
+            GroupConstant::OpSpecConstantTrue(_) => Value::Bool(true),
+            GroupConstant::OpConstantFalse(_) |
+            GroupConstant::OpSpecConstantFalse(_) => Value::Bool(false),
+            GroupConstant::OpConstant(OpConstant { ref result_type, ref value, .. }) |
+            GroupConstant::OpSpecConstant(OpSpecConstant { ref result_type, ref value, .. }) => {
+                let bits = value.iter()
+                    .take(2)
+                    .enumerate()
+                    .fold(0, |bits, (index, word)| bits | (*word as u64) << (index * 32));
+                match *try!(self.get_type(result_type)) {
+                    Type::Int(width) => Value::Int(mask(bits, width), width),
+                    Type::Float(32) => Value::Float(f32::from_bits(bits as u32) as f64, 32),
+                    Type::Float(width) => Value::Float(f64::from_bits(bits), width),
+                    _ => return Err(InterpretError::UnsupportedType(result_type.clone())),
+                }
+            }
+            GroupConstant::OpConstantComposite(OpConstantComposite { ref constituents, .. }) |
+            GroupConstant::OpSpecConstantComposite(OpSpecConstantComposite { ref constituents,
+                                                                             .. }) => {
+                let mut values = Vec::new();
+                for id in constituents {
+                    match self.constants.get(&id.0) {
+                        Some(value) => values.push(value.clone()),
+                        None => return Err(InterpretError::InvalidId(id.clone())),
+                    }
+                }
+                Value::Composite(values)
+            }
+            GroupConstant::OpSpecConstantOp(ref op) => {
+                return Err(InterpretError::UnsupportedInstruction(format!("{}", op)))
+            }
+        };
+        self.constants.insert(op.result_id().unwrap().0, value);
+        Ok(())
+    }
+
+    fn get_type(&self, id: &OpId) -> InterpretResult<&Type> {
+        self.types.get(&id.0).ok_or_else(|| InterpretError::UnsupportedType(id.clone()))
+    }
+
+    /// Returns the type a pointer type points to
+    fn pointed_type(&self, id: &OpId) -> InterpretResult<&Type> {
+        match *try!(self.get_type(id)) {
+            Type::Pointer(ref pointed) => Ok(pointed),
+            _ => Err(InterpretError::InvalidId(id.clone())),
+        }
+    }
+}
+
+/// The values of the ids of a function being executed
+type Values = HashMap<u32, Value>;
+
+/// The ids of an invocation within the grid of workgroups
+struct Builtins {
+    workgroup_id: [u32; 3],
+    local_id: [u32; 3],
+    workgroup_count: [u32; 3],
+    local_size: [u32; 3],
+}
+
+/// The memory of a single invocation during execution
+struct Invocation<'a, 'b> {
+    shader: &'b Shader<'a>,
+    global_id: [u32; 3],
+    buffers: &'b mut HashMap<ResourceBinding, Vec<u8>>,
+    workgroup: &'b mut HashMap<u32, Value>,
+    private: HashMap<u32, Value>,
+    /// The function variables of every function being executed
+    locals: Vec<Value>,
+}
+
+impl<'a, 'b> Invocation<'a, 'b> {
+    fn new(shader: &'b Shader<'a>,
+           buffers: &'b mut HashMap<ResourceBinding, Vec<u8>>,
+           workgroup: &'b mut HashMap<u32, Value>,
+           builtins: &Builtins)
+           -> InterpretResult<Invocation<'a, 'b>> {
+        let vector = |values: [u32; 3]| {
+            Value::Composite(values.iter().map(|value| Value::Int(*value as u64, 32)).collect())
+        };
+        let mut global = [0; 3];
+        for (i, global) in global.iter_mut().enumerate() {
+            *global = builtins.workgroup_id[i] * builtins.local_size[i] + builtins.local_id[i];
+        }
+        let [x, y, z] = builtins.local_id;
+        let index = (z * builtins.local_size[1] + y) * builtins.local_size[0] + x;
+
+        let mut private = HashMap::new();
+        for (id, var) in &shader.variables {
+            let ty = try!(shader.pointed_type(&var.result_type));
+            let value = match var.storage_class {
+                StorageClass::Input => {
+                    match shader.builtins.get(id) {
+                        Some(&BuiltIn::GlobalInvocationId) => vector(global),
+                        Some(&BuiltIn::LocalInvocationId) => vector(builtins.local_id),
+                        Some(&BuiltIn::WorkgroupId) => vector(builtins.workgroup_id),
+                        Some(&BuiltIn::NumWorkgroups) => vector(builtins.workgroup_count),
+                        Some(&BuiltIn::LocalInvocationIndex) => Value::Int(index as u64, 32),
+                        _ => return Err(InterpretError::UnsupportedBuiltIn(OpId(*id))),
+                    }
+                }
+                StorageClass::Output |
+                StorageClass::Private => {
+                    match var.initializer {
+                        Some(ref initializer) => {
+                            match shader.constants.get(&initializer.0) {
+                                Some(value) => value.clone(),
+                                None => return Err(InterpretError::InvalidId(initializer.clone())),
+                            }
+                        }
+                        None => ty.zero(),
+                    }
+                }
+                _ => continue,
+            };
+            private.insert(*id, value);
+        }
+
+        Ok(Invocation {
+            shader,
+            global_id: global,
+            buffers,
+            workgroup,
+            private,
+            locals: Vec::new(),
+        })
+    }
+
+    /// Returns the value of an id in a function
+    fn value(&self, values: &Values, id: &OpId) -> InterpretResult<Value> {
+        if let Some(value) = values.get(&id.0).or_else(|| self.shader.constants.get(&id.0)) {
+            return Ok(value.clone());
+        }
+        let root = match self.shader.variables.get(&id.0) {
+            Some(var) if var.storage_class == StorageClass::Uniform => Root::Buffer(id.0),
+            Some(_) => Root::Global(id.0),
+            None => return Err(InterpretError::InvalidId(id.clone())),
+        };
+        Ok(Value::Pointer(Pointer { root, path: vec![] }))
+    }
+
+    fn pointer(&self, values: &Values, id: &OpId) -> InterpretResult<Pointer> {
+        match try!(self.value(values, id)) {
+            Value::Pointer(pointer) => Ok(pointer),
+            _ => Err(InterpretError::InvalidOperand(id.clone())),
+        }
+    }
+
+    /// Returns the binding, offset and type a pointer into a buffer refers to
+    fn buffer_location(&self, var: u32, path: &[u32], id: &OpId)
+                       -> InterpretResult<(ResourceBinding, u32, &'b Type)> {
+        let shader = self.shader;
+        let binding = shader.bindings[&var];
+        let mut ty = try!(shader.pointed_type(&shader.variables[&var].result_type));
+        let mut offset = 0;
+        for &index in path {
+            let (element_offset, element) = match *ty {
+                Type::Vector(ref component, count) if index < count => {
+                    (index * component.size(), &**component)
+                }
+                Type::Matrix(ref element, count, stride) |
+                Type::Array(ref element, count, stride) if index < count => {
+                    (index * stride, &**element)
+                }
+                Type::RuntimeArray(ref element, stride) => (index * stride, &**element),
+                Type::Struct(ref members) if (index as usize) < members.len() => {
+                    let (ref member, member_offset) = members[index as usize];
+                    (member_offset, member)
+                }
+                _ => return Err(InterpretError::OutOfBounds(id.clone())),
+            };
+            offset += element_offset;
+            ty = element;
+        }
+        Ok((binding, offset, ty))
+    }
+
+    /// Returns the variable a pointer refers to, which is not a buffer
+    fn variable(&self, root: Root, id: &OpId) -> InterpretResult<&Value> {
+        let value = match root {
+            Root::Global(var) => self.private.get(&var).or_else(|| self.workgroup.get(&var)),
+            Root::Local(index) => self.locals.get(index),
+            Root::Buffer(_) => None,
+        };
+        value.ok_or_else(|| InterpretError::InvalidId(id.clone()))
+    }
+
+    fn variable_mut(&mut self, root: Root, id: &OpId) -> InterpretResult<&mut Value> {
+        let value = match root {
+            Root::Global(var) => {
+                match self.private.get_mut(&var) {
+                    Some(value) => Some(value),
+                    None => self.workgroup.get_mut(&var),
+                }
+            }
+            Root::Local(index) => self.locals.get_mut(index),
+            Root::Buffer(_) => None,
+        };
+        value.ok_or_else(|| InterpretError::InvalidId(id.clone()))
+    }
+
+    fn load(&self, pointer: &Pointer, id: &OpId) -> InterpretResult<Value> {
+        if let Root::Buffer(var) = pointer.root {
+            let (binding, offset, ty) = try!(self.buffer_location(var, &pointer.path, id));
+            let bytes = match self.buffers.get(&binding) {
+                Some(bytes) => bytes,
+                None => return Err(InterpretError::UnboundResource(binding)),
+            };
+            return read_value(bytes, offset, ty)
+                .ok_or_else(|| InterpretError::OutOfBounds(id.clone()));
+        }
+        let mut value = try!(self.variable(pointer.root, id));
+        for &index in &pointer.path {
+            value = match *value {
+                Value::Composite(ref values) if (index as usize) < values.len() => {
+                    &values[index as usize]
+                }
+                _ => return Err(InterpretError::OutOfBounds(id.clone())),
+            };
+        }
+        Ok(value.clone())
+    }
+
+    fn store(&mut self, pointer: &Pointer, object: Value, id: &OpId) -> InterpretResult<()> {
+        if let Root::Buffer(var) = pointer.root {
+            let (binding, offset, ty) = try!(self.buffer_location(var, &pointer.path, id));
+            let bytes = match self.buffers.get_mut(&binding) {
+                Some(bytes) => bytes,
+                None => return Err(InterpretError::UnboundResource(binding)),
+            };
+            if !write_value(bytes, offset, ty, &object) {
+                return Err(InterpretError::OutOfBounds(id.clone()));
+            }
+            return Ok(());
+        }
+        let mut value = try!(self.variable_mut(pointer.root, id));
+        for &index in &pointer.path {
+            value = match *value {
+                Value::Composite(ref mut values) if (index as usize) < values.len() => {
+                    &mut values[index as usize]
+                }
+                _ => return Err(InterpretError::OutOfBounds(id.clone())),
+            };
+        }
+        *value = object;
+        Ok(())
+    }
+
+    /// Executes a function, returning its result if it is not void
+    fn call(&mut self,
+            observer: &mut Observer,
+            function: &OpId,
+            arguments: Vec<Value>)
+            -> InterpretResult<Option<Value>> {
+        let function = match self.shader.functions.get(&function.0) {
+            Some(function) => *function,
+            None => return Err(InterpretError::InvalidId(function.clone())),
+        };
+        let mut values = HashMap::new();
+        for (parameter, argument) in function.parameters.iter().zip(arguments) {
+            values.insert(parameter.result_id.0, argument);
+        }
+        let locals = self.locals.len();
+
+        let mut block = &function.blocks[0];
+        let mut previous = None;
+        let result = loop {
+            let label = OpId(block.label.result_id.0);
+            observer.block(previous.map(OpId), label, &State { invocation: self, values: &values });
+            // Every phi is evaluated before any of the results are assigned
+            let mut phis = Vec::new();
+            for op in &block.code {
+                if let GroupCode::OpPhi(ref op) = *op {
+                    let arg = op.variables.iter().find(|arg| Some(arg.parent.0) == previous);
+                    let value = match arg {
+                        Some(arg) => try!(self.value(&values, &arg.variable)),
+                        None => return Err(InterpretError::InvalidId(OpId(op.result_id.0))),
+                    };
+                    phis.push((op.result_id.0, value));
+                }
+            }
+            values.extend(phis);
+            for op in &block.code {
+                try!(self.execute(observer, &mut values, op));
+                let result = op.result_id().and_then(|id| values.get(&id.0));
+                observer.instruction(op, result, &State { invocation: self, values: &values });
+            }
+
+            let target = match block.branch {
+                GroupBranch::OpBranch(ref op) => &op.target_label,
+                GroupBranch::OpBranchConditional(ref op) => {
+                    match try!(self.value(&values, &op.condition)) {
+                        Value::Bool(true) => &op.true_label,
+                        Value::Bool(false) => &op.false_label,
+                        _ => return Err(InterpretError::InvalidOperand(op.condition.clone())),
+                    }
+                }
+                GroupBranch::OpReturn(_) => break None,
+                GroupBranch::OpReturnValue(ref op) => {
+                    break Some(try!(self.value(&values, &op.value)))
+                }
+            };
+            previous = Some(block.label.result_id.0);
+            block = match self.shader.blocks.get(&target.0) {
+                Some(block) => block,
+                None => return Err(InterpretError::InvalidId(target.clone())),
+            };
+        };
+
+        self.locals.truncate(locals);
+        Ok(result)
+    }
+
+    fn int_op<F>(&self, values: &Values, op: &GroupCode, f: F) -> InterpretResult<Value>
+        where F: Fn(u64, u64, u32) -> Option<u64>
+    {
+        let (a, b) = try!(self.operands(values, op));
+        let id = OpId(op.result_id().unwrap().0);
+        let divide_by_zero = Cell::new(false);
+        let result = componentwise(&a, &b, &|a, b| match (a, b) {
+            (&Value::Int(a, width), &Value::Int(b, _)) => {
+                let bits = f(a, b, width).unwrap_or_else(|| {
+                    divide_by_zero.set(true);
+                    0
+                });
+                Some(Value::Int(mask(bits, width), width))
+            }
+            _ => None,
+        });
+        match result {
+            Some(_) if divide_by_zero.get() => Err(InterpretError::DivisionByZero(id)),
+            Some(result) => Ok(result),
+            None => Err(InterpretError::InvalidOperand(id)),
+        }
+    }
+
+    fn int_cmp<F>(&self, values: &Values, op: &GroupCode, f: F) -> InterpretResult<Value>
+        where F: Fn(u64, u64, u32) -> bool
+    {
+        let (a, b) = try!(self.operands(values, op));
+        let result = componentwise(&a, &b, &|a, b| match (a, b) {
+            (&Value::Int(a, width), &Value::Int(b, _)) => Some(Value::Bool(f(a, b, width))),
+            _ => None,
+        });
+        result.ok_or_else(|| InterpretError::InvalidOperand(OpId(op.result_id().unwrap().0)))
+    }
+
+    fn float_op<F>(&self, values: &Values, op: &GroupCode, f: F) -> InterpretResult<Value>
+        where F: Fn(f64, f64) -> f64
+    {
+        let (a, b) = try!(self.operands(values, op));
+        let result = componentwise(&a, &b, &|a, b| match (a, b) {
+            (&Value::Float(a, width), &Value::Float(b, _)) => {
+                Some(Value::Float(round(f(a, b), width), width))
+            }
+            _ => None,
+        });
+        result.ok_or_else(|| InterpretError::InvalidOperand(OpId(op.result_id().unwrap().0)))
+    }
+
+    fn float_cmp<F>(&self, values: &Values, op: &GroupCode, f: F) -> InterpretResult<Value>
+        where F: Fn(f64, f64) -> bool
+    {
+        let (a, b) = try!(self.operands(values, op));
+        let result = componentwise(&a, &b, &|a, b| match (a, b) {
+            (&Value::Float(a, _), &Value::Float(b, _)) => Some(Value::Bool(f(a, b))),
+            _ => None,
+        });
+        result.ok_or_else(|| InterpretError::InvalidOperand(OpId(op.result_id().unwrap().0)))
+    }
+
+    /// Returns the two operands of a binary instruction
+    fn operands(&self, values: &Values, op: &GroupCode) -> InterpretResult<(Value, Value)> {
+        let operands = op.operands();
+        Ok((try!(self.value(values, operands[operands.len() - 2])),
+            try!(self.value(values, operands[operands.len() - 1]))))
+    }
+
+    /// Executes an instruction, assigning its result to its id
+    fn execute(&mut self,
+               observer: &mut Observer,
+               values: &mut Values,
+               op: &GroupCode)
+               -> InterpretResult<()> {
+        use self::GroupCode::*;
+        let value = match *op {
+            OpNop(_) | OpLine(_) | OpNoLine(_) | OpPhi(_) => return Ok(()),
+            OpUndef(ref op) => try!(self.shader.get_type(&op.result_type)).zero(),
+            OpExtInst(ref op) => {
+                let set = self.shader.ext_inst_imports.get(&op.set.0).cloned();
+                let inst = op.instruction.as_ref().as_any().downcast_ref::<glsl450::Inst>();
+                let (x, f): (_, fn(f64) -> f64) = match (set, inst) {
+                    (Some("GLSL.std.450"), Some(glsl450::Inst::Sin(inst))) => (&inst.x, f64::sin),
+                    (Some("GLSL.std.450"), Some(glsl450::Inst::Cos(inst))) => (&inst.x, f64::cos),
+                    _ => return Err(InterpretError::UnsupportedInstruction(format!("{}", op))),
+                };
+                let x = try!(self.value(values, x));
+                let result = map_components(&x, &|x| match *x {
+                    Value::Float(x, width) => Some(Value::Float(round(f(x), width), width)),
+                    _ => None,
+                });
+                try!(result.ok_or(InterpretError::InvalidOperand(OpId(op.result_id.0))))
+            }
+            OpVariable(ref op) => {
+                let value = match op.initializer {
+                    Some(ref initializer) => try!(self.value(values, initializer)),
+                    None => try!(self.shader.pointed_type(&op.result_type)).zero(),
+                };
+                self.locals.push(value);
+                let root = Root::Local(self.locals.len() - 1);
+                Value::Pointer(Pointer { root, path: vec![] })
+            }
+            OpFunctionCall(ref op) => {
+                let mut arguments = Vec::new();
+                for argument in &op.arguments {
+                    arguments.push(try!(self.value(values, argument)));
+                }
+                match try!(self.call(observer, &op.function, arguments)) {
+                    Some(value) => value,
+                    None => return Ok(()),
+                }
+            }
+            OpLoad(ref op) => {
+                let pointer = try!(self.pointer(values, &op.pointer));
+                try!(self.load(&pointer, &OpId(op.result_id.0)))
+            }
+            OpStore(ref op) => {
+                let pointer = try!(self.pointer(values, &op.pointer));
+                let object = try!(self.value(values, &op.object));
+                return self.store(&pointer, object, &op.pointer);
+            }
+            OpAccessChain(ref op) => {
+                let mut pointer = try!(self.pointer(values, &op.base));
+                for index in &op.indexes {
+                    match try!(self.value(values, index)) {
+                        Value::Int(index, _) => pointer.path.push(index as u32),
+                        _ => return Err(InterpretError::InvalidOperand(index.clone())),
+                    }
+                }
+                Value::Pointer(pointer)
+            }
+            OpConvertUToF(ref op) => {
+                let value = try!(self.value(values, &op.unsigned_value));
+                let ty = try!(self.shader.get_type(&op.result_type)).scalar();
+                let result = map_components(&value, &|value| match (value, ty) {
+                    (&Value::Int(bits, _), &Type::Float(32)) => {
+                        Some(Value::Float(bits as f32 as f64, 32))
+                    }
+                    (&Value::Int(bits, _), &Type::Float(width)) => {
+                        Some(Value::Float(bits as f64, width))
+                    }
+                    _ => None,
+                });
+                try!(result.ok_or(InterpretError::InvalidOperand(OpId(op.result_id.0))))
+            }
+            OpBitcast(ref op) => {
+                let value = try!(self.value(values, &op.operand));
+                let ty = try!(self.shader.get_type(&op.result_type)).scalar().clone();
+                let result = map_components(&value, &|value| {
+                    let bits = match *value {
+                        Value::Int(bits, _) => bits,
+                        Value::Float(value, 32) => (value as f32).to_bits() as u64,
+                        Value::Float(value, _) => value.to_bits(),
+                        _ => return None,
+                    };
+                    match ty {
+                        Type::Int(width) => Some(Value::Int(mask(bits, width), width)),
+                        Type::Float(32) => {
+                            Some(Value::Float(f32::from_bits(bits as u32) as f64, 32))
+                        }
+                        Type::Float(width) => Some(Value::Float(f64::from_bits(bits), width)),
+                        _ => None,
+                    }
+                });
+                try!(result.ok_or(InterpretError::InvalidOperand(OpId(op.result_id.0))))
+            }
+            OpIAdd(_) => try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_add(b)))),
+            OpISub(_) => try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_sub(b)))),
+            OpIMul(_) => try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_mul(b)))),
+            OpUDiv(_) => try!(self.int_op(values, op, |a, b, _| a.checked_div(b))),
+            OpSDiv(_) => {
+                try!(self.int_op(values, op, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    if b == 0 { None } else { Some(a.wrapping_div(b) as u64) }
+                }))
+            }
+            OpUMod(_) => try!(self.int_op(values, op, |a, b, _| a.checked_rem(b))),
+            OpSRem(_) => {
+                // Sign of the result matches the sign of operand 1
+                try!(self.int_op(values, op, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    if b == 0 { None } else { Some(a.wrapping_rem(b) as u64) }
+                }))
+            }
+            OpSMod(_) => {
+                // Sign of the result matches the sign of operand 2
+                try!(self.int_op(values, op, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    if b == 0 {
+                        return None;
+                    }
+                    let r = a.wrapping_rem(b);
+                    Some(if r != 0 && (r < 0) != (b < 0) { r + b } else { r } as u64)
+                }))
+            }
+            OpIAddCarry(_) => {
+                let low = try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_add(b))));
+                let high = try!(self.int_op(values, op, |a, b, width| {
+                    let (sum, overflow) = a.overflowing_add(b);
+                    Some(if width >= 64 { overflow as u64 } else { (sum >> width) & 1 })
+                }));
+                Value::Composite(vec![low, high])
+            }
+            OpISubBorrow(_) => {
+                let low = try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_sub(b))));
+                let high = try!(self.int_op(values, op, |a, b, _| Some((a < b) as u64)));
+                Value::Composite(vec![low, high])
+            }
+            OpUMulExtended(_) => {
+                let low = try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_mul(b))));
+                let high = try!(self.int_op(values, op, |a, b, width| {
+                    Some(((a as u128 * b as u128) >> width) as u64)
+                }));
+                Value::Composite(vec![low, high])
+            }
+            OpSMulExtended(_) => {
+                let low = try!(self.int_op(values, op, |a, b, _| Some(a.wrapping_mul(b))));
+                let high = try!(self.int_op(values, op, |a, b, width| {
+                    let product = sign_extend(a, width) as i128 * sign_extend(b, width) as i128;
+                    Some((product >> width) as u64)
+                }));
+                Value::Composite(vec![low, high])
+            }
+            OpBitwiseOr(_) => try!(self.int_op(values, op, |a, b, _| Some(a | b))),
+            OpBitwiseXor(_) => try!(self.int_op(values, op, |a, b, _| Some(a ^ b))),
+            OpBitwiseAnd(_) => try!(self.int_op(values, op, |a, b, _| Some(a & b))),
+            OpFAdd(_) => try!(self.float_op(values, op, |a, b| a + b)),
+            OpFSub(_) => try!(self.float_op(values, op, |a, b| a - b)),
+            OpFMul(_) => try!(self.float_op(values, op, |a, b| a * b)),
+            OpFDiv(_) => try!(self.float_op(values, op, |a, b| a / b)),
+            OpFRem(_) => try!(self.float_op(values, op, |a, b| a % b)),
+            OpFMod(_) => {
+                try!(self.float_op(values, op, |a, b| {
+                    let r = a % b;
+                    if r != 0.0 && (r < 0.0) != (b < 0.0) { r + b } else { r }
+                }))
+            }
+            OpIEqual(_) => try!(self.int_cmp(values, op, |a, b, _| a == b)),
+            OpINotEqual(_) => try!(self.int_cmp(values, op, |a, b, _| a != b)),
+            OpUGreaterThan(_) => try!(self.int_cmp(values, op, |a, b, _| a > b)),
+            OpUGreaterThanEqual(_) => try!(self.int_cmp(values, op, |a, b, _| a >= b)),
+            OpULessThan(_) => try!(self.int_cmp(values, op, |a, b, _| a < b)),
+            OpULessThanEqual(_) => try!(self.int_cmp(values, op, |a, b, _| a <= b)),
+            OpSGreaterThan(_) => {
+                try!(self.int_cmp(values, op, |a, b, w| sign_extend(a, w) > sign_extend(b, w)))
+            }
+            OpSGreaterThanEqual(_) => {
+                try!(self.int_cmp(values, op, |a, b, w| sign_extend(a, w) >= sign_extend(b, w)))
+            }
+            OpSLessThan(_) => {
+                try!(self.int_cmp(values, op, |a, b, w| sign_extend(a, w) < sign_extend(b, w)))
+            }
+            OpSLessThanEqual(_) => {
+                try!(self.int_cmp(values, op, |a, b, w| sign_extend(a, w) <= sign_extend(b, w)))
+            }
+            // Ordered comparisons are false if either operand is NaN, which
+            // matches the float comparison operators
+            OpFOrdEqual(_) => try!(self.float_cmp(values, op, |a, b| a == b)),
+            OpFOrdNotEqual(_) => {
+                try!(self.float_cmp(values, op, |a, b| !a.is_nan() && !b.is_nan() && a != b))
+            }
+            OpFOrdLessThan(_) => try!(self.float_cmp(values, op, |a, b| a < b)),
+            OpFOrdGreaterThan(_) => try!(self.float_cmp(values, op, |a, b| a > b)),
+            OpFOrdLessThanEqual(_) => try!(self.float_cmp(values, op, |a, b| a <= b)),
+            OpFOrdGreaterThanEqual(_) => try!(self.float_cmp(values, op, |a, b| a >= b)),
+            // Unordered comparisons are true if either operand is NaN
+            OpFUnordEqual(_) => {
+                try!(self.float_cmp(values, op, |a, b| a.is_nan() || b.is_nan() || a == b))
+            }
+            OpFUnordNotEqual(_) => try!(self.float_cmp(values, op, |a, b| a != b)),
+            OpFUnordLessThan(_) => {
+                try!(self.float_cmp(values, op, |a, b| a.is_nan() || b.is_nan() || a < b))
+            }
+            OpFUnordGreaterThan(_) => {
+                try!(self.float_cmp(values, op, |a, b| a.is_nan() || b.is_nan() || a > b))
+            }
+            OpFUnordLessThanEqual(_) => {
+                try!(self.float_cmp(values, op, |a, b| a.is_nan() || b.is_nan() || a <= b))
+            }
+            OpFUnordGreaterThanEqual(_) => {
+                try!(self.float_cmp(values, op, |a, b| a.is_nan() || b.is_nan() || a >= b))
+            }
+        };
+        values.insert(op.result_id().unwrap().0, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt;
+use super::*;
+
+/// An event in the execution of an invocation
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// Execution entered a block, from the block it branched from or at the
+    /// start of a function
+    Block(Option<OpId>, OpId),
+    /// An instruction was executed, with its result if it has one
+    Instruction(GroupCode, Option<Value>),
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TraceEvent::Block(Some(ref from), ref to) => write!(f, "{} -> {}", from, to),
+            TraceEvent::Block(None, ref to) => write!(f, "-> {}", to),
+            TraceEvent::Instruction(ref op, Some(ref value)) => write!(f, "{} ; {}", op, value),
+            TraceEvent::Instruction(ref op, None) => write!(f, "{}", op),
+        }
+    }
+}
+
+/// The events of a single invocation, in the order they happened
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvocationTrace {
+    pub global_id: [u32; 3],
+    pub events: Vec<TraceEvent>,
+}
+
+/// Records every executed instruction and block transition of invocations
+///
+/// A trace is passed as the observer of a dispatch. It records either every
+/// invocation, which uses a lot of memory for large dispatches, or only the
+/// invocation with a given global invocation id.
+#[derive(Default)]
+pub struct Trace {
+    global_id: Option<[u32; 3]>,
+    invocations: Vec<InvocationTrace>,
+    /// If the invocation being executed is recorded
+    recording: bool,
+}
+
+impl Trace {
+    /// Creates a trace that records every invocation
+    pub fn new() -> Trace {
+        Trace::default()
+    }
+
+    /// Creates a trace that records the invocation with a global invocation
+    /// id
+    pub fn for_invocation(global_id: [u32; 3]) -> Trace {
+        Trace { global_id: Some(global_id), ..Trace::default() }
+    }
+
+    /// Returns the recorded invocations, in the order they were executed
+    pub fn invocations(&self) -> &[InvocationTrace] {
+        &self.invocations
+    }
+
+    fn push(&mut self, event: TraceEvent) {
+        if self.recording {
+            self.invocations.last_mut().unwrap().events.push(event);
+        }
+    }
+}
+
+impl Observer for Trace {
+    fn invocation(&mut self, global_id: [u32; 3]) {
+        self.recording = self.global_id.is_none() || self.global_id == Some(global_id);
+        if self.recording {
+            self.invocations.push(InvocationTrace { global_id, events: vec![] });
+        }
+    }
+
+    fn block(&mut self, from: Option<OpId>, to: OpId, _: &State) {
+        self.push(TraceEvent::Block(from, to));
+    }
+
+    fn instruction(&mut self, op: &GroupCode, result: Option<&Value>, _: &State) {
+        self.push(TraceEvent::Instruction(op.clone(), result.cloned()));
+    }
+}
+
+/// A point in execution where the debugger stops
+#[derive(Clone, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// After the instruction with a result id is executed
+    Result(OpId),
+    /// When execution enters the block with a label id
+    Block(OpId),
+}
+
+/// The state of the debugged invocation when it reached a breakpoint
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    pub breakpoint: Breakpoint,
+    /// The index of the event that reached the breakpoint in the events of
+    /// the invocation, where stepping through execution can continue from
+    pub event: usize,
+    values: HashMap<String, Value>,
+}
+
+impl Stop {
+    /// Returns the value of an id with a debug name at the breakpoint, which
+    /// is the pointed to value for variables
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+/// Debugs a single invocation
+///
+/// The debugger is passed as the observer of a dispatch. Execution is
+/// deterministic, so the invocation is recorded as it runs and each time it
+/// reaches a breakpoint the values of every named id are kept in a stop.
+/// Execution can then be stepped through with the recorded events, starting
+/// from the event of any stop.
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    trace: Trace,
+    stops: Vec<Stop>,
+}
+
+impl Debugger {
+    /// Creates a debugger for the invocation with a global invocation id
+    pub fn new(global_id: [u32; 3], breakpoints: Vec<Breakpoint>) -> Debugger {
+        Debugger {
+            breakpoints,
+            trace: Trace::for_invocation(global_id),
+            stops: Vec::new(),
+        }
+    }
+
+    /// Returns the breakpoints reached, in the order they were reached
+    pub fn stops(&self) -> &[Stop] {
+        &self.stops
+    }
+
+    /// Returns the events of the invocation
+    pub fn events(&self) -> &[TraceEvent] {
+        match self.trace.invocations().last() {
+            Some(invocation) => &invocation.events,
+            None => &[],
+        }
+    }
+
+    fn check(&mut self, breakpoint: Breakpoint, state: &State) {
+        if self.trace.recording && self.breakpoints.contains(&breakpoint) {
+            self.stops.push(Stop {
+                breakpoint,
+                event: self.events().len() - 1,
+                values: state.named_values(),
+            });
+        }
+    }
+}
+
+impl Observer for Debugger {
+    fn invocation(&mut self, global_id: [u32; 3]) {
+        self.trace.invocation(global_id);
+    }
+
+    fn block(&mut self, from: Option<OpId>, to: OpId, state: &State) {
+        self.trace.block(from, to.clone(), state);
+        self.check(Breakpoint::Block(to), state);
+    }
+
+    fn instruction(&mut self, op: &GroupCode, result: Option<&Value>, state: &State) {
+        self.trace.instruction(op, result, state);
+        if let Some(id) = op.result_id() {
+            self.check(Breakpoint::Result(OpId(id.0)), state);
+        }
+    }
+}
@@ -1,8 +1,15 @@
 
 extern crate byteorder;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub mod spv;
 pub mod glsl450;
+pub mod backend;
+pub mod interpreter;
 
 #[cfg(test)]
 mod tests;
@@ -2,50 +2,135 @@
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use spv::raw::FriendlyNames;
 use spv::types::*;
 
+const RESET: &str = "\x1b[0m";
+const OPCODE_COLOR: &str = "\x1b[34m";
+const ID_COLOR: &str = "\x1b[33m";
+const LITERAL_COLOR: &str = "\x1b[32m";
+
+/// Selects how instructions are printed
+///
+/// The default style is the style of `spirv-dis` with numeric ids.
+#[derive(Clone, Copy, Debug)]
+pub struct Style<'a> {
+    /// Right align result ids so every opcode starts in the same column
+    pub align_ids: bool,
+    /// Print ids with these names instead of their numbers
+    pub names: Option<&'a FriendlyNames>,
+    /// Colour opcodes, ids and literals with ANSI escape codes
+    pub color: bool,
+}
+
+impl<'a> Default for Style<'a> {
+    fn default() -> Style<'a> {
+        Style {
+            align_ids: true,
+            names: None,
+            color: false,
+        }
+    }
+}
+
+impl<'a> Style<'a> {
+    /// Returns an id as it is printed, with its name or number
+    fn id_text(&self, id: u32) -> String {
+        match self.names.and_then(|names| names.id(id)) {
+            Some(name) => format!("%{}", name),
+            None => format!("%{}", id),
+        }
+    }
+
+    /// Writes an id with its name or number
+    pub fn write_id(&self, id: u32, f: &mut Formatter) -> fmt::Result {
+        self.write_colored(ID_COLOR, &self.id_text(id), f)
+    }
+
+    /// Writes the name of an instruction
+    pub fn write_opcode(&self, name: &str, f: &mut Formatter) -> fmt::Result {
+        self.write_colored(OPCODE_COLOR, name, f)
+    }
+
+    /// Writes a number or string literal
+    pub fn write_literal(&self, literal: &Display, f: &mut Formatter) -> fmt::Result {
+        self.write_colored(LITERAL_COLOR, &format!("{}", literal), f)
+    }
+
+    fn write_colored(&self, color: &str, text: &str, f: &mut Formatter) -> fmt::Result {
+        if self.color {
+            write!(f, "{}{}{}", color, text, RESET)
+        } else {
+            f.write_str(text)
+        }
+    }
+}
+
+/// Formats an instruction as it is printed in disassembly
+pub trait Disassemble {
+    fn disassemble(&self, style: &Style, f: &mut Formatter) -> fmt::Result;
+}
+
+/// Helper for printing an instruction in a style
+pub struct Styled<'a, 'b: 'a, T: 'a>(pub &'a T, pub &'a Style<'b>);
+
+impl<'a, 'b, T: Disassemble> Display for Styled<'a, 'b, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.disassemble(self.1, f)
+    }
+}
+
 /// Helper for printing the result id
-pub struct Result<'a>(pub &'a ResultId);
+pub struct Result<'a, 'b: 'a>(pub &'a ResultId, pub &'a Style<'b>);
 
-impl<'a> Display for Result<'a> {
+impl<'a, 'b> Display for Result<'a, 'b> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{:>12} = ", OpId((self.0).0))
+        let id = (self.0).0;
+        if self.1.align_ids {
+            for _ in self.1.id_text(id).chars().count()..12 {
+                try!(f.write_str(" "));
+            }
+        }
+        try!(self.1.write_id(id, f));
+        write!(f, " = ")
     }
 }
 
 /// Helper for printing the space to align with instructions that return a result
-pub struct NoResult;
+pub struct NoResult<'a, 'b: 'a>(pub &'a Style<'b>);
 
-impl Display for NoResult {
+impl<'a, 'b> Display for NoResult<'a, 'b> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "               ")
-
+        if self.0.align_ids {
+            try!(write!(f, "               "));
+        }
+        Ok(())
     }
 }
 
 /// Formats an argument for display as an argument to an intruction. This
 /// / includes padding at the start and between elements if there are multiple.
 pub trait FormatArg {
-    fn format_arg(&self, f: &mut Formatter) -> fmt::Result;
+    fn format_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result;
 }
 
 impl<T> FormatArg for T
     where T: DisplayArg
 {
-    fn format_arg(&self, f: &mut Formatter) -> fmt::Result {
+    fn format_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
         try!(write!(f, " "));
-        <T as DisplayArg>::display_arg(self, f)
+        <T as DisplayArg>::display_arg(self, style, f)
     }
 }
 
 impl<T> FormatArg for Option<T>
     where T: DisplayArg
 {
-    fn format_arg(&self, f: &mut Formatter) -> fmt::Result {
+    fn format_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
         match *self {
             Some(ref t) => {
                 try!(write!(f, " "));
-                <T as DisplayArg>::display_arg(t, f)
+                <T as DisplayArg>::display_arg(t, style, f)
             }
             None => Ok(()),
         }
@@ -53,12 +138,12 @@ impl<T> FormatArg for Option<T>
 }
 
 impl<T> FormatArg for Vec<T>
-    where T: DisplayArgType
+    where T: DisplayArg
 {
-    fn format_arg(&self, f: &mut Formatter) -> fmt::Result {
+    fn format_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
         for element in self {
             try!(write!(f, " "));
-            try!(<T as DisplayArg>::display_arg(element, f));
+            try!(<T as DisplayArg>::display_arg(element, style, f));
         }
         Ok(())
     }
@@ -66,12 +151,25 @@ impl<T> FormatArg for Vec<T>
 
 /// Formats an argument element for display inside a formatted argument.
 pub trait DisplayArg {
-    fn display_arg(&self, f: &mut Formatter) -> fmt::Result;
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result;
 }
 
 impl DisplayArg for String {
-    fn display_arg(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "\"{}\"", self)
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        let escaped = self.replace('\\', "\\\\").replace('"', "\\\"");
+        style.write_literal(&format!("\"{}\"", escaped), f)
+    }
+}
+
+impl DisplayArg for OpId {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_id(self.0, f)
+    }
+}
+
+impl DisplayArg for u32 {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_literal(self, f)
     }
 }
 
@@ -81,40 +179,47 @@ impl DisplayArg for String {
 pub trait DisplayArgType: Display {}
 
 impl<T: DisplayArgType> DisplayArg for T {
-    fn display_arg(&self, f: &mut Formatter) -> fmt::Result {
+    fn display_arg(&self, _style: &Style, f: &mut Formatter) -> fmt::Result {
         <T as Display>::fmt(self, f)
     }
 }
 
-impl DisplayArgType for u32 {}
-
 /// Macro to implement displaying for an instruction
+///
+/// This also implements `Operands` for the instruction from the same operand
+/// list.
 macro_rules! def_op_display {
     ($name: ident; result_id = $($operand_name: ident)|*) => {
+        impl $crate::spv::dis::Disassemble for $name {
+            fn disassemble(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+                try!(write!(f, "{}", Result(&self.result_id, style)));
+                try!(style.write_opcode(stringify!($name), f));
+                $(try!($crate::spv::dis::FormatArg::format_arg(&self.$operand_name, style, f));)*
+                Ok(())
+            }
+        }
         impl Display for $name {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                try!(write!(f,
-                    "{}{}",
-                    Result(&self.result_id),
-                    stringify!($name),
-                ));
-                $(try!($crate::spv::dis::FormatArg::format_arg(&self.$operand_name, f));)*
-                Ok(())
+                $crate::spv::dis::Disassemble::disassemble(self, &Style::default(), f)
             }
         }
+        def_op_operands!($name; result_id = $($operand_name)|*);
     };
     ($name: ident; $($operand_name: ident)|*) => {
+        impl $crate::spv::dis::Disassemble for $name {
+            fn disassemble(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+                try!(write!(f, "{}", NoResult(style)));
+                try!(style.write_opcode(stringify!($name), f));
+                $(try!($crate::spv::dis::FormatArg::format_arg(&self.$operand_name, style, f));)*
+                Ok(())
+            }
+        }
         impl Display for $name {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                try!(write!(f,
-                    "{}{}",
-                    NoResult,
-                    stringify!($name),
-                ));
-                $(try!($crate::spv::dis::FormatArg::format_arg(&self.$operand_name, f));)*
-                Ok(())
+                $crate::spv::dis::Disassemble::disassemble(self, &Style::default(), f)
             }
         }
+        def_op_operands!($name; $($operand_name)|*);
     };
 }
 
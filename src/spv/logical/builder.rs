@@ -0,0 +1,651 @@
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// A block that is still having instructions added to it
+struct BlockScope {
+    label: OpLabel,
+    code: Vec<GroupCode>,
+    merge: Option<GroupMerge>,
+}
+
+/// Constructs a module one instruction at a time
+///
+/// Ids are allocated in increasing order starting from 1. Types and
+/// constants are deduplicated, so asking for the same type twice returns the
+/// same id. Struct types and variables are always new definitions. The
+/// module is given version 1.0 and the unregistered generator id of 0.
+///
+/// Functions are built by calling `begin_function`, adding parameters, then
+/// adding blocks with `begin_block` followed by code and a branch or return
+/// that ends the block, and finally calling `end_function`. Labels for
+/// blocks that are branched to before they are begun can be allocated with
+/// `id`. Adding code outside of a block or finishing a function or module
+/// with a block or function still open panics.
+pub struct Builder {
+    module: LogicalModule,
+    next_id: u32,
+    /// Deduplicated globals with their result id cleared
+    globals: Vec<(GroupGlobal, OpId)>,
+    function: Option<FunctionDefinition>,
+    block: Option<BlockScope>,
+}
+
+macro_rules! def_binary_ops {
+    ($($name: ident => $op: ident),*) => {
+        $(
+            pub fn $name(&mut self, result_type: OpId, operand1: OpId, operand2: OpId) -> OpId {
+                let result_id = self.id();
+                self.code(GroupCode::$op($op {
+                    result_type,
+                    result_id: ResultId(result_id.0),
+                    operand1,
+                    operand2,
+                }));
+                result_id
+            }
+        )*
+    }
+}
+
+impl Builder {
+    pub fn new(addressing_model: AddressingModel, memory_model: MemoryModel) -> Builder {
+        Builder {
+            module: LogicalModule {
+                version: Version(1, 0),
+                generator: Generator {
+                    tool: Tool::KhronosReserved,
+                    version: 0,
+                },
+                capabilities: Vec::new(),
+                extensions: Vec::new(),
+                ext_inst_imports: Vec::new(),
+                memory_model: OpMemoryModel {
+                    addressing_model,
+                    memory_model,
+                },
+                entry_points: Vec::new(),
+                execution_modes: Vec::new(),
+                debug: Vec::new(),
+                annotations: Vec::new(),
+                globals: Vec::new(),
+                function_declarations: Vec::new(),
+                function_definitions: Vec::new(),
+            },
+            next_id: 1,
+            globals: Vec::new(),
+            function: None,
+            block: None,
+        }
+    }
+
+    /// Allocates a new id
+    pub fn id(&mut self) -> OpId {
+        let id = self.next_id;
+        self.next_id += 1;
+        OpId(id)
+    }
+
+    /// Returns one more than the highest allocated id
+    pub fn bound(&self) -> u32 {
+        self.next_id
+    }
+
+    /// Returns the module that has been built
+    ///
+    /// Panics if a function has not been ended.
+    pub fn finish(self) -> LogicalModule {
+        if let Some(ref function) = self.function {
+            panic!("function {} was not ended", function.function.result_id.0);
+        }
+        self.module
+    }
+
+    pub fn capability(&mut self, capability: Capability) {
+        if !self.module.capabilities.contains(&capability) {
+            self.module.capabilities.push(capability);
+        }
+    }
+
+    pub fn extension(&mut self, name: &str) {
+        if !self.module.extensions.iter().any(|extension| extension == name) {
+            self.module.extensions.push(name.into());
+        }
+    }
+
+    pub fn ext_inst_import(&mut self, name: &str) -> OpId {
+        if let Some(op) = self.module.ext_inst_imports.iter().find(|op| op.name == name) {
+            return OpId(op.result_id.0);
+        }
+        let result_id = self.id();
+        self.module.ext_inst_imports.push(OpExtInstImport {
+            result_id: ResultId(result_id.0),
+            name: name.into(),
+        });
+        result_id
+    }
+
+    pub fn entry_point(&mut self,
+                       execution_model: ExecutionModel,
+                       entry_point: OpId,
+                       name: &str,
+                       interface: Vec<OpId>) {
+        self.module.entry_points.push(OpEntryPoint {
+            execution_model,
+            entry_point,
+            name: name.into(),
+            interface,
+        });
+    }
+
+    pub fn execution_mode(&mut self, entry_point: OpId, mode: ExecutionMode) {
+        self.module.execution_modes.push(OpExecutionMode { entry_point, mode });
+    }
+
+    pub fn name(&mut self, target: OpId, name: &str) {
+        self.module.debug.push(GroupDebug::OpName(OpName {
+            target,
+            name: name.into(),
+        }));
+    }
+
+    pub fn member_name(&mut self, struct_type: OpId, member: u32, name: &str) {
+        self.module.debug.push(GroupDebug::OpMemberName(OpMemberName {
+            struct_type,
+            member: MemberIndex(member),
+            name: name.into(),
+        }));
+    }
+
+    pub fn decorate(&mut self, target: OpId, decoration: Decoration) {
+        self.module.annotations.push(GroupAnnotation::OpDecorate(OpDecorate {
+            target,
+            decoration,
+        }));
+    }
+
+    pub fn member_decorate(&mut self, structure_type: OpId, member: u32, decoration: Decoration) {
+        self.module.annotations.push(GroupAnnotation::OpMemberDecorate(OpMemberDecorate {
+            structure_type,
+            member: MemberIndex(member),
+            decoration,
+        }));
+    }
+
+    /// Adds a global, or returns the id of an identical global that has
+    /// already been added
+    ///
+    /// The result id of the global is replaced with a new id.
+    fn global(&mut self, mut op: GroupGlobal) -> OpId {
+        if let Some(result_id) = op.result_id_mut() {
+            result_id.0 = 0;
+        }
+        if let Some((_, id)) = self.globals.iter().find(|global| global.0 == op) {
+            return id.clone();
+        }
+        let id = self.id();
+        self.globals.push((op.clone(), id.clone()));
+        self.new_global(op, id.clone());
+        id
+    }
+
+    /// Adds a global that is never deduplicated
+    fn new_global(&mut self, mut op: GroupGlobal, id: OpId) {
+        if let Some(result_id) = op.result_id_mut() {
+            result_id.0 = id.0;
+        }
+        self.module.globals.push(op);
+    }
+
+    pub fn type_void(&mut self) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeVoid(OpTypeVoid {
+            result_id: ResultId(0),
+        })))
+    }
+
+    pub fn type_bool(&mut self) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeBool(OpTypeBool {
+            result_id: ResultId(0),
+        })))
+    }
+
+    pub fn type_int(&mut self, width: u32, signed: bool) -> OpId {
+        let signedness = if signed {
+            Signedness::Signed
+        } else {
+            Signedness::UnsignedOrNone
+        };
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeInt(OpTypeInt {
+            result_id: ResultId(0),
+            width,
+            signedness,
+        })))
+    }
+
+    pub fn type_float(&mut self, width: u32) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeFloat(OpTypeFloat {
+            result_id: ResultId(0),
+            width,
+        })))
+    }
+
+    pub fn type_vector(&mut self, component_type: OpId, component_count: u32) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeVector(OpTypeVector {
+            result_id: ResultId(0),
+            component_type,
+            component_count,
+        })))
+    }
+
+    /// Adds an array type with a length that is the id of a constant
+    pub fn type_array(&mut self, element_type: OpId, length: OpId) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeArray(OpTypeArray {
+            result_id: ResultId(0),
+            element_type,
+            length,
+        })))
+    }
+
+    pub fn type_runtime_array(&mut self, element_type: OpId) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeRuntimeArray(OpTypeRuntimeArray {
+            result_id: ResultId(0),
+            element_type,
+        })))
+    }
+
+    /// Adds a new struct type
+    ///
+    /// Struct types are never deduplicated, as identical structs may be
+    /// decorated differently.
+    pub fn type_struct(&mut self, member_types: Vec<OpId>) -> OpId {
+        let id = self.id();
+        self.new_global(GroupGlobal::GroupType(GroupType::OpTypeStruct(OpTypeStruct {
+                            result_id: ResultId(0),
+                            member_types,
+                        })),
+                        id.clone());
+        id
+    }
+
+    pub fn type_pointer(&mut self, storage_class: StorageClass, pointed_type: OpId) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypePointer(OpTypePointer {
+            result_id: ResultId(0),
+            storage_class,
+            pointed_type,
+        })))
+    }
+
+    pub fn type_function(&mut self, return_type: OpId, parameter_types: Vec<OpId>) -> OpId {
+        self.global(GroupGlobal::GroupType(GroupType::OpTypeFunction(OpTypeFunction {
+            result_id: ResultId(0),
+            return_type,
+            parameter_types,
+        })))
+    }
+
+    /// Adds a boolean constant, adding the bool type if needed
+    pub fn constant_bool(&mut self, value: bool) -> OpId {
+        let result_type = self.type_bool();
+        let result_id = ResultId(0);
+        let constant = if value {
+            GroupConstant::OpConstantTrue(OpConstantTrue { result_type, result_id })
+        } else {
+            GroupConstant::OpConstantFalse(OpConstantFalse { result_type, result_id })
+        };
+        self.global(GroupGlobal::GroupConstant(constant))
+    }
+
+    /// Adds a scalar constant with the words of its value, lowest order word
+    /// first
+    pub fn constant(&mut self, result_type: OpId, value: LitBytes) -> OpId {
+        self.global(GroupGlobal::GroupConstant(GroupConstant::OpConstant(OpConstant {
+            result_type,
+            result_id: ResultId(0),
+            value,
+        })))
+    }
+
+    /// Adds a 32 bit unsigned integer constant, adding the type if needed
+    pub fn constant_u32(&mut self, value: u32) -> OpId {
+        let result_type = self.type_int(32, false);
+        self.constant(result_type, vec![value])
+    }
+
+    /// Adds a 32 bit signed integer constant, adding the type if needed
+    pub fn constant_i32(&mut self, value: i32) -> OpId {
+        let result_type = self.type_int(32, true);
+        self.constant(result_type, vec![value as u32])
+    }
+
+    /// Adds a 32 bit float constant, adding the type if needed
+    pub fn constant_f32(&mut self, value: f32) -> OpId {
+        let result_type = self.type_float(32);
+        self.constant(result_type, vec![value.to_bits()])
+    }
+
+    pub fn constant_composite(&mut self, result_type: OpId, constituents: Vec<OpId>) -> OpId {
+        let constant = OpConstantComposite {
+            result_type,
+            result_id: ResultId(0),
+            constituents,
+        };
+        self.global(GroupGlobal::GroupConstant(GroupConstant::OpConstantComposite(constant)))
+    }
+
+    /// Adds a global variable
+    ///
+    /// Variables with a storage class of Function are added to the first
+    /// block of the current function with `local_variable`.
+    pub fn variable(&mut self,
+                    result_type: OpId,
+                    storage_class: StorageClass,
+                    initializer: Option<OpId>)
+                    -> OpId {
+        let id = self.id();
+        self.new_global(GroupGlobal::OpVariable(OpVariable {
+                            result_type,
+                            result_id: ResultId(0),
+                            storage_class,
+                            initializer,
+                        }),
+                        id.clone());
+        id
+    }
+
+    /// Starts a function definition
+    ///
+    /// Panics if another function has not been ended.
+    pub fn begin_function(&mut self,
+                          result_type: OpId,
+                          function_type: OpId,
+                          function_control: FunctionControl)
+                          -> OpId {
+        if let Some(ref function) = self.function {
+            panic!("function {} was not ended", function.function.result_id.0);
+        }
+        let result_id = self.id();
+        self.function = Some(FunctionDefinition {
+            lines: Vec::new(),
+            function: OpFunction {
+                result_type,
+                result_id: ResultId(result_id.0),
+                function_control,
+                function_type,
+            },
+            parameters: Vec::new(),
+            blocks: Vec::new(),
+            end_lines: Vec::new(),
+        });
+        result_id
+    }
+
+    /// Adds a parameter to the current function
+    ///
+    /// Panics if there is no current function or it already has a block.
+    pub fn function_parameter(&mut self, result_type: OpId) -> OpId {
+        let result_id = self.id();
+        let function = self.function.as_mut().expect("no function to add a parameter to");
+        if !function.blocks.is_empty() || self.block.is_some() {
+            panic!("parameters must come before the blocks of a function");
+        }
+        function.parameters.push(OpFunctionParameter {
+            result_type,
+            result_id: ResultId(result_id.0),
+        });
+        result_id
+    }
+
+    /// Ends the current function
+    ///
+    /// Panics if there is no current function or a block has not been ended.
+    pub fn end_function(&mut self) {
+        if let Some(ref block) = self.block {
+            panic!("block {} was not ended", block.label.result_id.0);
+        }
+        let function = self.function.take().expect("no function to end");
+        self.module.function_definitions.push(function);
+    }
+
+    /// Starts a new block in the current function, returning its label
+    pub fn new_block(&mut self) -> OpId {
+        let label = self.id();
+        self.begin_block(label.clone());
+        label
+    }
+
+    /// Starts a block with a label allocated by `id`
+    ///
+    /// Panics if there is no current function or another block has not been
+    /// ended.
+    pub fn begin_block(&mut self, label: OpId) {
+        if self.function.is_none() {
+            panic!("no function to add block {} to", label.0);
+        }
+        if let Some(ref block) = self.block {
+            panic!("block {} was not ended", block.label.result_id.0);
+        }
+        self.block = Some(BlockScope {
+            label: OpLabel { result_id: ResultId(label.0) },
+            code: Vec::new(),
+            merge: None,
+        });
+    }
+
+    /// Adds an instruction to the current block
+    ///
+    /// Panics if there is no current block.
+    pub fn code(&mut self, op: GroupCode) {
+        self.block.as_mut().expect("no block to add code to").code.push(op);
+    }
+
+    fn merge(&mut self, merge: GroupMerge) {
+        let block = self.block.as_mut().expect("no block to add a merge to");
+        if block.merge.is_some() {
+            panic!("block {} already has a merge", block.label.result_id.0);
+        }
+        block.merge = Some(merge);
+    }
+
+    /// Ends the current block with a branch
+    fn end_block(&mut self, branch: GroupBranch) {
+        let block = self.block.take().expect("no block to end");
+        let function = self.function.as_mut().expect("no function to add a block to");
+        function.blocks.push(BasicBlock {
+            lines: Vec::new(),
+            label: block.label,
+            code: block.code,
+            merge: block.merge,
+            branch,
+        });
+    }
+
+    /// Adds a function variable to the first block of the current function
+    ///
+    /// Panics if the current function has no blocks.
+    pub fn local_variable(&mut self, result_type: OpId, initializer: Option<OpId>) -> OpId {
+        let result_id = self.id();
+        let op = GroupCode::OpVariable(OpVariable {
+            result_type,
+            result_id: ResultId(result_id.0),
+            storage_class: StorageClass::Function,
+            initializer,
+        });
+        let code = match self.function.as_mut().and_then(|function| function.blocks.first_mut()) {
+            Some(block) => &mut block.code,
+            None => &mut self.block.as_mut().expect("no block to add a variable to").code,
+        };
+        let position = code.iter()
+            .take_while(|op| match **op {
+                GroupCode::OpVariable(_) => true,
+                _ => false,
+            })
+            .count();
+        code.insert(position, op);
+        result_id
+    }
+
+    pub fn undef(&mut self, result_type: OpId) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpUndef(OpUndef {
+            result_type,
+            result_id: ResultId(result_id.0),
+        }));
+        result_id
+    }
+
+    pub fn load(&mut self, result_type: OpId, pointer: OpId) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpLoad(OpLoad {
+            result_type,
+            result_id: ResultId(result_id.0),
+            pointer,
+            memory_access: None,
+        }));
+        result_id
+    }
+
+    pub fn store(&mut self, pointer: OpId, object: OpId) {
+        self.code(GroupCode::OpStore(OpStore {
+            pointer,
+            object,
+            memory_access: None,
+        }));
+    }
+
+    pub fn access_chain(&mut self, result_type: OpId, base: OpId, indexes: Vec<OpId>) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpAccessChain(OpAccessChain {
+            result_type,
+            result_id: ResultId(result_id.0),
+            base,
+            indexes,
+        }));
+        result_id
+    }
+
+    pub fn function_call(&mut self,
+                         result_type: OpId,
+                         function: OpId,
+                         arguments: Vec<OpId>)
+                         -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpFunctionCall(OpFunctionCall {
+            result_type,
+            result_id: ResultId(result_id.0),
+            function,
+            arguments,
+        }));
+        result_id
+    }
+
+    pub fn convert_u_to_f(&mut self, result_type: OpId, unsigned_value: OpId) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpConvertUToF(OpConvertUToF {
+            result_type,
+            result_id: ResultId(result_id.0),
+            unsigned_value,
+        }));
+        result_id
+    }
+
+    pub fn bitcast(&mut self, result_type: OpId, operand: OpId) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpBitcast(OpBitcast {
+            result_type,
+            result_id: ResultId(result_id.0),
+            operand,
+        }));
+        result_id
+    }
+
+    /// Adds a phi with pairs of values and the labels of the blocks they come
+    /// from
+    pub fn phi(&mut self, result_type: OpId, variables: Vec<(OpId, OpId)>) -> OpId {
+        let result_id = self.id();
+        self.code(GroupCode::OpPhi(OpPhi {
+            result_type,
+            result_id: ResultId(result_id.0),
+            variables: variables.into_iter()
+                .map(|(variable, parent)| PhiArg { variable, parent })
+                .collect(),
+        }));
+        result_id
+    }
+
+    def_binary_ops!(iadd => OpIAdd,
+                    fadd => OpFAdd,
+                    isub => OpISub,
+                    fsub => OpFSub,
+                    imul => OpIMul,
+                    fmul => OpFMul,
+                    udiv => OpUDiv,
+                    sdiv => OpSDiv,
+                    fdiv => OpFDiv,
+                    umod => OpUMod,
+                    srem => OpSRem,
+                    smod => OpSMod,
+                    frem => OpFRem,
+                    fmod => OpFMod,
+                    bitwise_or => OpBitwiseOr,
+                    bitwise_xor => OpBitwiseXor,
+                    bitwise_and => OpBitwiseAnd,
+                    iequal => OpIEqual,
+                    inot_equal => OpINotEqual,
+                    ugreater_than => OpUGreaterThan,
+                    sgreater_than => OpSGreaterThan,
+                    ugreater_than_equal => OpUGreaterThanEqual,
+                    sgreater_than_equal => OpSGreaterThanEqual,
+                    uless_than => OpULessThan,
+                    sless_than => OpSLessThan,
+                    uless_than_equal => OpULessThanEqual,
+                    sless_than_equal => OpSLessThanEqual,
+                    ford_equal => OpFOrdEqual,
+                    ford_not_equal => OpFOrdNotEqual,
+                    ford_less_than => OpFOrdLessThan,
+                    ford_greater_than => OpFOrdGreaterThan,
+                    ford_less_than_equal => OpFOrdLessThanEqual,
+                    ford_greater_than_equal => OpFOrdGreaterThanEqual);
+
+    /// Marks the current block as the header of a selection
+    pub fn selection_merge(&mut self, merge_block: OpId) {
+        self.merge(GroupMerge::OpSelectionMerge(OpSelectionMerge {
+            merge_block,
+            selection_control: SelectionControl::default(),
+        }));
+    }
+
+    /// Marks the current block as the header of a loop
+    pub fn loop_merge(&mut self, merge_block: OpId, continue_target: OpId) {
+        self.merge(GroupMerge::OpLoopMerge(OpLoopMerge {
+            merge_block,
+            continue_target,
+            loop_control: LoopControl::default(),
+        }));
+    }
+
+    /// Ends the current block with a branch to another block
+    pub fn branch(&mut self, target_label: OpId) {
+        self.end_block(GroupBranch::OpBranch(OpBranch { target_label }));
+    }
+
+    /// Ends the current block with a conditional branch
+    pub fn branch_conditional(&mut self, condition: OpId, true_label: OpId, false_label: OpId) {
+        self.end_block(GroupBranch::OpBranchConditional(OpBranchConditional {
+            condition,
+            true_label,
+            false_label,
+            weights: None,
+        }));
+    }
+
+    /// Ends the current block by returning from a function without a value
+    pub fn ret(&mut self) {
+        self.end_block(GroupBranch::OpReturn(OpReturn));
+    }
+
+    /// Ends the current block by returning a value from the function
+    pub fn return_value(&mut self, value: OpId) {
+        self.end_block(GroupBranch::OpReturnValue(OpReturnValue { value }));
+    }
+}
@@ -8,6 +8,7 @@ pub use super::super::op::*;
 pub use super::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BlockId(u32);
 
 impl fmt::Display for BlockId {
@@ -16,7 +17,14 @@ impl fmt::Display for BlockId {
     }
 }
 
+impl From<BlockId> for OpId {
+    fn from(id: BlockId) -> OpId {
+        OpId(id.0)
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ControlFlowChain {
     Atom(BlockId),
     Block(Vec<ControlFlowChain>),
@@ -188,6 +196,8 @@ pub fn find_control_flow(decl: FunctionDefinition) -> ControlFlowResult<ControlF
 #[derive(Debug, PartialEq)]
 enum Continue {
     Next(BlockId),
+    /// Branched to the merge block of the innermost loop
+    Break,
     Return,
 }
 
@@ -277,6 +287,11 @@ fn search_block(id: BlockId,
     if flow_list.merges(&id) {
         return Ok((ControlFlowChain::block(vec![]), Continue::Next(id)));
     }
+    if let Some((_, merge)) = flow_list.innermost_loop() {
+        if merge == id {
+            return Ok((ControlFlowChain::Break, Continue::Break));
+        }
+    }
 
     let block = match block_map.get(&id) {
         Some(block) => block,
@@ -304,7 +319,8 @@ fn search_block(id: BlockId,
                     let (inner_chain, inner_next) =
                         try!(search_block(next_id, &inner_fl, block_map));
 
-                    if inner_next == Continue::Next(head) {
+                    // A body that always breaks never reaches the continue target
+                    if inner_next == Continue::Next(head) || inner_next == Continue::Break {
                         let inner_box = Box::new(inner_chain);
 
                         let ctc = ControlFlowChain::Loop(id, inner_box, hints, None);
@@ -350,13 +366,21 @@ fn search_block(id: BlockId,
                     let (false_chain, false_next) =
                         try!(search_block(false_block, &inner_fl, block_map));
 
-                    if true_next == false_next && true_next == Continue::Next(merge) {
+                    let converges = |next: &Continue| {
+                        *next == Continue::Next(merge) || *next == Continue::Break
+                    };
+                    if converges(&true_next) && converges(&false_next) {
 
                         let true_b = Box::new(true_chain);
                         let false_b = Box::new(false_chain);
                         let weights = op.weights.clone();
                         let ctc = ControlFlowChain::Selection(id, true_b, false_b, hints, weights);
 
+                        // The merge block can not be reached if both arms break
+                        if true_next == Continue::Break && false_next == Continue::Break {
+                            return Ok((ctc, Continue::Break));
+                        }
+
                         let (next_chain, next_next) =
                             try!(search_block(merge, flow_list, block_map));
 
@@ -382,7 +406,7 @@ fn search_block(id: BlockId,
                         let inner = try!(search_block(true_block, &flow_list, block_map));
                         let (inner_chain, inner_next) = inner;
 
-                        if inner_next == Continue::Next(head) {
+                        if inner_next == Continue::Next(head) || inner_next == Continue::Break {
                             let inner_box = Box::new(inner_chain);
                             let break_box = if is_break {
                                 Box::new(ControlFlowChain::Break)
@@ -406,7 +430,8 @@ fn search_block(id: BlockId,
                 }
             }
         }
-        GroupBranch::OpReturn(_) => {
+        GroupBranch::OpReturn(_) |
+        GroupBranch::OpReturnValue(_) => {
             // Return block
             Ok((ControlFlowChain::Atom(id), Continue::Return))
         }
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use spv::operands::Operands;
+use super::def_use::visit_instructions_mut;
+use super::*;
+
+/// Removes instructions that compute a value that is already available
+///
+/// Global types and constants are merged with an identical earlier
+/// definition. Inside functions the blocks are visited in dominator tree
+/// order and a pure instruction is removed when an instruction with the same
+/// opcode, result type and operands dominates it. Every use of a removed id
+/// is replaced by the id it duplicates, and names of removed ids are removed.
+///
+/// Struct and opaque types and forward declared pointer types are never
+/// merged, as these may be distinct from an otherwise identical definition.
+/// Globals are only merged when they have the same decorations, and
+/// decorated ids in functions are never merged. Loads, calls, phis and other
+/// instructions that may not produce the same result every time are not
+/// merged.
+pub fn eliminate_common_subexpressions(mut module: LogicalModule) -> LogicalModule {
+    let mut replacements = merge_globals(&mut module, false);
+
+    let pinned = module.annotations
+        .iter()
+        .flat_map(|op| op.operands())
+        .map(|id| id.0)
+        .collect::<HashSet<_>>();
+    for function in &mut module.function_definitions {
+        number_function(function, &pinned, &mut replacements);
+    }
+
+    replace_module_ids(&mut module, &replacements);
+    module
+}
+
+/// Merges global types and constants with an identical earlier definition,
+/// returning the ids that were removed and the ids that replace them
+///
+/// Only globals with the same decorations are merged. Struct types are
+/// merged if `merge_structs` is set, while opaque types, forward declared
+/// pointer types and variables are never merged. Uses of the removed ids are
+/// not replaced outside of the globals.
+pub fn merge_globals(module: &mut LogicalModule, merge_structs: bool) -> HashMap<u32, u32> {
+    let mut pinned = HashSet::new();
+    for op in &module.globals {
+        if let GroupGlobal::GroupType(GroupType::OpTypeForwardPointer(ref op)) = *op {
+            pinned.insert(op.pointer_type.0);
+        }
+    }
+    let mut decorations = HashMap::new();
+    for op in &module.annotations {
+        let (target, decoration) = match *op {
+            GroupAnnotation::OpDecorate(ref op) => (&op.target, (None, op.decoration.clone())),
+            GroupAnnotation::OpMemberDecorate(ref op) => {
+                (&op.structure_type, (Some(op.member.0), op.decoration.clone()))
+            }
+        };
+        decorations.entry(target.0).or_insert_with(Vec::new).push(decoration);
+    }
+    let same_decorations = |a: u32, b: u32| {
+        let empty = Vec::new();
+        let a = decorations.get(&a).unwrap_or(&empty);
+        let b = decorations.get(&b).unwrap_or(&empty);
+        a.len() == b.len() &&
+        a.iter().all(|decoration| {
+            a.iter().filter(|other| *other == decoration).count() ==
+            b.iter().filter(|other| *other == decoration).count()
+        })
+    };
+
+    let mut replacements = HashMap::new();
+    let mut table = ValueTable::new();
+    for mut op in mem::take(&mut module.globals) {
+        replace_ids(&mut op, &replacements);
+        let mergeable = match op {
+            GroupGlobal::GroupType(GroupType::OpTypeStruct(_)) => merge_structs,
+            GroupGlobal::GroupType(GroupType::OpTypeOpaque(_)) |
+            GroupGlobal::GroupType(GroupType::OpTypeForwardPointer(_)) |
+            GroupGlobal::OpVariable(_) => false,
+            _ => true,
+        };
+        if let (true, Some(result_id)) = (mergeable, mergeable_id(&op, &pinned)) {
+            match table.find_with(&op, |existing| same_decorations(existing, result_id)) {
+                Some(existing) => {
+                    replacements.insert(result_id, existing);
+                    continue;
+                }
+                None => {
+                    table.insert(&op);
+                }
+            }
+        }
+        module.globals.push(op);
+    }
+    replacements
+}
+
+/// Replaces every use of an id in a module, removing the names and
+/// decorations of the replaced ids
+pub fn replace_module_ids(module: &mut LogicalModule, replacements: &HashMap<u32, u32>) {
+    let is_kept = |op: &Operands| {
+        op.operands().into_iter().all(|id| !replacements.contains_key(&id.0))
+    };
+    module.debug.retain(|op| is_kept(op));
+    module.annotations.retain(|op| is_kept(op));
+    visit_instructions_mut(module, &mut |op| replace_ids(op, replacements));
+}
+
+/// Returns if an instruction always gives the same result for the same
+/// operands
+fn is_pure(op: &GroupCode) -> bool {
+    match *op {
+        GroupCode::OpAccessChain(_) |
+        GroupCode::OpConvertUToF(_) |
+        GroupCode::OpBitcast(_) |
+        GroupCode::OpIAdd(_) |
+        GroupCode::OpFAdd(_) |
+        GroupCode::OpISub(_) |
+        GroupCode::OpFSub(_) |
+        GroupCode::OpIMul(_) |
+        GroupCode::OpFMul(_) |
+        GroupCode::OpUDiv(_) |
+        GroupCode::OpSDiv(_) |
+        GroupCode::OpFDiv(_) |
+        GroupCode::OpUMod(_) |
+        GroupCode::OpSRem(_) |
+        GroupCode::OpSMod(_) |
+        GroupCode::OpFRem(_) |
+        GroupCode::OpFMod(_) |
+        GroupCode::OpIAddCarry(_) |
+        GroupCode::OpISubBorrow(_) |
+        GroupCode::OpUMulExtended(_) |
+        GroupCode::OpSMulExtended(_) |
+        GroupCode::OpBitwiseOr(_) |
+        GroupCode::OpBitwiseXor(_) |
+        GroupCode::OpBitwiseAnd(_) |
+        GroupCode::OpIEqual(_) |
+        GroupCode::OpINotEqual(_) |
+        GroupCode::OpUGreaterThan(_) |
+        GroupCode::OpSGreaterThan(_) |
+        GroupCode::OpUGreaterThanEqual(_) |
+        GroupCode::OpSGreaterThanEqual(_) |
+        GroupCode::OpULessThan(_) |
+        GroupCode::OpSLessThan(_) |
+        GroupCode::OpULessThanEqual(_) |
+        GroupCode::OpSLessThanEqual(_) |
+        GroupCode::OpFOrdEqual(_) |
+        GroupCode::OpFUnordEqual(_) |
+        GroupCode::OpFOrdNotEqual(_) |
+        GroupCode::OpFUnordNotEqual(_) |
+        GroupCode::OpFOrdLessThan(_) |
+        GroupCode::OpFUnordLessThan(_) |
+        GroupCode::OpFOrdGreaterThan(_) |
+        GroupCode::OpFUnordGreaterThan(_) |
+        GroupCode::OpFOrdLessThanEqual(_) |
+        GroupCode::OpFUnordLessThanEqual(_) |
+        GroupCode::OpFOrdGreaterThanEqual(_) |
+        GroupCode::OpFUnordGreaterThanEqual(_) => true,
+        _ => false,
+    }
+}
+
+enum Visit {
+    Enter(usize),
+    /// Leaves a block, removing the values that were added by it
+    Exit(Vec<ValueKey<GroupCode>>),
+}
+
+fn number_function(function: &mut FunctionDefinition,
+                   pinned: &HashSet<u32>,
+                   replacements: &mut HashMap<u32, u32>) {
+    if function.blocks.is_empty() {
+        return;
+    }
+    let dominators = find_dominators(function);
+
+    // Values are only available in the blocks dominated by the block that
+    // computes them
+    let mut table = ValueTable::new();
+    let mut pending = vec![Visit::Enter(0)];
+    while let Some(visit) = pending.pop() {
+        match visit {
+            Visit::Enter(block) => {
+                let mut added = Vec::new();
+                for mut op in mem::take(&mut function.blocks[block].code) {
+                    replace_ids(&mut op, replacements);
+                    if let (true, Some(result_id)) = (is_pure(&op), mergeable_id(&op, pinned)) {
+                        match table.find(&op) {
+                            Some(existing) => {
+                                replacements.insert(result_id, existing);
+                                continue;
+                            }
+                            None => added.push(table.insert(&op)),
+                        }
+                    }
+                    function.blocks[block].code.push(op);
+                }
+                pending.push(Visit::Exit(added));
+                for &child in dominators.children(block) {
+                    pending.push(Visit::Enter(child));
+                }
+            }
+            Visit::Exit(added) => {
+                for key in added {
+                    table.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the result id of an instruction if it may be merged with another
+fn mergeable_id<T: Operands>(op: &T, pinned: &HashSet<u32>) -> Option<u32> {
+    match op.result_id() {
+        Some(result_id) if !pinned.contains(&result_id.0) => Some(result_id.0),
+        _ => None,
+    }
+}
+
+fn replace_ids<T: Operands + ?Sized>(op: &mut T, replacements: &HashMap<u32, u32>) {
+    if let Some(result_type) = op.result_type_mut() {
+        if let Some(id) = replacements.get(&result_type.0) {
+            result_type.0 = *id;
+        }
+    }
+    for operand in op.operands_mut() {
+        if let Some(id) = replacements.get(&operand.0) {
+            operand.0 = *id;
+        }
+    }
+}
+
+type ValueKey<T> = (mem::Discriminant<T>, Option<u32>, Vec<u32>);
+
+/// Instructions that have been given a value number
+///
+/// Instructions are hashed by their opcode and ids, and compared in full
+/// with their result id cleared so that literal operands are also compared.
+struct ValueTable<T> {
+    values: HashMap<ValueKey<T>, Vec<(T, u32)>>,
+}
+
+impl<T: Operands + Clone + PartialEq> ValueTable<T> {
+    fn new() -> ValueTable<T> {
+        ValueTable { values: HashMap::new() }
+    }
+
+    fn key(op: &T) -> ValueKey<T> {
+        (mem::discriminant(op),
+         op.result_type().map(|id| id.0),
+         op.operands().into_iter().map(|id| id.0).collect())
+    }
+
+    fn normalize(op: &T) -> T {
+        let mut op = op.clone();
+        if let Some(result_id) = op.result_id_mut() {
+            result_id.0 = 0;
+        }
+        op
+    }
+
+    fn find(&self, op: &T) -> Option<u32> {
+        self.find_with(op, |_| true)
+    }
+
+    /// Finds an identical value whose id also passes a test
+    fn find_with<F: Fn(u32) -> bool>(&self, op: &T, accept: F) -> Option<u32> {
+        let normalized = ValueTable::normalize(op);
+        self.values
+            .get(&ValueTable::key(op))
+            .and_then(|values| {
+                values.iter().rev().find(|value| value.0 == normalized && accept(value.1))
+            })
+            .map(|value| value.1)
+    }
+
+    fn insert(&mut self, op: &T) -> ValueKey<T> {
+        let key = ValueTable::key(op);
+        let id = op.result_id().expect("value without a result id").0;
+        self.values.entry(key.clone()).or_default().push((ValueTable::normalize(op), id));
+        key
+    }
+
+    /// Removes the last value added with a key
+    fn remove(&mut self, key: &ValueKey<T>) {
+        if let Some(values) = self.values.get_mut(key) {
+            values.pop();
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, HashSet};
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// Removes code and globals that can not affect the result of a module
+///
+/// Blocks are removed when they can not be reached from the start of their
+/// function, other than merge blocks and continue targets that are kept to
+/// preserve structured control flow. Functions are removed when they are not
+/// reachable from an entry point. Globals and instructions without side
+/// effects are removed when their result is never used. Names and
+/// decorations that target a removed id are removed with it.
+///
+/// Globals decorated as a BuiltIn are always kept, as are functions and
+/// globals exported with LinkageAttributes, and OpStore, OpFunctionCall and
+/// OpExtInst instructions, as they may have side effects.
+pub fn eliminate_dead_code(mut module: LogicalModule) -> DefUseResult<LogicalModule> {
+    for function in &mut module.function_definitions {
+        remove_unreachable_blocks(function);
+    }
+
+    let def_use = try!(find_def_use(&module));
+    let live = find_live_ids(&module, &def_use);
+    let is_live = |id: &OpId| live.contains(id);
+    let defines_live = |op: &Operands| match op.result_id() {
+        Some(result_id) => is_live(&OpId(result_id.0)),
+        None => true,
+    };
+
+    module.debug.retain(|op| match *op {
+        GroupDebug::OpName(ref name) => is_live(&name.target),
+        GroupDebug::OpMemberName(ref name) => is_live(&name.struct_type),
+        _ => true,
+    });
+    module.annotations.retain(|op| op.operands().into_iter().all(&is_live));
+    module.globals.retain(|op| defines_live(op));
+    module.function_declarations.retain(|decl| defines_live(&decl.function));
+    module.function_definitions.retain(|def| defines_live(&def.function));
+    for function in &mut module.function_definitions {
+        for block in &mut function.blocks {
+            block.code.retain(|op| has_side_effects(op) || defines_live(op));
+        }
+    }
+
+    Ok(module)
+}
+
+/// Returns if an instruction must be kept even if its result is unused
+fn has_side_effects(op: &GroupCode) -> bool {
+    match *op {
+        GroupCode::OpStore(_) |
+        GroupCode::OpFunctionCall(_) |
+        GroupCode::OpExtInst(_) => true,
+        GroupCode::OpLoad(ref load) => {
+            match load.memory_access {
+                Some(ref access) => access.volatile,
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn remove_unreachable_blocks(function: &mut FunctionDefinition) {
+    let indexes = function.blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block.label.result_id.0, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut reachable = HashSet::new();
+    let mut pending = vec![0];
+    while let Some(index) = pending.pop() {
+        if index >= function.blocks.len() || !reachable.insert(index) {
+            continue;
+        }
+        let block = &function.blocks[index];
+        let mut targets = block.branch.operands();
+        if let Some(ref merge) = block.merge {
+            targets.extend(merge.operands());
+        }
+        for target in targets {
+            if let Some(target_index) = indexes.get(&target.0) {
+                pending.push(*target_index);
+            }
+        }
+    }
+
+    let removed = function.blocks
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| !reachable.contains(&index))
+        .map(|(_, block)| OpId(block.label.result_id.0))
+        .collect::<HashSet<_>>();
+    if removed.is_empty() {
+        return;
+    }
+
+    let mut index = 0;
+    function.blocks.retain(|_| {
+        index += 1;
+        reachable.contains(&(index - 1))
+    });
+    for block in &mut function.blocks {
+        for op in &mut block.code {
+            if let GroupCode::OpPhi(ref mut phi) = *op {
+                phi.variables.retain(|arg| !removed.contains(&arg.parent));
+            }
+        }
+    }
+}
+
+/// Marks every id that is required by an entry point or export
+fn find_live_ids(module: &LogicalModule, def_use: &DefUse) -> HashSet<OpId> {
+    let mut pending: Vec<OpId> = Vec::new();
+    for entry_point in &module.entry_points {
+        pending.extend(entry_point.operands().into_iter().cloned());
+    }
+    for mode in &module.execution_modes {
+        pending.extend(mode.operands().into_iter().cloned());
+    }
+    for op in &module.annotations {
+        if let GroupAnnotation::OpDecorate(ref decorate) = *op {
+            match decorate.decoration {
+                Decoration::BuiltIn(_) |
+                Decoration::LinkageAttributes(_, LinkageType::Export) => {
+                    pending.push(decorate.target.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut live = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if !live.insert(id.clone()) {
+            continue;
+        }
+        match def_use.get_definition(&id) {
+            Some(Location::Global(index)) => push_ids(&module.globals[index], &mut pending),
+            Some(Location::DeclarationFunction(index)) => {
+                let decl = &module.function_declarations[index];
+                push_ids(&decl.function, &mut pending);
+                for param in &decl.parameters {
+                    live.insert(OpId(param.result_id.0));
+                    push_ids(param, &mut pending);
+                }
+            }
+            Some(Location::Function(index)) => {
+                let function = &module.function_definitions[index];
+                push_ids(&function.function, &mut pending);
+                for param in &function.parameters {
+                    live.insert(OpId(param.result_id.0));
+                    push_ids(param, &mut pending);
+                }
+                for block in &function.blocks {
+                    live.insert(OpId(block.label.result_id.0));
+                    for op in block.code.iter().filter(|op| has_side_effects(op)) {
+                        push_ids(op, &mut pending);
+                    }
+                    if let Some(ref merge) = block.merge {
+                        push_ids(merge, &mut pending);
+                    }
+                    push_ids(&block.branch, &mut pending);
+                }
+            }
+            Some(Location::Block(function, block, BlockPosition::Code(index))) => {
+                let op = &module.function_definitions[function].blocks[block].code[index];
+                push_ids(op, &mut pending);
+            }
+            _ => {}
+        }
+    }
+    live
+}
+
+fn push_ids<T: Operands>(op: &T, pending: &mut Vec<OpId>) {
+    if let Some(result_type) = op.result_type() {
+        pending.push(result_type.clone());
+    }
+    pending.extend(op.operands().into_iter().cloned());
+}
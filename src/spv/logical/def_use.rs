@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::collections::hash_map;
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// Position of an instruction inside a basic block
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockPosition {
+    /// The OpLabel that starts the block
+    Label,
+    /// An instruction in the code of the block
+    Code(usize),
+    /// The merge instruction of the block
+    Merge,
+    /// The branch instruction that ends the block
+    Branch,
+}
+
+/// Position of an instruction inside a logical module
+///
+/// Indexes refer to the lists stored in the `LogicalModule` the location was
+/// found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    ExtInstImport(usize),
+    EntryPoint(usize),
+    ExecutionMode(usize),
+    Debug(usize),
+    Annotation(usize),
+    Global(usize),
+    /// The OpFunction of a function declaration
+    DeclarationFunction(usize),
+    /// A parameter of a function declaration
+    DeclarationParameter(usize, usize),
+    /// The OpFunction of a function definition
+    Function(usize),
+    /// A parameter of a function definition
+    Parameter(usize, usize),
+    /// An instruction inside a basic block of a function definition
+    Block(usize, usize, BlockPosition),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DefUseError {
+    DuplicateResultId(OpId),
+}
+
+pub type DefUseResult<T> = Result<T, DefUseError>;
+
+/// Definition and use information for every id in a module
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefUse {
+    definitions: HashMap<OpId, Location>,
+    uses: HashMap<OpId, Vec<Location>>,
+}
+
+impl DefUse {
+    /// Returns the location of the instruction that defines an id
+    pub fn get_definition(&self, id: &OpId) -> Option<Location> {
+        self.definitions.get(id).cloned()
+    }
+
+    /// Returns the locations of every instruction that uses an id
+    ///
+    /// An instruction is listed once for every time it references the id.
+    /// References as a result type are included.
+    pub fn get_uses(&self, id: &OpId) -> &[Location] {
+        match self.uses.get(id) {
+            Some(uses) => uses,
+            None => &[],
+        }
+    }
+
+    /// Returns if any instruction uses an id
+    pub fn is_used(&self, id: &OpId) -> bool {
+        !self.get_uses(id).is_empty()
+    }
+
+    /// Returns an iterator over every defined id and where it is defined
+    pub fn definitions<'a>(&'a self) -> hash_map::Iter<'a, OpId, Location> {
+        self.definitions.iter()
+    }
+
+    /// Returns one more than the highest defined id
+    ///
+    /// This is the first id that is free to be used for new instructions.
+    pub fn bound(&self) -> u32 {
+        self.definitions.keys().map(|id| id.0 + 1).max().unwrap_or(1)
+    }
+
+    fn add<T: Operands>(&mut self, op: &T, location: Location) -> DefUseResult<()> {
+        if let Some(result_id) = op.result_id() {
+            let id = OpId(result_id.0);
+            if self.definitions.insert(id.clone(), location).is_some() {
+                return Err(DefUseError::DuplicateResultId(id));
+            }
+        }
+        if let Some(result_type) = op.result_type() {
+            self.add_use(result_type, location);
+        }
+        for operand in op.operands() {
+            self.add_use(operand, location);
+        }
+        Ok(())
+    }
+
+    fn add_use(&mut self, id: &OpId, location: Location) {
+        self.uses.entry(id.clone()).or_default().push(location);
+    }
+}
+
+/// Builds the definition and use information for a module
+pub fn find_def_use(module: &LogicalModule) -> DefUseResult<DefUse> {
+    let mut def_use = DefUse {
+        definitions: HashMap::new(),
+        uses: HashMap::new(),
+    };
+
+    for (index, op) in module.ext_inst_imports.iter().enumerate() {
+        try!(def_use.add(op, Location::ExtInstImport(index)));
+    }
+    for (index, op) in module.entry_points.iter().enumerate() {
+        try!(def_use.add(op, Location::EntryPoint(index)));
+    }
+    for (index, op) in module.execution_modes.iter().enumerate() {
+        try!(def_use.add(op, Location::ExecutionMode(index)));
+    }
+    for (index, op) in module.debug.iter().enumerate() {
+        try!(def_use.add(op, Location::Debug(index)));
+    }
+    for (index, op) in module.annotations.iter().enumerate() {
+        try!(def_use.add(op, Location::Annotation(index)));
+    }
+    for (index, op) in module.globals.iter().enumerate() {
+        try!(def_use.add(op, Location::Global(index)));
+    }
+    for (index, decl) in module.function_declarations.iter().enumerate() {
+        try!(def_use.add(&decl.function, Location::DeclarationFunction(index)));
+        for (param_index, param) in decl.parameters.iter().enumerate() {
+            try!(def_use.add(param, Location::DeclarationParameter(index, param_index)));
+        }
+    }
+    for (index, def) in module.function_definitions.iter().enumerate() {
+        try!(def_use.add(&def.function, Location::Function(index)));
+        for (param_index, param) in def.parameters.iter().enumerate() {
+            try!(def_use.add(param, Location::Parameter(index, param_index)));
+        }
+        for (block_index, block) in def.blocks.iter().enumerate() {
+            let location = |position| Location::Block(index, block_index, position);
+            try!(def_use.add(&block.label, location(BlockPosition::Label)));
+            for (code_index, op) in block.code.iter().enumerate() {
+                try!(def_use.add(op, location(BlockPosition::Code(code_index))));
+            }
+            if let Some(ref merge) = block.merge {
+                try!(def_use.add(merge, location(BlockPosition::Merge)));
+            }
+            try!(def_use.add(&block.branch, location(BlockPosition::Branch)));
+        }
+    }
+
+    Ok(def_use)
+}
+
+/// Calls a function with every instruction of a module that may reference
+/// an id
+pub fn visit_instructions_mut(module: &mut LogicalModule, f: &mut FnMut(&mut Operands)) {
+    for op in &mut module.ext_inst_imports {
+        f(op);
+    }
+    for op in &mut module.entry_points {
+        f(op);
+    }
+    for op in &mut module.execution_modes {
+        f(op);
+    }
+    for op in &mut module.debug {
+        f(op);
+    }
+    for op in &mut module.annotations {
+        f(op);
+    }
+    for op in &mut module.globals {
+        f(op);
+    }
+    for decl in &mut module.function_declarations {
+        for op in decl.lines.iter_mut().chain(decl.end_lines.iter_mut()) {
+            f(op);
+        }
+        f(&mut decl.function);
+        for param in &mut decl.parameters {
+            f(param);
+        }
+    }
+    for def in &mut module.function_definitions {
+        for op in def.lines.iter_mut().chain(def.end_lines.iter_mut()) {
+            f(op);
+        }
+        f(&mut def.function);
+        for param in &mut def.parameters {
+            f(param);
+        }
+        for block in &mut def.blocks {
+            for op in &mut block.lines {
+                f(op);
+            }
+            f(&mut block.label);
+            for op in &mut block.code {
+                f(op);
+            }
+            if let Some(ref mut merge) = block.merge {
+                f(merge);
+            }
+            f(&mut block.branch);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use spv::operands::Operands;
+use super::*;
+
+/// Control flow and dominance information for the blocks of a function
+///
+/// Blocks are referred to by their index in the `blocks` of the function the
+/// information was found for. The first block is the entry of the function.
+/// Only branch targets are edges in the control flow graph, merge
+/// instructions do not add edges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dominators {
+    predecessors: Vec<Vec<usize>>,
+    successors: Vec<Vec<usize>>,
+    idoms: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    frontiers: Vec<Vec<usize>>,
+    reachable: Vec<bool>,
+    order: Vec<usize>,
+}
+
+impl Dominators {
+    /// Returns the blocks that branch to a block
+    pub fn predecessors(&self, block: usize) -> &[usize] {
+        &self.predecessors[block]
+    }
+
+    /// Returns the blocks a block branches to
+    pub fn successors(&self, block: usize) -> &[usize] {
+        &self.successors[block]
+    }
+
+    /// Returns if a block can be reached from the entry block
+    pub fn is_reachable(&self, block: usize) -> bool {
+        self.reachable[block]
+    }
+
+    /// Returns the closest block that dominates a block
+    ///
+    /// The entry block and unreachable blocks have no immediate dominator.
+    pub fn immediate_dominator(&self, block: usize) -> Option<usize> {
+        self.idoms[block]
+    }
+
+    /// Returns the blocks that are immediately dominated by a block
+    pub fn children(&self, block: usize) -> &[usize] {
+        &self.children[block]
+    }
+
+    /// Returns the dominance frontier of a block
+    ///
+    /// These are the blocks where the dominance of a block ends: blocks that
+    /// are not strictly dominated by it but have a predecessor that is.
+    pub fn frontier(&self, block: usize) -> &[usize] {
+        &self.frontiers[block]
+    }
+
+    /// Returns if every path from the entry to block `b` passes through
+    /// block `a`
+    ///
+    /// A block dominates itself. Unreachable blocks neither dominate nor are
+    /// dominated by any block.
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        if !self.reachable[a] || !self.reachable[b] {
+            return false;
+        }
+        let mut current = b;
+        loop {
+            if current == a {
+                return true;
+            }
+            match self.idoms[current] {
+                Some(idom) => current = idom,
+                None => return false,
+            }
+        }
+    }
+
+    /// Returns the reachable blocks in reverse postorder
+    ///
+    /// Every block comes after its dominators in this order.
+    pub fn reverse_postorder(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+/// Finds the dominator tree and dominance frontiers of a function
+pub fn find_dominators(function: &FunctionDefinition) -> Dominators {
+    let count = function.blocks.len();
+    let indexes = function.blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block.label.result_id.0, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut predecessors = vec![Vec::new(); count];
+    let mut successors = vec![Vec::new(); count];
+    for (index, block) in function.blocks.iter().enumerate() {
+        for target in block.branch.operands() {
+            if let Some(&target) = indexes.get(&target.0) {
+                if !successors[index].contains(&target) {
+                    successors[index].push(target);
+                    predecessors[target].push(index);
+                }
+            }
+        }
+    }
+
+    let mut reachable = vec![false; count];
+    let mut order = Vec::new();
+    if count > 0 {
+        // Depth first search that records blocks once all their successors
+        // have been visited
+        let mut stack = vec![(0, 0)];
+        reachable[0] = true;
+        while let Some((block, next)) = stack.pop() {
+            if next < successors[block].len() {
+                stack.push((block, next + 1));
+                let successor = successors[block][next];
+                if !reachable[successor] {
+                    reachable[successor] = true;
+                    stack.push((successor, 0));
+                }
+            } else {
+                order.push(block);
+            }
+        }
+        order.reverse();
+    }
+    let mut positions = vec![0; count];
+    for (position, &block) in order.iter().enumerate() {
+        positions[block] = position;
+    }
+
+    // Iterative algorithm from "A Simple, Fast Dominance Algorithm" by
+    // Cooper, Harvey and Kennedy
+    let mut idoms: Vec<Option<usize>> = vec![None; count];
+    if count > 0 {
+        idoms[0] = Some(0);
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in order.iter().skip(1) {
+            let mut new_idom = None;
+            for &pred in &predecessors[block] {
+                if idoms[pred].is_none() {
+                    continue;
+                }
+                new_idom = Some(match new_idom {
+                    None => pred,
+                    Some(other) => intersect(&idoms, &positions, pred, other),
+                });
+            }
+            if new_idom.is_some() && idoms[block] != new_idom {
+                idoms[block] = new_idom;
+                changed = true;
+            }
+        }
+    }
+    if count > 0 {
+        idoms[0] = None;
+    }
+
+    let mut children = vec![Vec::new(); count];
+    for &block in &order {
+        if let Some(idom) = idoms[block] {
+            children[idom].push(block);
+        }
+    }
+
+    let mut frontiers = vec![Vec::new(); count];
+    for &block in &order {
+        let preds = predecessors[block].iter().filter(|pred| reachable[**pred]).collect::<Vec<_>>();
+        if preds.len() < 2 {
+            continue;
+        }
+        for &pred in preds {
+            let mut runner = pred;
+            while Some(runner) != idoms[block] {
+                if !frontiers[runner].contains(&block) {
+                    frontiers[runner].push(block);
+                }
+                match idoms[runner] {
+                    Some(idom) => runner = idom,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    Dominators {
+        predecessors,
+        successors,
+        idoms,
+        children,
+        frontiers,
+        reachable,
+        order,
+    }
+}
+
+/// Finds the closest common dominator of two blocks
+fn intersect(idoms: &[Option<usize>], positions: &[usize], a: usize, b: usize) -> usize {
+    let mut a = a;
+    let mut b = b;
+    while a != b {
+        while positions[a] > positions[b] {
+            a = idoms[a].expect("processed block has no dominator");
+        }
+        while positions[b] > positions[a] {
+            b = idoms[b].expect("processed block has no dominator");
+        }
+    }
+    a
+}
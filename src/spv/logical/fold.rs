@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// Type of a scalar value that can be folded
+#[derive(Clone, Debug, PartialEq)]
+enum ScalarType {
+    Bool,
+    Int(u32, Signedness),
+    Float(u32),
+}
+
+/// Value of a scalar constant
+///
+/// Integers and floats are stored as their bit pattern, with any bits above
+/// the width of the type cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Bool(bool),
+    Bits(u64),
+}
+
+pub fn mask(bits: u64, width: u32) -> u64 {
+    if width >= 64 { bits } else { bits & ((1 << width) - 1) }
+}
+
+pub fn sign_extend(bits: u64, width: u32) -> i64 {
+    if width >= 64 {
+        bits as i64
+    } else {
+        let shift = 64 - width;
+        ((bits << shift) as i64) >> shift
+    }
+}
+
+pub fn float_from_bits(bits: u64, width: u32) -> Option<f64> {
+    match width {
+        32 => Some(f32::from_bits(bits as u32) as f64),
+        64 => Some(f64::from_bits(bits)),
+        _ => None,
+    }
+}
+
+pub fn float_to_bits(value: f64, width: u32) -> Option<u64> {
+    match width {
+        32 => Some((value as f32).to_bits() as u64),
+        64 => Some(value.to_bits()),
+        _ => None,
+    }
+}
+
+pub struct Folder {
+    types: HashMap<OpId, ScalarType>,
+    /// Type and value of every id known to be constant
+    values: HashMap<OpId, (OpId, Value)>,
+    constants: HashMap<(OpId, Value), OpId>,
+    new_constants: Vec<GroupGlobal>,
+    next_id: u32,
+}
+
+impl Folder {
+    pub fn new(module: &LogicalModule, next_id: u32) -> Folder {
+        let mut folder = Folder {
+            types: HashMap::new(),
+            values: HashMap::new(),
+            constants: HashMap::new(),
+            new_constants: Vec::new(),
+            next_id,
+        };
+        for global in &module.globals {
+            match *global {
+                GroupGlobal::GroupType(GroupType::OpTypeBool(ref op)) => {
+                    folder.types.insert(OpId(op.result_id.0), ScalarType::Bool);
+                }
+                GroupGlobal::GroupType(GroupType::OpTypeInt(ref op)) => {
+                    let ty = ScalarType::Int(op.width, op.signedness.clone());
+                    folder.types.insert(OpId(op.result_id.0), ty);
+                }
+                GroupGlobal::GroupType(GroupType::OpTypeFloat(ref op)) => {
+                    folder.types.insert(OpId(op.result_id.0), ScalarType::Float(op.width));
+                }
+                GroupGlobal::GroupConstant(ref constant) => folder.add_constant(constant),
+                _ => {}
+            }
+        }
+        folder
+    }
+
+    pub fn add_constant(&mut self, constant: &GroupConstant) {
+        let value = match *constant {
+            GroupConstant::OpConstantTrue(_) => Value::Bool(true),
+            GroupConstant::OpConstantFalse(_) => Value::Bool(false),
+            GroupConstant::OpConstant(ref op) => {
+                let width = match self.types.get(&op.result_type) {
+                    Some(&ScalarType::Int(width, _)) |
+                    Some(&ScalarType::Float(width)) => width,
+                    _ => return,
+                };
+                let bits = op.value
+                    .iter()
+                    .take(2)
+                    .enumerate()
+                    .fold(0, |bits, (index, word)| bits | (*word as u64) << (index * 32));
+                Value::Bits(mask(bits, width))
+            }
+            GroupConstant::OpConstantComposite(_) |
+            GroupConstant::OpSpecConstantTrue(_) |
+            GroupConstant::OpSpecConstantFalse(_) |
+            GroupConstant::OpSpecConstant(_) |
+            GroupConstant::OpSpecConstantComposite(_) |
+            GroupConstant::OpSpecConstantOp(_) => return,
+        };
+        let id = OpId(constant.result_id().unwrap().0);
+        let result_type = constant.result_type().unwrap().clone();
+        self.values.insert(id.clone(), (result_type.clone(), value));
+        self.constants.entry((result_type, value)).or_insert(id);
+    }
+
+    /// Returns the id of a constant with the given type and value, creating
+    /// the constant if it does not exist
+    fn get_constant(&mut self, result_type: &OpId, value: Value) -> OpId {
+        if let Some(id) = self.constants.get(&(result_type.clone(), value)) {
+            return id.clone();
+        }
+        let id = OpId(self.next_id);
+        self.next_id += 1;
+        let constant = self.make_constant(result_type, ResultId(id.0), value);
+        self.constants.insert((result_type.clone(), value), id.clone());
+        self.new_constants.push(GroupGlobal::GroupConstant(constant));
+        id
+    }
+
+    /// Creates the instruction for a constant with the given value
+    pub fn make_constant(&self,
+                         result_type: &OpId,
+                         result_id: ResultId,
+                         value: Value)
+                         -> GroupConstant {
+        match value {
+            Value::Bool(true) => {
+                GroupConstant::OpConstantTrue(OpConstantTrue {
+                    result_type: result_type.clone(),
+                    result_id,
+                })
+            }
+            Value::Bool(false) => {
+                GroupConstant::OpConstantFalse(OpConstantFalse {
+                    result_type: result_type.clone(),
+                    result_id,
+                })
+            }
+            Value::Bits(bits) => {
+                let words = match self.types.get(result_type) {
+                    Some(&ScalarType::Int(64, _)) |
+                    Some(&ScalarType::Float(64)) => vec![bits as u32, (bits >> 32) as u32],
+                    // Narrow signed integers are stored sign extended
+                    Some(&ScalarType::Int(width, Signedness::Signed)) => {
+                        vec![sign_extend(bits, width) as u32]
+                    }
+                    _ => vec![bits as u32],
+                };
+                GroupConstant::OpConstant(OpConstant {
+                    result_type: result_type.clone(),
+                    result_id,
+                    value: words,
+                })
+            }
+        }
+    }
+
+    /// Returns the value of a scalar constant
+    pub fn get_value(&self, id: &OpId) -> Option<Value> {
+        self.values.get(id).map(|value| value.1)
+    }
+
+    pub fn get_bool(&self, id: &OpId) -> Option<bool> {
+        match self.values.get(id) {
+            Some(&(_, Value::Bool(value))) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_width(&self, result_type: &OpId) -> Option<u32> {
+        match self.types.get(result_type) {
+            Some(&ScalarType::Int(width, _)) |
+            Some(&ScalarType::Float(width)) => Some(width),
+            _ => None,
+        }
+    }
+
+    /// Returns the bits of an integer constant along with its width
+    pub fn get_int(&self, id: &OpId) -> Option<(u64, u32)> {
+        match self.values.get(id) {
+            Some(&(ref result_type, Value::Bits(bits))) => {
+                match self.types.get(result_type) {
+                    Some(&ScalarType::Int(width, _)) => Some((bits, width)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a float constant along with its width
+    pub fn get_float(&self, id: &OpId) -> Option<(f64, u32)> {
+        match self.values.get(id) {
+            Some(&(ref result_type, Value::Bits(bits))) => {
+                match self.types.get(result_type) {
+                    Some(&ScalarType::Float(width)) => {
+                        float_from_bits(bits, width).map(|value| (value, width))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn int_op<F>(&self, a: &OpId, b: &OpId, f: F) -> Option<Value>
+        where F: Fn(u64, u64, u32) -> Option<u64>
+    {
+        match (self.get_int(a), self.get_int(b)) {
+            (Some((a, width)), Some((b, _))) => {
+                f(a, b, width).map(|bits| Value::Bits(mask(bits, width)))
+            }
+            _ => None,
+        }
+    }
+
+    fn int_cmp<F>(&self, a: &OpId, b: &OpId, f: F) -> Option<Value>
+        where F: Fn(u64, u64, u32) -> bool
+    {
+        match (self.get_int(a), self.get_int(b)) {
+            (Some((a, width)), Some((b, _))) => Some(Value::Bool(f(a, b, width))),
+            _ => None,
+        }
+    }
+
+    fn float_op<F>(&self, a: &OpId, b: &OpId, f: F) -> Option<Value>
+        where F: Fn(f64, f64) -> f64
+    {
+        match (self.get_float(a), self.get_float(b)) {
+            (Some((a, width)), Some((b, _))) => float_to_bits(f(a, b), width).map(Value::Bits),
+            _ => None,
+        }
+    }
+
+    fn float_cmp<F>(&self, a: &OpId, b: &OpId, f: F) -> Option<Value>
+        where F: Fn(f64, f64) -> bool
+    {
+        match (self.get_float(a), self.get_float(b)) {
+            (Some((a, _)), Some((b, _))) => Some(Value::Bool(f(a, b))),
+            _ => None,
+        }
+    }
+
+    /// Evaluates an instruction if all of its operands are constant
+    pub fn fold(&self, op: &GroupCode) -> Option<Value> {
+        use self::GroupCode::*;
+        match *op {
+            OpConvertUToF(ref op) => {
+                match (self.get_int(&op.unsigned_value), self.types.get(&op.result_type)) {
+                    (Some((bits, _)), Some(&ScalarType::Float(32))) => {
+                        float_to_bits(bits as f32 as f64, 32).map(Value::Bits)
+                    }
+                    (Some((bits, _)), Some(&ScalarType::Float(64))) => {
+                        float_to_bits(bits as f64, 64).map(Value::Bits)
+                    }
+                    _ => None,
+                }
+            }
+            OpBitcast(ref op) => {
+                let width = self.get_width(&op.result_type);
+                let operand = match self.values.get(&op.operand) {
+                    Some(&(ref operand_type, Value::Bits(bits))) => {
+                        self.get_width(operand_type).map(|width| (bits, width))
+                    }
+                    _ => None,
+                };
+                match (operand, width) {
+                    (Some((bits, from)), Some(to)) if from == to => Some(Value::Bits(bits)),
+                    _ => None,
+                }
+            }
+            OpIAdd(ref op) => {
+                self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a.wrapping_add(b)))
+            }
+            OpISub(ref op) => {
+                self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a.wrapping_sub(b)))
+            }
+            OpIMul(ref op) => {
+                self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a.wrapping_mul(b)))
+            }
+            OpUDiv(ref op) => self.int_op(&op.operand1, &op.operand2, |a, b, _| a.checked_div(b)),
+            OpSDiv(ref op) => {
+                self.int_op(&op.operand1, &op.operand2, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    a.checked_div(b).map(|r| r as u64)
+                })
+            }
+            OpUMod(ref op) => self.int_op(&op.operand1, &op.operand2, |a, b, _| a.checked_rem(b)),
+            OpSRem(ref op) => {
+                // Sign of the result matches the sign of operand 1
+                self.int_op(&op.operand1, &op.operand2, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    a.checked_rem(b).map(|r| r as u64)
+                })
+            }
+            OpSMod(ref op) => {
+                // Sign of the result matches the sign of operand 2
+                self.int_op(&op.operand1, &op.operand2, |a, b, width| {
+                    let (a, b) = (sign_extend(a, width), sign_extend(b, width));
+                    a.checked_rem(b).map(|r| {
+                        if r != 0 && (r < 0) != (b < 0) { r + b } else { r }
+                    } as u64)
+                })
+            }
+            OpBitwiseOr(ref op) => self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a | b)),
+            OpBitwiseXor(ref op) => self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a ^ b)),
+            OpBitwiseAnd(ref op) => self.int_op(&op.operand1, &op.operand2, |a, b, _| Some(a & b)),
+            OpFAdd(ref op) => self.float_op(&op.operand1, &op.operand2, |a, b| a + b),
+            OpFSub(ref op) => self.float_op(&op.operand1, &op.operand2, |a, b| a - b),
+            OpFMul(ref op) => self.float_op(&op.operand1, &op.operand2, |a, b| a * b),
+            OpFDiv(ref op) => self.float_op(&op.operand1, &op.operand2, |a, b| a / b),
+            OpFRem(ref op) => self.float_op(&op.operand1, &op.operand2, |a, b| a % b),
+            OpFMod(ref op) => {
+                self.float_op(&op.operand1, &op.operand2, |a, b| {
+                    let r = a % b;
+                    if r != 0.0 && (r < 0.0) != (b < 0.0) { r + b } else { r }
+                })
+            }
+            OpIEqual(ref op) => self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a == b),
+            OpINotEqual(ref op) => self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a != b),
+            OpUGreaterThan(ref op) => self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a > b),
+            OpUGreaterThanEqual(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a >= b)
+            }
+            OpULessThan(ref op) => self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a < b),
+            OpULessThanEqual(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, _| a <= b)
+            }
+            OpSGreaterThan(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, width| {
+                    sign_extend(a, width) > sign_extend(b, width)
+                })
+            }
+            OpSGreaterThanEqual(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, width| {
+                    sign_extend(a, width) >= sign_extend(b, width)
+                })
+            }
+            OpSLessThan(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, width| {
+                    sign_extend(a, width) < sign_extend(b, width)
+                })
+            }
+            OpSLessThanEqual(ref op) => {
+                self.int_cmp(&op.operand1, &op.operand2, |a, b, width| {
+                    sign_extend(a, width) <= sign_extend(b, width)
+                })
+            }
+            // Ordered comparisons are false if either operand is NaN, which
+            // matches the float comparison operators
+            OpFOrdEqual(ref op) => self.float_cmp(&op.operand1, &op.operand2, |a, b| a == b),
+            OpFOrdNotEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| {
+                    !a.is_nan() && !b.is_nan() && a != b
+                })
+            }
+            OpFOrdLessThan(ref op) => self.float_cmp(&op.operand1, &op.operand2, |a, b| a < b),
+            OpFOrdGreaterThan(ref op) => self.float_cmp(&op.operand1, &op.operand2, |a, b| a > b),
+            OpFOrdLessThanEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a <= b)
+            }
+            OpFOrdGreaterThanEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a >= b)
+            }
+            // Unordered comparisons are true if either operand is NaN
+            OpFUnordEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| {
+                    a.is_nan() || b.is_nan() || a == b
+                })
+            }
+            OpFUnordNotEqual(ref op) => self.float_cmp(&op.operand1, &op.operand2, |a, b| a != b),
+            OpFUnordLessThan(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a.is_nan() || b.is_nan() || a < b)
+            }
+            OpFUnordGreaterThan(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a.is_nan() || b.is_nan() || a > b)
+            }
+            OpFUnordLessThanEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a.is_nan() || b.is_nan() || a <= b)
+            }
+            OpFUnordGreaterThanEqual(ref op) => {
+                self.float_cmp(&op.operand1, &op.operand2, |a, b| a.is_nan() || b.is_nan() || a >= b)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates instructions whose operands are all scalar constants
+///
+/// Folded instructions are removed and every use of their result is replaced
+/// with a global constant, which is added to the module if an identical one
+/// does not already exist. Conditional branches on a constant condition are
+/// replaced with a branch to the taken label, and the selection merge for the
+/// branch is removed. Blocks that become unreachable are left in place.
+///
+/// Integer arithmetic wraps. Division or remainder by zero, instructions with
+/// composite results, and floats that are not 32 or 64 bits wide are not
+/// folded.
+pub fn fold_constants(mut module: LogicalModule) -> DefUseResult<LogicalModule> {
+    let def_use = try!(find_def_use(&module));
+    let mut folder = Folder::new(&module, def_use.bound());
+    let mut replacements = HashMap::new();
+
+    for function in &mut module.function_definitions {
+        let mut removed_edges = Vec::new();
+        for block in &mut function.blocks {
+            let mut code = Vec::new();
+            for op in block.code.drain(..) {
+                match folder.fold(&op) {
+                    Some(value) => {
+                        let id = OpId(op.result_id().unwrap().0);
+                        let result_type = op.result_type().unwrap().clone();
+                        let constant = folder.get_constant(&result_type, value);
+                        folder.values.insert(id.clone(), (result_type, value));
+                        replacements.insert(id, constant);
+                    }
+                    None => code.push(op),
+                }
+            }
+            block.code = code;
+
+            let folded = match block.branch {
+                GroupBranch::OpBranchConditional(ref op) => {
+                    folder.get_bool(&op.condition).map(|condition| if condition {
+                        (op.true_label.clone(), op.false_label.clone())
+                    } else {
+                        (op.false_label.clone(), op.true_label.clone())
+                    })
+                }
+                _ => None,
+            };
+            if let Some((taken, not_taken)) = folded {
+                if let Some(GroupMerge::OpSelectionMerge(_)) = block.merge {
+                    block.merge = None;
+                }
+                block.branch = GroupBranch::OpBranch(OpBranch { target_label: taken.clone() });
+                if taken != not_taken {
+                    removed_edges.push((OpId(block.label.result_id.0), not_taken));
+                }
+            }
+        }
+
+        // Remove phi arguments from branches that can no longer be taken
+        for (parent, target) in removed_edges {
+            for block in &mut function.blocks {
+                if block.label.result_id.0 != target.0 {
+                    continue;
+                }
+                for op in &mut block.code {
+                    if let GroupCode::OpPhi(ref mut phi) = *op {
+                        phi.variables.retain(|arg| arg.parent != parent);
+                    }
+                }
+            }
+        }
+    }
+
+    for function in &mut module.function_definitions {
+        for block in &mut function.blocks {
+            let mut operands = block.branch.operands_mut();
+            for op in &mut block.code {
+                operands.extend(op.operands_mut());
+            }
+            for id in operands {
+                if let Some(new_id) = replacements.get(id) {
+                    *id = new_id.clone();
+                }
+            }
+        }
+    }
+
+    let folded = replacements.keys().collect::<HashSet<_>>();
+    module.debug.retain(|op| !op.operands().iter().any(|id| folded.contains(id)));
+    module.annotations.retain(|op| !op.operands().iter().any(|id| folded.contains(id)));
+    module.globals.extend(folder.new_constants);
+
+    Ok(module)
+}
@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// Selects which function calls are inlined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InlineMode {
+    /// Inline every call to a function not marked as DontInline
+    All,
+    /// Only inline calls to functions marked as Inline
+    Hinted,
+}
+
+impl InlineMode {
+    fn allows(&self, control: &FunctionControl) -> bool {
+        match *self {
+            InlineMode::All => !control.dont_inline,
+            InlineMode::Hinted => control.inline && !control.dont_inline,
+        }
+    }
+}
+
+/// Replaces function calls with the body of the called function
+///
+/// Callees are inlined into their callers before their callers are inlined,
+/// so a call graph without recursion is flattened in a single pass. Recursive
+/// calls and calls to function declarations are left in place.
+///
+/// A callee with a single return is spliced directly into the caller and its
+/// return value replaces the result of the call. A callee with multiple
+/// returns is wrapped in a loop that runs once, with each return becoming a
+/// break to a new merge block where an OpPhi selects the return value. Calls
+/// to callees with multiple returns that also contain loops are left in
+/// place, as a return from inside a loop can not be expressed as a break.
+///
+/// Variables of an inlined callee are declared in the first block of the
+/// caller, and any initializer is stored to them where the call was.
+/// Names and decorations of a call result that is replaced are removed.
+/// Functions that are no longer called are not removed.
+pub fn inline_functions(mut module: LogicalModule,
+                        mode: InlineMode)
+                        -> DefUseResult<LogicalModule> {
+    let def_use = try!(find_def_use(&module));
+    let mut inliner = Inliner {
+        next_id: def_use.bound(),
+        removed: HashSet::new(),
+    };
+
+    let indexes = module.function_definitions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| (OpId(function.function.result_id.0), index))
+        .collect::<HashMap<_, _>>();
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    for index in 0..module.function_definitions.len() {
+        visit_callees(index, &module.function_definitions, &indexes, &mut visited, &mut order);
+    }
+
+    // Only callees that have been fully processed are inlined, which leaves
+    // recursive calls in place
+    let mut done = HashSet::new();
+    for index in order {
+        let mut caller = module.function_definitions[index].clone();
+        let mut skipped = HashSet::new();
+        loop {
+            let call = {
+                let is_candidate = |call: &OpFunctionCall| {
+                    match indexes.get(&call.function) {
+                        Some(callee) if done.contains(callee) => {
+                            let control = &module.function_definitions[*callee]
+                                .function
+                                .function_control;
+                            mode.allows(control) && !skipped.contains(&call.result_id.0)
+                        }
+                        _ => false,
+                    }
+                };
+                find_call(&caller, is_candidate)
+            };
+            let (block_index, code_index, call) = match call {
+                Some(call) => call,
+                None => break,
+            };
+            let callee = &module.function_definitions[indexes[&call.function]];
+            if !inliner.inline_call(&mut caller, block_index, code_index, callee) {
+                skipped.insert(call.result_id.0);
+            }
+        }
+        module.function_definitions[index] = caller;
+        done.insert(index);
+    }
+
+    let removed = inliner.removed;
+    module.debug.retain(|op| match *op {
+        GroupDebug::OpName(ref name) => !removed.contains(&name.target.0),
+        _ => true,
+    });
+    module.annotations.retain(|op| match *op {
+        GroupAnnotation::OpDecorate(ref decorate) => !removed.contains(&decorate.target.0),
+        _ => true,
+    });
+
+    Ok(module)
+}
+
+/// Orders functions so that callees come before their callers
+fn visit_callees(index: usize,
+                 functions: &[FunctionDefinition],
+                 indexes: &HashMap<OpId, usize>,
+                 visited: &mut HashSet<usize>,
+                 order: &mut Vec<usize>) {
+    if !visited.insert(index) {
+        return;
+    }
+    for block in &functions[index].blocks {
+        for op in &block.code {
+            if let GroupCode::OpFunctionCall(ref call) = *op {
+                if let Some(callee) = indexes.get(&call.function) {
+                    visit_callees(*callee, functions, indexes, visited, order);
+                }
+            }
+        }
+    }
+    order.push(index);
+}
+
+/// Finds the position of the first call in a function that passes a test
+fn find_call<F>(function: &FunctionDefinition, test: F) -> Option<(usize, usize, OpFunctionCall)>
+    where F: Fn(&OpFunctionCall) -> bool
+{
+    for (block_index, block) in function.blocks.iter().enumerate() {
+        for (code_index, op) in block.code.iter().enumerate() {
+            if let GroupCode::OpFunctionCall(ref call) = *op {
+                if test(call) {
+                    return Some((block_index, code_index, call.clone()));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn is_return(branch: &GroupBranch) -> bool {
+    match *branch {
+        GroupBranch::OpReturn(_) |
+        GroupBranch::OpReturnValue(_) => true,
+        _ => false,
+    }
+}
+
+/// Changes the parent of phi arguments in the successors of a block after the
+/// branch of the block has been moved to a new block
+fn rename_phi_parents(function: &mut FunctionDefinition, branch: &GroupBranch, from: u32, to: u32) {
+    let targets = branch.operands().into_iter().map(|id| id.0).collect::<HashSet<_>>();
+    for block in &mut function.blocks {
+        if !targets.contains(&block.label.result_id.0) {
+            continue;
+        }
+        for op in &mut block.code {
+            if let GroupCode::OpPhi(ref mut phi) = *op {
+                for arg in &mut phi.variables {
+                    if arg.parent.0 == from {
+                        arg.parent = OpId(to);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replaces the ids defined and used by an instruction
+fn remap_ids<T: Operands>(op: &mut T, ids: &HashMap<u32, u32>) {
+    if let Some(result_id) = op.result_id_mut() {
+        if let Some(id) = ids.get(&result_id.0) {
+            result_id.0 = *id;
+        }
+    }
+    for operand in op.operands_mut() {
+        if let Some(id) = ids.get(&operand.0) {
+            operand.0 = *id;
+        }
+    }
+}
+
+fn new_block(label: u32, code: Vec<GroupCode>, branch: GroupBranch) -> BasicBlock {
+    BasicBlock {
+        lines: Vec::new(),
+        label: OpLabel { result_id: ResultId(label) },
+        code,
+        merge: None,
+        branch,
+    }
+}
+
+fn branch_to(label: u32) -> GroupBranch {
+    GroupBranch::OpBranch(OpBranch { target_label: OpId(label) })
+}
+
+struct Inliner {
+    next_id: u32,
+    /// Results of inlined calls that are no longer defined
+    removed: HashSet<u32>,
+}
+
+impl Inliner {
+    fn fresh_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Inlines the call at the given position, returning false if the call
+    /// could not be inlined
+    fn inline_call(&mut self,
+                   caller: &mut FunctionDefinition,
+                   mut block_index: usize,
+                   mut code_index: usize,
+                   callee: &FunctionDefinition)
+                   -> bool {
+        let returns = callee.blocks.iter().filter(|block| is_return(&block.branch)).count();
+        let has_loops = callee.blocks.iter().any(|block| match block.merge {
+            Some(GroupMerge::OpLoopMerge(_)) => true,
+            _ => false,
+        });
+        if callee.blocks.is_empty() || returns == 0 || (returns > 1 && has_loops) {
+            return false;
+        }
+
+        // A loop header must keep its merge instruction, so move the code
+        // after any phis to a new block that the header branches to
+        if let Some(GroupMerge::OpLoopMerge(_)) = caller.blocks[block_index].merge {
+            match caller.blocks[block_index].branch {
+                GroupBranch::OpBranch(_) => {}
+                _ => return false,
+            }
+            let header = caller.blocks[block_index].label.result_id.0;
+            let body = self.fresh_id();
+            let phis = caller.blocks[block_index]
+                .code
+                .iter()
+                .take_while(|op| match **op {
+                    GroupCode::OpPhi(_) => true,
+                    _ => false,
+                })
+                .count();
+            let code = caller.blocks[block_index].code.split_off(phis);
+            let branch = caller.blocks[block_index].branch.clone();
+            caller.blocks[block_index].branch = branch_to(body);
+            rename_phi_parents(caller, &branch, header, body);
+            caller.blocks.insert(block_index + 1, new_block(body, code, branch));
+            block_index += 1;
+            code_index -= phis;
+        }
+
+        let call = match caller.blocks[block_index].code.remove(code_index) {
+            GroupCode::OpFunctionCall(call) => call,
+            _ => panic!("expected a function call"),
+        };
+
+        // Map parameters to arguments and give every other id defined in the
+        // callee a new id
+        let mut ids = HashMap::new();
+        for (param, arg) in callee.parameters.iter().zip(call.arguments.iter()) {
+            ids.insert(param.result_id.0, arg.0);
+        }
+        for block in &callee.blocks {
+            ids.insert(block.label.result_id.0, self.fresh_id());
+            for op in &block.code {
+                if let Some(result_id) = op.result_id() {
+                    ids.insert(result_id.0, self.fresh_id());
+                }
+            }
+        }
+        let mut blocks = callee.blocks.clone();
+        let mut variables = Vec::new();
+        let mut initializers = Vec::new();
+        for block in &mut blocks {
+            remap_ids(&mut block.label, &ids);
+            for op in &mut block.code {
+                remap_ids(op, &ids);
+            }
+            if let Some(ref mut merge) = block.merge {
+                remap_ids(merge, &ids);
+            }
+            remap_ids(&mut block.branch, &ids);
+
+            // Function variables must be declared in the first block. Their
+            // initializers are stored at the call instead, as the first block
+            // only runs once while the call may be inside a loop.
+            let (vars, code): (Vec<_>, Vec<_>) = block.code
+                .drain(..)
+                .partition(|op| match *op {
+                    GroupCode::OpVariable(_) => true,
+                    _ => false,
+                });
+            for mut var in vars {
+                if let GroupCode::OpVariable(ref mut var) = var {
+                    if let Some(initializer) = var.initializer.take() {
+                        initializers.push(GroupCode::OpStore(OpStore {
+                            pointer: OpId(var.result_id.0),
+                            object: initializer,
+                            memory_access: None,
+                        }));
+                    }
+                }
+                variables.push(var);
+            }
+            block.code = code;
+        }
+
+        // Split the block at the call
+        let after = self.fresh_id();
+        let (before, code, merge, branch) = {
+            let block = &mut caller.blocks[block_index];
+            let code = block.code.split_off(code_index);
+            block.code.extend(initializers);
+            let merge = block.merge.take();
+            let branch = block.branch.clone();
+            (block.label.result_id.0, code, merge, branch)
+        };
+        rename_phi_parents(caller, &branch, before, after);
+        let mut continuation = new_block(after, code, branch);
+        continuation.merge = merge;
+
+        let entry = blocks[0].label.result_id.0;
+        let mut results = Vec::new();
+        for block in &mut blocks {
+            if is_return(&block.branch) {
+                if let GroupBranch::OpReturnValue(ref op) = block.branch {
+                    results.push(PhiArg {
+                        variable: op.value.clone(),
+                        parent: OpId(block.label.result_id.0),
+                    });
+                }
+                block.branch = branch_to(after);
+            }
+        }
+
+        let mut inserted = Vec::new();
+        if returns == 1 {
+            caller.blocks[block_index].branch = branch_to(entry);
+            inserted.extend(blocks);
+            self.removed.insert(call.result_id.0);
+            if let Some(result) = results.pop() {
+                let mut operands = continuation.code
+                    .iter_mut()
+                    .flat_map(|op| op.operands_mut())
+                    .collect::<Vec<_>>();
+                operands.extend(continuation.branch.operands_mut());
+                for block in &mut caller.blocks {
+                    for op in &mut block.code {
+                        operands.extend(op.operands_mut());
+                    }
+                    operands.extend(block.branch.operands_mut());
+                }
+                for operand in operands {
+                    if operand.0 == call.result_id.0 {
+                        operand.0 = result.variable.0;
+                    }
+                }
+            }
+        } else {
+            let header = self.fresh_id();
+            let continue_target = self.fresh_id();
+            caller.blocks[block_index].branch = branch_to(header);
+            let mut header_block = new_block(header, vec![], branch_to(entry));
+            header_block.merge = Some(GroupMerge::OpLoopMerge(OpLoopMerge {
+                merge_block: OpId(after),
+                continue_target: OpId(continue_target),
+                loop_control: LoopControl::default(),
+            }));
+            inserted.push(header_block);
+            inserted.extend(blocks);
+            inserted.push(new_block(continue_target, vec![], branch_to(header)));
+            if results.is_empty() {
+                self.removed.insert(call.result_id.0);
+            } else {
+                continuation.code.insert(0,
+                                         GroupCode::OpPhi(OpPhi {
+                                             result_type: call.result_type.clone(),
+                                             result_id: call.result_id.clone(),
+                                             variables: results,
+                                         }));
+            }
+        }
+        inserted.push(continuation);
+
+        let position = block_index + 1;
+        let tail = caller.blocks.split_off(position);
+        caller.blocks.extend(inserted);
+        caller.blocks.extend(tail);
+
+        let first = &mut caller.blocks[0].code;
+        let declared = first.iter()
+            .take_while(|op| match **op {
+                GroupCode::OpVariable(_) => true,
+                _ => false,
+            })
+            .count();
+        let rest = first.split_off(declared);
+        first.extend(variables);
+        first.extend(rest);
+
+        true
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use super::cse::{merge_globals, replace_module_ids};
+use super::def_use::visit_instructions_mut;
+use super::*;
+
+#[derive(Debug, PartialEq)]
+pub enum LinkError {
+    /// No modules were given to link
+    NoModules,
+    /// The module at an index defines an id more than once
+    InvalidModule(usize, DefUseError),
+    /// The modules have different addressing or memory models
+    MemoryModelMismatch,
+    /// An imported name is not exported by any module
+    MissingSymbol(String),
+    /// A name is exported more than once
+    DuplicateSymbol(String),
+    /// An import does not have the same type as the export with its name
+    TypeMismatch(String),
+}
+
+pub type LinkResult<T> = Result<T, LinkError>;
+
+/// The type of a function or variable that can be imported or exported
+#[derive(PartialEq)]
+enum Symbol {
+    /// A function with its return type and function type
+    Function(OpId, OpId),
+    /// A variable with its pointer type
+    Variable(OpId),
+}
+
+/// Links modules together into a single module
+///
+/// The ids of each module are moved past the ids of the modules before it
+/// and the sections of the modules are concatenated. Capabilities,
+/// extensions and extended instruction set imports that appear in more than
+/// one module are only kept once, and identical types and constants are
+/// merged, including struct types with the same decorations.
+///
+/// Every function declaration or global variable decorated with an Import
+/// LinkageAttributes is replaced by the function or variable that exports
+/// the same name. The import is removed along with its names and
+/// decorations. Exports are kept so that the result may be linked again, and
+/// the Linkage capability is removed if no linkage decorations remain.
+pub fn link_modules(modules: Vec<LogicalModule>) -> LinkResult<LogicalModule> {
+    let mut modules = modules.into_iter().enumerate();
+    let mut linked = match modules.next() {
+        Some((_, module)) => module,
+        None => return Err(LinkError::NoModules),
+    };
+    let mut next_id = match find_def_use(&linked) {
+        Ok(def_use) => def_use.bound(),
+        Err(err) => return Err(LinkError::InvalidModule(0, err)),
+    };
+
+    for (index, mut module) in modules {
+        let bound = match find_def_use(&module) {
+            Ok(def_use) => def_use.bound(),
+            Err(err) => return Err(LinkError::InvalidModule(index, err)),
+        };
+        if module.memory_model != linked.memory_model {
+            return Err(LinkError::MemoryModelMismatch);
+        }
+        // Ids start from one, so the first id of the module becomes the
+        // first free id
+        let offset = next_id - 1;
+        visit_instructions_mut(&mut module, &mut |op| offset_ids(op, offset));
+        next_id += bound - 1;
+        append_module(&mut linked, module);
+    }
+
+    // Names come after the other debug instructions
+    let (names, mut debug): (Vec<_>, Vec<_>) = mem::take(&mut linked.debug)
+        .into_iter()
+        .partition(|op| match *op {
+            GroupDebug::OpName(_) |
+            GroupDebug::OpMemberName(_) => true,
+            _ => false,
+        });
+    debug.extend(names);
+    linked.debug = debug;
+
+    let mut replacements = HashMap::new();
+    let mut ext_inst_imports: Vec<OpExtInstImport> = Vec::new();
+    for op in mem::take(&mut linked.ext_inst_imports) {
+        match ext_inst_imports.iter().find(|existing| existing.name == op.name) {
+            Some(existing) => {
+                replacements.insert(op.result_id.0, existing.result_id.0);
+            }
+            None => ext_inst_imports.push(op),
+        }
+    }
+    linked.ext_inst_imports = ext_inst_imports;
+    replacements.extend(merge_globals(&mut linked, true));
+    replace_module_ids(&mut linked, &replacements);
+
+    let imports = try!(resolve_imports(&linked));
+    let mut removed = HashSet::new();
+    linked.function_declarations.retain(|decl| {
+        if imports.contains_key(&decl.function.result_id.0) {
+            removed.extend(decl.parameters.iter().map(|param| param.result_id.0));
+            false
+        } else {
+            true
+        }
+    });
+    linked.globals.retain(|op| match *op {
+        GroupGlobal::OpVariable(ref var) => !imports.contains_key(&var.result_id.0),
+        _ => true,
+    });
+    linked.debug.retain(|op| op.operands().into_iter().all(|id| !removed.contains(&id.0)));
+    replace_module_ids(&mut linked, &imports);
+
+    let has_linkage = linked.annotations.iter().any(|op| match *op {
+        GroupAnnotation::OpDecorate(OpDecorate {
+            decoration: Decoration::LinkageAttributes(_, _), ..
+        }) => true,
+        _ => false,
+    });
+    if !has_linkage {
+        linked.capabilities.retain(|capability| *capability != Capability::Linkage);
+    }
+
+    Ok(linked)
+}
+
+fn offset_ids(op: &mut Operands, offset: u32) {
+    if let Some(result_id) = op.result_id_mut() {
+        result_id.0 += offset;
+    }
+    if let Some(result_type) = op.result_type_mut() {
+        result_type.0 += offset;
+    }
+    for operand in op.operands_mut() {
+        operand.0 += offset;
+    }
+}
+
+fn append_module(linked: &mut LogicalModule, module: LogicalModule) {
+    for capability in module.capabilities {
+        if !linked.capabilities.contains(&capability) {
+            linked.capabilities.push(capability);
+        }
+    }
+    for extension in module.extensions {
+        if !linked.extensions.contains(&extension) {
+            linked.extensions.push(extension);
+        }
+    }
+    linked.ext_inst_imports.extend(module.ext_inst_imports);
+    linked.entry_points.extend(module.entry_points);
+    linked.execution_modes.extend(module.execution_modes);
+    linked.debug.extend(module.debug);
+    linked.annotations.extend(module.annotations);
+    linked.globals.extend(module.globals);
+    linked.function_declarations.extend(module.function_declarations);
+    linked.function_definitions.extend(module.function_definitions);
+}
+
+/// Finds the export for every import, returning the id of each import and
+/// the id of the export that replaces it
+fn resolve_imports(module: &LogicalModule) -> LinkResult<HashMap<u32, u32>> {
+    let mut exports = HashMap::new();
+    let mut imports = Vec::new();
+    for op in &module.annotations {
+        if let GroupAnnotation::OpDecorate(OpDecorate {
+            ref target,
+            decoration: Decoration::LinkageAttributes(ref name, ref linkage_type),
+        }) = *op {
+            match *linkage_type {
+                LinkageType::Export => {
+                    if exports.insert(name.clone(), target.0).is_some() {
+                        return Err(LinkError::DuplicateSymbol(name.clone()));
+                    }
+                }
+                LinkageType::Import => imports.push((name, target.0)),
+            }
+        }
+    }
+
+    let symbols = find_symbols(module);
+    let mut resolved = HashMap::new();
+    for (name, import) in imports {
+        let export = match exports.get(name) {
+            Some(export) => *export,
+            None => return Err(LinkError::MissingSymbol(name.clone())),
+        };
+        match (symbols.get(&import), symbols.get(&export)) {
+            (Some(import_symbol), Some(export_symbol)) if import_symbol == export_symbol => {}
+            _ => return Err(LinkError::TypeMismatch(name.clone())),
+        }
+        resolved.insert(import, export);
+    }
+    Ok(resolved)
+}
+
+fn find_symbols(module: &LogicalModule) -> HashMap<u32, Symbol> {
+    let mut symbols = HashMap::new();
+    let functions = module.function_declarations
+        .iter()
+        .map(|decl| &decl.function)
+        .chain(module.function_definitions.iter().map(|def| &def.function));
+    for function in functions {
+        symbols.insert(function.result_id.0,
+                       Symbol::Function(function.result_type.clone(),
+                                        function.function_type.clone()));
+    }
+    for op in &module.globals {
+        if let GroupGlobal::OpVariable(ref var) = *op {
+            symbols.insert(var.result_id.0, Symbol::Variable(var.result_type.clone()));
+        }
+    }
+    symbols
+}
@@ -0,0 +1,136 @@
+use spv::op::*;
+use spv::operands::Operands;
+use spv::raw::{Core, RawModule};
+use super::*;
+
+macro_rules! def_into_core {
+    ($group: ident; $($variant: ident),*) => {
+        impl From<$group> for Core {
+            fn from(op: $group) -> Core {
+                match op {
+                    $($group::$variant(op) => Core::$variant(op),)*
+                }
+            }
+        }
+    }
+}
+
+def_into_core!(GroupDebug;
+    OpSourceContinued, OpSource, OpSourceExtension, OpName, OpMemberName, OpString
+);
+
+def_into_core!(GroupAnnotation; OpDecorate, OpMemberDecorate);
+
+def_into_core!(GroupType;
+    OpTypeVoid, OpTypeBool, OpTypeInt, OpTypeFloat, OpTypeVector, OpTypeMatrix, OpTypeImage,
+    OpTypeSampler, OpTypeSampledImage, OpTypeArray, OpTypeRuntimeArray, OpTypeStruct, OpTypeOpaque,
+    OpTypePointer, OpTypeFunction, OpTypeEvent, OpTypeDeviceEvent, OpTypeQueue, OpTypePipe,
+    OpTypeForwardPointer
+);
+
+def_into_core!(GroupConstant;
+    OpConstantTrue, OpConstantFalse, OpConstant, OpConstantComposite, OpSpecConstantTrue,
+    OpSpecConstantFalse, OpSpecConstant, OpSpecConstantComposite, OpSpecConstantOp
+);
+
+def_into_core!(GroupCode;
+    OpNop, OpUndef, OpLine, OpExtInst, OpVariable, OpFunctionCall, OpLoad, OpStore, OpAccessChain,
+    OpConvertUToF, OpBitcast, OpIAdd, OpFAdd, OpISub, OpFSub, OpIMul, OpFMul, OpUDiv, OpSDiv,
+    OpFDiv, OpUMod, OpSRem, OpSMod, OpFRem, OpFMod, OpIAddCarry, OpISubBorrow, OpUMulExtended,
+    OpSMulExtended, OpBitwiseOr, OpBitwiseXor, OpBitwiseAnd, OpIEqual, OpINotEqual, OpUGreaterThan,
+    OpSGreaterThan, OpUGreaterThanEqual, OpSGreaterThanEqual, OpULessThan, OpSLessThan,
+    OpULessThanEqual, OpSLessThanEqual, OpFOrdEqual, OpFUnordEqual, OpFOrdNotEqual,
+    OpFUnordNotEqual, OpFOrdLessThan, OpFUnordLessThan, OpFOrdGreaterThan, OpFUnordGreaterThan,
+    OpFOrdLessThanEqual, OpFUnordLessThanEqual, OpFOrdGreaterThanEqual, OpFUnordGreaterThanEqual,
+    OpPhi, OpNoLine
+);
+
+def_into_core!(GroupLine; OpLine, OpNoLine);
+
+def_into_core!(GroupMerge; OpLoopMerge, OpSelectionMerge);
+
+def_into_core!(GroupBranch; OpBranch, OpBranchConditional, OpReturn, OpReturnValue);
+
+impl From<GroupGlobal> for Core {
+    fn from(op: GroupGlobal) -> Core {
+        match op {
+            GroupGlobal::GroupType(op) => op.into(),
+            GroupGlobal::GroupConstant(op) => op.into(),
+            GroupGlobal::OpVariable(op) => Core::OpVariable(op),
+            GroupGlobal::OpLine(op) => Core::OpLine(op),
+            GroupGlobal::OpNoLine(op) => Core::OpNoLine(op),
+        }
+    }
+}
+
+/// Converts a module back to a list of instructions
+///
+/// Sections are written in the order required by the specification, with
+/// names after the other debug instructions. The version and generator of
+/// the module are kept, and the bound is one more than the highest id that
+/// is defined or referenced.
+pub fn lower_module(module: LogicalModule) -> RawModule {
+    let mut insts = Vec::new();
+    insts.extend(module.capabilities
+        .into_iter()
+        .map(|capability| Core::OpCapability(OpCapability { capability })));
+    insts.extend(module.extensions.into_iter().map(|name| Core::OpExtension(OpExtension { name })));
+    insts.extend(module.ext_inst_imports.into_iter().map(Core::OpExtInstImport));
+    insts.push(Core::OpMemoryModel(module.memory_model));
+    insts.extend(module.entry_points.into_iter().map(Core::OpEntryPoint));
+    insts.extend(module.execution_modes.into_iter().map(Core::OpExecutionMode));
+
+    let (names, debug): (Vec<_>, Vec<_>) = module.debug
+        .into_iter()
+        .partition(|op| match *op {
+            GroupDebug::OpName(_) | GroupDebug::OpMemberName(_) => true,
+            _ => false,
+        });
+    insts.extend(debug.into_iter().map(Core::from));
+    insts.extend(names.into_iter().map(Core::from));
+    insts.extend(module.annotations.into_iter().map(Core::from));
+    insts.extend(module.globals.into_iter().map(Core::from));
+
+    for decl in module.function_declarations {
+        insts.extend(decl.lines.into_iter().map(Core::from));
+        insts.push(Core::OpFunction(decl.function));
+        insts.extend(decl.parameters.into_iter().map(Core::OpFunctionParameter));
+        insts.extend(decl.end_lines.into_iter().map(Core::from));
+        insts.push(Core::OpFunctionEnd(OpFunctionEnd));
+    }
+    for def in module.function_definitions {
+        insts.extend(def.lines.into_iter().map(Core::from));
+        insts.push(Core::OpFunction(def.function));
+        insts.extend(def.parameters.into_iter().map(Core::OpFunctionParameter));
+        for block in def.blocks {
+            insts.extend(block.lines.into_iter().map(Core::from));
+            insts.push(Core::OpLabel(block.label));
+            insts.extend(block.code.into_iter().map(Core::from));
+            if let Some(merge) = block.merge {
+                insts.push(merge.into());
+            }
+            insts.push(block.branch.into());
+        }
+        insts.extend(def.end_lines.into_iter().map(Core::from));
+        insts.push(Core::OpFunctionEnd(OpFunctionEnd));
+    }
+
+    let mut bound = 1;
+    for inst in &insts {
+        let ids = inst.result_id()
+            .map(|id| id.0)
+            .into_iter()
+            .chain(inst.result_type().map(|id| id.0))
+            .chain(inst.operands().into_iter().map(|id| id.0));
+        for id in ids {
+            bound = bound.max(id + 1);
+        }
+    }
+
+    RawModule {
+        version: module.version,
+        generator: module.generator,
+        bound,
+        instructions: insts,
+    }
+}
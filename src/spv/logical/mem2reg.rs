@@ -0,0 +1,352 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use spv::op::*;
+use spv::types::*;
+use spv::operands::Operands;
+use super::*;
+
+/// Promotes function variables to values in SSA form
+///
+/// A function variable is promoted when it holds a scalar or vector and its
+/// pointer is only used directly by non-volatile OpLoad and OpStore
+/// instructions. The variable and every load and store of it are removed.
+/// Loads are replaced by the last value stored to the variable, and an OpPhi
+/// is inserted at the dominance frontiers of the stores where control flow
+/// joins different values. Reading a variable before it has been stored to
+/// gives its initializer, or the value of a new OpUndef if it has none.
+///
+/// Phis are inserted for every variable that is loaded from, even where the
+/// variable is not read again. Unused phis can be removed with
+/// `eliminate_dead_code`.
+pub fn promote_variables(mut module: LogicalModule) -> DefUseResult<LogicalModule> {
+    let def_use = try!(find_def_use(&module));
+    let mut next_id = def_use.bound();
+
+    // Pointer types to the type of the value they point to
+    let mut pointed_types = HashMap::new();
+    let mut value_types = HashSet::new();
+    for op in &module.globals {
+        match *op {
+            GroupGlobal::GroupType(GroupType::OpTypeBool(ref op)) => {
+                value_types.insert(op.result_id.0);
+            }
+            GroupGlobal::GroupType(GroupType::OpTypeInt(ref op)) => {
+                value_types.insert(op.result_id.0);
+            }
+            GroupGlobal::GroupType(GroupType::OpTypeFloat(ref op)) => {
+                value_types.insert(op.result_id.0);
+            }
+            GroupGlobal::GroupType(GroupType::OpTypeVector(ref op)) => {
+                value_types.insert(op.result_id.0);
+            }
+            GroupGlobal::GroupType(GroupType::OpTypePointer(ref op)) => {
+                if let StorageClass::Function = op.storage_class {
+                    pointed_types.insert(OpId(op.result_id.0), op.pointed_type.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    pointed_types.retain(|_, pointed_type| value_types.contains(&pointed_type.0));
+
+    let mut removed = HashSet::new();
+    for function in &mut module.function_definitions {
+        let variables = find_promotable_variables(function, &pointed_types);
+        if !variables.is_empty() {
+            let mut promotion = Promotion::new(function, variables, next_id);
+            promotion.run(function);
+            next_id = promotion.next_id;
+            removed.extend(promotion.removed);
+        }
+    }
+
+    let is_kept = |op: &Operands| op.operands().into_iter().all(|id| !removed.contains(&id.0));
+    module.debug.retain(|op| is_kept(op));
+    module.annotations.retain(|op| is_kept(op));
+
+    Ok(module)
+}
+
+struct Variable {
+    id: u32,
+    value_type: OpId,
+    initializer: Option<u32>,
+}
+
+fn is_volatile(memory_access: &Option<MemoryAccess>) -> bool {
+    match *memory_access {
+        Some(ref access) => access.volatile,
+        None => false,
+    }
+}
+
+/// Finds the variables of a function that can be promoted, in the order they
+/// are declared
+fn find_promotable_variables(function: &FunctionDefinition,
+                             pointed_types: &HashMap<OpId, OpId>)
+                             -> Vec<Variable> {
+    let mut variables = Vec::new();
+    if let Some(block) = function.blocks.first() {
+        for op in &block.code {
+            if let GroupCode::OpVariable(ref var) = *op {
+                if let Some(value_type) = pointed_types.get(&var.result_type) {
+                    variables.push(Variable {
+                        id: var.result_id.0,
+                        value_type: value_type.clone(),
+                        initializer: var.initializer.as_ref().map(|id| id.0),
+                    });
+                }
+            }
+        }
+    }
+
+    // Any use other than as the pointer of a load or store may let the
+    // variable be accessed in a way that can not be tracked
+    let mut escaped = HashSet::new();
+    for block in &function.blocks {
+        for op in &block.code {
+            match *op {
+                GroupCode::OpLoad(ref load) if !is_volatile(&load.memory_access) => {}
+                GroupCode::OpStore(ref store) if !is_volatile(&store.memory_access) => {
+                    escaped.insert(store.object.0);
+                }
+                _ => escaped.extend(op.operands().into_iter().map(|id| id.0)),
+            }
+        }
+        if let Some(ref merge) = block.merge {
+            escaped.extend(merge.operands().into_iter().map(|id| id.0));
+        }
+        escaped.extend(block.branch.operands().into_iter().map(|id| id.0));
+    }
+
+    variables.retain(|var| !escaped.contains(&var.id));
+    variables
+}
+
+struct Promotion {
+    variables: Vec<Variable>,
+    indexes: HashMap<u32, usize>,
+    dominators: Dominators,
+    /// The phis to insert at the start of each block, as the index of the
+    /// variable and the id of the phi
+    phis: Vec<Vec<(usize, u32)>>,
+    phi_args: HashMap<u32, Vec<PhiArg>>,
+    /// The values that replace removed loads
+    replacements: HashMap<u32, u32>,
+    undefs: Vec<(OpId, u32)>,
+    removed: HashSet<u32>,
+    next_id: u32,
+}
+
+impl Promotion {
+    fn new(function: &FunctionDefinition, variables: Vec<Variable>, next_id: u32) -> Promotion {
+        let indexes = variables.iter()
+            .enumerate()
+            .map(|(index, var)| (var.id, index))
+            .collect::<HashMap<_, _>>();
+        let mut promotion = Promotion {
+            variables,
+            indexes,
+            dominators: find_dominators(function),
+            phis: vec![Vec::new(); function.blocks.len()],
+            phi_args: HashMap::new(),
+            replacements: HashMap::new(),
+            undefs: Vec::new(),
+            removed: HashSet::new(),
+            next_id,
+        };
+        promotion.place_phis(function);
+        promotion
+    }
+
+    fn fresh_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Places phis at the iterated dominance frontier of the blocks that
+    /// store to each variable that is loaded from
+    fn place_phis(&mut self, function: &FunctionDefinition) {
+        let mut stores = vec![Vec::new(); self.variables.len()];
+        let mut loaded = vec![false; self.variables.len()];
+        for (block_index, block) in function.blocks.iter().enumerate() {
+            for op in &block.code {
+                match *op {
+                    GroupCode::OpLoad(ref load) => {
+                        if let Some(&var) = self.indexes.get(&load.pointer.0) {
+                            loaded[var] = true;
+                        }
+                    }
+                    GroupCode::OpStore(ref store) => {
+                        if let Some(&var) = self.indexes.get(&store.pointer.0) {
+                            stores[var].push(block_index);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for (var, mut pending) in stores.into_iter().enumerate() {
+            if !loaded[var] {
+                continue;
+            }
+            let mut placed = vec![false; function.blocks.len()];
+            while let Some(block) = pending.pop() {
+                for &frontier in self.dominators.frontier(block) {
+                    if !placed[frontier] {
+                        placed[frontier] = true;
+                        let phi = self.next_id;
+                        self.next_id += 1;
+                        self.phis[frontier].push((var, phi));
+                        self.phi_args.insert(phi, Vec::new());
+                        pending.push(frontier);
+                    }
+                }
+            }
+        }
+    }
+
+    fn run(&mut self, function: &mut FunctionDefinition) {
+        let initial = self.variables.iter().map(|var| var.initializer).collect::<Vec<_>>();
+
+        // Walk the dominator tree so that the value of each variable at the
+        // start of a block is the value at the end of its immediate dominator
+        let mut pending = Vec::new();
+        if !function.blocks.is_empty() {
+            pending.push((0, initial.clone()));
+        }
+        while let Some((block, mut values)) = pending.pop() {
+            self.rename_block(function, block, &mut values);
+            for &child in self.dominators.children(block) {
+                pending.push((child, values.clone()));
+            }
+        }
+        // Unreachable blocks have no defined values to start with
+        for block in 0..function.blocks.len() {
+            if !self.dominators.is_reachable(block) {
+                let mut values = vec![None; self.variables.len()];
+                self.rename_block(function, block, &mut values);
+            }
+        }
+
+        for block in &mut function.blocks {
+            for op in &mut block.code {
+                self.replace_operands(op);
+            }
+            if let Some(ref mut merge) = block.merge {
+                self.replace_operands(merge);
+            }
+            self.replace_operands(&mut block.branch);
+        }
+
+        for (block_index, block) in function.blocks.iter_mut().enumerate() {
+            let phis = mem::take(&mut self.phis[block_index])
+                .into_iter()
+                .map(|(var, phi)| {
+                    GroupCode::OpPhi(OpPhi {
+                        result_type: self.variables[var].value_type.clone(),
+                        result_id: ResultId(phi),
+                        variables: self.phi_args.remove(&phi).unwrap_or_default(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let code = mem::replace(&mut block.code, phis);
+            block.code.extend(code);
+        }
+
+        if let Some(block) = function.blocks.first_mut() {
+            let declared = block.code
+                .iter()
+                .take_while(|op| match **op {
+                    GroupCode::OpVariable(_) => true,
+                    _ => false,
+                })
+                .count();
+            let rest = block.code.split_off(declared);
+            block.code.extend(self.undefs.iter().map(|&(ref result_type, id)| {
+                GroupCode::OpUndef(OpUndef {
+                    result_type: result_type.clone(),
+                    result_id: ResultId(id),
+                })
+            }));
+            block.code.extend(rest);
+        }
+    }
+
+    /// Removes the loads and stores of promoted variables in a block and
+    /// passes the resulting values on to the phis of its successors
+    fn rename_block(&mut self,
+                    function: &mut FunctionDefinition,
+                    block: usize,
+                    values: &mut [Option<u32>]) {
+        for &(var, phi) in &self.phis[block] {
+            values[var] = Some(phi);
+        }
+
+        let code = mem::take(&mut function.blocks[block].code);
+        for op in code {
+            match op {
+                GroupCode::OpVariable(ref var) if self.indexes.contains_key(&var.result_id.0) => {
+                    self.removed.insert(var.result_id.0);
+                    continue;
+                }
+                GroupCode::OpLoad(ref load) if self.indexes.contains_key(&load.pointer.0) => {
+                    let var = self.indexes[&load.pointer.0];
+                    let value = self.value(var, values);
+                    self.replacements.insert(load.result_id.0, value);
+                    self.removed.insert(load.result_id.0);
+                    continue;
+                }
+                GroupCode::OpStore(ref store) if self.indexes.contains_key(&store.pointer.0) => {
+                    let var = self.indexes[&store.pointer.0];
+                    let object = store.object.0;
+                    values[var] = Some(*self.replacements.get(&object).unwrap_or(&object));
+                    continue;
+                }
+                _ => {}
+            }
+            function.blocks[block].code.push(op);
+        }
+
+        let label = function.blocks[block].label.result_id.0;
+        let successors = self.dominators.successors(block).to_vec();
+        for successor in successors {
+            for (var, phi) in self.phis[successor].clone() {
+                let value = self.value(var, values);
+                self.phi_args.get_mut(&phi).expect("phi was not placed").push(PhiArg {
+                    variable: OpId(value),
+                    parent: OpId(label),
+                });
+            }
+        }
+    }
+
+    /// Returns the current value of a variable, using an undefined value if
+    /// nothing has been stored to it
+    fn value(&mut self, var: usize, values: &mut [Option<u32>]) -> u32 {
+        if let Some(value) = values[var] {
+            return value;
+        }
+        let value_type = self.variables[var].value_type.clone();
+        let undef = match self.undefs.iter().find(|undef| undef.0 == value_type) {
+            Some(&(_, id)) => id,
+            None => {
+                let id = self.fresh_id();
+                self.undefs.push((value_type, id));
+                id
+            }
+        };
+        values[var] = Some(undef);
+        undef
+    }
+
+    fn replace_operands<T: Operands>(&self, op: &mut T) {
+        for operand in op.operands_mut() {
+            if let Some(value) = self.replacements.get(&operand.0) {
+                operand.0 = *value;
+            }
+        }
+    }
+}
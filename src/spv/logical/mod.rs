@@ -7,11 +7,50 @@ pub use self::control_flow::{BlockId, ControlFlowChain, ControlFlowFunctionPrint
 pub use self::control_flow::{ControlType, ControlFlowError, ControlFlowResult};
 pub use self::control_flow::find_control_flow;
 
+mod def_use;
+pub use self::def_use::{DefUse, DefUseError, DefUseResult, Location, BlockPosition};
+pub use self::def_use::find_def_use;
+
+mod dominance;
+pub use self::dominance::{Dominators, find_dominators};
+
+mod strip;
+pub use self::strip::{strip_debug, StripOptions};
+
+mod dce;
+pub use self::dce::eliminate_dead_code;
+
+mod fold;
+pub use self::fold::fold_constants;
+
+mod spec;
+pub use self::spec::{SpecConstant, FreezeError, FreezeResult};
+pub use self::spec::{find_spec_constants, freeze_spec_constants};
+
+mod inline;
+pub use self::inline::{inline_functions, InlineMode};
+
+mod mem2reg;
+pub use self::mem2reg::promote_variables;
+
+mod cse;
+pub use self::cse::eliminate_common_subexpressions;
+
+mod builder;
+pub use self::builder::Builder;
+
+mod link;
+pub use self::link::{link_modules, LinkError, LinkResult};
+
+mod lower;
+pub use self::lower::lower_module;
+
 use super::op::*;
 use super::types::*;
 use std::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupDebug {
     OpSourceContinued(OpSourceContinued),
     OpSource(OpSource),
@@ -22,12 +61,14 @@ pub enum GroupDebug {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupAnnotation {
     OpDecorate(OpDecorate),
     OpMemberDecorate(OpMemberDecorate),
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupType {
     OpTypeVoid(OpTypeVoid),
     OpTypeBool(OpTypeBool),
@@ -52,26 +93,48 @@ pub enum GroupType {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupConstant {
+    OpConstantTrue(OpConstantTrue),
+    OpConstantFalse(OpConstantFalse),
     OpConstant(OpConstant),
     OpConstantComposite(OpConstantComposite),
+    OpSpecConstantTrue(OpSpecConstantTrue),
+    OpSpecConstantFalse(OpSpecConstantFalse),
+    OpSpecConstant(OpSpecConstant),
+    OpSpecConstantComposite(OpSpecConstantComposite),
+    OpSpecConstantOp(OpSpecConstantOp),
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupGlobal {
     GroupType(GroupType),
     GroupConstant(GroupConstant),
     /// Variables as globals must have a storage class that is not Function
     OpVariable(OpVariable),
+    OpLine(OpLine),
+    OpNoLine(OpNoLine),
 }
 
+/// Line instructions outside of basic blocks and globals
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GroupLine {
+    OpLine(OpLine),
+    OpNoLine(OpNoLine),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupCode {
     OpNop(OpNop),
     OpUndef(OpUndef),
+    OpLine(OpLine),
     OpExtInst(OpExtInst),
     /// Variables inside blocks must have a storage class of Function
     OpVariable(OpVariable),
+    OpFunctionCall(OpFunctionCall),
     OpLoad(OpLoad),
     OpStore(OpStore),
     OpAccessChain(OpAccessChain),
@@ -121,6 +184,7 @@ pub enum GroupCode {
     OpFOrdGreaterThanEqual(OpFOrdGreaterThanEqual),
     OpFUnordGreaterThanEqual(OpFUnordGreaterThanEqual),
     OpPhi(OpPhi),
+    OpNoLine(OpNoLine),
 }
 
 impl fmt::Display for GroupCode {
@@ -129,8 +193,10 @@ impl fmt::Display for GroupCode {
         match *self {
             GroupCode::OpNop(ref op) => Display::fmt(op, f),
             GroupCode::OpUndef(ref op) => Display::fmt(op, f),
+            GroupCode::OpLine(ref op) => Display::fmt(op, f),
             GroupCode::OpExtInst(ref op) => Display::fmt(op, f),
             GroupCode::OpVariable(ref op) => Display::fmt(op, f),
+            GroupCode::OpFunctionCall(ref op) => Display::fmt(op, f),
             GroupCode::OpLoad(ref op) => Display::fmt(op, f),
             GroupCode::OpStore(ref op) => Display::fmt(op, f),
             GroupCode::OpAccessChain(ref op) => Display::fmt(op, f),
@@ -180,21 +246,25 @@ impl fmt::Display for GroupCode {
             GroupCode::OpFOrdGreaterThanEqual(ref op) => Display::fmt(op, f),
             GroupCode::OpFUnordGreaterThanEqual(ref op) => Display::fmt(op, f),
             GroupCode::OpPhi(ref op) => Display::fmt(op, f),
+            GroupCode::OpNoLine(ref op) => Display::fmt(op, f),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupMerge {
     OpLoopMerge(OpLoopMerge),
     OpSelectionMerge(OpSelectionMerge),
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupBranch {
     OpBranch(OpBranch),
     OpBranchConditional(OpBranchConditional),
     OpReturn(OpReturn),
+    OpReturnValue(OpReturnValue),
 }
 
 impl fmt::Display for GroupBranch {
@@ -204,18 +274,65 @@ impl fmt::Display for GroupBranch {
             GroupBranch::OpBranch(ref op) => Display::fmt(op, f),
             GroupBranch::OpBranchConditional(ref op) => Display::fmt(op, f),
             GroupBranch::OpReturn(ref op) => Display::fmt(op, f),
+            GroupBranch::OpReturnValue(ref op) => Display::fmt(op, f),
         }
     }
 }
 
+def_enum_operands!(GroupDebug;
+    OpSourceContinued, OpSource, OpSourceExtension, OpName, OpMemberName, OpString
+);
+
+def_enum_operands!(GroupAnnotation; OpDecorate, OpMemberDecorate);
+
+def_enum_operands!(GroupType;
+    OpTypeVoid, OpTypeBool, OpTypeInt, OpTypeFloat, OpTypeVector, OpTypeMatrix, OpTypeImage,
+    OpTypeSampler, OpTypeSampledImage, OpTypeArray, OpTypeRuntimeArray, OpTypeStruct, OpTypeOpaque,
+    OpTypePointer, OpTypeFunction, OpTypeEvent, OpTypeDeviceEvent, OpTypeQueue, OpTypePipe,
+    OpTypeForwardPointer
+);
+
+def_enum_operands!(GroupConstant;
+    OpConstantTrue, OpConstantFalse, OpConstant, OpConstantComposite, OpSpecConstantTrue,
+    OpSpecConstantFalse, OpSpecConstant, OpSpecConstantComposite, OpSpecConstantOp
+);
+
+def_enum_operands!(GroupGlobal; GroupType, GroupConstant, OpVariable, OpLine, OpNoLine);
+
+def_enum_operands!(GroupLine; OpLine, OpNoLine);
+
+def_enum_operands!(GroupCode;
+    OpNop, OpUndef, OpLine, OpExtInst, OpVariable, OpFunctionCall, OpLoad, OpStore, OpAccessChain,
+    OpConvertUToF, OpBitcast, OpIAdd, OpFAdd, OpISub, OpFSub, OpIMul, OpFMul, OpUDiv, OpSDiv,
+    OpFDiv, OpUMod, OpSRem, OpSMod, OpFRem, OpFMod, OpIAddCarry, OpISubBorrow, OpUMulExtended,
+    OpSMulExtended, OpBitwiseOr, OpBitwiseXor, OpBitwiseAnd, OpIEqual, OpINotEqual, OpUGreaterThan,
+    OpSGreaterThan, OpUGreaterThanEqual, OpSGreaterThanEqual, OpULessThan, OpSLessThan,
+    OpULessThanEqual, OpSLessThanEqual, OpFOrdEqual, OpFUnordEqual, OpFOrdNotEqual,
+    OpFUnordNotEqual, OpFOrdLessThan, OpFUnordLessThan, OpFOrdGreaterThan, OpFUnordGreaterThan,
+    OpFOrdLessThanEqual, OpFUnordLessThanEqual, OpFOrdGreaterThanEqual, OpFUnordGreaterThanEqual,
+    OpPhi, OpNoLine
+);
+
+def_enum_operands!(GroupMerge; OpLoopMerge, OpSelectionMerge);
+
+def_enum_operands!(GroupBranch; OpBranch, OpBranchConditional, OpReturn, OpReturnValue);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionDeclaration {
+    /// Line instructions before the OpFunction
+    pub lines: Vec<GroupLine>,
     pub function: OpFunction,
     pub parameters: Vec<OpFunctionParameter>,
+    /// Line instructions before the OpFunctionEnd
+    pub end_lines: Vec<GroupLine>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BasicBlock {
+    /// Line instructions before the OpLabel
+    pub lines: Vec<GroupLine>,
     pub label: OpLabel,
     pub code: Vec<GroupCode>,
     pub merge: Option<GroupMerge>,
@@ -223,14 +340,24 @@ pub struct BasicBlock {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionDefinition {
+    /// Line instructions before the OpFunction
+    pub lines: Vec<GroupLine>,
     pub function: OpFunction,
     pub parameters: Vec<OpFunctionParameter>,
     pub blocks: Vec<BasicBlock>,
+    /// Line instructions before the OpFunctionEnd
+    pub end_lines: Vec<GroupLine>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LogicalModule {
+    /// Version number of the module
+    pub version: Version,
+    /// Tool and tool version used to generate the module
+    pub generator: Generator,
     pub capabilities: Vec<Capability>,
     pub extensions: Vec<String>,
     pub ext_inst_imports: Vec<OpExtInstImport>,
@@ -26,6 +26,9 @@ enum OpByBlock {
     GroupDebug(GroupDebug),
     GroupAnnotation(GroupAnnotation),
     GroupGlobal(GroupGlobal),
+    /// Line instructions may appear among the globals or inside blocks
+    OpLine(OpLine),
+    OpNoLine(OpNoLine),
     OpFunction(OpFunction),
     OpFunctionParameter(OpFunctionParameter),
     OpFunctionEnd(OpFunctionEnd),
@@ -104,6 +107,7 @@ impl From<Core> for OpByBlock {
             OpName(op) => GroupDebug::OpName(op).into(),
             OpMemberName(op) => GroupDebug::OpMemberName(op).into(),
             OpString(op) => GroupDebug::OpString(op).into(),
+            OpLine(op) => OpByBlock::OpLine(op),
             OpExtension(op) => OpByBlock::OpExtension(op),
             OpExtInstImport(op) => OpByBlock::OpExtInstImport(op),
             OpExtInst(op) => GroupCode::OpExtInst(op).into(),
@@ -131,11 +135,19 @@ impl From<Core> for OpByBlock {
             OpTypeQueue(op) => GroupType::OpTypeQueue(op).into(),
             OpTypePipe(op) => GroupType::OpTypePipe(op).into(),
             OpTypeForwardPointer(op) => GroupType::OpTypeForwardPointer(op).into(),
+            OpConstantTrue(op) => GroupConstant::OpConstantTrue(op).into(),
+            OpConstantFalse(op) => GroupConstant::OpConstantFalse(op).into(),
             OpConstant(op) => GroupConstant::OpConstant(op).into(),
             OpConstantComposite(op) => GroupConstant::OpConstantComposite(op).into(),
+            OpSpecConstantTrue(op) => GroupConstant::OpSpecConstantTrue(op).into(),
+            OpSpecConstantFalse(op) => GroupConstant::OpSpecConstantFalse(op).into(),
+            OpSpecConstant(op) => GroupConstant::OpSpecConstant(op).into(),
+            OpSpecConstantComposite(op) => GroupConstant::OpSpecConstantComposite(op).into(),
+            OpSpecConstantOp(op) => GroupConstant::OpSpecConstantOp(op).into(),
             OpFunction(op) => OpByBlock::OpFunction(op),
             OpFunctionParameter(op) => OpByBlock::OpFunctionParameter(op),
             OpFunctionEnd(op) => OpByBlock::OpFunctionEnd(op),
+            OpFunctionCall(op) => GroupCode::OpFunctionCall(op).into(),
             OpVariable(op) => op.into(),
             OpLoad(op) => GroupCode::OpLoad(op).into(),
             OpStore(op) => GroupCode::OpStore(op).into(),
@@ -194,6 +206,8 @@ impl From<Core> for OpByBlock {
             OpBranch(op) => GroupBranch::OpBranch(op).into(),
             OpBranchConditional(op) => GroupBranch::OpBranchConditional(op).into(),
             OpReturn(op) => GroupBranch::OpReturn(op).into(),
+            OpReturnValue(op) => GroupBranch::OpReturnValue(op).into(),
+            OpNoLine(op) => OpByBlock::OpNoLine(op),
         }
     }
 }
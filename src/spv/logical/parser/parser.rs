@@ -1,5 +1,6 @@
 
 use spv::op::*;
+use spv::operands::Operands;
 use spv::types::*;
 use spv::raw::*;
 use spv::logical::*;
@@ -12,6 +13,9 @@ pub enum ValidationError {
     ExpectedOpFunctionEnd(usize),
     ExpectedBranch(usize),
     UnexpectedInstruction(usize),
+    /// A SpecId decoration targets an id that is not a scalar specialization
+    /// constant
+    InvalidSpecIdTarget(OpId),
 }
 
 pub type ValidationResult<T> = Result<T, ValidationError>;
@@ -36,8 +40,11 @@ pub fn validate(raw: RawModule) -> ValidationResult<LogicalModule> {
     if insts.get_remaining() > 0 {
         return Err(ValidationError::UnexpectedInstruction(insts.get_slot()));
     }
+    try!(validate_spec_ids(&annotations, &globals));
 
     Ok(LogicalModule {
+        version: raw.version,
+        generator: raw.generator,
         capabilities: capabilities,
         extensions: extensions,
         ext_inst_imports: ext_inst_imports,
@@ -52,6 +59,34 @@ pub fn validate(raw: RawModule) -> ValidationResult<LogicalModule> {
     })
 }
 
+fn validate_spec_ids(annotations: &[GroupAnnotation],
+                     globals: &[GroupGlobal])
+                     -> ValidationResult<()> {
+    for annotation in annotations {
+        if let GroupAnnotation::OpDecorate(OpDecorate { ref target,
+                                                        decoration: Decoration::SpecId(_) }) =
+            *annotation {
+            let is_spec_constant = globals.iter().any(|global| {
+                is_scalar_spec_constant(global) &&
+                global.result_id().map(|id| id.0) == Some(target.0)
+            });
+            if !is_spec_constant {
+                return Err(ValidationError::InvalidSpecIdTarget(target.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_scalar_spec_constant(global: &GroupGlobal) -> bool {
+    match *global {
+        GroupGlobal::GroupConstant(GroupConstant::OpSpecConstantTrue(_)) |
+        GroupGlobal::GroupConstant(GroupConstant::OpSpecConstantFalse(_)) |
+        GroupGlobal::GroupConstant(GroupConstant::OpSpecConstant(_)) => true,
+        _ => false,
+    }
+}
+
 #[derive(Clone, Copy)]
 struct OpSlice<'a> {
     insts: &'a [OpByBlock],
@@ -177,10 +212,27 @@ fn read_annotation(insts: OpSlice) -> PhaseResult<GroupAnnotation> {
 }
 
 fn read_global(insts: OpSlice) -> PhaseResult<GroupGlobal> {
-    if let Some(&OpByBlock::GroupGlobal(ref op)) = insts.first() {
-        PhaseResult::Ok(op.clone(), insts.advance())
-    } else {
-        PhaseResult::Next(insts)
+    match insts.first() {
+        Some(&OpByBlock::GroupGlobal(ref op)) => PhaseResult::Ok(op.clone(), insts.advance()),
+        Some(&OpByBlock::OpLine(ref op)) => {
+            PhaseResult::Ok(GroupGlobal::OpLine(op.clone()), insts.advance())
+        }
+        Some(&OpByBlock::OpNoLine(ref op)) => {
+            PhaseResult::Ok(GroupGlobal::OpNoLine(op.clone()), insts.advance())
+        }
+        _ => PhaseResult::Next(insts),
+    }
+}
+
+fn read_line(insts: OpSlice) -> PhaseResult<GroupLine> {
+    match insts.first() {
+        Some(&OpByBlock::OpLine(ref op)) => {
+            PhaseResult::Ok(GroupLine::OpLine(op.clone()), insts.advance())
+        }
+        Some(&OpByBlock::OpNoLine(ref op)) => {
+            PhaseResult::Ok(GroupLine::OpNoLine(op.clone()), insts.advance())
+        }
+        _ => PhaseResult::Next(insts),
     }
 }
 
@@ -195,16 +247,26 @@ fn read_function_parameter(insts: OpSlice) -> PhaseResult<OpFunctionParameter> {
 fn read_function_declaration(insts: OpSlice) -> PhaseResult<FunctionDeclaration> {
     // Preserve start point so we can recurse if we're actually a function definition
     let start_insts = insts;
+    let (lines, insts) = match read_many(insts, read_line) {
+        Ok((lines, insts)) => (lines, insts),
+        Err(err) => return PhaseResult::Err(err),
+    };
     if insts.get_remaining() < 2 {
-        return PhaseResult::Next(insts);
+        return PhaseResult::Next(start_insts);
     }
     if let Some(&OpByBlock::OpFunction(ref op)) = insts.first() {
         match read_many(insts.advance(), read_function_parameter) {
             Ok((params, insts)) => {
+                let (end_lines, insts) = match read_many(insts, read_line) {
+                    Ok((end_lines, insts)) => (end_lines, insts),
+                    Err(err) => return PhaseResult::Err(err),
+                };
                 if let Some(&OpByBlock::OpFunctionEnd(_)) = insts.first() {
                     let decl = FunctionDeclaration {
+                        lines: lines,
                         function: op.clone(),
                         parameters: params,
+                        end_lines: end_lines,
                     };
                     PhaseResult::Ok(decl, insts.advance())
                 } else {
@@ -219,10 +281,15 @@ fn read_function_declaration(insts: OpSlice) -> PhaseResult<FunctionDeclaration>
 }
 
 fn read_code(insts: OpSlice) -> PhaseResult<GroupCode> {
-    if let Some(&OpByBlock::GroupCode(ref op)) = insts.first() {
-        PhaseResult::Ok(op.clone(), insts.advance())
-    } else {
-        PhaseResult::Next(insts)
+    match insts.first() {
+        Some(&OpByBlock::GroupCode(ref op)) => PhaseResult::Ok(op.clone(), insts.advance()),
+        Some(&OpByBlock::OpLine(ref op)) => {
+            PhaseResult::Ok(GroupCode::OpLine(op.clone()), insts.advance())
+        }
+        Some(&OpByBlock::OpNoLine(ref op)) => {
+            PhaseResult::Ok(GroupCode::OpNoLine(op.clone()), insts.advance())
+        }
+        _ => PhaseResult::Next(insts),
     }
 }
 
@@ -243,6 +310,11 @@ fn read_merge(insts: OpSlice) -> (Option<GroupMerge>, OpSlice) {
 }
 
 fn read_basic_block(insts: OpSlice) -> PhaseResult<BasicBlock> {
+    let start_insts = insts;
+    let (lines, insts) = match read_many(insts, read_line) {
+        Ok((lines, insts)) => (lines, insts),
+        Err(err) => return PhaseResult::Err(err),
+    };
     if let Some(&OpByBlock::OpLabel(ref op)) = insts.first() {
         let label = op.clone();
         let (code, insts) = match read_many(insts.advance(), read_code) {
@@ -255,6 +327,7 @@ fn read_basic_block(insts: OpSlice) -> PhaseResult<BasicBlock> {
             Err(err) => return PhaseResult::Err(err),
         };
         let block = BasicBlock {
+            lines: lines,
             label: label,
             code: code,
             merge: merge,
@@ -262,13 +335,18 @@ fn read_basic_block(insts: OpSlice) -> PhaseResult<BasicBlock> {
         };
         PhaseResult::Ok(block, insts)
     } else {
-        PhaseResult::Next(insts)
+        PhaseResult::Next(start_insts)
     }
 }
 
 fn read_function_definition(insts: OpSlice) -> PhaseResult<FunctionDefinition> {
+    let start_insts = insts;
+    let (lines, insts) = match read_many(insts, read_line) {
+        Ok((lines, insts)) => (lines, insts),
+        Err(err) => return PhaseResult::Err(err),
+    };
     if insts.get_remaining() < 2 {
-        return PhaseResult::Next(insts);
+        return PhaseResult::Next(start_insts);
     }
     if let Some(&OpByBlock::OpFunction(ref op)) = insts.first() {
         match read_many(insts.advance(), read_function_parameter) {
@@ -277,12 +355,18 @@ fn read_function_definition(insts: OpSlice) -> PhaseResult<FunctionDefinition> {
                     Ok((blocks, insts)) => (blocks, insts),
                     Err(err) => return PhaseResult::Err(err),
                 };
+                let (end_lines, insts) = match read_many(insts, read_line) {
+                    Ok((end_lines, insts)) => (end_lines, insts),
+                    Err(err) => return PhaseResult::Err(err),
+                };
                 if let Some(&OpByBlock::OpFunctionEnd(_)) = insts.first() {
                     let insts = insts.advance();
                     let def = FunctionDefinition {
+                        lines: lines,
                         function: op.clone(),
                         parameters: params,
                         blocks: blocks,
+                        end_lines: end_lines,
                     };
                     PhaseResult::Ok(def, insts)
                 } else {
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use spv::op::*;
+use spv::types::*;
+use super::fold::{Folder, Value, mask, sign_extend, float_to_bits};
+use super::*;
+
+/// A specialization constant that can be set when a module is used
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecConstant {
+    /// The SpecId the constant is decorated with
+    pub spec_id: u32,
+    /// The id of the constant in the module
+    pub id: OpId,
+    pub result_type: OpId,
+    /// The value of the constant if it is not specialized
+    ///
+    /// Booleans are a single word of 0 or 1.
+    pub default: LitBytes,
+}
+
+/// Lists the specialization constants of a module that have a SpecId
+pub fn find_spec_constants(module: &LogicalModule) -> Vec<SpecConstant> {
+    let spec_ids = find_spec_ids(module);
+    module.globals
+        .iter()
+        .filter_map(|global| {
+            let (result_type, result_id, default) = match *global {
+                GroupGlobal::GroupConstant(GroupConstant::OpSpecConstantTrue(ref op)) => {
+                    (&op.result_type, &op.result_id, vec![1])
+                }
+                GroupGlobal::GroupConstant(GroupConstant::OpSpecConstantFalse(ref op)) => {
+                    (&op.result_type, &op.result_id, vec![0])
+                }
+                GroupGlobal::GroupConstant(GroupConstant::OpSpecConstant(ref op)) => {
+                    (&op.result_type, &op.result_id, op.value.clone())
+                }
+                _ => return None,
+            };
+            let id = OpId(result_id.0);
+            spec_ids.get(&id).map(|spec_id| {
+                SpecConstant {
+                    spec_id: *spec_id,
+                    id,
+                    result_type: result_type.clone(),
+                    default,
+                }
+            })
+        })
+        .collect()
+}
+
+fn find_spec_ids(module: &LogicalModule) -> HashMap<OpId, u32> {
+    module.annotations
+        .iter()
+        .filter_map(|op| match *op {
+            GroupAnnotation::OpDecorate(OpDecorate {
+                ref target,
+                decoration: Decoration::SpecId(spec_id),
+            }) => Some((target.clone(), spec_id)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FreezeError {
+    /// The value given for a SpecId has a different number of words to the
+    /// default value of the constant
+    ValueSizeMismatch(u32),
+    /// An OpSpecConstantOp could not be evaluated
+    UnsupportedOperation(OpId, SpecConstantOpcode),
+}
+
+pub type FreezeResult<T> = Result<T, FreezeError>;
+
+/// Replaces every specialization constant with a regular constant
+///
+/// Constants with a SpecId in `values` take the given value, which is a
+/// single word that is zero for false or nonzero for true for boolean
+/// constants. Other constants keep their default value. The SpecId
+/// decorations are removed.
+///
+/// OpSpecConstantOp instructions are evaluated with the same rules as
+/// `fold_constants`. Integer and float arithmetic, comparisons, conversions,
+/// logical operations, selects and extracting from composite constants are
+/// supported.
+pub fn freeze_spec_constants(mut module: LogicalModule,
+                             values: &HashMap<u32, LitBytes>)
+                             -> FreezeResult<LogicalModule> {
+    let spec_ids = find_spec_ids(&module);
+    let mut folder = Folder::new(&module, 0);
+    let mut composites = HashMap::new();
+
+    for global in &mut module.globals {
+        let constant = match *global {
+            GroupGlobal::GroupConstant(ref constant) => constant,
+            _ => continue,
+        };
+        let specialized = |result_id: &ResultId| {
+            spec_ids.get(&OpId(result_id.0)).and_then(|spec_id| {
+                values.get(spec_id).map(|value| (*spec_id, value))
+            })
+        };
+        let frozen = match *constant {
+            GroupConstant::OpSpecConstantTrue(OpSpecConstantTrue { ref result_type,
+                                                                   ref result_id }) |
+            GroupConstant::OpSpecConstantFalse(OpSpecConstantFalse { ref result_type,
+                                                                     ref result_id }) => {
+                let default = match *constant {
+                    GroupConstant::OpSpecConstantTrue(_) => true,
+                    _ => false,
+                };
+                let value = specialized(result_id)
+                    .map_or(default, |(_, value)| value.iter().any(|word| *word != 0));
+                folder.make_constant(result_type, result_id.clone(), Value::Bool(value))
+            }
+            GroupConstant::OpSpecConstant(ref op) => {
+                let value = match specialized(&op.result_id) {
+                    Some((spec_id, value)) => {
+                        if value.len() != op.value.len() {
+                            return Err(FreezeError::ValueSizeMismatch(spec_id));
+                        }
+                        value.clone()
+                    }
+                    None => op.value.clone(),
+                };
+                GroupConstant::OpConstant(OpConstant {
+                    result_type: op.result_type.clone(),
+                    result_id: op.result_id.clone(),
+                    value,
+                })
+            }
+            GroupConstant::OpSpecConstantComposite(ref op) => {
+                GroupConstant::OpConstantComposite(OpConstantComposite {
+                    result_type: op.result_type.clone(),
+                    result_id: op.result_id.clone(),
+                    constituents: op.constituents.clone(),
+                })
+            }
+            GroupConstant::OpSpecConstantOp(ref op) => {
+                match evaluate(&folder, &composites, op) {
+                    Some(constant) => constant,
+                    None => {
+                        let id = OpId(op.result_id.0);
+                        return Err(FreezeError::UnsupportedOperation(id, op.opcode));
+                    }
+                }
+            }
+            _ => constant.clone(),
+        };
+        if let GroupConstant::OpConstantComposite(ref op) = frozen {
+            composites.insert(OpId(op.result_id.0), op.constituents.clone());
+        }
+        folder.add_constant(&frozen);
+        *global = GroupGlobal::GroupConstant(frozen);
+    }
+
+    module.annotations.retain(|op| match *op {
+        GroupAnnotation::OpDecorate(OpDecorate { decoration: Decoration::SpecId(_), .. }) => false,
+        _ => true,
+    });
+
+    Ok(module)
+}
+
+/// Evaluates an OpSpecConstantOp whose operands have all been frozen
+fn evaluate(folder: &Folder,
+            composites: &HashMap<OpId, Vec<OpId>>,
+            op: &OpSpecConstantOp)
+            -> Option<GroupConstant> {
+    use spv::types::SpecConstantOpcode::*;
+
+    let result_type = &op.result_type;
+    let result_id = op.result_id.clone();
+    let operand = |index: usize| op.operands.get(index);
+    let width = folder.get_width(result_type);
+    let int = |index| operand(index).and_then(|id| folder.get_int(id));
+    let int_op = |f: &Fn(u64, u64, u32) -> Option<u64>| match (int(0), int(1)) {
+        (Some((a, width)), Some((b, _))) => {
+            f(a, b, width).map(|bits| Value::Bits(mask(bits, width)))
+        }
+        _ => None,
+    };
+    let boolean = |index| operand(index).and_then(|id| folder.get_bool(id));
+    let logical_op = |f: &Fn(bool, bool) -> bool| match (boolean(0), boolean(1)) {
+        (Some(a), Some(b)) => Some(Value::Bool(f(a, b))),
+        _ => None,
+    };
+
+    macro_rules! try_opt {
+        ($e: expr) => {
+            match $e {
+                Some(value) => value,
+                None => return None,
+            }
+        }
+    }
+
+    // Operations shared with function code are evaluated by the folder
+    macro_rules! fold_unary {
+        ($name: ident, $operand: ident) => {{
+            let code = GroupCode::$name($name {
+                result_type: result_type.clone(),
+                result_id: result_id.clone(),
+                $operand: try_opt!(operand(0)).clone(),
+            });
+            folder.fold(&code)
+        }}
+    }
+    macro_rules! fold_binary {
+        ($name: ident) => {{
+            let code = GroupCode::$name($name {
+                result_type: result_type.clone(),
+                result_id: result_id.clone(),
+                operand1: try_opt!(operand(0)).clone(),
+                operand2: try_opt!(operand(1)).clone(),
+            });
+            folder.fold(&code)
+        }}
+    }
+
+    let value = match op.opcode {
+        CompositeExtract => {
+            let mut id = try_opt!(operand(0));
+            for index in &op.indexes {
+                id = try_opt!(composites.get(id).and_then(|ids| ids.get(*index as usize)));
+            }
+            return match composites.get(id) {
+                Some(constituents) => {
+                    Some(GroupConstant::OpConstantComposite(OpConstantComposite {
+                        result_type: result_type.clone(),
+                        result_id,
+                        constituents: constituents.clone(),
+                    }))
+                }
+                None => {
+                    folder.get_value(id)
+                        .map(|value| folder.make_constant(result_type, result_id, value))
+                }
+            };
+        }
+        SConvert => {
+            match (int(0), width) {
+                (Some((bits, from)), Some(to)) => {
+                    Some(Value::Bits(mask(sign_extend(bits, from) as u64, to)))
+                }
+                _ => None,
+            }
+        }
+        UConvert => {
+            match (int(0), width) {
+                (Some((bits, _)), Some(to)) => Some(Value::Bits(mask(bits, to))),
+                _ => None,
+            }
+        }
+        FConvert => {
+            match (operand(0).and_then(|id| folder.get_float(id)), width) {
+                (Some((value, _)), Some(to)) => float_to_bits(value, to).map(Value::Bits),
+                _ => None,
+            }
+        }
+        ConvertUToF => fold_unary!(OpConvertUToF, unsigned_value),
+        Bitcast => fold_unary!(OpBitcast, operand),
+        SNegate => int(0).map(|(a, width)| Value::Bits(mask(0u64.wrapping_sub(a), width))),
+        Not => int(0).map(|(a, width)| Value::Bits(mask(!a, width))),
+        FNegate => {
+            operand(0)
+                .and_then(|id| folder.get_float(id))
+                .and_then(|(value, width)| float_to_bits(-value, width))
+                .map(Value::Bits)
+        }
+        // Shifting by the width of the type or more is undefined
+        ShiftRightLogical => {
+            int_op(&|a, b, width| if b < width as u64 { Some(a >> b) } else { None })
+        }
+        ShiftRightArithmetic => {
+            int_op(&|a, b, width| {
+                if b < width as u64 {
+                    Some((sign_extend(a, width) >> b) as u64)
+                } else {
+                    None
+                }
+            })
+        }
+        ShiftLeftLogical => {
+            int_op(&|a, b, width| if b < width as u64 { Some(a << b) } else { None })
+        }
+        LogicalOr => logical_op(&|a, b| a || b),
+        LogicalAnd => logical_op(&|a, b| a && b),
+        LogicalEqual => logical_op(&|a, b| a == b),
+        LogicalNotEqual => logical_op(&|a, b| a != b),
+        LogicalNot => boolean(0).map(|a| Value::Bool(!a)),
+        Select => {
+            let chosen = match boolean(0) {
+                Some(true) => operand(1),
+                Some(false) => operand(2),
+                None => None,
+            };
+            chosen.and_then(|id| folder.get_value(id))
+        }
+        IAdd => fold_binary!(OpIAdd),
+        ISub => fold_binary!(OpISub),
+        IMul => fold_binary!(OpIMul),
+        UDiv => fold_binary!(OpUDiv),
+        SDiv => fold_binary!(OpSDiv),
+        UMod => fold_binary!(OpUMod),
+        SRem => fold_binary!(OpSRem),
+        SMod => fold_binary!(OpSMod),
+        FAdd => fold_binary!(OpFAdd),
+        FSub => fold_binary!(OpFSub),
+        FMul => fold_binary!(OpFMul),
+        FDiv => fold_binary!(OpFDiv),
+        FRem => fold_binary!(OpFRem),
+        FMod => fold_binary!(OpFMod),
+        BitwiseOr => fold_binary!(OpBitwiseOr),
+        BitwiseXor => fold_binary!(OpBitwiseXor),
+        BitwiseAnd => fold_binary!(OpBitwiseAnd),
+        IEqual => fold_binary!(OpIEqual),
+        INotEqual => fold_binary!(OpINotEqual),
+        ULessThan => fold_binary!(OpULessThan),
+        SLessThan => fold_binary!(OpSLessThan),
+        UGreaterThan => fold_binary!(OpUGreaterThan),
+        SGreaterThan => fold_binary!(OpSGreaterThan),
+        ULessThanEqual => fold_binary!(OpULessThanEqual),
+        SLessThanEqual => fold_binary!(OpSLessThanEqual),
+        UGreaterThanEqual => fold_binary!(OpUGreaterThanEqual),
+        SGreaterThanEqual => fold_binary!(OpSGreaterThanEqual),
+        _ => None,
+    };
+    value.map(|value| folder.make_constant(result_type, result_id, value))
+}
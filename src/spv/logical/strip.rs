@@ -0,0 +1,115 @@
+use super::*;
+
+/// Selects which debug instructions to remove from a module
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StripOptions {
+    /// Remove OpName and OpMemberName
+    pub names: bool,
+    /// Remove OpSource, OpSourceContinued and OpSourceExtension
+    pub source: bool,
+    /// Remove OpString
+    pub strings: bool,
+    /// Remove OpLine and OpNoLine
+    pub lines: bool,
+    /// Remove extended instructions from NonSemantic.* sets and their imports
+    pub non_semantic: bool,
+}
+
+impl StripOptions {
+    /// Removes all debug instructions
+    pub fn all() -> StripOptions {
+        StripOptions {
+            names: true,
+            source: true,
+            strings: true,
+            lines: true,
+            non_semantic: true,
+        }
+    }
+
+    /// Removes source and string instructions while keeping names
+    pub fn keep_names() -> StripOptions {
+        StripOptions {
+            names: false,
+            source: true,
+            strings: true,
+            lines: true,
+            non_semantic: true,
+        }
+    }
+}
+
+impl Default for StripOptions {
+    fn default() -> StripOptions {
+        StripOptions::all()
+    }
+}
+
+/// Removes debug instructions from a module
+///
+/// If strings are removed but source instructions are kept then the file
+/// reference of any OpSource is cleared. Removing strings also removes line
+/// instructions, as they name their file with an OpString. Removing
+/// non-semantic instructions also removes the SPV_KHR_non_semantic_info
+/// extension.
+pub fn strip_debug(mut module: LogicalModule, options: StripOptions) -> LogicalModule {
+    module.debug = module.debug
+        .into_iter()
+        .filter(|op| match *op {
+            GroupDebug::OpName(_) |
+            GroupDebug::OpMemberName(_) => !options.names,
+            GroupDebug::OpSourceContinued(_) |
+            GroupDebug::OpSource(_) |
+            GroupDebug::OpSourceExtension(_) => !options.source,
+            GroupDebug::OpString(_) => !options.strings,
+        })
+        .map(|op| match op {
+            GroupDebug::OpSource(mut source) => {
+                if options.strings {
+                    source.file = None;
+                }
+                GroupDebug::OpSource(source)
+            }
+            op => op,
+        })
+        .collect();
+    if options.lines || options.strings {
+        module.globals.retain(|op| match *op {
+            GroupGlobal::OpLine(_) | GroupGlobal::OpNoLine(_) => false,
+            _ => true,
+        });
+        for decl in &mut module.function_declarations {
+            decl.lines.clear();
+            decl.end_lines.clear();
+        }
+        for function in &mut module.function_definitions {
+            function.lines.clear();
+            function.end_lines.clear();
+            for block in &mut function.blocks {
+                block.lines.clear();
+                block.code.retain(|op| match *op {
+                    GroupCode::OpLine(_) | GroupCode::OpNoLine(_) => false,
+                    _ => true,
+                });
+            }
+        }
+    }
+    if options.non_semantic {
+        let sets = module.ext_inst_imports
+            .iter()
+            .filter(|op| op.name.starts_with("NonSemantic."))
+            .map(|op| op.result_id.0)
+            .collect::<Vec<_>>();
+        module.ext_inst_imports.retain(|op| !sets.contains(&op.result_id.0));
+        module.extensions.retain(|name| name != "SPV_KHR_non_semantic_info");
+        for function in &mut module.function_definitions {
+            for block in &mut function.blocks {
+                block.code.retain(|op| match *op {
+                    GroupCode::OpExtInst(ref op) => !sets.contains(&op.set.0),
+                    _ => true,
+                });
+            }
+        }
+    }
+    module
+}
@@ -1,17 +1,24 @@
 
 #[macro_use]
 mod dis;
+#[macro_use]
+pub mod operands;
 
 pub mod types;
 pub mod op;
 pub mod raw;
 pub mod logical;
+#[cfg(feature = "serde")]
+mod serialize;
+
+pub use self::dis::{Disassemble, Style};
 
 use std::any::Any;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 use self::raw::MemoryBlockResult;
 use self::raw::MemoryBlock;
+use self::operands::Operands;
 
 /// An instruction
 pub trait Op: Debug + Display {
@@ -22,13 +29,19 @@ pub trait Op: Debug + Display {
 /// An instruction from an extended instruction set
 ///
 /// This is expected to itself be an enum variant in the instruction set
-pub trait ExtInst: Any + ExtInstClone + Debug + Display {
+pub trait ExtInst: Any + ExtInstClone + Operands + Debug + Display {
     /// Returns the op in the extended instruction
     fn get_op(&self) -> &Op;
 
     fn as_any(&self) -> &Any;
 
     fn eq(&self, other: &ExtInst) -> bool;
+
+    /// Formats the instruction as it is printed in disassembly, after the
+    /// instruction set
+    fn disassemble(&self, _style: &Style, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(self, f)
+    }
 }
 
 /// Helper trait to implement box cloning for all ExtInst
@@ -72,7 +85,11 @@ impl Display for ExtInstBox {
     }
 }
 
-impl dis::DisplayArgType for ExtInstBox {}
+impl dis::DisplayArg for ExtInstBox {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        self.0.disassemble(style, f)
+    }
+}
 
 /// Represents an object that controls an extended instruction set
 pub trait ExtInstSet {
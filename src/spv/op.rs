@@ -9,11 +9,13 @@ use super::dis::*;
 // Miscellaneous Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpNop;
 
 def_op_display!(OpNop;);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUndef {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -24,6 +26,7 @@ def_op_display!(OpUndef; result_id = result_type);
 // Debug Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSourceContinued {
     pub continued_source: LitString,
 }
@@ -31,6 +34,7 @@ pub struct OpSourceContinued {
 def_op_display!(OpSourceContinued; continued_source);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSource {
     pub language: SourceLanguage,
     pub version: SourceVersion,
@@ -41,6 +45,7 @@ pub struct OpSource {
 def_op_display!(OpSource; language | version | file | source);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSourceExtension {
     pub extension: LitString,
 }
@@ -48,6 +53,7 @@ pub struct OpSourceExtension {
 def_op_display!(OpSourceExtension; extension);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpName {
     pub target: OpId,
     pub name: LitString,
@@ -56,6 +62,7 @@ pub struct OpName {
 def_op_display!(OpName; target | name);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMemberName {
     pub struct_type: OpId,
     pub member: MemberIndex,
@@ -65,6 +72,7 @@ pub struct OpMemberName {
 def_op_display!(OpMemberName; struct_type | member | name);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpString {
     pub result_id: ResultId,
     pub string: LitString,
@@ -73,6 +81,7 @@ pub struct OpString {
 def_op_display!(OpString; result_id = string);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLine {
     pub file: OpId,
     pub line: Line,
@@ -82,6 +91,7 @@ pub struct OpLine {
 def_op_display!(OpLine; file | line | column);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpNoLine;
 
 def_op_display!(OpNoLine;);
@@ -89,6 +99,7 @@ def_op_display!(OpNoLine;);
 // Annotation Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDecorate {
     pub target: OpId,
     pub decoration: Decoration,
@@ -97,6 +108,7 @@ pub struct OpDecorate {
 def_op_display!(OpDecorate; target | decoration);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMemberDecorate {
     pub structure_type: OpId,
     pub member: MemberIndex,
@@ -106,6 +118,7 @@ pub struct OpMemberDecorate {
 def_op_display!(OpMemberDecorate; structure_type | member | decoration);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDecorationGroup {
     pub result_id: ResultId,
 }
@@ -113,6 +126,7 @@ pub struct OpDecorationGroup {
 def_op_display!(OpDecorationGroup; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupDecorate {
     pub decoration_group: OpId,
     pub targets: Vec<OpId>,
@@ -121,14 +135,18 @@ pub struct OpGroupDecorate {
 def_op_display!(OpGroupDecorate; decoration_group | targets);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupMemberDecorate {
     pub decoration_group: OpId,
     pub targets: Vec<(OpId, MemberIndex)>,
 }
 
+def_op_operands!(OpGroupMemberDecorate; decoration_group | targets);
+
 // Extension Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpExtension {
     pub name: LitString,
 }
@@ -136,6 +154,7 @@ pub struct OpExtension {
 def_op_display!(OpExtension; name);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpExtInstImport {
     pub result_id: ResultId,
     pub name: LitString,
@@ -144,6 +163,7 @@ pub struct OpExtInstImport {
 def_op_display!(OpExtInstImport; result_id = name);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpExtInst {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -156,6 +176,7 @@ def_op_display!(OpExtInst; result_id = result_type | set | instruction);
 // Mode-Setting Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMemoryModel {
     pub addressing_model: AddressingModel,
     pub memory_model: MemoryModel,
@@ -164,6 +185,7 @@ pub struct OpMemoryModel {
 def_op_display!(OpMemoryModel; addressing_model | memory_model);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEntryPoint {
     pub execution_model: ExecutionModel,
     pub entry_point: OpId,
@@ -174,6 +196,7 @@ pub struct OpEntryPoint {
 def_op_display!(OpEntryPoint; execution_model | entry_point | name | interface);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpExecutionMode {
     pub entry_point: OpId,
     pub mode: ExecutionMode,
@@ -182,6 +205,7 @@ pub struct OpExecutionMode {
 def_op_display!(OpExecutionMode; entry_point | mode);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCapability {
     pub capability: Capability,
 }
@@ -191,6 +215,7 @@ def_op_display!(OpCapability; capability);
 // Type-Declaration Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeVoid {
     pub result_id: ResultId,
 }
@@ -198,6 +223,7 @@ pub struct OpTypeVoid {
 def_op_display!(OpTypeVoid; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeBool {
     pub result_id: ResultId,
 }
@@ -205,6 +231,7 @@ pub struct OpTypeBool {
 def_op_display!(OpTypeBool; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeInt {
     pub result_id: ResultId,
     pub width: u32,
@@ -214,6 +241,7 @@ pub struct OpTypeInt {
 def_op_display!(OpTypeInt; result_id = width | signedness);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeFloat {
     pub result_id: ResultId,
     pub width: u32,
@@ -222,6 +250,7 @@ pub struct OpTypeFloat {
 def_op_display!(OpTypeFloat; result_id = width);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeVector {
     pub result_id: ResultId,
     pub component_type: OpId,
@@ -231,6 +260,7 @@ pub struct OpTypeVector {
 def_op_display!(OpTypeVector; result_id = component_type | component_count);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeMatrix {
     pub result_id: ResultId,
     pub column_type: OpId,
@@ -240,6 +270,7 @@ pub struct OpTypeMatrix {
 def_op_display!(OpTypeMatrix; result_id = column_type | column_count);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeImage {
     pub result_id: ResultId,
     pub sampled_type: OpId,
@@ -264,6 +295,7 @@ def_op_display!(OpTypeImage; result_id =
 );
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeSampler {
     pub result_id: ResultId,
 }
@@ -271,6 +303,7 @@ pub struct OpTypeSampler {
 def_op_display!(OpTypeSampler; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeSampledImage {
     pub result_id: ResultId,
     pub image_type: OpId,
@@ -279,6 +312,7 @@ pub struct OpTypeSampledImage {
 def_op_display!(OpTypeSampledImage; result_id = image_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeArray {
     pub result_id: ResultId,
     pub element_type: OpId,
@@ -288,6 +322,7 @@ pub struct OpTypeArray {
 def_op_display!(OpTypeArray; result_id = element_type | length);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeRuntimeArray {
     pub result_id: ResultId,
     pub element_type: OpId,
@@ -296,6 +331,7 @@ pub struct OpTypeRuntimeArray {
 def_op_display!(OpTypeRuntimeArray; result_id = element_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeStruct {
     pub result_id: ResultId,
     pub member_types: Vec<OpId>,
@@ -304,6 +340,7 @@ pub struct OpTypeStruct {
 def_op_display!(OpTypeStruct; result_id = member_types);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeOpaque {
     pub result_id: ResultId,
     pub name: LitString,
@@ -312,6 +349,7 @@ pub struct OpTypeOpaque {
 def_op_display!(OpTypeOpaque; result_id = name);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypePointer {
     pub result_id: ResultId,
     pub storage_class: StorageClass,
@@ -321,6 +359,7 @@ pub struct OpTypePointer {
 def_op_display!(OpTypePointer; result_id = storage_class | pointed_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeFunction {
     pub result_id: ResultId,
     pub return_type: OpId,
@@ -330,6 +369,7 @@ pub struct OpTypeFunction {
 def_op_display!(OpTypeFunction; result_id = return_type | parameter_types);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeEvent {
     pub result_id: ResultId,
 }
@@ -337,6 +377,7 @@ pub struct OpTypeEvent {
 def_op_display!(OpTypeEvent; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeDeviceEvent {
     pub result_id: ResultId,
 }
@@ -344,6 +385,7 @@ pub struct OpTypeDeviceEvent {
 def_op_display!(OpTypeDeviceEvent; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeReserveId {
     pub result_id: ResultId,
 }
@@ -351,6 +393,7 @@ pub struct OpTypeReserveId {
 def_op_display!(OpTypeReserveId; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeQueue {
     pub result_id: ResultId,
 }
@@ -358,6 +401,7 @@ pub struct OpTypeQueue {
 def_op_display!(OpTypeQueue; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypePipe {
     pub result_id: ResultId,
     pub access_qualifier: AccessQualifier,
@@ -366,6 +410,7 @@ pub struct OpTypePipe {
 def_op_display!(OpTypePipe; result_id = access_qualifier);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTypeForwardPointer {
     pub pointer_type: OpId,
     pub storage_class: StorageClass,
@@ -376,6 +421,7 @@ def_op_display!(OpTypeForwardPointer; pointer_type | storage_class);
 // Constant-Creation Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantTrue {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -384,6 +430,7 @@ pub struct OpConstantTrue {
 def_op_display!(OpConstantTrue; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantFalse {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -392,6 +439,7 @@ pub struct OpConstantFalse {
 def_op_display!(OpConstantFalse; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstant {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -401,6 +449,7 @@ pub struct OpConstant {
 def_op_display!(OpConstant; result_id = result_type | value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantComposite {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -410,6 +459,7 @@ pub struct OpConstantComposite {
 def_op_display!(OpConstantComposite; result_id = result_type | constituents);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantSampler {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -421,6 +471,7 @@ pub struct OpConstantSampler {
 def_op_display!(OpConstantSampler; result_id = result_type | addressing_mode | param | filter_mode);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantNull {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -429,6 +480,7 @@ pub struct OpConstantNull {
 def_op_display!(OpConstantNull; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSpecConstantTrue {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -437,6 +489,7 @@ pub struct OpSpecConstantTrue {
 def_op_display!(OpSpecConstantTrue; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSpecConstantFalse {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -445,6 +498,7 @@ pub struct OpSpecConstantFalse {
 def_op_display!(OpSpecConstantFalse; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSpecConstant {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -454,6 +508,7 @@ pub struct OpSpecConstant {
 def_op_display!(OpSpecConstant; result_id = result_type | value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSpecConstantComposite {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -462,9 +517,27 @@ pub struct OpSpecConstantComposite {
 
 def_op_display!(OpSpecConstantComposite; result_id = result_type | constituents);
 
+/// A constant defined by an operation on other constants
+///
+/// The `<id>` operands of the operation are stored separately from the
+/// literal indexes taken by CompositeExtract, CompositeInsert and
+/// VectorShuffle.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpSpecConstantOp {
+    pub result_type: OpId,
+    pub result_id: ResultId,
+    pub opcode: SpecConstantOpcode,
+    pub operands: Vec<OpId>,
+    pub indexes: Vec<u32>,
+}
+
+def_op_display!(OpSpecConstantOp; result_id = result_type | opcode | operands | indexes);
+
 // Memory Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVariable {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -475,6 +548,7 @@ pub struct OpVariable {
 def_op_display!(OpVariable; result_id = result_type | storage_class | initializer);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageTexelPointer {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -486,6 +560,7 @@ pub struct OpImageTexelPointer {
 def_op_display!(OpImageTexelPointer; result_id = result_type | image | coordinate | sample);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLoad {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -496,6 +571,7 @@ pub struct OpLoad {
 def_op_display!(OpLoad; result_id = result_type | pointer | memory_access);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpStore {
     pub pointer: OpId,
     pub object: OpId,
@@ -505,6 +581,7 @@ pub struct OpStore {
 def_op_display!(OpStore; pointer | object | memory_access);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCopyMemory {
     pub target: OpId,
     pub source: OpId,
@@ -514,6 +591,7 @@ pub struct OpCopyMemory {
 def_op_display!(OpCopyMemory; target | source | memory_access);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCopyMemorySized {
     pub target: OpId,
     pub source: OpId,
@@ -524,6 +602,7 @@ pub struct OpCopyMemorySized {
 def_op_display!(OpCopyMemorySized; target | source | size | memory_access);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAccessChain {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -534,6 +613,7 @@ pub struct OpAccessChain {
 def_op_display!(OpAccessChain; result_id = result_type | base | indexes);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpInBoundsAccessChain {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -544,6 +624,7 @@ pub struct OpInBoundsAccessChain {
 def_op_display!(OpInBoundsAccessChain; result_id = result_type | base | indexes);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpPtrAccessChain {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -555,6 +636,7 @@ pub struct OpPtrAccessChain {
 def_op_display!(OpPtrAccessChain; result_id = result_type | base | element | indexes);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpArrayLength {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -565,6 +647,7 @@ pub struct OpArrayLength {
 def_op_display!(OpArrayLength; result_id = result_type | structure | array_member);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGenericPtrMemSemantics {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -574,6 +657,7 @@ pub struct OpGenericPtrMemSemantics {
 def_op_display!(OpGenericPtrMemSemantics; result_id = result_type | pointer);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpInBoundsPtrAccessChain {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -587,6 +671,7 @@ def_op_display!(OpInBoundsPtrAccessChain; result_id = result_type | base | eleme
 // Function Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFunction {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -597,6 +682,7 @@ pub struct OpFunction {
 def_op_display!(OpFunction; result_id = result_type | function_control | function_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFunctionParameter {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -605,11 +691,13 @@ pub struct OpFunctionParameter {
 def_op_display!(OpFunctionParameter; result_id = result_type);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFunctionEnd;
 
 def_op_display!(OpFunctionEnd;);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFunctionCall {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -622,6 +710,7 @@ def_op_display!(OpFunctionCall; result_id = result_type | function | arguments);
 // Image Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSampledImage {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -632,16 +721,23 @@ pub struct OpSampledImage {
 def_op_display!(OpSampledImage; result_id = result_type | image | sampler);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampledImplicitLod(pub OpId,
                                      pub ResultId,
                                      pub OpId,
                                      pub OpId,
                                      pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSampledImplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampledExplicitLod(pub OpId, pub ResultId, pub OpId, pub OpId, pub ImageOperands);
 
+def_op_operands!(OpImageSampledExplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleDrefImplicitLod(pub OpId,
                                         pub ResultId,
                                         pub OpId,
@@ -649,7 +745,10 @@ pub struct OpImageSampleDrefImplicitLod(pub OpId,
                                         pub OpId,
                                         pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSampleDrefImplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleDrefExplicitLod(pub OpId,
                                         pub ResultId,
                                         pub OpId,
@@ -657,22 +756,31 @@ pub struct OpImageSampleDrefExplicitLod(pub OpId,
                                         pub OpId,
                                         pub ImageOperands);
 
+def_op_operands!(OpImageSampleDrefExplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleProjImplicitLod(pub OpId,
                                         pub ResultId,
                                         pub OpId,
                                         pub OpId,
                                         pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSampleProjImplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleProjExplicitLod(pub OpId,
                                         pub ResultId,
                                         pub OpId,
                                         pub OpId,
                                         pub ImageOperands);
 
+def_op_operands!(OpImageSampleProjExplicitLod; 1 = 0 | 2 | 3 | 4);
+
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleProjDrefImplicitLod(pub OpId,
                                             pub ResultId,
                                             pub OpId,
@@ -680,7 +788,10 @@ pub struct OpImageSampleProjDrefImplicitLod(pub OpId,
                                             pub OpId,
                                             pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSampleProjDrefImplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSampleProjDrefExplicitLod(pub OpId,
                                             pub ResultId,
                                             pub OpId,
@@ -688,10 +799,16 @@ pub struct OpImageSampleProjDrefExplicitLod(pub OpId,
                                             pub OpId,
                                             pub ImageOperands);
 
+def_op_operands!(OpImageSampleProjDrefExplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageFetch(pub OpId, pub ResultId, pub OpId, pub OpId, pub Option<ImageOperands>);
 
+def_op_operands!(OpImageFetch; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageGather(pub OpId,
                          pub ResultId,
                          pub OpId,
@@ -699,7 +816,10 @@ pub struct OpImageGather(pub OpId,
                          pub OpId,
                          pub Option<ImageOperands>);
 
+def_op_operands!(OpImageGather; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageDrefGather(pub OpId,
                              pub ResultId,
                              pub OpId,
@@ -707,51 +827,90 @@ pub struct OpImageDrefGather(pub OpId,
                              pub OpId,
                              pub Option<ImageOperands>);
 
+def_op_operands!(OpImageDrefGather; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageRead(pub OpId, pub ResultId, pub OpId, pub OpId, pub Option<ImageOperands>);
 
+def_op_operands!(OpImageRead; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageWrite(pub OpId, pub OpId, pub OpId, pub Option<ImageOperands>);
 
+def_op_operands!(OpImageWrite; 0 | 1 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImage(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImage; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQueryFormat(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageQueryFormat; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQueryOrder(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageQueryOrder; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQuerySizeLod(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpImageQuerySizeLod; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQuerySize(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageQuerySize; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQueryLod(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpImageQueryLod; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQueryLevels(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageQueryLevels; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageQuerySamples(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageQuerySamples; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleImplicitLod(pub OpId,
                                           pub ResultId,
                                           pub OpId,
                                           pub OpId,
                                           pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseSampleImplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleExplicitLod(pub OpId,
                                           pub ResultId,
                                           pub OpId,
                                           pub OpId,
                                           pub ImageOperands);
 
+def_op_operands!(OpImageSparseSampleExplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleDrefImplicitLod(pub OpId,
                                               pub ResultId,
                                               pub OpId,
@@ -759,7 +918,10 @@ pub struct OpImageSparseSampleDrefImplicitLod(pub OpId,
                                               pub OpId,
                                               pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseSampleDrefImplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleDrefExplicitLod(pub OpId,
                                               pub ResultId,
                                               pub OpId,
@@ -767,21 +929,30 @@ pub struct OpImageSparseSampleDrefExplicitLod(pub OpId,
                                               pub OpId,
                                               pub ImageOperands);
 
+def_op_operands!(OpImageSparseSampleDrefExplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleProjImplicitLod(pub OpId,
                                               pub ResultId,
                                               pub OpId,
                                               pub OpId,
                                               pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseSampleProjImplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleProjExplicitLod(pub OpId,
                                               pub ResultId,
                                               pub OpId,
                                               pub OpId,
                                               pub ImageOperands);
 
+def_op_operands!(OpImageSparseSampleProjExplicitLod; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleProjDrefImplicitLod(pub OpId,
                                                   pub ResultId,
                                                   pub OpId,
@@ -789,7 +960,10 @@ pub struct OpImageSparseSampleProjDrefImplicitLod(pub OpId,
                                                   pub OpId,
                                                   pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseSampleProjDrefImplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseSampleProjDrefExplicitLod(pub OpId,
                                                   pub ResultId,
                                                   pub OpId,
@@ -797,14 +971,20 @@ pub struct OpImageSparseSampleProjDrefExplicitLod(pub OpId,
                                                   pub OpId,
                                                   pub ImageOperands);
 
+def_op_operands!(OpImageSparseSampleProjDrefExplicitLod; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseFetch(pub OpId,
                               pub ResultId,
                               pub OpId,
                               pub OpId,
                               pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseFetch; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseGather(pub OpId,
                                pub ResultId,
                                pub OpId,
@@ -812,7 +992,10 @@ pub struct OpImageSparseGather(pub OpId,
                                pub OpId,
                                pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseGather; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseDrefGather(pub OpId,
                                    pub ResultId,
                                    pub OpId,
@@ -820,15 +1003,24 @@ pub struct OpImageSparseDrefGather(pub OpId,
                                    pub OpId,
                                    pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseDrefGather; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseTexelsResident(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpImageSparseTexelsResident; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpImageSparseRead(pub OpId, pub ResultId, pub OpId, pub OpId, pub Option<ImageOperands>);
 
+def_op_operands!(OpImageSparseRead; 1 = 0 | 2 | 3 | 4);
+
 // Conversion Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertFToU {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -838,6 +1030,7 @@ pub struct OpConvertFToU {
 def_op_display!(OpConvertFToU; result_id = result_type | float_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertFToS {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -847,6 +1040,7 @@ pub struct OpConvertFToS {
 def_op_display!(OpConvertFToS; result_id = result_type | float_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertSToF {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -856,6 +1050,7 @@ pub struct OpConvertSToF {
 def_op_display!(OpConvertSToF; result_id = result_type | signed_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertUToF {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -865,6 +1060,7 @@ pub struct OpConvertUToF {
 def_op_display!(OpConvertUToF; result_id = result_type | unsigned_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUConvert {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -874,6 +1070,7 @@ pub struct OpUConvert {
 def_op_display!(OpUConvert; result_id = result_type | unsigned_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSConvert {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -883,6 +1080,7 @@ pub struct OpSConvert {
 def_op_display!(OpSConvert; result_id = result_type | signed_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFConvert {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -892,6 +1090,7 @@ pub struct OpFConvert {
 def_op_display!(OpFConvert; result_id = result_type | float_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpQuantizeToF16 {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -901,6 +1100,7 @@ pub struct OpQuantizeToF16 {
 def_op_display!(OpQuantizeToF16; result_id = result_type | value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertPtrToU {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -910,6 +1110,7 @@ pub struct OpConvertPtrToU {
 def_op_display!(OpConvertPtrToU; result_id = result_type | pointer);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSatConvertSToU {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -919,6 +1120,7 @@ pub struct OpSatConvertSToU {
 def_op_display!(OpSatConvertSToU; result_id = result_type | signed_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSatConvertUToS {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -928,6 +1130,7 @@ pub struct OpSatConvertUToS {
 def_op_display!(OpSatConvertUToS; result_id = result_type | unsigned_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConvertUToPtr {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -937,6 +1140,7 @@ pub struct OpConvertUToPtr {
 def_op_display!(OpConvertUToPtr; result_id = result_type | integer_value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpPtrCastToGeneric {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -946,6 +1150,7 @@ pub struct OpPtrCastToGeneric {
 def_op_display!(OpPtrCastToGeneric; result_id = result_type | pointer);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGenericCastToPtr {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -955,6 +1160,7 @@ pub struct OpGenericCastToPtr {
 def_op_display!(OpGenericCastToPtr; result_id = result_type | pointer);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGenericCastToPtrExplicit {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -965,6 +1171,7 @@ pub struct OpGenericCastToPtrExplicit {
 def_op_display!(OpGenericCastToPtrExplicit; result_id = result_type | pointer | storage_class);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitcast {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -976,6 +1183,7 @@ def_op_display_s1!(OpBitcast);
 // Composite Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVectorExtractDynamic {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -986,6 +1194,7 @@ pub struct OpVectorExtractDynamic {
 def_op_display!(OpVectorExtractDynamic; result_id = result_type | vector | index);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVectorInsertDynamic {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -997,6 +1206,7 @@ pub struct OpVectorInsertDynamic {
 def_op_display!(OpVectorInsertDynamic; result_id = result_type | vector | component | index);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVectorShuffle {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1008,6 +1218,7 @@ pub struct OpVectorShuffle {
 def_op_display!(OpVectorShuffle; result_id = result_type | operand1 | operand2 | components);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCompositeConstruct {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1017,6 +1228,7 @@ pub struct OpCompositeConstruct {
 def_op_display!(OpCompositeConstruct; result_id = result_type | constituents);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCompositeExtract {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1027,6 +1239,7 @@ pub struct OpCompositeExtract {
 def_op_display!(OpCompositeExtract; result_id = result_type | composite | indexes);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCompositeInsert {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1038,6 +1251,7 @@ pub struct OpCompositeInsert {
 def_op_display!(OpCompositeInsert; result_id = result_type | object | composite | indexes);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCopyObject {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1047,6 +1261,7 @@ pub struct OpCopyObject {
 def_op_display_s1!(OpCopyObject);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpTranspose {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1058,6 +1273,7 @@ def_op_display!(OpTranspose; result_id = result_type | matrix);
 // Arithmetic Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSNegate {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1067,6 +1283,7 @@ pub struct OpSNegate {
 def_op_display_s1!(OpSNegate);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFNegate {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1076,6 +1293,7 @@ pub struct OpFNegate {
 def_op_display_s1!(OpFNegate);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIAdd {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1086,6 +1304,7 @@ pub struct OpIAdd {
 def_op_display_s2!(OpIAdd);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFAdd {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1096,6 +1315,7 @@ pub struct OpFAdd {
 def_op_display_s2!(OpFAdd);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpISub {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1106,6 +1326,7 @@ pub struct OpISub {
 def_op_display_s2!(OpISub);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFSub {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1116,6 +1337,7 @@ pub struct OpFSub {
 def_op_display_s2!(OpFSub);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIMul {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1126,6 +1348,7 @@ pub struct OpIMul {
 def_op_display_s2!(OpIMul);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFMul {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1136,6 +1359,7 @@ pub struct OpFMul {
 def_op_display_s2!(OpFMul);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUDiv {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1146,6 +1370,7 @@ pub struct OpUDiv {
 def_op_display_s2!(OpUDiv);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSDiv {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1156,6 +1381,7 @@ pub struct OpSDiv {
 def_op_display_s2!(OpSDiv);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFDiv {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1166,6 +1392,7 @@ pub struct OpFDiv {
 def_op_display_s2!(OpFDiv);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUMod {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1176,6 +1403,7 @@ pub struct OpUMod {
 def_op_display_s2!(OpUMod);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSRem {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1186,6 +1414,7 @@ pub struct OpSRem {
 def_op_display_s2!(OpSRem);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSMod {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1196,6 +1425,7 @@ pub struct OpSMod {
 def_op_display_s2!(OpSMod);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFRem {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1206,6 +1436,7 @@ pub struct OpFRem {
 def_op_display_s2!(OpFRem);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFMod {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1216,6 +1447,7 @@ pub struct OpFMod {
 def_op_display_s2!(OpFMod);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVectorTimesScalar {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1226,6 +1458,7 @@ pub struct OpVectorTimesScalar {
 def_op_display!(OpVectorTimesScalar; result_id = result_type | vector | scalar);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMatrixTimesScalar {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1236,6 +1469,7 @@ pub struct OpMatrixTimesScalar {
 def_op_display!(OpMatrixTimesScalar; result_id = result_type | matrix | scalar);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpVectorTimesMatrix {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1246,6 +1480,7 @@ pub struct OpVectorTimesMatrix {
 def_op_display!(OpVectorTimesMatrix; result_id = result_type | vector | matrix);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMatrixTimesVector {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1256,6 +1491,7 @@ pub struct OpMatrixTimesVector {
 def_op_display!(OpMatrixTimesVector; result_id = result_type | matrix | vector);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMatrixTimesMatrix {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1266,6 +1502,7 @@ pub struct OpMatrixTimesMatrix {
 def_op_display!(OpMatrixTimesMatrix; result_id = result_type | left_matrix | right_matrix);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpOuterProduct {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1276,6 +1513,7 @@ pub struct OpOuterProduct {
 def_op_display!(OpOuterProduct; result_id = result_type | vector1 | vector2);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDot {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1286,6 +1524,7 @@ pub struct OpDot {
 def_op_display!(OpDot; result_id = result_type | vector1 | vector2);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIAddCarry {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1296,6 +1535,7 @@ pub struct OpIAddCarry {
 def_op_display_s2!(OpIAddCarry);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpISubBorrow {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1306,6 +1546,7 @@ pub struct OpISubBorrow {
 def_op_display_s2!(OpISubBorrow);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUMulExtended {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1316,6 +1557,7 @@ pub struct OpUMulExtended {
 def_op_display_s2!(OpUMulExtended);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSMulExtended {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1328,6 +1570,7 @@ def_op_display_s2!(OpSMulExtended);
 // Bit Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpShiftRightLogical {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1338,6 +1581,7 @@ pub struct OpShiftRightLogical {
 def_op_display!(OpShiftRightLogical; result_id = result_type | base | shift);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpShiftRightArithmetic {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1348,6 +1592,7 @@ pub struct OpShiftRightArithmetic {
 def_op_display!(OpShiftRightArithmetic; result_id = result_type | base | shift);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpShiftLeftLogical {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1358,6 +1603,7 @@ pub struct OpShiftLeftLogical {
 def_op_display!(OpShiftLeftLogical; result_id = result_type | base | shift);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitwiseOr {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1368,6 +1614,7 @@ pub struct OpBitwiseOr {
 def_op_display_s2!(OpBitwiseOr);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitwiseXor {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1378,6 +1625,7 @@ pub struct OpBitwiseXor {
 def_op_display_s2!(OpBitwiseXor);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitwiseAnd {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1388,6 +1636,7 @@ pub struct OpBitwiseAnd {
 def_op_display_s2!(OpBitwiseAnd);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpNot {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1397,71 +1646,135 @@ pub struct OpNot {
 def_op_display_s1!(OpNot);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitFieldInsert(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpBitFieldInsert; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitFieldSExtract(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpBitFieldSExtract; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitFieldUExtract(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpBitFieldUExtract; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitReverse(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpBitReverse; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBitCount(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpBitCount; 1 = 0 | 2);
+
 // Relational and Logical Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAny(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpAny; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAll(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpAll; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsNan(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsNan; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsInf(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsInf; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsFinite(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsFinite; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsNormal(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsNormal; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSignBitSet(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpSignBitSet; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLessOrGreater(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpLessOrGreater; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpOrdered(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpOrdered; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUnordered(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpUnordered; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLogicalEqual(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpLogicalEqual; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLogicalNotEqual(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpLogicalNotEqual; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLogicalOr(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpLogicalOr; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLogicalAnd(pub OpId, pub ResultId, pub OpId, pub OpId);
 
+def_op_operands!(OpLogicalAnd; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLogicalNot(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpLogicalNot; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSelect(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpSelect; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1472,6 +1785,7 @@ pub struct OpIEqual {
 def_op_display_s2!(OpIEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpINotEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1482,6 +1796,7 @@ pub struct OpINotEqual {
 def_op_display_s2!(OpINotEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUGreaterThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1492,6 +1807,7 @@ pub struct OpUGreaterThan {
 def_op_display_s2!(OpUGreaterThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSGreaterThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1502,6 +1818,7 @@ pub struct OpSGreaterThan {
 def_op_display_s2!(OpSGreaterThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUGreaterThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1512,6 +1829,7 @@ pub struct OpUGreaterThanEqual {
 def_op_display_s2!(OpUGreaterThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSGreaterThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1522,6 +1840,7 @@ pub struct OpSGreaterThanEqual {
 def_op_display_s2!(OpSGreaterThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpULessThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1532,6 +1851,7 @@ pub struct OpULessThan {
 def_op_display_s2!(OpULessThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSLessThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1542,6 +1862,7 @@ pub struct OpSLessThan {
 def_op_display_s2!(OpSLessThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpULessThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1552,6 +1873,7 @@ pub struct OpULessThanEqual {
 def_op_display_s2!(OpULessThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSLessThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1562,6 +1884,7 @@ pub struct OpSLessThanEqual {
 def_op_display_s2!(OpSLessThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1572,6 +1895,7 @@ pub struct OpFOrdEqual {
 def_op_display_s2!(OpFOrdEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1582,6 +1906,7 @@ pub struct OpFUnordEqual {
 def_op_display_s2!(OpFUnordEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdNotEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1592,6 +1917,7 @@ pub struct OpFOrdNotEqual {
 def_op_display_s2!(OpFOrdNotEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordNotEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1602,6 +1928,7 @@ pub struct OpFUnordNotEqual {
 def_op_display_s2!(OpFUnordNotEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdLessThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1612,6 +1939,7 @@ pub struct OpFOrdLessThan {
 def_op_display_s2!(OpFOrdLessThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordLessThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1622,6 +1950,7 @@ pub struct OpFUnordLessThan {
 def_op_display_s2!(OpFUnordLessThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdGreaterThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1632,6 +1961,7 @@ pub struct OpFOrdGreaterThan {
 def_op_display_s2!(OpFOrdGreaterThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordGreaterThan {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1642,6 +1972,7 @@ pub struct OpFUnordGreaterThan {
 def_op_display_s2!(OpFUnordGreaterThan);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdLessThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1652,6 +1983,7 @@ pub struct OpFOrdLessThanEqual {
 def_op_display_s2!(OpFOrdLessThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordLessThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1662,6 +1994,7 @@ pub struct OpFUnordLessThanEqual {
 def_op_display_s2!(OpFUnordLessThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFOrdGreaterThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1672,6 +2005,7 @@ pub struct OpFOrdGreaterThanEqual {
 def_op_display_s2!(OpFOrdGreaterThanEqual);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFUnordGreaterThanEqual {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1684,35 +2018,63 @@ def_op_display_s2!(OpFUnordGreaterThanEqual);
 // Derivative Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdx(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdx; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdy(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdy; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFwidth(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpFwidth; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdxFine(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdxFine; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdyFine(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdyFine; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFwidthFine(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpFwidthFine; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdxCoarse(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdxCoarse; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpDPdyCoarse(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpDPdyCoarse; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpFwidthCoarse(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpFwidthCoarse; 1 = 0 | 2);
+
 // Control-Flow Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpPhi {
     pub result_type: OpId,
     pub result_id: ResultId,
@@ -1722,6 +2084,7 @@ pub struct OpPhi {
 def_op_display!(OpPhi; result_id = result_type | variables);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLoopMerge {
     pub merge_block: OpId,
     pub continue_target: OpId,
@@ -1731,6 +2094,7 @@ pub struct OpLoopMerge {
 def_op_display!(OpLoopMerge; merge_block | continue_target | loop_control);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSelectionMerge {
     pub merge_block: OpId,
     pub selection_control: SelectionControl,
@@ -1739,6 +2103,7 @@ pub struct OpSelectionMerge {
 def_op_display!(OpSelectionMerge; merge_block | selection_control);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLabel {
     pub result_id: ResultId,
 }
@@ -1746,6 +2111,7 @@ pub struct OpLabel {
 def_op_display!(OpLabel; result_id =);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBranch {
     pub target_label: OpId,
 }
@@ -1753,6 +2119,7 @@ pub struct OpBranch {
 def_op_display!(OpBranch; target_label);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBranchConditional {
     pub condition: OpId,
     pub true_label: OpId,
@@ -1763,37 +2130,65 @@ pub struct OpBranchConditional {
 def_op_display!(OpBranchConditional; condition | true_label | false_label | weights);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSwitch(pub OpId, pub OpId, pub Vec<(LitBytes, OpId)>);
 
+def_op_operands!(OpSwitch; 0 | 1 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpKill;
 
+def_op_operands!(OpKill;);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReturn;
 
 def_op_display!(OpReturn;);
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct OpReturnValue(pub OpId);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpReturnValue {
+    pub value: OpId,
+}
+
+def_op_display!(OpReturnValue; value);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpUnreachable;
 
+def_op_operands!(OpUnreachable;);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLifetimeStart(pub OpId, pub u32);
 
+def_op_operands!(OpLifetimeStart; 0 | 1);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpLifetimeStop(pub OpId, pub u32);
 
+def_op_operands!(OpLifetimeStop; 0 | 1);
+
 // Atomic Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicLoad(pub OpId, pub ResultId, pub OpId, pub ScopeId, pub MemorySemanticsId);
 
+def_op_operands!(OpAtomicLoad; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicStore(pub OpId, pub ScopeId, pub MemorySemanticsId, pub OpId);
 
+def_op_operands!(OpAtomicStore; 0 | 1 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicExchange(pub OpId,
                             pub ResultId,
                             pub OpId,
@@ -1801,7 +2196,10 @@ pub struct OpAtomicExchange(pub OpId,
                             pub MemorySemanticsId,
                             pub OpId);
 
+def_op_operands!(OpAtomicExchange; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicCompareExchange(pub OpId,
                                    pub ResultId,
                                    pub OpId,
@@ -1811,7 +2209,10 @@ pub struct OpAtomicCompareExchange(pub OpId,
                                    pub OpId,
                                    pub OpId);
 
+def_op_operands!(OpAtomicCompareExchange; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicCompareExchangeWeak(pub OpId,
                                        pub ResultId,
                                        pub OpId,
@@ -1821,7 +2222,10 @@ pub struct OpAtomicCompareExchangeWeak(pub OpId,
                                        pub OpId,
                                        pub OpId);
 
+def_op_operands!(OpAtomicCompareExchangeWeak; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicIIncrement(pub OpId,
                               pub ResultId,
                               pub OpId,
@@ -1829,7 +2233,10 @@ pub struct OpAtomicIIncrement(pub OpId,
                               pub MemorySemanticsId,
                               pub OpId);
 
+def_op_operands!(OpAtomicIIncrement; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicIDecrement(pub OpId,
                               pub ResultId,
                               pub OpId,
@@ -1837,7 +2244,10 @@ pub struct OpAtomicIDecrement(pub OpId,
                               pub MemorySemanticsId,
                               pub OpId);
 
+def_op_operands!(OpAtomicIDecrement; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicIAdd(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1845,7 +2255,10 @@ pub struct OpAtomicIAdd(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicIAdd; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicISub(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1853,7 +2266,10 @@ pub struct OpAtomicISub(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicISub; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicSMin(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1861,7 +2277,10 @@ pub struct OpAtomicSMin(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicSMin; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicUMin(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1869,7 +2288,10 @@ pub struct OpAtomicUMin(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicUMin; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicSMax(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1877,7 +2299,10 @@ pub struct OpAtomicSMax(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicSMax; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicUMax(pub OpId,
                         pub ResultId,
                         pub OpId,
@@ -1885,7 +2310,10 @@ pub struct OpAtomicUMax(pub OpId,
                         pub MemorySemanticsId,
                         pub OpId);
 
+def_op_operands!(OpAtomicUMax; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicAnd(pub OpId,
                        pub ResultId,
                        pub OpId,
@@ -1893,7 +2321,10 @@ pub struct OpAtomicAnd(pub OpId,
                        pub MemorySemanticsId,
                        pub OpId);
 
+def_op_operands!(OpAtomicAnd; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicOr(pub OpId,
                       pub ResultId,
                       pub OpId,
@@ -1901,7 +2332,10 @@ pub struct OpAtomicOr(pub OpId,
                       pub MemorySemanticsId,
                       pub OpId);
 
+def_op_operands!(OpAtomicOr; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicXor(pub OpId,
                        pub ResultId,
                        pub OpId,
@@ -1909,47 +2343,80 @@ pub struct OpAtomicXor(pub OpId,
                        pub MemorySemanticsId,
                        pub OpId);
 
+def_op_operands!(OpAtomicXor; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicFlagTestAndSet(pub OpId,
                                   pub ResultId,
                                   pub OpId,
                                   pub ScopeId,
                                   pub MemorySemanticsId);
 
+def_op_operands!(OpAtomicFlagTestAndSet; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpAtomicFlagClear(pub OpId, pub ScopeId, pub MemorySemanticsId);
 
+def_op_operands!(OpAtomicFlagClear; 0 | 1 | 2);
+
 // Primitive Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEmitVertex;
 
+def_op_operands!(OpEmitVertex;);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEndPrimitive;
 
+def_op_operands!(OpEndPrimitive;);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEmitStreamVertex(pub OpId);
 
+def_op_operands!(OpEmitStreamVertex; 0);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEndStreamPrimitive(pub OpId);
 
+def_op_operands!(OpEndStreamPrimitive; 0);
+
 // Barrier Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpControlBarrier(pub ScopeId, pub ScopeId, pub MemorySemanticsId);
 
+def_op_operands!(OpControlBarrier; 0 | 1 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMemoryBarrier(pub ScopeId, pub MemorySemanticsId);
 
+def_op_operands!(OpMemoryBarrier; 0 | 1);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpNamedBarrierInitialize(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpNamedBarrierInitialize; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpMemoryNamedBarrier(pub OpId, pub ScopeId, pub MemorySemanticsId);
 
+def_op_operands!(OpMemoryNamedBarrier; 0 | 1 | 2);
+
 // Group Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupAsyncCopy(pub OpId,
                             pub ResultId,
                             pub ScopeId,
@@ -1959,48 +2426,90 @@ pub struct OpGroupAsyncCopy(pub OpId,
                             pub OpId,
                             pub OpId);
 
+def_op_operands!(OpGroupAsyncCopy; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupWaitEvents(pub ScopeId, pub OpId, pub OpId);
 
+def_op_operands!(OpGroupWaitEvents; 0 | 1 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupAll(pub OpId, pub ResultId, pub ScopeId, pub OpId);
 
+def_op_operands!(OpGroupAll; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupAny(pub OpId, pub ResultId, pub ScopeId, pub OpId);
 
+def_op_operands!(OpGroupAny; 1 = 0 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupBroadcast(pub OpId, pub ResultId, pub ScopeId, pub OpId, pub OpId);
 
+def_op_operands!(OpGroupBroadcast; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupIAdd(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupIAdd; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupFAdd(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupFAdd; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupFMin(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupFMin; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupUMin(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupUMin; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupSMin(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupSMin; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupFMax(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupFMax; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupUMax(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupUMax; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupSMax(pub OpId, pub ResultId, pub ScopeId, pub GroupOperation, pub OpId);
 
+def_op_operands!(OpGroupSMax; 1 = 0 | 2 | 3 | 4);
+
 // Device-Side Enqueue Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEnqueueMarker(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpEnqueueMarker; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpEnqueueKernel(pub OpId,
                            pub ResultId,
                            pub OpId,
@@ -2015,7 +2524,10 @@ pub struct OpEnqueueKernel(pub OpId,
                            pub OpId,
                            pub Vec<(OpId, OpId)>);
 
+def_op_operands!(OpEnqueueKernel; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelNDrangeSubGroupCount(pub OpId,
                                            pub ResultId,
                                            pub OpId,
@@ -2024,7 +2536,10 @@ pub struct OpGetKernelNDrangeSubGroupCount(pub OpId,
                                            pub OpId,
                                            pub OpId);
 
+def_op_operands!(OpGetKernelNDrangeSubGroupCount; 1 = 0 | 2 | 3 | 4 | 5 | 6);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelNDrangeMaxSubGroupSize(pub OpId,
                                              pub ResultId,
                                              pub OpId,
@@ -2033,10 +2548,16 @@ pub struct OpGetKernelNDrangeMaxSubGroupSize(pub OpId,
                                              pub OpId,
                                              pub OpId);
 
+def_op_operands!(OpGetKernelNDrangeMaxSubGroupSize; 1 = 0 | 2 | 3 | 4 | 5 | 6);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelWorkGroupSize(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpGetKernelWorkGroupSize; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelPreferredWorkGroupSizeMultiple(pub OpId,
                                                      pub ResultId,
                                                      pub OpId,
@@ -2044,31 +2565,58 @@ pub struct OpGetKernelPreferredWorkGroupSizeMultiple(pub OpId,
                                                      pub OpId,
                                                      pub OpId);
 
+def_op_operands!(OpGetKernelPreferredWorkGroupSizeMultiple; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpRetainEvent(pub OpId);
 
+def_op_operands!(OpRetainEvent; 0);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReleaseEvent(pub OpId);
 
+def_op_operands!(OpReleaseEvent; 0);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCreateUserEvent(pub OpId, pub ResultId);
 
+def_op_operands!(OpCreateUserEvent; 1 = 0);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsValidEvent(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsValidEvent; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpSetUserEventStatus(pub OpId, pub OpId);
 
+def_op_operands!(OpSetUserEventStatus; 0 | 1);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCaptureEventProfilingInfo(pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpCaptureEventProfilingInfo; 0 | 1 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetDefaultQueue(pub OpId, pub ResultId);
 
+def_op_operands!(OpGetDefaultQueue; 1 = 0);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpBuildNDRange(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpBuildNDRange; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelLocalSizeForSubgroupCount(pub OpId,
                                                 pub ResultId,
                                                 pub OpId,
@@ -2077,7 +2625,10 @@ pub struct OpGetKernelLocalSizeForSubgroupCount(pub OpId,
                                                 pub OpId,
                                                 pub OpId);
 
+def_op_operands!(OpGetKernelLocalSizeForSubgroupCount; 1 = 0 | 2 | 3 | 4 | 5 | 6);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetKernelMaxNumSubgroups(pub OpId,
                                       pub ResultId,
                                       pub OpId,
@@ -2085,15 +2636,24 @@ pub struct OpGetKernelMaxNumSubgroups(pub OpId,
                                       pub OpId,
                                       pub OpId);
 
+def_op_operands!(OpGetKernelMaxNumSubgroups; 1 = 0 | 2 | 3 | 4 | 5);
+
 // Pipe Instructions
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReadPipe(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpReadPipe; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpWritePipe(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpWritePipe; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReservedReadPipe(pub OpId,
                               pub ResultId,
                               pub OpId,
@@ -2103,7 +2663,10 @@ pub struct OpReservedReadPipe(pub OpId,
                               pub OpId,
                               pub OpId);
 
+def_op_operands!(OpReservedReadPipe; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReservedWritePipe(pub OpId,
                                pub ResultId,
                                pub OpId,
@@ -2113,10 +2676,16 @@ pub struct OpReservedWritePipe(pub OpId,
                                pub OpId,
                                pub OpId);
 
+def_op_operands!(OpReservedWritePipe; 1 = 0 | 2 | 3 | 4 | 5 | 6 | 7);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReserveReadPipePackets(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpReserveReadPipePackets; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpReserveWritePipePackets(pub OpId,
                                      pub ResultId,
                                      pub OpId,
@@ -2124,22 +2693,40 @@ pub struct OpReserveWritePipePackets(pub OpId,
                                      pub OpId,
                                      pub OpId);
 
+def_op_operands!(OpReserveWritePipePackets; 1 = 0 | 2 | 3 | 4 | 5);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCommitReadPipe(pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpCommitReadPipe; 0 | 1 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCommitWritePipe(pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpCommitWritePipe; 0 | 1 | 2 | 3);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpIsValidReserveId(pub OpId, pub ResultId, pub OpId);
 
+def_op_operands!(OpIsValidReserveId; 1 = 0 | 2);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetNumPipePackets(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpGetNumPipePackets; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGetMaxPipePackets(pub OpId, pub ResultId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpGetMaxPipePackets; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupReserveReadPipePackets(pub OpId,
                                          pub ResultId,
                                          pub ScopeId,
@@ -2148,7 +2735,10 @@ pub struct OpGroupReserveReadPipePackets(pub OpId,
                                          pub OpId,
                                          pub OpId);
 
+def_op_operands!(OpGroupReserveReadPipePackets; 1 = 0 | 2 | 3 | 4 | 5 | 6);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupReserveWritePipePackets(pub OpId,
                                           pub ResultId,
                                           pub ScopeId,
@@ -2157,14 +2747,28 @@ pub struct OpGroupReserveWritePipePackets(pub OpId,
                                           pub OpId,
                                           pub OpId);
 
+def_op_operands!(OpGroupReserveWritePipePackets; 1 = 0 | 2 | 3 | 4 | 5 | 6);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupCommitReadPipe(pub ScopeId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpGroupCommitReadPipe; 0 | 1 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpGroupCommitWritePipe(pub ScopeId, pub OpId, pub OpId, pub OpId, pub OpId);
 
+def_op_operands!(OpGroupCommitWritePipe; 0 | 1 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpConstantPipeStorage(pub OpId, pub ResultId, pub u32, pub u32, pub u32);
 
+def_op_operands!(OpConstantPipeStorage; 1 = 0 | 2 | 3 | 4);
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpCreatePipeFromPipeStorage(pub OpId, pub ResultId, pub OpId);
+
+def_op_operands!(OpCreatePipeFromPipeStorage; 1 = 0 | 2);
@@ -0,0 +1,294 @@
+//! Generic access to the ids referenced by instructions
+
+use spv::types::*;
+use spv::ExtInstBox;
+
+/// Access to the ids defined and referenced by an instruction
+pub trait Operands {
+    /// Returns the id the instruction defines, if it has one
+    fn result_id(&self) -> Option<&ResultId>;
+
+    /// Returns the type of the defined id, if the instruction has one
+    fn result_type(&self) -> Option<&OpId>;
+
+    /// Returns every id referenced by the instruction, excluding the result
+    /// type
+    fn operands(&self) -> Vec<&OpId>;
+
+    /// Returns the id the instruction defines for modification
+    fn result_id_mut(&mut self) -> Option<&mut ResultId>;
+
+    /// Returns the type of the defined id for modification
+    fn result_type_mut(&mut self) -> Option<&mut OpId>;
+
+    /// Returns every id referenced by the instruction for modification,
+    /// excluding the result type
+    fn operands_mut(&mut self) -> Vec<&mut OpId>;
+}
+
+/// Collects the ids referenced by an argument to an instruction
+pub trait OperandArg {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>);
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>);
+}
+
+impl OperandArg for OpId {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        out.push(self);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        out.push(self);
+    }
+}
+
+impl<T: OperandArg> OperandArg for Option<T> {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        if let Some(ref t) = *self {
+            t.visit_operands(out);
+        }
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        if let Some(ref mut t) = *self {
+            t.visit_operands_mut(out);
+        }
+    }
+}
+
+impl<T: OperandArg> OperandArg for Vec<T> {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        for element in self {
+            element.visit_operands(out);
+        }
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        for element in self {
+            element.visit_operands_mut(out);
+        }
+    }
+}
+
+impl<A: OperandArg, B: OperandArg> OperandArg for (A, B) {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        self.0.visit_operands(out);
+        self.1.visit_operands(out);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        self.0.visit_operands_mut(out);
+        self.1.visit_operands_mut(out);
+    }
+}
+
+impl OperandArg for String {
+    fn visit_operands<'a>(&'a self, _: &mut Vec<&'a OpId>) {}
+    fn visit_operands_mut<'a>(&'a mut self, _: &mut Vec<&'a mut OpId>) {}
+}
+
+impl OperandArg for PhiArg {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        out.push(&self.variable);
+        out.push(&self.parent);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        out.push(&mut self.variable);
+        out.push(&mut self.parent);
+    }
+}
+
+impl OperandArg for ExecutionMode {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        if let ExecutionMode::VecTypeHint(ref id) = *self {
+            out.push(id);
+        }
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        if let ExecutionMode::VecTypeHint(ref mut id) = *self {
+            out.push(id);
+        }
+    }
+}
+
+impl OperandArg for ImageOperands {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        self.bias.visit_operands(out);
+        self.lod.visit_operands(out);
+        self.grad.visit_operands(out);
+        self.const_offset.visit_operands(out);
+        self.offset.visit_operands(out);
+        self.const_offsets.visit_operands(out);
+        self.min_lod.visit_operands(out);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        self.bias.visit_operands_mut(out);
+        self.lod.visit_operands_mut(out);
+        self.grad.visit_operands_mut(out);
+        self.const_offset.visit_operands_mut(out);
+        self.offset.visit_operands_mut(out);
+        self.const_offsets.visit_operands_mut(out);
+        self.min_lod.visit_operands_mut(out);
+    }
+}
+
+impl OperandArg for ScopeId {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        out.push(&self.0);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        out.push(&mut self.0);
+    }
+}
+
+impl OperandArg for MemorySemanticsId {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        out.push(&self.0);
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        out.push(&mut self.0);
+    }
+}
+
+impl OperandArg for ExtInstBox {
+    fn visit_operands<'a>(&'a self, out: &mut Vec<&'a OpId>) {
+        out.extend(self.as_ref().operands());
+    }
+    fn visit_operands_mut<'a>(&'a mut self, out: &mut Vec<&'a mut OpId>) {
+        out.extend(self.0.operands_mut());
+    }
+}
+
+/// Marker trait for argument types that never contain an id
+pub trait LiteralArg {}
+
+impl<T: LiteralArg> OperandArg for T {
+    fn visit_operands<'a>(&'a self, _: &mut Vec<&'a OpId>) {}
+    fn visit_operands_mut<'a>(&'a mut self, _: &mut Vec<&'a mut OpId>) {}
+}
+
+impl LiteralArg for u32 {}
+impl LiteralArg for SourceLanguage {}
+impl LiteralArg for SourceVersion {}
+impl LiteralArg for MemberIndex {}
+impl LiteralArg for Line {}
+impl LiteralArg for Column {}
+impl LiteralArg for Decoration {}
+impl LiteralArg for AddressingModel {}
+impl LiteralArg for MemoryModel {}
+impl LiteralArg for ExecutionModel {}
+impl LiteralArg for Capability {}
+impl LiteralArg for Signedness {}
+impl LiteralArg for StorageClass {}
+impl LiteralArg for Dim {}
+impl LiteralArg for DepthStatus {}
+impl LiteralArg for Arrayed {}
+impl LiteralArg for MS {}
+impl LiteralArg for SampledStatus {}
+impl LiteralArg for ImageFormat {}
+impl LiteralArg for AccessQualifier {}
+impl LiteralArg for SamplerAddressingMode {}
+impl LiteralArg for SamplerParam {}
+impl LiteralArg for SamplerFilterMode {}
+impl LiteralArg for MemoryAccess {}
+impl LiteralArg for FunctionControl {}
+impl LiteralArg for LoopControl {}
+impl LiteralArg for SelectionControl {}
+impl LiteralArg for BranchWeights {}
+impl LiteralArg for SpecConstantOpcode {}
+impl LiteralArg for GroupOperation {}
+
+/// Macro to implement operand access for an instruction
+///
+/// Operands are listed by field name in the same form as `def_op_display!`.
+/// Instructions stored as tuple structs list field indexes instead, with the
+/// result id and result type written as `1 = 0`.
+macro_rules! def_op_operands {
+    (@impl $name: ident; [$($result_id: tt)*]; [$($result_type: tt)*]; $($operand: tt)*) => {
+        impl $crate::spv::operands::Operands for $name {
+            fn result_id(&self) -> Option<&$crate::spv::types::ResultId> {
+                def_op_operands!(@get self; $(& self.$result_id)*)
+            }
+            fn result_type(&self) -> Option<&$crate::spv::types::OpId> {
+                def_op_operands!(@get self; $(& self.$result_type)*)
+            }
+            fn operands(&self) -> Vec<&$crate::spv::types::OpId> {
+                #[allow(unused_mut)]
+                let mut out = Vec::new();
+                $($crate::spv::operands::OperandArg::visit_operands(&self.$operand, &mut out);)*
+                out
+            }
+            fn result_id_mut(&mut self) -> Option<&mut $crate::spv::types::ResultId> {
+                def_op_operands!(@get self; $(&mut self.$result_id)*)
+            }
+            fn result_type_mut(&mut self) -> Option<&mut $crate::spv::types::OpId> {
+                def_op_operands!(@get self; $(&mut self.$result_type)*)
+            }
+            fn operands_mut(&mut self) -> Vec<&mut $crate::spv::types::OpId> {
+                #[allow(unused_mut)]
+                let mut out = Vec::new();
+                $($crate::spv::operands::OperandArg::visit_operands_mut(&mut self.$operand,
+                                                                         &mut out);)*
+                out
+            }
+        }
+    };
+    (@get $s: ident; $field: expr) => { Some($field) };
+    (@get $s: ident;) => { None };
+    ($name: ident; result_id = result_type $(| $operand_name: ident)*) => {
+        def_op_operands!(@impl $name; [result_id]; [result_type]; $($operand_name)*);
+    };
+    ($name: ident; result_id = $($operand_name: ident)|*) => {
+        def_op_operands!(@impl $name; [result_id]; []; $($operand_name)*);
+    };
+    ($name: ident; $($operand_name: ident)|*) => {
+        def_op_operands!(@impl $name; []; []; $($operand_name)*);
+    };
+    ($name: ident; $result_id: tt = $result_type: tt $(| $operand_index: tt)*) => {
+        def_op_operands!(@impl $name; [$result_id]; [$result_type]; $($operand_index)*);
+    };
+    ($name: ident; $($operand_index: tt)|*) => {
+        def_op_operands!(@impl $name; []; []; $($operand_index)*);
+    };
+}
+
+/// Macro to implement operand access for an enum of instructions by
+/// forwarding to the contained instruction
+macro_rules! def_enum_operands {
+    ($name: ident; $($variant: ident),*) => {
+        impl $crate::spv::operands::Operands for $name {
+            fn result_id(&self) -> Option<&$crate::spv::types::ResultId> {
+                match *self {
+                    $($name::$variant(ref op) => $crate::spv::operands::Operands::result_id(op),)*
+                }
+            }
+            fn result_type(&self) -> Option<&$crate::spv::types::OpId> {
+                match *self {
+                    $($name::$variant(ref op) => $crate::spv::operands::Operands::result_type(op),)*
+                }
+            }
+            fn operands(&self) -> Vec<&$crate::spv::types::OpId> {
+                match *self {
+                    $($name::$variant(ref op) => $crate::spv::operands::Operands::operands(op),)*
+                }
+            }
+            fn result_id_mut(&mut self) -> Option<&mut $crate::spv::types::ResultId> {
+                match *self {
+                    $($name::$variant(ref mut op) => {
+                        $crate::spv::operands::Operands::result_id_mut(op)
+                    })*
+                }
+            }
+            fn result_type_mut(&mut self) -> Option<&mut $crate::spv::types::OpId> {
+                match *self {
+                    $($name::$variant(ref mut op) => {
+                        $crate::spv::operands::Operands::result_type_mut(op)
+                    })*
+                }
+            }
+            fn operands_mut(&mut self) -> Vec<&mut $crate::spv::types::OpId> {
+                match *self {
+                    $($name::$variant(ref mut op) => {
+                        $crate::spv::operands::Operands::operands_mut(op)
+                    })*
+                }
+            }
+        }
+    };
+}
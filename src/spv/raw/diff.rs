@@ -0,0 +1,566 @@
+//! Semantic differences between modules
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use spv::operands::Operands;
+use super::{Core, RawModule};
+
+/// A difference between the instructions of two modules
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// An instruction that is only in the new module
+    Added(Core),
+    /// An instruction that is only in the old module
+    Removed(Core),
+    /// An instruction of the old module that became a different instruction
+    /// in the new module
+    Changed(Core, Core),
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Change::Added(ref inst) => write!(f, "+{}", inst),
+            Change::Removed(ref inst) => write!(f, "-{}", inst),
+            Change::Changed(ref old, ref new) => write!(f, "-{}\n+{}", old, new),
+        }
+    }
+}
+
+/// The differences in one section of a module
+#[derive(Clone, Debug, PartialEq)]
+pub struct SectionDiff {
+    /// The instructions before the first function are in the `module`
+    /// section, and each function is in a section like `function main`
+    pub name: String,
+    pub changes: Vec<Change>,
+}
+
+/// The differences between two modules
+///
+/// Only sections with changes are included. Instructions of the old module
+/// use the ids of the new module, so only real changes show up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleDiff {
+    pub sections: Vec<SectionDiff>,
+}
+
+impl ModuleDiff {
+    /// Returns true if the modules are the same apart from their choice of
+    /// ids
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+impl Display for ModuleDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for section in &self.sections {
+            try!(write!(f, "@@ {} @@\n", section.name));
+            for change in &section.changes {
+                try!(write!(f, "{}\n", change));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the differences between an old and a new version of a module
+///
+/// Ids are matched between the modules before instructions are compared.
+/// Types and constants are matched by their structure and global variables
+/// by their debug name, falling back to their structure. Functions are
+/// matched by entry point name or debug name, and unnamed functions by
+/// their order. The instructions in each pair of functions are aligned to
+/// match ids within the functions. Ids that are only in the old module are
+/// numbered after the bound of the new module.
+///
+/// Logical modules can be compared with `lower_module`.
+pub fn diff_modules(old: &RawModule, new: &RawModule) -> ModuleDiff {
+    let old_split = Split::new(old);
+    let new_split = Split::new(new);
+    let mut ids = IdMap::new(new.bound);
+
+    let mut keys = HashMap::new();
+    let old_keys = global_keys(&old_split, &mut keys);
+    let new_keys = global_keys(&new_split, &mut keys);
+    let mut candidates = HashMap::<usize, Vec<u32>>::new();
+    for &(id, key) in &old_keys {
+        candidates.entry(key).or_default().push(id);
+    }
+    for &(id, key) in &new_keys {
+        if let Some(candidates) = candidates.get_mut(&key) {
+            if candidates.is_empty() {
+                continue;
+            }
+            let name = new_split.names.get(&id);
+            let index = candidates.iter()
+                .position(|old_id| name.is_some() && old_split.names.get(old_id) == name)
+                .unwrap_or(0);
+            ids.map.insert(candidates.remove(index), id);
+        }
+    }
+
+    let mut function_pairs = Vec::new();
+    let mut matched_functions = HashSet::new();
+    let mut added_functions = Vec::new();
+    let mut unnamed = old_split.functions.iter().filter(|function| function.name.is_none());
+    for new_function in &new_split.functions {
+        let old_function = match new_function.name {
+            Some(ref name) => {
+                old_split.functions.iter().find(|function| function.name.as_ref() == Some(name))
+            }
+            None => unnamed.next(),
+        };
+        match old_function {
+            Some(old_function) => {
+                ids.map.insert(old_function.id, new_function.id);
+                matched_functions.insert(old_function.id);
+                function_pairs.push((old_function, new_function));
+            }
+            None => added_functions.push(new_function),
+        }
+    }
+
+    let mut function_sections = Vec::new();
+    for (old_function, new_function) in function_pairs {
+        let changes = diff_function(old_function, new_function, &mut ids);
+        function_sections.push(SectionDiff {
+            name: new_function.section_name(),
+            changes,
+        });
+    }
+    for old_function in &old_split.functions {
+        if !matched_functions.contains(&old_function.id) {
+            function_sections.push(SectionDiff {
+                name: old_function.section_name(),
+                changes: old_function.insts
+                    .iter()
+                    .map(|inst| Change::Removed(ids.rename(inst)))
+                    .collect(),
+            });
+        }
+    }
+    for new_function in added_functions {
+        function_sections.push(SectionDiff {
+            name: new_function.section_name(),
+            changes: new_function.insts.iter().cloned().map(Change::Added).collect(),
+        });
+    }
+
+    let old_globals = old_split.globals.iter().map(|inst| ids.rename(inst)).collect::<Vec<_>>();
+    let global_key = |inst: &Core| match inst.result_id() {
+        Some(id) => format!("%{}", id.0),
+        None => format!("{}", inst),
+    };
+    let steps = align(&old_globals.iter().map(&global_key).collect::<Vec<_>>(),
+                      &new_split.globals.iter().map(&global_key).collect::<Vec<_>>(),
+                      &old_globals.iter().map(operand_key).collect::<Vec<_>>(),
+                      &new_split.globals.iter().map(operand_key).collect::<Vec<_>>());
+    let mut sections = vec![SectionDiff {
+                                name: "module".into(),
+                                changes: changes(steps, &old_globals, new_split.globals),
+                            }];
+    sections.extend(function_sections);
+    sections.retain(|section| !section.changes.is_empty());
+    ModuleDiff { sections }
+}
+
+/// A function of a module
+struct Function<'a> {
+    id: u32,
+    name: Option<String>,
+    /// Instructions from OpFunction to OpFunctionEnd
+    insts: &'a [Core],
+}
+
+impl<'a> Function<'a> {
+    fn section_name(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("function {}", name),
+            None => format!("function %{}", self.id),
+        }
+    }
+}
+
+/// A module split into its global instructions and functions
+struct Split<'a> {
+    globals: &'a [Core],
+    functions: Vec<Function<'a>>,
+    /// The debug names of ids, with entry point names taking priority
+    names: HashMap<u32, String>,
+}
+
+impl<'a> Split<'a> {
+    fn new(module: &'a RawModule) -> Split<'a> {
+        let insts = &module.instructions;
+        let first_function = insts.iter()
+            .position(|inst| match *inst {
+                Core::OpFunction(_) => true,
+                _ => false,
+            })
+            .unwrap_or(insts.len());
+
+        let mut names = HashMap::new();
+        for inst in &insts[..first_function] {
+            if let Core::OpName(ref op) = *inst {
+                if !op.name.is_empty() {
+                    names.insert(op.target.0, op.name.clone());
+                }
+            }
+        }
+        for inst in &insts[..first_function] {
+            if let Core::OpEntryPoint(ref op) = *inst {
+                names.insert(op.entry_point.0, op.name.clone());
+            }
+        }
+
+        let mut functions = Vec::new();
+        let mut start = first_function;
+        for (index, inst) in insts.iter().enumerate().skip(first_function) {
+            if let Core::OpFunctionEnd(_) = *inst {
+                let id = insts[start].result_id().map_or(0, |id| id.0);
+                functions.push(Function {
+                    id,
+                    name: names.get(&id).cloned(),
+                    insts: &insts[start..index + 1],
+                });
+                start = index + 1;
+            }
+        }
+
+        Split {
+            globals: &insts[..first_function],
+            functions,
+            names,
+        }
+    }
+}
+
+/// Numbers the global ids of a module by their structure
+///
+/// Keys are shared between modules, so globals with the same key in two
+/// modules have the same structure.
+fn global_keys(split: &Split, keys: &mut HashMap<String, usize>) -> Vec<(u32, usize)> {
+    let mut ids = HashMap::new();
+    let mut out = Vec::new();
+    for inst in split.globals {
+        let id = match inst.result_id() {
+            Some(id) => id.0,
+            None => continue,
+        };
+        let name = split.names.get(&id);
+        let structure = match (inst, name) {
+            (&Core::OpVariable(_), Some(name)) => format!("variable {}", name),
+            _ => {
+                let mut shape = inst.clone();
+                let mut referenced = Vec::new();
+                shape.result_id_mut().unwrap().0 = 0;
+                map_references(&mut shape, |id| {
+                    match ids.get(&id) {
+                        Some(key) => referenced.push(format!("#{}", key)),
+                        None => referenced.push("?".into()),
+                    }
+                    0
+                });
+                format!("{} [{}]", shape, referenced.join(" "))
+            }
+        };
+        let next = keys.len();
+        let key = *keys.entry(structure).or_insert(next);
+        ids.insert(id, key);
+        out.push((id, key));
+    }
+    out
+}
+
+/// Compares a function of the old module with a function of the new module,
+/// matching the ids defined inside the functions
+fn diff_function(old: &Function, new: &Function, ids: &mut IdMap) -> Vec<Change> {
+    let old_locals = old.insts.iter().filter_map(|inst| inst.result_id()).map(|id| id.0);
+    let old_locals = old_locals.collect::<HashSet<_>>();
+    let new_locals = new.insts.iter().filter_map(|inst| inst.result_id()).map(|id| id.0);
+    let new_locals = new_locals.collect::<HashSet<_>>();
+
+    // Shapes are instructions with the global ids renamed and the local ids
+    // cleared, as local ids are only matched after aligning the functions
+    let old_shapes = old.insts
+        .iter()
+        .map(|inst| {
+            let mut shape = inst.clone();
+            if let Some(id) = shape.result_id_mut() {
+                id.0 = 0;
+            }
+            map_references(&mut shape,
+                           |id| if old_locals.contains(&id) { 0 } else { ids.get(id) });
+            format!("{}", shape)
+        })
+        .collect::<Vec<_>>();
+    let new_shapes = new.insts
+        .iter()
+        .map(|inst| {
+            let mut shape = inst.clone();
+            if let Some(id) = shape.result_id_mut() {
+                id.0 = 0;
+            }
+            map_references(&mut shape, |id| if new_locals.contains(&id) { 0 } else { id });
+            format!("{}", shape)
+        })
+        .collect::<Vec<_>>();
+    // Instructions with results are paired by their type, so an instruction
+    // that is replaced by a different operation keeps its id
+    let old_pairs = old.insts
+        .iter()
+        .map(|inst| match inst.result_type() {
+            Some(id) if inst.result_id().is_some() => format!("%{}", ids.get(id.0)),
+            _ => opcode(inst),
+        })
+        .collect::<Vec<_>>();
+    let new_pairs = new.insts
+        .iter()
+        .map(|inst| match inst.result_type() {
+            Some(id) if inst.result_id().is_some() => format!("%{}", id.0),
+            _ => opcode(inst),
+        })
+        .collect::<Vec<_>>();
+    let steps = align(&old_shapes, &new_shapes, &old_pairs, &new_pairs);
+
+    for step in &steps {
+        if let Step::Keep(old_index, new_index) = *step {
+            let old_id = old.insts[old_index].result_id();
+            let new_id = new.insts[new_index].result_id();
+            if let (Some(old_id), Some(new_id)) = (old_id, new_id) {
+                ids.map.insert(old_id.0, new_id.0);
+            }
+        }
+    }
+
+    let old_insts = old.insts.iter().map(|inst| ids.rename(inst)).collect::<Vec<_>>();
+    changes(steps, &old_insts, new.insts)
+}
+
+/// Maps ids of the old module to ids of the new module
+struct IdMap {
+    map: HashMap<u32, u32>,
+    /// The next id for ids that are only in the old module
+    next: u32,
+}
+
+impl IdMap {
+    fn new(bound: u32) -> IdMap {
+        IdMap {
+            map: HashMap::new(),
+            next: bound,
+        }
+    }
+
+    fn get(&mut self, id: u32) -> u32 {
+        let next = &mut self.next;
+        *self.map.entry(id).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn rename(&mut self, inst: &Core) -> Core {
+        let mut inst = inst.clone();
+        if let Some(id) = inst.result_id_mut() {
+            id.0 = self.get(id.0);
+        }
+        map_references(&mut inst, |id| self.get(id));
+        inst
+    }
+}
+
+/// Replaces the result type and operand ids of an instruction
+fn map_references<F: FnMut(u32) -> u32>(inst: &mut Core, mut f: F) {
+    if let Some(id) = inst.result_type_mut() {
+        id.0 = f(id.0);
+    }
+    for operand in inst.operands_mut() {
+        operand.0 = f(operand.0);
+    }
+}
+
+/// Returns the name of the instruction
+fn opcode(inst: &Core) -> String {
+    let text = format!("{}", inst);
+    text.split_whitespace().find(|word| word.starts_with("Op")).unwrap_or("").into()
+}
+
+/// Returns the name of the instruction with the ids it references, to pair
+/// removed and added instructions that are the same apart from literals
+fn operand_key(inst: &Core) -> String {
+    let operands = inst.operands().into_iter().map(|id| format!(" %{}", id.0));
+    opcode(inst) + &operands.collect::<String>()
+}
+
+/// A step in the alignment of two lists of instructions
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Step {
+    Keep(usize, usize),
+    Remove(usize),
+    Add(usize),
+}
+
+/// The largest number of removed and added instructions that a section is
+/// aligned with
+///
+/// Memory use of the alignment grows with the square of the number of
+/// edits, so sections that differ by more are reported as removed and added
+/// wholesale past their common start and end.
+const MAX_EDITS: usize = 1024;
+
+/// Aligns two lists of instructions by a shortest edit script of their keys
+///
+/// Removed and added instructions between two kept instructions are then
+/// paired up by their pair keys, in order, so that they are reported as
+/// changed instructions.
+fn align(old: &[String], new: &[String], old_pair: &[String], new_pair: &[String]) -> Vec<Step> {
+    let prefix = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let suffix = old[prefix..].iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+
+    let mut script: Vec<Step> = (0..prefix).map(|i| Step::Keep(i, i)).collect();
+    match edit_script(&old[prefix..old_end], &new[prefix..new_end]) {
+        Some(middle) => {
+            script.extend(middle.into_iter().map(|step| match step {
+                Step::Keep(i, j) => Step::Keep(prefix + i, prefix + j),
+                Step::Remove(i) => Step::Remove(prefix + i),
+                Step::Add(j) => Step::Add(prefix + j),
+            }))
+        }
+        None => {
+            script.extend((prefix..old_end).map(Step::Remove));
+            script.extend((prefix..new_end).map(Step::Add));
+        }
+    }
+    script.extend((0..suffix).map(|n| Step::Keep(old_end + n, new_end + n)));
+
+    let mut steps = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for step in script {
+        match step {
+            Step::Keep(i, j) => {
+                pair_run(&mut removed, &mut added, old_pair, new_pair, &mut steps);
+                steps.push(Step::Keep(i, j));
+            }
+            Step::Remove(i) => removed.push(i),
+            Step::Add(j) => added.push(j),
+        }
+    }
+    pair_run(&mut removed, &mut added, old_pair, new_pair, &mut steps);
+    steps
+}
+
+/// Finds a shortest edit script between two lists of keys with Myers'
+/// algorithm, or `None` if it needs more than `MAX_EDITS` edits
+fn edit_script(old: &[String], new: &[String]) -> Option<Vec<Step>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_EDITS) as isize;
+    // The furthest x reached on each diagonal k = x - y, at index k + max + 1
+    let mut furthest = vec![0isize; 2 * max as usize + 3];
+    // The furthest x on diagonals -d..=d before each round d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let at = |k: isize| (k + max + 1) as usize;
+
+    for d in 0..max + 1 {
+        trace.push(furthest[at(-d)..at(d) + 1].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+                furthest[at(k + 1)]
+            } else {
+                furthest[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[at(k)] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+/// Walks back from the end of both lists through the rounds of Myers'
+/// algorithm to recover the edit script
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Step> {
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let furthest = &trace[d as usize];
+        let at = |k: isize| (k + d) as usize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = furthest[at(prev_k)];
+        let prev_y = prev_x - prev_k;
+        let edit_x = if prev_k == k + 1 { prev_x } else { prev_x + 1 };
+        while x > edit_x {
+            steps.push(Step::Keep((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if prev_k == k + 1 {
+            steps.push(Step::Add(prev_y as usize));
+        } else {
+            steps.push(Step::Remove(prev_x as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        steps.push(Step::Keep((x - 1) as usize, (y - 1) as usize));
+        x -= 1;
+        y -= 1;
+    }
+    steps.reverse();
+    steps
+}
+
+/// Pairs up a run of removed and added instructions, keeping both in order
+fn pair_run(removed: &mut Vec<usize>,
+            added: &mut Vec<usize>,
+            old_pair: &[String],
+            new_pair: &[String],
+            steps: &mut Vec<Step>) {
+    let mut next = 0;
+    for j in added.drain(..) {
+        match removed[next..].iter().position(|&i| old_pair[i] == new_pair[j]) {
+            Some(skipped) => {
+                steps.extend(removed[next..next + skipped].iter().cloned().map(Step::Remove));
+                steps.push(Step::Keep(removed[next + skipped], j));
+                next += skipped + 1;
+            }
+            None => steps.push(Step::Add(j)),
+        }
+    }
+    steps.extend(removed.drain(..).skip(next).map(Step::Remove));
+}
+
+/// Converts the steps of an alignment into changes
+fn changes(steps: Vec<Step>, old: &[Core], new: &[Core]) -> Vec<Change> {
+    steps.into_iter()
+        .filter_map(|step| match step {
+            Step::Keep(i, j) if old[i] == new[j] => None,
+            Step::Keep(i, j) => Some(Change::Changed(old[i].clone(), new[j].clone())),
+            Step::Remove(i) => Some(Change::Removed(old[i].clone())),
+            Step::Add(j) => Some(Change::Added(new[j].clone())),
+        })
+        .collect()
+}
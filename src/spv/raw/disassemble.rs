@@ -0,0 +1,150 @@
+//! Disassembly of modules into text
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+use byteorder::{ByteOrder, LittleEndian};
+use spv::{Disassemble, Style};
+use super::{FriendlyNames, RawModule};
+
+/// Selects the format of disassembly
+///
+/// The default format is the format of `spirv-dis` with numeric ids, which
+/// is also the format of the `Display` of a `RawModule`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisassemblyOptions {
+    /// Start with comments for the version, generator and bound
+    pub header: bool,
+    /// Right align result ids so every opcode starts in the same column
+    pub align_ids: bool,
+    /// Print ids with the names from `FriendlyNames`
+    pub friendly_names: bool,
+    /// Follow each instruction with a comment of its offset in bytes in the
+    /// binary
+    pub byte_offsets: bool,
+    /// Follow each instruction with a comment of its words in the binary
+    pub raw_words: bool,
+    /// Colour opcodes, ids and literals with ANSI escape codes
+    pub color: bool,
+}
+
+impl Default for DisassemblyOptions {
+    fn default() -> DisassemblyOptions {
+        DisassemblyOptions {
+            header: true,
+            align_ids: true,
+            friendly_names: false,
+            byte_offsets: false,
+            raw_words: false,
+            color: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DisassemblyError {
+    /// Byte offsets or raw words were selected without the binary of the
+    /// module
+    MissingBinary,
+    /// The binary does not contain the instructions of the module
+    BinaryMismatch,
+    Io(io::Error),
+}
+
+pub type DisassemblyResult<T> = Result<T, DisassemblyError>;
+
+/// Writes the disassembly of a module
+///
+/// Byte offsets and raw words are read from the binary the module was read
+/// from, which is only required when they are selected.
+pub fn disassemble(module: &RawModule,
+                   binary: Option<&[u8]>,
+                   options: &DisassemblyOptions,
+                   out: &mut io::Write)
+                   -> DisassemblyResult<()> {
+    let words = if options.byte_offsets || options.raw_words {
+        let words = match binary {
+            Some(binary) => split_binary(binary),
+            None => return Err(DisassemblyError::MissingBinary),
+        };
+        match words {
+            Some(ref words) if words.len() == module.instructions.len() => {}
+            _ => return Err(DisassemblyError::BinaryMismatch),
+        }
+        words
+    } else {
+        None
+    };
+    let disassembly = Disassembly {
+        module,
+        words,
+        options: *options,
+    };
+    write!(out, "{}", disassembly).map_err(DisassemblyError::Io)
+}
+
+/// Splits the binary of a module into the byte offset and words of each
+/// instruction
+fn split_binary(binary: &[u8]) -> Option<Vec<(usize, Vec<u32>)>> {
+    if binary.len() < 20 || binary.len() & 3 != 0 {
+        return None;
+    }
+    let mut words = binary.chunks(4).map(LittleEndian::read_u32).collect::<Vec<_>>();
+    if words[0] == 0x03022307 {
+        for word in &mut words {
+            *word = word.swap_bytes();
+        }
+    }
+    let mut insts = Vec::new();
+    let mut offset = 5;
+    while offset < words.len() {
+        let count = (words[offset] >> 16) as usize;
+        if count == 0 || offset + count > words.len() {
+            return None;
+        }
+        insts.push((offset * 4, words[offset..offset + count].to_vec()));
+        offset += count;
+    }
+    Some(insts)
+}
+
+struct Disassembly<'a> {
+    module: &'a RawModule,
+    words: Option<Vec<(usize, Vec<u32>)>>,
+    options: DisassemblyOptions,
+}
+
+impl<'a> Display for Disassembly<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.options.header {
+            try!(self.module.write_header(f));
+        }
+        let names = if self.options.friendly_names {
+            Some(FriendlyNames::new(self.module))
+        } else {
+            None
+        };
+        let style = Style {
+            align_ids: self.options.align_ids,
+            names: names.as_ref(),
+            color: self.options.color,
+        };
+        for (index, inst) in self.module.instructions.iter().enumerate() {
+            try!(inst.disassemble(&style, f));
+            if let Some(ref words) = self.words {
+                let (offset, ref words) = words[index];
+                if self.options.byte_offsets {
+                    try!(write!(f, " ; 0x{:08x}", offset));
+                }
+                if self.options.raw_words {
+                    try!(write!(f, " ;"));
+                    for word in words {
+                        try!(write!(f, " {:08x}", word));
+                    }
+                }
+            }
+            try!(write!(f, "\n"));
+        }
+        Ok(())
+    }
+}
@@ -3,16 +3,30 @@ use std;
 use std::error::Error;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use spv::{Disassemble, Style};
 use spv::op::*;
 use spv::types::*;
 
 mod reader;
 pub use self::reader::read_module;
 
+mod remap;
+pub use self::remap::{compact_ids, IdOrder};
+
+mod diff;
+pub use self::diff::{diff_modules, Change, ModuleDiff, SectionDiff};
+
+mod names;
+pub use self::names::{Friendly, FriendlyNames};
+
+mod disassemble;
+pub use self::disassemble::{disassemble, DisassemblyOptions, DisassemblyError, DisassemblyResult};
+
 /// Raw list of SpirV instructions
 ///
 /// Instructions do not nessessarily pass validation rules.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RawModule {
     /// Version number of the module
     pub version: Version,
@@ -24,8 +38,9 @@ pub struct RawModule {
     pub instructions: Vec<Core>,
 }
 
-impl Display for RawModule {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+impl RawModule {
+    /// Writes the comments at the start of disassembly
+    fn write_header(&self, f: &mut Formatter) -> fmt::Result {
         try!(write!(f, "; SPIR-V\n"));
         try!(write!(f, "; Version: {}.{}\n", self.version.0, self.version.1));
         let generator_vendor = self.generator.tool.get_vendor();
@@ -40,7 +55,13 @@ impl Display for RawModule {
         try!(write!(f, "; Bound: {}\n", self.bound));
         // Trivially 0 as we only support loading a RawModule with 0 in slot
         // reserved for schema
-        try!(write!(f, "; Schema: 0\n"));
+        write!(f, "; Schema: 0\n")
+    }
+}
+
+impl Display for RawModule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        try!(self.write_header(f));
         for inst in &self.instructions {
             try!(write!(f, "{}\n", inst));
         }
@@ -50,6 +71,7 @@ impl Display for RawModule {
 
 /// Enumeration of all core instructions (incomplete)
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Core {
     OpNop(OpNop),
     OpUndef(OpUndef),
@@ -59,6 +81,7 @@ pub enum Core {
     OpName(OpName),
     OpMemberName(OpMemberName),
     OpString(OpString),
+    OpLine(OpLine),
     OpExtension(OpExtension),
     OpExtInstImport(OpExtInstImport),
     OpExtInst(OpExtInst),
@@ -86,11 +109,19 @@ pub enum Core {
     OpTypeQueue(OpTypeQueue),
     OpTypePipe(OpTypePipe),
     OpTypeForwardPointer(OpTypeForwardPointer),
+    OpConstantTrue(OpConstantTrue),
+    OpConstantFalse(OpConstantFalse),
     OpConstant(OpConstant),
     OpConstantComposite(OpConstantComposite),
+    OpSpecConstantTrue(OpSpecConstantTrue),
+    OpSpecConstantFalse(OpSpecConstantFalse),
+    OpSpecConstant(OpSpecConstant),
+    OpSpecConstantComposite(OpSpecConstantComposite),
+    OpSpecConstantOp(OpSpecConstantOp),
     OpFunction(OpFunction),
     OpFunctionParameter(OpFunctionParameter),
     OpFunctionEnd(OpFunctionEnd),
+    OpFunctionCall(OpFunctionCall),
     OpVariable(OpVariable),
     OpLoad(OpLoad),
     OpStore(OpStore),
@@ -149,113 +180,161 @@ pub enum Core {
     OpBranch(OpBranch),
     OpBranchConditional(OpBranchConditional),
     OpReturn(OpReturn),
+    OpReturnValue(OpReturnValue),
+    OpNoLine(OpNoLine),
 }
 
-impl Display for Core {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+/// Writes OpMemberDecorate followed by a comment with the name of the
+/// member, when ids are named
+impl Disassemble for Core {
+    fn disassemble(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
         match *self {
-            Core::OpNop(ref op) => Display::fmt(op, f),
-            Core::OpUndef(ref op) => Display::fmt(op, f),
-            Core::OpSourceContinued(ref op) => Display::fmt(op, f),
-            Core::OpSource(ref op) => Display::fmt(op, f),
-            Core::OpSourceExtension(ref op) => Display::fmt(op, f),
-            Core::OpName(ref op) => Display::fmt(op, f),
-            Core::OpMemberName(ref op) => Display::fmt(op, f),
-            Core::OpString(ref op) => Display::fmt(op, f),
-            Core::OpExtension(ref op) => Display::fmt(op, f),
-            Core::OpExtInstImport(ref op) => Display::fmt(op, f),
-            Core::OpExtInst(ref op) => Display::fmt(op, f),
-            Core::OpMemoryModel(ref op) => Display::fmt(op, f),
-            Core::OpEntryPoint(ref op) => Display::fmt(op, f),
-            Core::OpExecutionMode(ref op) => Display::fmt(op, f),
-            Core::OpCapability(ref op) => Display::fmt(op, f),
-            Core::OpTypeVoid(ref op) => Display::fmt(op, f),
-            Core::OpTypeBool(ref op) => Display::fmt(op, f),
-            Core::OpTypeInt(ref op) => Display::fmt(op, f),
-            Core::OpTypeFloat(ref op) => Display::fmt(op, f),
-            Core::OpTypeVector(ref op) => Display::fmt(op, f),
-            Core::OpTypeMatrix(ref op) => Display::fmt(op, f),
-            Core::OpTypeImage(ref op) => Display::fmt(op, f),
-            Core::OpTypeSampler(ref op) => Display::fmt(op, f),
-            Core::OpTypeSampledImage(ref op) => Display::fmt(op, f),
-            Core::OpTypeArray(ref op) => Display::fmt(op, f),
-            Core::OpTypeRuntimeArray(ref op) => Display::fmt(op, f),
-            Core::OpTypeStruct(ref op) => Display::fmt(op, f),
-            Core::OpTypeOpaque(ref op) => Display::fmt(op, f),
-            Core::OpTypePointer(ref op) => Display::fmt(op, f),
-            Core::OpTypeFunction(ref op) => Display::fmt(op, f),
-            Core::OpTypeEvent(ref op) => Display::fmt(op, f),
-            Core::OpTypeDeviceEvent(ref op) => Display::fmt(op, f),
-            Core::OpTypeQueue(ref op) => Display::fmt(op, f),
-            Core::OpTypePipe(ref op) => Display::fmt(op, f),
-            Core::OpTypeForwardPointer(ref op) => Display::fmt(op, f),
-            Core::OpConstant(ref op) => Display::fmt(op, f),
-            Core::OpConstantComposite(ref op) => Display::fmt(op, f),
-            Core::OpFunction(ref op) => Display::fmt(op, f),
-            Core::OpFunctionParameter(ref op) => Display::fmt(op, f),
-            Core::OpFunctionEnd(ref op) => Display::fmt(op, f),
-            Core::OpVariable(ref op) => Display::fmt(op, f),
-            Core::OpLoad(ref op) => Display::fmt(op, f),
-            Core::OpStore(ref op) => Display::fmt(op, f),
-            Core::OpAccessChain(ref op) => Display::fmt(op, f),
-            Core::OpDecorate(ref op) => Display::fmt(op, f),
-            Core::OpMemberDecorate(ref op) => Display::fmt(op, f),
-            Core::OpConvertUToF(ref op) => Display::fmt(op, f),
-            Core::OpBitcast(ref op) => Display::fmt(op, f),
-            Core::OpIAdd(ref op) => Display::fmt(op, f),
-            Core::OpFAdd(ref op) => Display::fmt(op, f),
-            Core::OpISub(ref op) => Display::fmt(op, f),
-            Core::OpFSub(ref op) => Display::fmt(op, f),
-            Core::OpIMul(ref op) => Display::fmt(op, f),
-            Core::OpFMul(ref op) => Display::fmt(op, f),
-            Core::OpUDiv(ref op) => Display::fmt(op, f),
-            Core::OpSDiv(ref op) => Display::fmt(op, f),
-            Core::OpFDiv(ref op) => Display::fmt(op, f),
-            Core::OpUMod(ref op) => Display::fmt(op, f),
-            Core::OpSRem(ref op) => Display::fmt(op, f),
-            Core::OpSMod(ref op) => Display::fmt(op, f),
-            Core::OpFRem(ref op) => Display::fmt(op, f),
-            Core::OpFMod(ref op) => Display::fmt(op, f),
-            Core::OpIAddCarry(ref op) => Display::fmt(op, f),
-            Core::OpISubBorrow(ref op) => Display::fmt(op, f),
-            Core::OpUMulExtended(ref op) => Display::fmt(op, f),
-            Core::OpSMulExtended(ref op) => Display::fmt(op, f),
-            Core::OpBitwiseOr(ref op) => Display::fmt(op, f),
-            Core::OpBitwiseXor(ref op) => Display::fmt(op, f),
-            Core::OpBitwiseAnd(ref op) => Display::fmt(op, f),
-            Core::OpIEqual(ref op) => Display::fmt(op, f),
-            Core::OpINotEqual(ref op) => Display::fmt(op, f),
-            Core::OpUGreaterThan(ref op) => Display::fmt(op, f),
-            Core::OpSGreaterThan(ref op) => Display::fmt(op, f),
-            Core::OpUGreaterThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpSGreaterThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpULessThan(ref op) => Display::fmt(op, f),
-            Core::OpSLessThan(ref op) => Display::fmt(op, f),
-            Core::OpULessThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpSLessThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpFOrdEqual(ref op) => Display::fmt(op, f),
-            Core::OpFUnordEqual(ref op) => Display::fmt(op, f),
-            Core::OpFOrdNotEqual(ref op) => Display::fmt(op, f),
-            Core::OpFUnordNotEqual(ref op) => Display::fmt(op, f),
-            Core::OpFOrdLessThan(ref op) => Display::fmt(op, f),
-            Core::OpFUnordLessThan(ref op) => Display::fmt(op, f),
-            Core::OpFOrdGreaterThan(ref op) => Display::fmt(op, f),
-            Core::OpFUnordGreaterThan(ref op) => Display::fmt(op, f),
-            Core::OpFOrdLessThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpFUnordLessThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpFOrdGreaterThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpFUnordGreaterThanEqual(ref op) => Display::fmt(op, f),
-            Core::OpPhi(ref op) => Display::fmt(op, f),
-            Core::OpLoopMerge(ref op) => Display::fmt(op, f),
-            Core::OpSelectionMerge(ref op) => Display::fmt(op, f),
-            Core::OpLabel(ref op) => Display::fmt(op, f),
-            Core::OpBranch(ref op) => Display::fmt(op, f),
-            Core::OpBranchConditional(ref op) => Display::fmt(op, f),
-            Core::OpReturn(ref op) => Display::fmt(op, f),
+            Core::OpNop(ref op) => op.disassemble(style, f),
+            Core::OpUndef(ref op) => op.disassemble(style, f),
+            Core::OpSourceContinued(ref op) => op.disassemble(style, f),
+            Core::OpSource(ref op) => op.disassemble(style, f),
+            Core::OpSourceExtension(ref op) => op.disassemble(style, f),
+            Core::OpName(ref op) => op.disassemble(style, f),
+            Core::OpMemberName(ref op) => op.disassemble(style, f),
+            Core::OpString(ref op) => op.disassemble(style, f),
+            Core::OpLine(ref op) => op.disassemble(style, f),
+            Core::OpExtension(ref op) => op.disassemble(style, f),
+            Core::OpExtInstImport(ref op) => op.disassemble(style, f),
+            Core::OpExtInst(ref op) => op.disassemble(style, f),
+            Core::OpMemoryModel(ref op) => op.disassemble(style, f),
+            Core::OpEntryPoint(ref op) => op.disassemble(style, f),
+            Core::OpExecutionMode(ref op) => op.disassemble(style, f),
+            Core::OpCapability(ref op) => op.disassemble(style, f),
+            Core::OpTypeVoid(ref op) => op.disassemble(style, f),
+            Core::OpTypeBool(ref op) => op.disassemble(style, f),
+            Core::OpTypeInt(ref op) => op.disassemble(style, f),
+            Core::OpTypeFloat(ref op) => op.disassemble(style, f),
+            Core::OpTypeVector(ref op) => op.disassemble(style, f),
+            Core::OpTypeMatrix(ref op) => op.disassemble(style, f),
+            Core::OpTypeImage(ref op) => op.disassemble(style, f),
+            Core::OpTypeSampler(ref op) => op.disassemble(style, f),
+            Core::OpTypeSampledImage(ref op) => op.disassemble(style, f),
+            Core::OpTypeArray(ref op) => op.disassemble(style, f),
+            Core::OpTypeRuntimeArray(ref op) => op.disassemble(style, f),
+            Core::OpTypeStruct(ref op) => op.disassemble(style, f),
+            Core::OpTypeOpaque(ref op) => op.disassemble(style, f),
+            Core::OpTypePointer(ref op) => op.disassemble(style, f),
+            Core::OpTypeFunction(ref op) => op.disassemble(style, f),
+            Core::OpTypeEvent(ref op) => op.disassemble(style, f),
+            Core::OpTypeDeviceEvent(ref op) => op.disassemble(style, f),
+            Core::OpTypeQueue(ref op) => op.disassemble(style, f),
+            Core::OpTypePipe(ref op) => op.disassemble(style, f),
+            Core::OpTypeForwardPointer(ref op) => op.disassemble(style, f),
+            Core::OpConstantTrue(ref op) => op.disassemble(style, f),
+            Core::OpConstantFalse(ref op) => op.disassemble(style, f),
+            Core::OpConstant(ref op) => op.disassemble(style, f),
+            Core::OpConstantComposite(ref op) => op.disassemble(style, f),
+            Core::OpSpecConstantTrue(ref op) => op.disassemble(style, f),
+            Core::OpSpecConstantFalse(ref op) => op.disassemble(style, f),
+            Core::OpSpecConstant(ref op) => op.disassemble(style, f),
+            Core::OpSpecConstantComposite(ref op) => op.disassemble(style, f),
+            Core::OpSpecConstantOp(ref op) => op.disassemble(style, f),
+            Core::OpFunction(ref op) => op.disassemble(style, f),
+            Core::OpFunctionParameter(ref op) => op.disassemble(style, f),
+            Core::OpFunctionEnd(ref op) => op.disassemble(style, f),
+            Core::OpFunctionCall(ref op) => op.disassemble(style, f),
+            Core::OpVariable(ref op) => op.disassemble(style, f),
+            Core::OpLoad(ref op) => op.disassemble(style, f),
+            Core::OpStore(ref op) => op.disassemble(style, f),
+            Core::OpAccessChain(ref op) => op.disassemble(style, f),
+            Core::OpDecorate(ref op) => op.disassemble(style, f),
+            Core::OpMemberDecorate(ref op) => {
+                try!(op.disassemble(style, f));
+                match style.names.and_then(|names| names.member(op.structure_type.0, op.member.0)) {
+                    Some(name) => write!(f, " ; {}", name),
+                    None => Ok(()),
+                }
+            }
+            Core::OpConvertUToF(ref op) => op.disassemble(style, f),
+            Core::OpBitcast(ref op) => op.disassemble(style, f),
+            Core::OpIAdd(ref op) => op.disassemble(style, f),
+            Core::OpFAdd(ref op) => op.disassemble(style, f),
+            Core::OpISub(ref op) => op.disassemble(style, f),
+            Core::OpFSub(ref op) => op.disassemble(style, f),
+            Core::OpIMul(ref op) => op.disassemble(style, f),
+            Core::OpFMul(ref op) => op.disassemble(style, f),
+            Core::OpUDiv(ref op) => op.disassemble(style, f),
+            Core::OpSDiv(ref op) => op.disassemble(style, f),
+            Core::OpFDiv(ref op) => op.disassemble(style, f),
+            Core::OpUMod(ref op) => op.disassemble(style, f),
+            Core::OpSRem(ref op) => op.disassemble(style, f),
+            Core::OpSMod(ref op) => op.disassemble(style, f),
+            Core::OpFRem(ref op) => op.disassemble(style, f),
+            Core::OpFMod(ref op) => op.disassemble(style, f),
+            Core::OpIAddCarry(ref op) => op.disassemble(style, f),
+            Core::OpISubBorrow(ref op) => op.disassemble(style, f),
+            Core::OpUMulExtended(ref op) => op.disassemble(style, f),
+            Core::OpSMulExtended(ref op) => op.disassemble(style, f),
+            Core::OpBitwiseOr(ref op) => op.disassemble(style, f),
+            Core::OpBitwiseXor(ref op) => op.disassemble(style, f),
+            Core::OpBitwiseAnd(ref op) => op.disassemble(style, f),
+            Core::OpIEqual(ref op) => op.disassemble(style, f),
+            Core::OpINotEqual(ref op) => op.disassemble(style, f),
+            Core::OpUGreaterThan(ref op) => op.disassemble(style, f),
+            Core::OpSGreaterThan(ref op) => op.disassemble(style, f),
+            Core::OpUGreaterThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpSGreaterThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpULessThan(ref op) => op.disassemble(style, f),
+            Core::OpSLessThan(ref op) => op.disassemble(style, f),
+            Core::OpULessThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpSLessThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpFOrdEqual(ref op) => op.disassemble(style, f),
+            Core::OpFUnordEqual(ref op) => op.disassemble(style, f),
+            Core::OpFOrdNotEqual(ref op) => op.disassemble(style, f),
+            Core::OpFUnordNotEqual(ref op) => op.disassemble(style, f),
+            Core::OpFOrdLessThan(ref op) => op.disassemble(style, f),
+            Core::OpFUnordLessThan(ref op) => op.disassemble(style, f),
+            Core::OpFOrdGreaterThan(ref op) => op.disassemble(style, f),
+            Core::OpFUnordGreaterThan(ref op) => op.disassemble(style, f),
+            Core::OpFOrdLessThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpFUnordLessThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpFOrdGreaterThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpFUnordGreaterThanEqual(ref op) => op.disassemble(style, f),
+            Core::OpPhi(ref op) => op.disassemble(style, f),
+            Core::OpLoopMerge(ref op) => op.disassemble(style, f),
+            Core::OpSelectionMerge(ref op) => op.disassemble(style, f),
+            Core::OpLabel(ref op) => op.disassemble(style, f),
+            Core::OpBranch(ref op) => op.disassemble(style, f),
+            Core::OpBranchConditional(ref op) => op.disassemble(style, f),
+            Core::OpReturn(ref op) => op.disassemble(style, f),
+            Core::OpReturnValue(ref op) => op.disassemble(style, f),
+            Core::OpNoLine(ref op) => op.disassemble(style, f),
         }
     }
 }
 
+impl Display for Core {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.disassemble(&Style::default(), f)
+    }
+}
+
+def_enum_operands!(Core;
+    OpNop, OpUndef, OpSourceContinued, OpSource, OpSourceExtension, OpName, OpMemberName, OpString,
+    OpLine, OpExtension, OpExtInstImport, OpExtInst, OpMemoryModel, OpEntryPoint, OpExecutionMode,
+    OpCapability, OpTypeVoid, OpTypeBool, OpTypeInt, OpTypeFloat, OpTypeVector, OpTypeMatrix,
+    OpTypeImage, OpTypeSampler, OpTypeSampledImage, OpTypeArray, OpTypeRuntimeArray, OpTypeStruct,
+    OpTypeOpaque, OpTypePointer, OpTypeFunction, OpTypeEvent, OpTypeDeviceEvent, OpTypeQueue,
+    OpTypePipe, OpTypeForwardPointer, OpConstantTrue, OpConstantFalse, OpConstant,
+    OpConstantComposite, OpSpecConstantTrue, OpSpecConstantFalse, OpSpecConstant,
+    OpSpecConstantComposite, OpSpecConstantOp, OpFunction, OpFunctionParameter, OpFunctionEnd,
+    OpFunctionCall, OpVariable, OpLoad, OpStore, OpAccessChain, OpDecorate, OpMemberDecorate,
+    OpConvertUToF, OpBitcast, OpIAdd, OpFAdd, OpISub, OpFSub, OpIMul, OpFMul, OpUDiv, OpSDiv,
+    OpFDiv, OpUMod, OpSRem, OpSMod, OpFRem, OpFMod, OpIAddCarry, OpISubBorrow, OpUMulExtended,
+    OpSMulExtended, OpBitwiseOr, OpBitwiseXor, OpBitwiseAnd, OpIEqual, OpINotEqual, OpUGreaterThan,
+    OpSGreaterThan, OpUGreaterThanEqual, OpSGreaterThanEqual, OpULessThan, OpSLessThan,
+    OpULessThanEqual, OpSLessThanEqual, OpFOrdEqual, OpFUnordEqual, OpFOrdNotEqual,
+    OpFUnordNotEqual, OpFOrdLessThan, OpFUnordLessThan, OpFOrdGreaterThan, OpFUnordGreaterThan,
+    OpFOrdLessThanEqual, OpFUnordLessThanEqual, OpFOrdGreaterThanEqual, OpFUnordGreaterThanEqual,
+    OpPhi, OpLoopMerge, OpSelectionMerge, OpLabel, OpBranch, OpBranchConditional, OpReturn,
+    OpReturnValue, OpNoLine
+);
+
 #[derive(Debug, PartialEq)]
 pub enum ReadError {
     UnexpectedEndOfStream,
@@ -299,6 +378,7 @@ pub enum ReadError {
     UnknownAccessQualifier(u32),
     UnknownLoopControl(u32),
     UnknownSelectionControl(u32),
+    UnknownSpecConstantOpcode(u32),
 }
 
 impl Display for ReadError {
@@ -350,6 +430,7 @@ impl Error for ReadError {
             UnknownAccessQualifier(_) => "unknown access qualifier",
             UnknownLoopControl(_) => "unknown loop control",
             UnknownSelectionControl(_) => "unknown selection control",
+            UnknownSpecConstantOpcode(_) => "unknown spec constant operation",
         }
     }
 }
@@ -492,6 +573,20 @@ impl MemoryBlockRead for MemberIndex {
     }
 }
 
+impl MemoryBlockRead for Line {
+    fn read(block: MemoryBlock) -> MemoryBlockResult<Line> {
+        let (block, word) = try!(block.read_word());
+        Ok((block, Line(word)))
+    }
+}
+
+impl MemoryBlockRead for Column {
+    fn read(block: MemoryBlock) -> MemoryBlockResult<Column> {
+        let (block, word) = try!(block.read_word());
+        Ok((block, Column(word)))
+    }
+}
+
 impl MemoryBlockRead for AddressingModel {
     fn read(block: MemoryBlock) -> MemoryBlockResult<AddressingModel> {
         let (block, word) = try!(block.read_word());
@@ -1125,6 +1220,75 @@ impl MemoryBlockRead for BranchWeights {
     }
 }
 
+impl MemoryBlockRead for SpecConstantOpcode {
+    fn read(block: MemoryBlock) -> MemoryBlockResult<SpecConstantOpcode> {
+        let (block, word) = try!(block.read_word());
+        let opcode = match word {
+            65 => SpecConstantOpcode::AccessChain,
+            66 => SpecConstantOpcode::InBoundsAccessChain,
+            67 => SpecConstantOpcode::PtrAccessChain,
+            70 => SpecConstantOpcode::InBoundsPtrAccessChain,
+            79 => SpecConstantOpcode::VectorShuffle,
+            81 => SpecConstantOpcode::CompositeExtract,
+            82 => SpecConstantOpcode::CompositeInsert,
+            109 => SpecConstantOpcode::ConvertFToU,
+            110 => SpecConstantOpcode::ConvertFToS,
+            111 => SpecConstantOpcode::ConvertSToF,
+            112 => SpecConstantOpcode::ConvertUToF,
+            113 => SpecConstantOpcode::UConvert,
+            114 => SpecConstantOpcode::SConvert,
+            115 => SpecConstantOpcode::FConvert,
+            116 => SpecConstantOpcode::QuantizeToF16,
+            117 => SpecConstantOpcode::ConvertPtrToU,
+            120 => SpecConstantOpcode::ConvertUToPtr,
+            121 => SpecConstantOpcode::PtrCastToGeneric,
+            122 => SpecConstantOpcode::GenericCastToPtr,
+            124 => SpecConstantOpcode::Bitcast,
+            126 => SpecConstantOpcode::SNegate,
+            127 => SpecConstantOpcode::FNegate,
+            128 => SpecConstantOpcode::IAdd,
+            129 => SpecConstantOpcode::FAdd,
+            130 => SpecConstantOpcode::ISub,
+            131 => SpecConstantOpcode::FSub,
+            132 => SpecConstantOpcode::IMul,
+            133 => SpecConstantOpcode::FMul,
+            134 => SpecConstantOpcode::UDiv,
+            135 => SpecConstantOpcode::SDiv,
+            136 => SpecConstantOpcode::FDiv,
+            137 => SpecConstantOpcode::UMod,
+            138 => SpecConstantOpcode::SRem,
+            139 => SpecConstantOpcode::SMod,
+            140 => SpecConstantOpcode::FRem,
+            141 => SpecConstantOpcode::FMod,
+            164 => SpecConstantOpcode::LogicalEqual,
+            165 => SpecConstantOpcode::LogicalNotEqual,
+            166 => SpecConstantOpcode::LogicalOr,
+            167 => SpecConstantOpcode::LogicalAnd,
+            168 => SpecConstantOpcode::LogicalNot,
+            169 => SpecConstantOpcode::Select,
+            170 => SpecConstantOpcode::IEqual,
+            171 => SpecConstantOpcode::INotEqual,
+            172 => SpecConstantOpcode::UGreaterThan,
+            173 => SpecConstantOpcode::SGreaterThan,
+            174 => SpecConstantOpcode::UGreaterThanEqual,
+            175 => SpecConstantOpcode::SGreaterThanEqual,
+            176 => SpecConstantOpcode::ULessThan,
+            177 => SpecConstantOpcode::SLessThan,
+            178 => SpecConstantOpcode::ULessThanEqual,
+            179 => SpecConstantOpcode::SLessThanEqual,
+            194 => SpecConstantOpcode::ShiftRightLogical,
+            195 => SpecConstantOpcode::ShiftRightArithmetic,
+            196 => SpecConstantOpcode::ShiftLeftLogical,
+            197 => SpecConstantOpcode::BitwiseOr,
+            198 => SpecConstantOpcode::BitwiseXor,
+            199 => SpecConstantOpcode::BitwiseAnd,
+            200 => SpecConstantOpcode::Not,
+            n => return Err(ReadError::UnknownSpecConstantOpcode(n)),
+        };
+        Ok((block, opcode))
+    }
+}
+
 impl MemoryBlockRead for PhiArg {
     fn read(block: MemoryBlock) -> MemoryBlockResult<PhiArg> {
         let (block, variable) = try!(MemoryBlockRead::read(block));
@@ -0,0 +1,203 @@
+//! Friendly names for ids in disassembly
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use spv::Style;
+use spv::dis::Styled;
+use spv::operands::Operands;
+use spv::types::*;
+use super::{Core, RawModule};
+
+/// Names for the ids of a module, for disassembly like `%main` and `%uint`
+/// instead of `%4` and `%9`
+///
+/// Ids are named by their OpName, and types and constants without a debug
+/// name are named by their structure. Names are made of letters, digits and
+/// underscores, and never start with a digit so they can not be confused
+/// with numeric ids. When two ids would get the same name, the later one
+/// gets a suffix like `_0`. Ids without a name keep their number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriendlyNames {
+    ids: HashMap<u32, String>,
+    /// The OpMemberName of each struct member
+    members: HashMap<(u32, u32), String>,
+}
+
+impl FriendlyNames {
+    pub fn new(module: &RawModule) -> FriendlyNames {
+        let mut names = FriendlyNames {
+            ids: HashMap::new(),
+            members: HashMap::new(),
+        };
+        let mut taken = HashSet::new();
+        for inst in &module.instructions {
+            match *inst {
+                Core::OpName(ref op) if !op.name.is_empty() => {
+                    names.insert(op.target.0, &op.name, &mut taken);
+                }
+                Core::OpMemberName(ref op) => {
+                    names.members.insert((op.struct_type.0, op.member.0), op.name.clone());
+                }
+                _ => {}
+            }
+        }
+        for inst in &module.instructions {
+            let id = match inst.result_id() {
+                Some(id) if !names.ids.contains_key(&id.0) => id.0,
+                _ => continue,
+            };
+            if let Some(name) = names.structural_name(inst, &module.instructions) {
+                names.insert(id, &name, &mut taken);
+            }
+        }
+        names
+    }
+
+    /// Returns the name of an id, without the `%`
+    pub fn id(&self, id: u32) -> Option<&str> {
+        self.ids.get(&id).map(|name| name.as_str())
+    }
+
+    /// Returns the OpMemberName of a member of a struct type
+    pub fn member(&self, structure: u32, member: u32) -> Option<&str> {
+        self.members.get(&(structure, member)).map(|name| name.as_str())
+    }
+
+    /// Returns an id as it is printed, with its name or number
+    fn display_id(&self, id: u32) -> String {
+        match self.ids.get(&id) {
+            Some(name) => name.clone(),
+            None => format!("{}", id),
+        }
+    }
+
+    fn insert(&mut self, id: u32, name: &str, taken: &mut HashSet<String>) {
+        let mut name = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert(0, '_');
+        }
+        let mut unique = name.clone();
+        let mut suffix = 0;
+        while taken.contains(&unique) {
+            unique = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        taken.insert(unique.clone());
+        self.ids.insert(id, unique);
+    }
+
+    /// Names a type or constant after its structure, like `v3uint` or
+    /// `uint_1`
+    fn structural_name(&self, inst: &Core, insts: &[Core]) -> Option<String> {
+        let name = match *inst {
+            Core::OpTypeVoid(_) => "void".into(),
+            Core::OpTypeBool(_) => "bool".into(),
+            Core::OpTypeInt(ref op) => {
+                let name = match op.width {
+                    8 => "char",
+                    16 => "short",
+                    32 => "int",
+                    64 => "long",
+                    width => return Some(format!("int{}", width)),
+                };
+                match op.signedness {
+                    Signedness::Signed => name.into(),
+                    Signedness::UnsignedOrNone => format!("u{}", name),
+                }
+            }
+            Core::OpTypeFloat(ref op) => {
+                match op.width {
+                    16 => "half".into(),
+                    32 => "float".into(),
+                    64 => "double".into(),
+                    width => format!("fp{}", width),
+                }
+            }
+            Core::OpTypeVector(ref op) => {
+                format!("v{}{}", op.component_count, self.display_id(op.component_type.0))
+            }
+            Core::OpTypeMatrix(ref op) => {
+                format!("mat{}{}", op.column_count, self.display_id(op.column_type.0))
+            }
+            Core::OpTypeArray(ref op) => {
+                format!("_arr_{}_{}",
+                        self.display_id(op.element_type.0),
+                        self.display_id(op.length.0))
+            }
+            Core::OpTypeRuntimeArray(ref op) => {
+                format!("_runtimearr_{}", self.display_id(op.element_type.0))
+            }
+            Core::OpTypeStruct(ref op) => format!("_struct_{}", op.result_id.0),
+            Core::OpTypePointer(ref op) => {
+                format!("_ptr_{}_{}", op.storage_class, self.display_id(op.pointed_type.0))
+            }
+            Core::OpTypeFunction(ref op) => {
+                let mut name = format!("fn_{}", self.display_id(op.return_type.0));
+                for parameter in &op.parameter_types {
+                    name.push('_');
+                    name.push_str(&self.display_id(parameter.0));
+                }
+                name
+            }
+            Core::OpConstantTrue(_) => "true".into(),
+            Core::OpConstantFalse(_) => "false".into(),
+            Core::OpConstant(ref op) => {
+                return constant_value(insts, &op.result_type, &op.value)
+                    .map(|value| format!("{}_{}", self.display_id(op.result_type.0), value));
+            }
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    /// Returns the disassembly of an instruction with friendly ids
+    ///
+    /// Result ids are right aligned like numeric ids, and OpMemberDecorate
+    /// is followed by a comment with the name of the member.
+    pub fn instruction(&self, inst: &Core) -> String {
+        let style = Style { names: Some(self), ..Style::default() };
+        format!("{}", Styled(inst, &style))
+    }
+}
+
+/// Formats the value of an integer or float constant for a name, with `n`
+/// for a minus sign and `_` for a decimal point
+fn constant_value(insts: &[Core], result_type: &OpId, value: &[u32]) -> Option<String> {
+    let bits = match *value {
+        [low] => low as u64,
+        [low, high] => low as u64 | (high as u64) << 32,
+        _ => return None,
+    };
+    let ty = insts.iter().find(|inst| inst.result_id().map(|id| id.0) == Some(result_type.0));
+    let value = match ty {
+        Some(Core::OpTypeInt(op)) if op.signedness == Signedness::Signed &&
+                                       (1..=64).contains(&op.width) => {
+            let shift = 64 - op.width;
+            format!("{}", ((bits << shift) as i64) >> shift)
+        }
+        Some(Core::OpTypeInt(_)) => format!("{}", bits),
+        Some(Core::OpTypeFloat(op)) if op.width == 32 => {
+            format!("{}", f32::from_bits(bits as u32))
+        }
+        Some(Core::OpTypeFloat(op)) if op.width == 64 => format!("{}", f64::from_bits(bits)),
+        _ => return None,
+    };
+    Some(value.replace('-', "n").replace('.', "_"))
+}
+
+/// Disassembles a module with friendly ids
+pub struct Friendly<'a>(pub &'a RawModule);
+
+impl<'a> Display for Friendly<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let names = FriendlyNames::new(self.0);
+        try!(self.0.write_header(f));
+        for inst in &self.0.instructions {
+            try!(write!(f, "{}\n", names.instruction(inst)));
+        }
+        Ok(())
+    }
+}
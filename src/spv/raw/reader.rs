@@ -144,7 +144,7 @@ fn read_instruction(stream: &mut Stream,
         5 => OpName::read_core,
         6 => OpMemberName::read_core,
         7 => OpString::read_core,
-        8 => return Err(ReadError::UnimplementedOp("OpLine")),
+        8 => OpLine::read_core,
         10 => OpExtension::read_core,
         11 => OpExtInstImport::read_core,
         12 => OpExtInst::read_core,
@@ -167,21 +167,21 @@ fn read_instruction(stream: &mut Stream,
         31 => OpTypeOpaque::read_core,
         32 => OpTypePointer::read_core,
         33 => OpTypeFunction::read_core,
-        41 => return Err(ReadError::UnimplementedOp("OpConstantTrue")),
-        42 => return Err(ReadError::UnimplementedOp("OpConstantFalse")),
+        41 => OpConstantTrue::read_core,
+        42 => OpConstantFalse::read_core,
         43 => OpConstant::read_core,
         44 => OpConstantComposite::read_core,
         45 => return Err(ReadError::UnimplementedOp("OpConstantSampler")),
         46 => return Err(ReadError::UnimplementedOp("OpConstantNull")),
-        48 => return Err(ReadError::UnimplementedOp("OpSpecConstantTrue")),
-        49 => return Err(ReadError::UnimplementedOp("OpSpecConstantFalse")),
-        50 => return Err(ReadError::UnimplementedOp("OpSpecConstant")),
-        51 => return Err(ReadError::UnimplementedOp("OpSpecConstantComposite")),
-        52 => return Err(ReadError::UnimplementedOp("OpSpecConstantOp")),
+        48 => OpSpecConstantTrue::read_core,
+        49 => OpSpecConstantFalse::read_core,
+        50 => OpSpecConstant::read_core,
+        51 => OpSpecConstantComposite::read_core,
+        52 => OpSpecConstantOp::read_core,
         54 => OpFunction::read_core,
         55 => OpFunctionParameter::read_core,
         56 => OpFunctionEnd::read_core,
-        57 => return Err(ReadError::UnimplementedOp("OpFunctionCall")),
+        57 => OpFunctionCall::read_core,
         59 => OpVariable::read_core,
         60 => return Err(ReadError::UnimplementedOp("OpImageTexelPointer")),
         61 => OpLoad::read_core,
@@ -359,7 +359,7 @@ fn read_instruction(stream: &mut Stream,
         251 => return Err(ReadError::UnimplementedOp("OpSwitch")),
         252 => return Err(ReadError::UnimplementedOp("OpKill")),
         253 => OpReturn::read_core,
-        254 => return Err(ReadError::UnimplementedOp("OpReturnValue")),
+        254 => OpReturnValue::read_core,
         255 => return Err(ReadError::UnimplementedOp("OpUnreachable")),
         256 => return Err(ReadError::UnimplementedOp("OpLifetimeStart")),
         257 => return Err(ReadError::UnimplementedOp("OpLifetimeStop")),
@@ -417,7 +417,7 @@ fn read_instruction(stream: &mut Stream,
         314 => return Err(ReadError::UnimplementedOp("OpImageSparseGather")),
         315 => return Err(ReadError::UnimplementedOp("OpImageSparseDrefGather")),
         316 => return Err(ReadError::UnimplementedOp("OpImageSparseTexelsResident")),
-        317 => return Err(ReadError::UnimplementedOp("OpNoLine")),
+        317 => OpNoLine::read_core,
         318 => return Err(ReadError::UnimplementedOp("OpAtomicFlagTestAndSet")),
         319 => return Err(ReadError::UnimplementedOp("OpAtomicFlagClear")),
         320 => return Err(ReadError::UnimplementedOp("OpImageSparseRead")),
@@ -491,6 +491,8 @@ def_op_read!(OpSourceExtension; extension);
 def_op_read!(OpName; target | name);
 def_op_read!(OpMemberName; struct_type | member | name);
 def_op_read!(OpString; result_id | string);
+def_op_read!(OpLine; file | line | column);
+def_op_read!(OpNoLine;);
 
 // Annotation Instructions
 def_op_read!(OpDecorate; target | decoration);
@@ -581,13 +583,47 @@ def_op_read!(OpTypePointer; result_id | storage_class | pointed_type);
 def_op_read!(OpTypeFunction; result_id | return_type | parameter_types);
 
 // Constant-Creation Instructions
+def_op_read!(OpConstantTrue; result_type | result_id);
+def_op_read!(OpConstantFalse; result_type | result_id);
 def_op_read!(OpConstant; result_type | result_id | value);
 def_op_read!(OpConstantComposite; result_type | result_id | constituents);
+def_op_read!(OpSpecConstantTrue; result_type | result_id);
+def_op_read!(OpSpecConstantFalse; result_type | result_id);
+def_op_read!(OpSpecConstant; result_type | result_id | value);
+def_op_read!(OpSpecConstantComposite; result_type | result_id | constituents);
+
+impl CoreRead for OpSpecConstantOp {
+    fn read_core<'a>(block: MemoryBlock<'a>,
+                     _: &[Box<ExtInstSet>],
+                     _: &mut HashMap<OpId, Box<ExtInstSet>>)
+                     -> MemoryBlockResult<'a, Core> {
+        let (block, result_type) = try!(OpId::read(block));
+        let (block, result_id) = try!(ResultId::read(block));
+        let (mut block, opcode) = try!(SpecConstantOpcode::read(block));
+        let count = opcode.id_operand_count().unwrap_or(block.remaining());
+        let mut operands = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (next, operand) = try!(block.read_op_id());
+            operands.push(operand);
+            block = next;
+        }
+        let (block, indexes) = try!(<Vec<u32>>::read(block));
+        let op = Core::OpSpecConstantOp(OpSpecConstantOp {
+            result_type,
+            result_id,
+            opcode,
+            operands,
+            indexes,
+        });
+        Ok((block, op))
+    }
+}
 
 // Function Instructions
 def_op_read!(OpFunction; result_type | result_id | function_control | function_type);
 def_op_read!(OpFunctionParameter; result_type | result_id);
 def_op_read!(OpFunctionEnd;);
+def_op_read!(OpFunctionCall; result_type | result_id | function | arguments);
 
 // Memory Instructions
 def_op_read!(OpVariable; result_type | result_id | storage_class | initializer);
@@ -661,3 +697,4 @@ def_op_read!(OpBranch; target_label);
 def_op_read!(OpBranchConditional; condition | true_label | false_label | weights);
 
 def_op_read!(OpReturn;);
+def_op_read!(OpReturnValue; value);
@@ -0,0 +1,63 @@
+//! Renumbering of the ids in a module
+
+use std::collections::HashMap;
+use spv::operands::Operands;
+use super::RawModule;
+
+/// Order to assign new ids in when compacting a module
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdOrder {
+    /// Keep the relative order of the existing ids
+    Preserve,
+    /// Number ids in the order they are defined in the module
+    ///
+    /// Modules that only differ by the choice of ids are compacted into
+    /// identical modules.
+    FirstDefinition,
+}
+
+/// Renumbers every id in a module so they are dense and starting at 1
+///
+/// All references to an id are updated, including result types, decoration
+/// targets, debug names and entry points. The bound of the module is set to
+/// one more than the highest new id. Ids that are referenced but never
+/// defined are numbered after all defined ids.
+pub fn compact_ids(mut module: RawModule, order: IdOrder) -> RawModule {
+    let mut defined = Vec::new();
+    let mut referenced = Vec::new();
+    for inst in &module.instructions {
+        if let Some(result_id) = inst.result_id() {
+            defined.push(result_id.0);
+        }
+        if let Some(result_type) = inst.result_type() {
+            referenced.push(result_type.0);
+        }
+        referenced.extend(inst.operands().into_iter().map(|id| id.0));
+    }
+
+    if order == IdOrder::Preserve {
+        defined.sort();
+        referenced.sort();
+    }
+
+    let mut map = HashMap::new();
+    for id in defined.into_iter().chain(referenced) {
+        let next = map.len() as u32 + 1;
+        map.entry(id).or_insert(next);
+    }
+
+    for inst in &mut module.instructions {
+        if let Some(result_id) = inst.result_id_mut() {
+            result_id.0 = map[&result_id.0];
+        }
+        if let Some(result_type) = inst.result_type_mut() {
+            result_type.0 = map[&result_type.0];
+        }
+        for operand in inst.operands_mut() {
+            operand.0 = map[&operand.0];
+        }
+    }
+    module.bound = map.len() as u32 + 1;
+
+    module
+}
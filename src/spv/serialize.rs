@@ -0,0 +1,56 @@
+//! Serialization of extended instructions
+//!
+//! An extended instruction is written as a map with a single entry, from the
+//! name of its instruction set to the instruction, like
+//! `{"GLSL.std.450": {"Sin": {"x": 5}}}`. Only instruction sets defined in
+//! this crate can be serialized.
+
+use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::{self, SerializeMap};
+use glsl450;
+use super::{ExtInst, ExtInstBox, ExtInstSet};
+
+impl Serialize for ExtInstBox {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = try!(serializer.serialize_map(Some(1)));
+        match self.0.as_any().downcast_ref::<glsl450::Inst>() {
+            Some(inst) => try!(map.serialize_entry(glsl450::InstSet.get_name(), inst)),
+            None => {
+                let message = format!("no instruction set can serialize {}", self);
+                return Err(ser::Error::custom(message));
+            }
+        }
+        map.end()
+    }
+}
+
+struct ExtInstVisitor;
+
+impl<'de> Visitor<'de> for ExtInstVisitor {
+    type Value = ExtInstBox;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an instruction keyed by the name of its instruction set")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ExtInstBox, A::Error> {
+        let set = match try!(map.next_key::<String>()) {
+            Some(set) => set,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        let inst: Box<ExtInst> = if set == glsl450::InstSet.get_name() {
+            Box::new(try!(map.next_value::<glsl450::Inst>()))
+        } else {
+            return Err(de::Error::custom(format!("unknown instruction set {}", set)));
+        };
+        Ok(ExtInstBox(inst))
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtInstBox {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ExtInstBox, D::Error> {
+        deserializer.deserialize_map(ExtInstVisitor)
+    }
+}
@@ -6,6 +6,7 @@ use super::dis::*;
 
 /// A SPIR-V `<id>`
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OpId(pub u32);
 
 impl Display for OpId {
@@ -14,10 +15,9 @@ impl Display for OpId {
     }
 }
 
-impl DisplayArgType for OpId {}
-
 /// A SPIR-V `Result <id>`
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ResultId(pub u32);
 
 /// Set of words used to represent a literal constant
@@ -28,10 +28,12 @@ pub type LitString = String;
 
 /// Version for a module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Version(pub u8, pub u8);
 
 /// Struct to hold type and version for the generator of a module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Generator {
     pub tool: Tool,
     pub version: u16,
@@ -39,6 +41,7 @@ pub struct Generator {
 
 /// The tool used to generate a module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Tool {
     KhronosReserved,
     LunarG,
@@ -86,6 +89,7 @@ impl Tool {
 /// Source language the module was created from
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SourceLanguage {
     Unknown,
     Essl,
@@ -113,6 +117,7 @@ impl DisplayArgType for SourceLanguage {}
 
 /// Version of the source language
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SourceVersion(pub u32);
 
 impl Display for SourceVersion {
@@ -121,10 +126,15 @@ impl Display for SourceVersion {
     }
 }
 
-impl DisplayArgType for SourceVersion {}
+impl DisplayArg for SourceVersion {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_literal(&self.0, f)
+    }
+}
 
 /// Line number used with OpLine instruction
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Line(pub u32);
 
 impl Display for Line {
@@ -133,10 +143,15 @@ impl Display for Line {
     }
 }
 
-impl DisplayArgType for Line {}
+impl DisplayArg for Line {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_literal(&self.0, f)
+    }
+}
 
 /// Column number used with OpLine instruction
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Column(pub u32);
 
 impl Display for Column {
@@ -145,10 +160,15 @@ impl Display for Column {
     }
 }
 
-impl DisplayArgType for Column {}
+impl DisplayArg for Column {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_literal(&self.0, f)
+    }
+}
 
 /// Type of decoration to annotate an instruction with
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Decoration {
     RelaxedPrecision,
     SpecId(u32),
@@ -258,6 +278,7 @@ impl DisplayArgType for Decoration {}
 
 /// Marks a special built in variable or member
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BuiltIn {
     Position,
     PointSize,
@@ -355,6 +376,7 @@ impl DisplayArgType for BuiltIn {}
 
 /// Offset of a member in a type
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MemberIndex(pub u32);
 
 impl Display for MemberIndex {
@@ -363,10 +385,15 @@ impl Display for MemberIndex {
     }
 }
 
-impl DisplayArgType for MemberIndex {}
+impl DisplayArg for MemberIndex {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        style.write_literal(&self.0, f)
+    }
+}
 
 /// The addressing model used by the module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AddressingModel {
     Logical,
     Physical32,
@@ -388,6 +415,7 @@ impl DisplayArgType for AddressingModel {}
 
 /// The memory model required by the module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MemoryModel {
     Simple,
     Glsl450,
@@ -409,6 +437,7 @@ impl DisplayArgType for MemoryModel {}
 
 /// The execution model for an entry point into the module
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExecutionModel {
     Vertex,
     TesselationControl,
@@ -437,6 +466,7 @@ impl Display for ExecutionModel {
 impl DisplayArgType for ExecutionModel {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExecutionMode {
     Invocations(u32),
     SpacingEqual,
@@ -520,6 +550,7 @@ impl DisplayArgType for ExecutionMode {}
 ///
 /// Many instructions and variants depend on a certain capability
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Capability {
     Matrix,
     Shader,
@@ -646,6 +677,7 @@ impl Display for Capability {
 impl DisplayArgType for Capability {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Signedness {
     UnsignedOrNone,
     Signed,
@@ -664,6 +696,7 @@ impl Display for Signedness {
 impl DisplayArgType for Signedness {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StorageClass {
     UniformConstant,
     Input,
@@ -702,6 +735,7 @@ impl Display for StorageClass {
 impl DisplayArgType for StorageClass {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FpFastMathMode {
     pub not_nan: bool,
     pub not_inf: bool,
@@ -739,6 +773,7 @@ impl Display for FpFastMathMode {
 impl DisplayArgType for FpFastMathMode {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FpRoundingMode {
     Rte,
     Rtz,
@@ -761,6 +796,7 @@ impl Display for FpRoundingMode {
 impl DisplayArgType for FpRoundingMode {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum LinkageType {
     Export,
     Import,
@@ -779,6 +815,7 @@ impl Display for LinkageType {
 impl DisplayArgType for LinkageType {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FunctionParameterAttribute {
     Zext,
     Sext,
@@ -810,6 +847,7 @@ impl DisplayArgType for FunctionParameterAttribute {}
 
 /// The dimension for an image type
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Dim {
     Tex1D,
     Tex2D,
@@ -839,6 +877,7 @@ impl DisplayArgType for Dim {}
 
 /// Indicates if it is known if an image is a depth image
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DepthStatus {
     NotDepth,
     Depth,
@@ -860,6 +899,7 @@ impl DisplayArgType for DepthStatus {}
 
 /// Indicates if an image is an array or not
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Arrayed {
     False,
     True,
@@ -879,6 +919,7 @@ impl DisplayArgType for Arrayed {}
 
 /// The multisample state of an image
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MS {
     Single,
     Multi,
@@ -898,6 +939,7 @@ impl DisplayArgType for MS {}
 
 /// Indicates how an image is used with samplers
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SampledStatus {
     RuntimeChoice,
     WithSampler,
@@ -919,6 +961,7 @@ impl DisplayArgType for SampledStatus {}
 
 /// The format for an image type
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImageFormat {
     Unknown,
     Rgba32f,
@@ -1013,6 +1056,7 @@ impl Display for ImageFormat {
 impl DisplayArgType for ImageFormat {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AccessQualifier {
     ReadOnly,
     WriteOnly,
@@ -1033,6 +1077,7 @@ impl Display for AccessQualifier {
 impl DisplayArgType for AccessQualifier {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SamplerAddressingMode {
     None,
     ClampToEdge,
@@ -1057,6 +1102,7 @@ impl Display for SamplerAddressingMode {
 impl DisplayArgType for SamplerAddressingMode {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SamplerParam {
     NonNormalized,
     Normalized,
@@ -1075,6 +1121,7 @@ impl Display for SamplerParam {
 impl DisplayArgType for SamplerParam {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SamplerFilterMode {
     Nearest,
     Linear,
@@ -1093,6 +1140,7 @@ impl Display for SamplerFilterMode {
 impl DisplayArgType for SamplerFilterMode {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImageOperands {
     pub bias: Option<OpId>,
     pub lod: Option<OpId>,
@@ -1104,6 +1152,7 @@ pub struct ImageOperands {
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MemoryAccess {
     pub volatile: bool,
     pub aligned: bool,
@@ -1133,6 +1182,7 @@ impl Display for MemoryAccess {
 impl DisplayArgType for MemoryAccess {}
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionControl {
     pub inline: bool,
     pub dont_inline: bool,
@@ -1166,6 +1216,7 @@ impl Display for FunctionControl {
 impl DisplayArgType for FunctionControl {}
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LoopControl {
     pub unroll: bool,
     pub dont_unroll: bool,
@@ -1201,6 +1252,7 @@ impl Display for LoopControl {
 impl DisplayArgType for LoopControl {}
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SelectionControl {
     pub flatten: bool,
     pub dont_flatten: bool,
@@ -1226,6 +1278,7 @@ impl Display for SelectionControl {
 impl DisplayArgType for SelectionControl {}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PhiArg {
     pub variable: OpId,
     pub parent: OpId,
@@ -1237,9 +1290,16 @@ impl Display for PhiArg {
     }
 }
 
-impl DisplayArgType for PhiArg {}
+impl DisplayArg for PhiArg {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        try!(style.write_id(self.variable.0, f));
+        try!(write!(f, " "));
+        style.write_id(self.parent.0, f)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BranchWeights {
     pub true_weight: u32,
     pub false_weight: u32,
@@ -1251,17 +1311,175 @@ impl Display for BranchWeights {
     }
 }
 
-impl DisplayArgType for BranchWeights {}
+impl DisplayArg for BranchWeights {
+    fn display_arg(&self, style: &Style, f: &mut Formatter) -> fmt::Result {
+        try!(style.write_literal(&self.true_weight, f));
+        try!(write!(f, " "));
+        style.write_literal(&self.false_weight, f)
+    }
+}
+
+/// Operation performed by an OpSpecConstantOp
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SpecConstantOpcode {
+    SConvert,
+    FConvert,
+    UConvert,
+    SNegate,
+    Not,
+    IAdd,
+    ISub,
+    IMul,
+    UDiv,
+    SDiv,
+    UMod,
+    SRem,
+    SMod,
+    ShiftRightLogical,
+    ShiftRightArithmetic,
+    ShiftLeftLogical,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    VectorShuffle,
+    CompositeExtract,
+    CompositeInsert,
+    LogicalOr,
+    LogicalAnd,
+    LogicalNot,
+    LogicalEqual,
+    LogicalNotEqual,
+    Select,
+    IEqual,
+    INotEqual,
+    ULessThan,
+    SLessThan,
+    UGreaterThan,
+    SGreaterThan,
+    ULessThanEqual,
+    SLessThanEqual,
+    UGreaterThanEqual,
+    SGreaterThanEqual,
+    QuantizeToF16,
+    ConvertFToS,
+    ConvertSToF,
+    ConvertFToU,
+    ConvertUToF,
+    ConvertPtrToU,
+    ConvertUToPtr,
+    GenericCastToPtr,
+    PtrCastToGeneric,
+    Bitcast,
+    FNegate,
+    FAdd,
+    FSub,
+    FMul,
+    FDiv,
+    FRem,
+    FMod,
+    AccessChain,
+    InBoundsAccessChain,
+    PtrAccessChain,
+    InBoundsPtrAccessChain,
+}
+
+impl SpecConstantOpcode {
+    /// Returns the number of `<id>` operands of the operation, or `None` if
+    /// every operand is an `<id>`
+    ///
+    /// Any operands after these are literal indexes.
+    pub fn id_operand_count(&self) -> Option<usize> {
+        match *self {
+            SpecConstantOpcode::CompositeExtract => Some(1),
+            SpecConstantOpcode::VectorShuffle |
+            SpecConstantOpcode::CompositeInsert => Some(2),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SpecConstantOpcode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match *self {
+            SpecConstantOpcode::SConvert => "SConvert",
+            SpecConstantOpcode::FConvert => "FConvert",
+            SpecConstantOpcode::UConvert => "UConvert",
+            SpecConstantOpcode::SNegate => "SNegate",
+            SpecConstantOpcode::Not => "Not",
+            SpecConstantOpcode::IAdd => "IAdd",
+            SpecConstantOpcode::ISub => "ISub",
+            SpecConstantOpcode::IMul => "IMul",
+            SpecConstantOpcode::UDiv => "UDiv",
+            SpecConstantOpcode::SDiv => "SDiv",
+            SpecConstantOpcode::UMod => "UMod",
+            SpecConstantOpcode::SRem => "SRem",
+            SpecConstantOpcode::SMod => "SMod",
+            SpecConstantOpcode::ShiftRightLogical => "ShiftRightLogical",
+            SpecConstantOpcode::ShiftRightArithmetic => "ShiftRightArithmetic",
+            SpecConstantOpcode::ShiftLeftLogical => "ShiftLeftLogical",
+            SpecConstantOpcode::BitwiseOr => "BitwiseOr",
+            SpecConstantOpcode::BitwiseXor => "BitwiseXor",
+            SpecConstantOpcode::BitwiseAnd => "BitwiseAnd",
+            SpecConstantOpcode::VectorShuffle => "VectorShuffle",
+            SpecConstantOpcode::CompositeExtract => "CompositeExtract",
+            SpecConstantOpcode::CompositeInsert => "CompositeInsert",
+            SpecConstantOpcode::LogicalOr => "LogicalOr",
+            SpecConstantOpcode::LogicalAnd => "LogicalAnd",
+            SpecConstantOpcode::LogicalNot => "LogicalNot",
+            SpecConstantOpcode::LogicalEqual => "LogicalEqual",
+            SpecConstantOpcode::LogicalNotEqual => "LogicalNotEqual",
+            SpecConstantOpcode::Select => "Select",
+            SpecConstantOpcode::IEqual => "IEqual",
+            SpecConstantOpcode::INotEqual => "INotEqual",
+            SpecConstantOpcode::ULessThan => "ULessThan",
+            SpecConstantOpcode::SLessThan => "SLessThan",
+            SpecConstantOpcode::UGreaterThan => "UGreaterThan",
+            SpecConstantOpcode::SGreaterThan => "SGreaterThan",
+            SpecConstantOpcode::ULessThanEqual => "ULessThanEqual",
+            SpecConstantOpcode::SLessThanEqual => "SLessThanEqual",
+            SpecConstantOpcode::UGreaterThanEqual => "UGreaterThanEqual",
+            SpecConstantOpcode::SGreaterThanEqual => "SGreaterThanEqual",
+            SpecConstantOpcode::QuantizeToF16 => "QuantizeToF16",
+            SpecConstantOpcode::ConvertFToS => "ConvertFToS",
+            SpecConstantOpcode::ConvertSToF => "ConvertSToF",
+            SpecConstantOpcode::ConvertFToU => "ConvertFToU",
+            SpecConstantOpcode::ConvertUToF => "ConvertUToF",
+            SpecConstantOpcode::ConvertPtrToU => "ConvertPtrToU",
+            SpecConstantOpcode::ConvertUToPtr => "ConvertUToPtr",
+            SpecConstantOpcode::GenericCastToPtr => "GenericCastToPtr",
+            SpecConstantOpcode::PtrCastToGeneric => "PtrCastToGeneric",
+            SpecConstantOpcode::Bitcast => "Bitcast",
+            SpecConstantOpcode::FNegate => "FNegate",
+            SpecConstantOpcode::FAdd => "FAdd",
+            SpecConstantOpcode::FSub => "FSub",
+            SpecConstantOpcode::FMul => "FMul",
+            SpecConstantOpcode::FDiv => "FDiv",
+            SpecConstantOpcode::FRem => "FRem",
+            SpecConstantOpcode::FMod => "FMod",
+            SpecConstantOpcode::AccessChain => "AccessChain",
+            SpecConstantOpcode::InBoundsAccessChain => "InBoundsAccessChain",
+            SpecConstantOpcode::PtrAccessChain => "PtrAccessChain",
+            SpecConstantOpcode::InBoundsPtrAccessChain => "InBoundsPtrAccessChain",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl DisplayArgType for SpecConstantOpcode {}
 
 /// An `<id>` that refers to a scope
 #[derive(Clone, Debug, PartialEq)]
-pub struct ScopeId(pub u32);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScopeId(pub OpId);
 
 /// An `<id>` that refers to memory semantics
 #[derive(Clone, Debug, PartialEq)]
-pub struct MemorySemanticsId(pub u32);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemorySemanticsId(pub OpId);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GroupOperation {
     Reduce,
     InclusiveScan,
@@ -1269,6 +1487,7 @@ pub enum GroupOperation {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum KernelEnqueueFlags {
     NoWait,
     WaitKernel,
@@ -1276,6 +1495,7 @@ pub enum KernelEnqueueFlags {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KernelProfilingInfo {
     cmd_exec_time: bool,
 }
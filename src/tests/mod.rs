@@ -107,6 +107,1710 @@ fn load_noop() {
     assert_eq!(result, Ok(expected));
 }
 
+#[test]
+fn def_use_write_multiply() {
+    use spv::logical::*;
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let def_use = find_def_use(&module).unwrap();
+    let imul = OpId(20);
+    assert_eq!(def_use.get_definition(&imul),
+               Some(Location::Block(0, 0, BlockPosition::Code(5))));
+    assert_eq!(def_use.get_uses(&imul),
+               &[Location::Block(0, 0, BlockPosition::Code(6))]);
+    let buffer = OpId(25);
+    assert_eq!(def_use.get_definition(&buffer), Some(Location::Global(14)));
+    assert_eq!(def_use.get_uses(&buffer),
+               &[Location::Debug(6),
+                 Location::Annotation(4),
+                 Location::Annotation(5),
+                 Location::Block(0, 0, BlockPosition::Code(11))]);
+}
+
+#[test]
+fn operands_mut() {
+    use spv::operands::Operands;
+
+    let mut phi = OpPhi {
+        result_type: OpId(1),
+        result_id: ResultId(2),
+        variables: vec![PhiArg {
+                            variable: OpId(3),
+                            parent: OpId(4),
+                        },
+                        PhiArg {
+                            variable: OpId(5),
+                            parent: OpId(6),
+                        }],
+    };
+    for operand in phi.operands_mut() {
+        operand.0 += 10;
+    }
+    phi.result_id_mut().unwrap().0 = 20;
+    assert_eq!(phi.result_id(), Some(&ResultId(20)));
+    assert_eq!(phi.result_type(), Some(&OpId(1)));
+    assert_eq!(phi.operands(),
+               vec![&OpId(13), &OpId(14), &OpId(15), &OpId(16)]);
+
+    let select = OpSelect(OpId(1), ResultId(2), OpId(3), OpId(4), OpId(5));
+    assert_eq!(select.result_id(), Some(&ResultId(2)));
+    assert_eq!(select.result_type(), Some(&OpId(1)));
+    assert_eq!(select.operands(), vec![&OpId(3), &OpId(4), &OpId(5)]);
+
+    let switch = OpSwitch(OpId(1), OpId(2), vec![(vec![0], OpId(3))]);
+    assert_eq!(switch.result_id(), None);
+    assert_eq!(switch.operands(), vec![&OpId(1), &OpId(2), &OpId(3)]);
+
+    let mut store = OpAtomicStore(OpId(1), ScopeId(OpId(2)), MemorySemanticsId(OpId(3)), OpId(4));
+    for operand in store.operands_mut() {
+        operand.0 += 10;
+    }
+    assert_eq!(store.operands(),
+               vec![&OpId(11), &OpId(12), &OpId(13), &OpId(14)]);
+}
+
+#[test]
+fn strip_write_multiply() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    assert!(!module.debug.is_empty());
+
+    let stripped = strip_debug(module.clone(), StripOptions::all());
+    assert_eq!(stripped.debug, vec![]);
+    assert_eq!(stripped.function_definitions, module.function_definitions);
+
+    let named = strip_debug(module.clone(), StripOptions::keep_names());
+    assert!(!named.debug.is_empty());
+    for op in &named.debug {
+        match *op {
+            GroupDebug::OpName(_) |
+            GroupDebug::OpMemberName(_) => {}
+            ref op => panic!("{:?} was not stripped", op),
+        }
+    }
+}
+
+#[test]
+fn strip_cond_trig_lines() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("cond_trig_lines.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let is_line = |op: &GroupCode| match *op {
+        GroupCode::OpLine(_) | GroupCode::OpNoLine(_) => true,
+        _ => false,
+    };
+    let code_lines = |module: &LogicalModule| {
+        module.function_definitions[0]
+            .blocks
+            .iter()
+            .map(|block| block.code.iter().filter(|op| is_line(op)).count())
+            .collect::<Vec<_>>()
+    };
+    let global_lines = |module: &LogicalModule| {
+        module.globals
+            .iter()
+            .filter(|op| match **op {
+                GroupGlobal::OpLine(_) | GroupGlobal::OpNoLine(_) => true,
+                _ => false,
+            })
+            .count()
+    };
+    assert_eq!(global_lines(&module), 2);
+    assert_eq!(code_lines(&module), vec![1, 1, 1, 2]);
+    match module.function_definitions[0].blocks[1].code[0] {
+        GroupCode::OpLine(ref op) => assert_eq!(op.line, Line(9)),
+        ref op => panic!("expected OpLine but found {:?}", op),
+    }
+
+    let options = StripOptions {
+        names: false,
+        source: false,
+        strings: false,
+        lines: true,
+        non_semantic: false,
+    };
+    let stripped = strip_debug(module.clone(), options);
+    assert_eq!(stripped.debug, module.debug);
+    assert_eq!(global_lines(&stripped), 0);
+    assert_eq!(stripped.globals.len(), module.globals.len() - 2);
+    assert_eq!(code_lines(&stripped), vec![0, 0, 0, 0]);
+
+    let stripped = strip_debug(module.clone(), StripOptions::all());
+    assert_eq!(stripped.debug, vec![]);
+    assert_eq!(code_lines(&stripped), vec![0, 0, 0, 0]);
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let unannotated = validate(raw_module).unwrap();
+    assert_eq!(stripped.globals, unannotated.globals);
+    assert_eq!(stripped.function_definitions, unannotated.function_definitions);
+}
+
+#[test]
+fn strip_non_semantic() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let mut annotated = module.clone();
+    annotated.extensions.push("SPV_KHR_non_semantic_info".into());
+    annotated.ext_inst_imports.push(OpExtInstImport {
+        result_id: ResultId(60),
+        name: "NonSemantic.Test".into(),
+    });
+    let code = &mut annotated.function_definitions[0].blocks[1].code;
+    let index = code.iter()
+        .position(|op| match *op {
+            GroupCode::OpExtInst(_) => true,
+            _ => false,
+        })
+        .unwrap();
+    let non_semantic = match code[index] {
+        GroupCode::OpExtInst(ref op) => {
+            GroupCode::OpExtInst(OpExtInst {
+                result_type: op.result_type.clone(),
+                result_id: ResultId(61),
+                set: OpId(60),
+                instruction: op.instruction.clone(),
+            })
+        }
+        _ => unreachable!(),
+    };
+    code.insert(index + 1, non_semantic);
+
+    let mut options = StripOptions::all();
+    options.non_semantic = false;
+    let stripped = strip_debug(annotated.clone(), options);
+    assert_eq!(stripped.ext_inst_imports, annotated.ext_inst_imports);
+    assert_eq!(stripped.function_definitions, annotated.function_definitions);
+
+    let stripped = strip_debug(annotated, StripOptions::all());
+    assert_eq!(stripped.extensions, module.extensions);
+    assert_eq!(stripped.ext_inst_imports, module.ext_inst_imports);
+    assert_eq!(stripped.function_definitions, module.function_definitions);
+}
+
+#[test]
+fn lines_outside_blocks() {
+    use spv::logical::*;
+
+    let mut raw_module = read(include_bytes!("cond_trig_lines.spv")).unwrap();
+    let line = |line| {
+        Core::OpLine(OpLine {
+            file: OpId(50),
+            line: Line(line),
+            column: Column(0),
+        })
+    };
+    let position = |raw_module: &RawModule, inst: &Core| {
+        raw_module.instructions.iter().position(|op| op == inst).unwrap()
+    };
+    let label = position(&raw_module, &Core::OpLabel(OpLabel { result_id: ResultId(19) }));
+    raw_module.instructions.insert(label, line(9));
+    let end = position(&raw_module, &Core::OpFunctionEnd(OpFunctionEnd));
+    raw_module.instructions.insert(end, Core::OpNoLine(OpNoLine));
+
+    let module = validate(raw_module.clone()).unwrap();
+    let function = &module.function_definitions[0];
+    assert_eq!(function.lines, vec![]);
+    assert_eq!(function.blocks[1].lines, vec![GroupLine::OpLine(OpLine {
+                   file: OpId(50),
+                   line: Line(9),
+                   column: Column(0),
+               })]);
+    assert_eq!(function.end_lines, vec![GroupLine::OpNoLine(OpNoLine)]);
+
+    let lowered = lower_module(module.clone());
+    let start = position(&raw_module, &Core::OpLabel(OpLabel { result_id: ResultId(5) }));
+    assert_eq!(lowered.instructions[start..], raw_module.instructions[start..]);
+
+    let stripped = strip_debug(module, StripOptions::all());
+    assert_eq!(stripped.function_definitions[0].blocks[1].lines, vec![]);
+    assert_eq!(stripped.function_definitions[0].end_lines, vec![]);
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let unannotated = validate(raw_module).unwrap();
+    assert_eq!(stripped.function_definitions, unannotated.function_definitions);
+}
+
+#[test]
+fn dce_write_multiply() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    let dead_add = GroupCode::OpIAdd(OpIAdd {
+        result_type: OpId(9),
+        result_id: ResultId(40),
+        operand1: OpId(16),
+        operand2: OpId(19),
+    });
+    module.function_definitions[0].blocks[0].code.insert(5, dead_add.clone());
+
+    let module = eliminate_dead_code(module).unwrap();
+    let def_use = find_def_use(&module).unwrap();
+    for id in 36..41 {
+        assert_eq!(def_use.get_definition(&OpId(id)), None);
+    }
+    assert!(def_use.get_definition(&OpId(35)).is_some());
+    assert!(!module.function_definitions[0].blocks[0].code.contains(&dead_add));
+    assert_eq!(module.debug.len(), 7);
+    assert_eq!(module.annotations.len(), 7);
+}
+
+#[test]
+fn dce_link_library() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("link_library.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    assert!(module.entry_points.is_empty());
+
+    // The exported function is kept along with the types it uses
+    let eliminated = eliminate_dead_code(module.clone()).unwrap();
+    assert_eq!(eliminated.function_definitions.len(), 1);
+    assert_eq!(eliminated.globals.len(), 2);
+    assert_eq!(eliminated, module);
+
+    // Names of the parameters and blocks of a kept function are kept
+    let mut module = module;
+    let name = |target: &ResultId, name: &str| {
+        GroupDebug::OpName(OpName {
+            target: OpId(target.0),
+            name: name.into(),
+        })
+    };
+    let param = name(&module.function_definitions[0].parameters[0].result_id, "x");
+    let label = name(&module.function_definitions[0].blocks[0].label.result_id, "entry");
+    module.debug.push(param.clone());
+    module.debug.push(label.clone());
+    let eliminated = eliminate_dead_code(module).unwrap();
+    assert!(eliminated.debug.contains(&param));
+    assert!(eliminated.debug.contains(&label));
+}
+
+#[test]
+fn fold_cond_trig() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    let constant = |id, ty, value| {
+        GroupGlobal::GroupConstant(GroupConstant::OpConstant(OpConstant {
+            result_type: OpId(ty),
+            result_id: ResultId(id),
+            value: vec![value],
+        }))
+    };
+    module.globals.push(constant(50, 37, -7i32 as u32));
+    module.globals.push(constant(51, 37, 3));
+    macro_rules! binary {
+        ($name: ident, $id: expr, $ty: expr, $a: expr, $b: expr) => {
+            GroupCode::$name($name {
+                result_type: OpId($ty),
+                result_id: ResultId($id),
+                operand1: OpId($a),
+                operand2: OpId($b),
+            })
+        }
+    }
+    let code = vec![binary!(OpSRem, 52, 37, 50, 51),
+                    binary!(OpSMod, 53, 37, 50, 51),
+                    binary!(OpSLessThan, 54, 17, 50, 51),
+                    binary!(OpISub, 55, 6, 16, 10),
+                    GroupCode::OpConvertUToF(OpConvertUToF {
+                        result_type: OpId(21),
+                        result_id: ResultId(56),
+                        unsigned_value: OpId(44),
+                    }),
+                    binary!(OpFSub, 57, 21, 56, 56),
+                    binary!(OpFDiv, 58, 21, 57, 57),
+                    binary!(OpFOrdEqual, 59, 17, 58, 58)];
+    {
+        let block = &mut module.function_definitions[0].blocks[0];
+        block.code.extend(code);
+        // Make the branch condition 32 % 2 == 0
+        if let GroupCode::OpUMod(ref mut op) = block.code[3] {
+            op.operand1 = OpId(44);
+        } else {
+            panic!("unexpected instruction {:?}", block.code[3]);
+        }
+    }
+
+    let module = fold_constants(module).unwrap();
+    let has_constant = |ty, value| {
+        module.globals.iter().any(|global| match *global {
+            GroupGlobal::GroupConstant(GroupConstant::OpConstant(ref op)) => {
+                op.result_type == OpId(ty) && op.value == vec![value]
+            }
+            _ => false,
+        })
+    };
+    assert!(has_constant(37, -1i32 as u32));
+    assert!(has_constant(37, 2));
+    assert!(has_constant(6, 0xFFFFFFFF));
+    assert!(has_constant(21, 32f32.to_bits()));
+    assert!(has_constant(21, 0f32.to_bits()));
+    let bools = module.globals
+        .iter()
+        .filter_map(|global| match *global {
+            GroupGlobal::GroupConstant(GroupConstant::OpConstantTrue(_)) => Some(true),
+            GroupGlobal::GroupConstant(GroupConstant::OpConstantFalse(_)) => Some(false),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(bools.len(), 2);
+
+    let block = &module.function_definitions[0].blocks[0];
+    assert_eq!(block.code.len(), 3);
+    assert_eq!(block.merge, None);
+    assert_eq!(block.branch,
+               GroupBranch::OpBranch(OpBranch { target_label: OpId(19) }));
+
+    let module = eliminate_dead_code(module).unwrap();
+    assert_eq!(module.function_definitions[0].blocks.len(), 3);
+    find_control_flow(module.function_definitions[0].clone()).unwrap();
+}
+
+/// Runs a workgroup of one of the inline fixtures and returns the value
+/// written by each invocation
+fn run_inline_fixture(module: &::spv::logical::LogicalModule) -> Vec<u32> {
+    use byteorder::{LittleEndian, ByteOrder};
+    use backend::ResourceBinding;
+    use interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new(module, "main").unwrap();
+    let binding = ResourceBinding { set: 0, binding: 0 };
+    interpreter.bind_buffer(binding, vec![0; 32 * 4]);
+    interpreter.dispatch(1, 1, 1).unwrap();
+    let buffer = interpreter.buffer(binding).unwrap();
+    (0..32).map(|index| LittleEndian::read_u32(&buffer[index * 4..])).collect()
+}
+
+#[test]
+fn inline_select() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("inline_select.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let expected = (0..32).map(|x| u32::max(x, 8) + 16).collect::<Vec<_>>();
+    assert_eq!(run_inline_fixture(&module), expected);
+
+    let module = inline_functions(module, InlineMode::All).unwrap();
+    assert_eq!(run_inline_fixture(&module), expected);
+    // The result of the call is replaced, so its name and decoration go too
+    for op in &module.debug {
+        if let GroupDebug::OpName(ref name) = *op {
+            assert!(name.target != OpId(26));
+        }
+    }
+    for op in &module.annotations {
+        if let GroupAnnotation::OpDecorate(ref decorate) = *op {
+            assert!(decorate.target != OpId(26));
+        }
+    }
+
+    let main = &module.function_definitions[0];
+    assert_eq!(main.blocks.len(), 6);
+    for block in &main.blocks {
+        for op in &block.code {
+            if let GroupCode::OpFunctionCall(_) = *op {
+                panic!("call was not inlined");
+            }
+        }
+    }
+    // The stored value now uses the result computed in the inlined merge block
+    let sum = match main.blocks[4].code[1] {
+        GroupCode::OpIAdd(ref op) => OpId(op.result_id.0),
+        ref op => panic!("expected add, found {}", op),
+    };
+    match main.blocks[5].code[1] {
+        GroupCode::OpStore(ref op) => assert_eq!(op.object, sum),
+        ref op => panic!("expected store, found {}", op),
+    }
+    find_control_flow(main.clone()).unwrap();
+
+    let module = eliminate_dead_code(module).unwrap();
+    assert_eq!(module.function_definitions.len(), 1);
+}
+
+#[test]
+fn inline_multiple_returns() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("inline_returns.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let expected = (0..32).map(|x| u32::max(x, 8)).collect::<Vec<_>>();
+    assert_eq!(run_inline_fixture(&module), expected);
+
+    let module = inline_functions(module, InlineMode::All).unwrap();
+    assert_eq!(run_inline_fixture(&module), expected);
+    let main = &module.function_definitions[0];
+    assert_eq!(main.blocks.len(), 8);
+    match main.blocks[1].merge {
+        Some(GroupMerge::OpLoopMerge(_)) => {}
+        ref merge => panic!("expected loop merge, found {:?}", merge),
+    }
+    match main.blocks[7].code[0] {
+        GroupCode::OpPhi(ref phi) => {
+            assert_eq!(phi.result_id, ResultId(26));
+            assert_eq!(phi.variables.len(), 3);
+        }
+        ref op => panic!("expected phi, found {}", op),
+    }
+    // Each return breaks out of the loop, so the merge of the selection and
+    // the continue target are never reached
+    let chain = find_control_flow(main.clone()).unwrap();
+    assert_eq!(chain.to_string(),
+               "%5\nloop %44 {\n    selection %38 {\n        %40\n        break;\n    } \
+                else {\n        %41\n        break;\n    }\n}\n%43\n");
+}
+
+#[test]
+fn inline_return_in_loop() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("inline_loop.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let expected = (0..32).map(|x| x + (0..x).sum::<u32>()).collect::<Vec<_>>();
+    assert_eq!(run_inline_fixture(&module), expected);
+
+    // find returns from inside its loop, so only the call to count, which
+    // returns once after its loop, is inlined
+    let module = inline_functions(module, InlineMode::All).unwrap();
+    assert_eq!(run_inline_fixture(&module), expected);
+    let main = &module.function_definitions[0];
+    let calls = main.blocks
+        .iter()
+        .flat_map(|block| block.code.iter())
+        .filter_map(|op| match *op {
+            GroupCode::OpFunctionCall(ref call) => Some(call.function.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(calls, vec![OpId(30)]);
+    find_control_flow(main.clone()).unwrap();
+
+    let module = eliminate_dead_code(module).unwrap();
+    assert_eq!(module.function_definitions.len(), 2);
+}
+
+#[test]
+fn inline_variable_initializer() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("inline_select.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let module = inline_functions(module, InlineMode::All).unwrap();
+    let main = &module.function_definitions[0];
+    let variable = match main.blocks[0].code[0] {
+        GroupCode::OpVariable(ref var) => {
+            assert_eq!(var.initializer, None);
+            OpId(var.result_id.0)
+        }
+        ref op => panic!("expected variable, found {}", op),
+    };
+    // The variable is initialized every time the inlined body is entered
+    assert_eq!(main.blocks[0].code.last(),
+               Some(&GroupCode::OpStore(OpStore {
+                   pointer: variable,
+                   object: OpId(23),
+                   memory_access: None,
+               })));
+    let entry = OpId(main.blocks[1].label.result_id.0);
+    assert_eq!(main.blocks[0].branch,
+               GroupBranch::OpBranch(OpBranch { target_label: entry }));
+}
+
+#[test]
+fn inline_respects_function_control() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("inline_select.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let hinted = inline_functions(module.clone(), InlineMode::Hinted).unwrap();
+    assert_eq!(hinted, module);
+
+    let mut module = module;
+    module.function_definitions[1].function.function_control.dont_inline = true;
+    let inlined = inline_functions(module.clone(), InlineMode::All).unwrap();
+    assert_eq!(inlined, module);
+
+    module.function_definitions[1].function.function_control.dont_inline = false;
+    module.function_definitions[1].function.function_control.inline = true;
+    let inlined = inline_functions(module.clone(), InlineMode::Hinted).unwrap();
+    assert_eq!(inlined.function_definitions[0].blocks.len(), 6);
+}
+
+#[test]
+fn dominators_loop_simple() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let function = &module.function_definitions[0];
+    let labels = function.blocks.iter().map(|block| block.label.result_id.0).collect::<Vec<_>>();
+    assert_eq!(labels, vec![5, 12, 16, 13, 15, 14]);
+
+    let dominators = find_dominators(function);
+    let idoms = (0..labels.len()).map(|block| dominators.immediate_dominator(block)).collect::<Vec<_>>();
+    assert_eq!(idoms, vec![None, Some(0), Some(1), Some(2), Some(3), Some(2)]);
+    assert_eq!(dominators.predecessors(1), &[0, 4]);
+    assert_eq!(dominators.frontier(3), &[1]);
+    assert_eq!(dominators.frontier(5), &[] as &[usize]);
+    assert!(dominators.dominates(2, 5));
+    assert!(!dominators.dominates(3, 5));
+}
+
+#[test]
+fn mem2reg_loop_simple() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let module = promote_variables(module).unwrap();
+    assert!(!module.debug.contains(&GroupDebug::OpName(OpName {
+        target: OpId(10),
+        name: "i".into(),
+    })));
+
+    let function = &module.function_definitions[0];
+    for block in &function.blocks {
+        for op in &block.code {
+            match *op {
+                GroupCode::OpVariable(_) => panic!("variable was not promoted"),
+                GroupCode::OpLoad(ref op) => assert!(op.pointer != OpId(8) && op.pointer != OpId(10)),
+                GroupCode::OpStore(ref op) => assert!(op.pointer != OpId(8) && op.pointer != OpId(10)),
+                _ => {}
+            }
+        }
+    }
+
+    // Both variables are joined in the loop header
+    let phis = function.blocks[1]
+        .code
+        .iter()
+        .filter_map(|op| match *op {
+            GroupCode::OpPhi(ref phi) => Some(phi.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(phis.len(), 2);
+    let arg = |variable, parent| PhiArg { variable: OpId(variable), parent: OpId(parent) };
+    assert_eq!(phis[0].variables, vec![arg(9, 5), arg(30, 15)]);
+    assert_eq!(phis[1].variables, vec![arg(11, 5), arg(34, 15)]);
+
+    match function.blocks[2].code[2] {
+        GroupCode::OpULessThan(ref op) => assert_eq!(op.operand1, OpId(phis[1].result_id.0)),
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+    match function.blocks[5].code[3] {
+        GroupCode::OpStore(ref op) => assert_eq!(op.object, OpId(phis[0].result_id.0)),
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+}
+
+#[test]
+fn cse_write_multiply() {
+    use spv::logical::*;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let original = validate(raw_module).unwrap();
+    let mut module = original.clone();
+    module.debug.push(GroupDebug::OpName(OpName {
+        target: OpId(50),
+        name: "uint".into(),
+    }));
+    module.globals.push(GroupGlobal::GroupType(GroupType::OpTypeInt(OpTypeInt {
+        result_id: ResultId(50),
+        width: 32,
+        signedness: Signedness::UnsignedOrNone,
+    })));
+    module.globals.push(GroupGlobal::GroupConstant(GroupConstant::OpConstant(OpConstant {
+        result_type: OpId(50),
+        result_id: ResultId(51),
+        value: vec![2],
+    })));
+    module.globals.push(GroupGlobal::GroupType(GroupType::OpTypePointer(OpTypePointer {
+        result_id: ResultId(52),
+        storage_class: StorageClass::Input,
+        pointed_type: OpId(50),
+    })));
+    {
+        let code = &mut module.function_definitions[0].blocks[0].code;
+        code.insert(5,
+                    GroupCode::OpAccessChain(OpAccessChain {
+                        result_type: OpId(52),
+                        result_id: ResultId(53),
+                        base: OpId(12),
+                        indexes: vec![OpId(51)],
+                    }));
+        code.insert(6,
+                    GroupCode::OpLoad(OpLoad {
+                        result_type: OpId(50),
+                        result_id: ResultId(54),
+                        pointer: OpId(53),
+                        memory_access: None,
+                    }));
+        code.insert(7,
+                    GroupCode::OpIMul(OpIMul {
+                        result_type: OpId(50),
+                        result_id: ResultId(55),
+                        operand1: OpId(16),
+                        operand2: OpId(54),
+                    }));
+        code.insert(8,
+                    GroupCode::OpIMul(OpIMul {
+                        result_type: OpId(9),
+                        result_id: ResultId(56),
+                        operand1: OpId(16),
+                        operand2: OpId(54),
+                    }));
+        if let GroupCode::OpConvertUToF(ref mut op) = code[10] {
+            op.unsigned_value = OpId(56);
+        } else {
+            panic!("unexpected instruction {:?}", code[10]);
+        }
+    }
+
+    let module = eliminate_common_subexpressions(module);
+    assert_eq!(module.debug, original.debug);
+    assert_eq!(module.globals, original.globals);
+
+    // The load can not be merged, but the multiplications of it can be
+    let code = &module.function_definitions[0].blocks[0].code;
+    assert_eq!(code.len(), original.function_definitions[0].blocks[0].code.len() + 2);
+    match code[5] {
+        GroupCode::OpLoad(ref op) => assert_eq!(op.pointer, OpId(18)),
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+    match code[8] {
+        GroupCode::OpConvertUToF(ref op) => assert_eq!(op.unsigned_value, OpId(55)),
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+    assert_eq!(eliminate_common_subexpressions(module.clone()), module);
+}
+
+#[test]
+fn freeze_spec_constants_values() {
+    use std::collections::HashMap;
+    use spv::logical::*;
+    use spv::operands::Operands;
+
+    let raw_module = read(include_bytes!("spec_constants.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let spec_constants = find_spec_constants(&module);
+    assert_eq!(spec_constants,
+               vec![SpecConstant {
+                        spec_id: 0,
+                        id: OpId(8),
+                        result_type: OpId(6),
+                        default: vec![4],
+                    },
+                    SpecConstant {
+                        spec_id: 1,
+                        id: OpId(10),
+                        result_type: OpId(7),
+                        default: vec![1],
+                    }]);
+
+    let constant = |module: &LogicalModule, id| {
+        module.globals
+            .iter()
+            .filter_map(|global| match *global {
+                GroupGlobal::GroupConstant(ref constant) => Some(constant.clone()),
+                _ => None,
+            })
+            .find(|constant| constant.result_id() == Some(&ResultId(id)))
+            .unwrap()
+    };
+    let uint = |id, value| {
+        GroupConstant::OpConstant(OpConstant {
+            result_type: OpId(6),
+            result_id: ResultId(id),
+            value: vec![value],
+        })
+    };
+
+    let frozen = freeze_spec_constants(module.clone(), &HashMap::new()).unwrap();
+    assert_eq!(constant(&frozen, 11), uint(11, 8));
+    assert_eq!(constant(&frozen, 16), uint(16, 8));
+    assert_eq!(constant(&frozen, 13),
+               GroupConstant::OpConstantTrue(OpConstantTrue {
+                   result_type: OpId(7),
+                   result_id: ResultId(13),
+               }));
+    assert!(find_spec_constants(&frozen).is_empty());
+    assert_eq!(frozen.annotations, vec![]);
+
+    let mut values = HashMap::new();
+    values.insert(0, vec![1]);
+    let frozen = freeze_spec_constants(module.clone(), &values).unwrap();
+    assert_eq!(constant(&frozen, 8), uint(8, 1));
+    assert_eq!(constant(&frozen, 11), uint(11, 2));
+    assert_eq!(constant(&frozen, 12),
+               GroupConstant::OpConstantFalse(OpConstantFalse {
+                   result_type: OpId(7),
+                   result_id: ResultId(12),
+               }));
+
+    values.insert(1, vec![0, 0]);
+    assert_eq!(freeze_spec_constants(module.clone(), &values).map(|_| ()), Ok(()));
+    values.insert(0, vec![0, 0]);
+    assert_eq!(freeze_spec_constants(module, &values).map(|_| ()),
+               Err(FreezeError::ValueSizeMismatch(0)));
+}
+
+#[test]
+fn link_library() {
+    use spv::logical::*;
+
+    let library = validate(read(include_bytes!("link_library.spv")).unwrap()).unwrap();
+    let main = validate(read(include_bytes!("link_main.spv")).unwrap()).unwrap();
+    assert_eq!(link_modules(vec![]).map(|_| ()), Err(LinkError::NoModules));
+    assert_eq!(link_modules(vec![main.clone()]).map(|_| ()),
+               Err(LinkError::MissingSymbol("square".into())));
+
+    let module = link_modules(vec![library.clone(), main.clone()]).unwrap();
+    assert_eq!(module.capabilities, vec![Capability::Shader, Capability::Linkage]);
+    assert_eq!(module.ext_inst_imports.len(), 1);
+    assert_eq!(module.entry_points[0].entry_point, OpId(10));
+    assert_eq!(module.annotations, library.annotations);
+    assert!(module.function_declarations.is_empty());
+    assert_eq!(module.function_definitions.len(), 2);
+
+    // The float type and the function type are shared by both modules
+    let float_types = module.globals
+        .iter()
+        .filter(|op| match **op {
+            GroupGlobal::GroupType(GroupType::OpTypeFloat(_)) => true,
+            _ => false,
+        })
+        .count();
+    assert_eq!(float_types, 1);
+    match module.function_definitions[1].blocks[0].code[0] {
+        GroupCode::OpFunctionCall(ref call) => {
+            assert_eq!(call.result_type, OpId(1));
+            assert_eq!(call.function, OpId(3));
+            assert_eq!(call.arguments, vec![OpId(13)]);
+        }
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+    find_def_use(&module).unwrap();
+
+    // The export no longer matches when it returns a different type
+    let mut library = library;
+    library.globals.push(GroupGlobal::GroupType(GroupType::OpTypeInt(OpTypeInt {
+        result_id: ResultId(7),
+        width: 32,
+        signedness: Signedness::Signed,
+    })));
+    library.function_definitions[0].function.result_type = OpId(7);
+    assert_eq!(link_modules(vec![library, main]).map(|_| ()),
+               Err(LinkError::TypeMismatch("square".into())));
+}
+
+#[test]
+fn builder_loop() {
+    use spv::logical::*;
+
+    let mut builder = Builder::new(AddressingModel::Logical, MemoryModel::Glsl450);
+    builder.capability(Capability::Shader);
+    builder.capability(Capability::Shader);
+    let void = builder.type_void();
+    let main_type = builder.type_function(void.clone(), vec![]);
+    let uint = builder.type_int(32, false);
+    assert_eq!(builder.type_int(32, false), uint);
+    let bool_type = builder.type_bool();
+    let ptr_type = builder.type_pointer(StorageClass::Function, uint.clone());
+    let zero = builder.constant_u32(0);
+    let one = builder.constant_u32(1);
+    let ten = builder.constant_u32(10);
+    assert_eq!(builder.constant_u32(1), one);
+
+    let main = builder.begin_function(void, main_type, FunctionControl::default());
+    builder.entry_point(ExecutionModel::GlCompute, main.clone(), "main", vec![]);
+    builder.execution_mode(main.clone(), ExecutionMode::LocalSize(1, 1, 1));
+    let entry = builder.new_block();
+    let sum = builder.local_variable(ptr_type, Some(zero.clone()));
+    let header = builder.id();
+    let body = builder.id();
+    let continue_target = builder.id();
+    let merge = builder.id();
+    builder.branch(header.clone());
+    builder.begin_block(header.clone());
+    let next = builder.id();
+    let counter = builder.phi(uint.clone(),
+                              vec![(zero, entry), (next.clone(), continue_target.clone())]);
+    builder.loop_merge(merge.clone(), continue_target.clone());
+    let condition = builder.id();
+    builder.branch(condition.clone());
+    builder.begin_block(condition);
+    let done = builder.uless_than(bool_type, counter.clone(), ten);
+    builder.branch_conditional(done, body.clone(), merge.clone());
+    builder.begin_block(body);
+    let value = builder.load(uint.clone(), sum.clone());
+    let value = builder.iadd(uint.clone(), value, counter.clone());
+    builder.store(sum, value);
+    builder.branch(continue_target.clone());
+    builder.begin_block(continue_target);
+    builder.code(GroupCode::OpIAdd(OpIAdd {
+        result_type: uint,
+        result_id: ResultId(next.0),
+        operand1: counter,
+        operand2: one,
+    }));
+    builder.branch(header);
+    builder.begin_block(merge);
+    builder.ret();
+    builder.end_function();
+
+    let bound = builder.bound();
+    let module = builder.finish();
+    assert_eq!(module.capabilities, vec![Capability::Shader]);
+    assert_eq!(module.globals.len(), 8);
+    let def_use = find_def_use(&module).unwrap();
+    assert_eq!(def_use.bound(), bound);
+    assert_eq!(module.function_definitions[0].blocks.len(), 6);
+    match module.function_definitions[0].blocks[0].code[0] {
+        GroupCode::OpVariable(ref var) => assert_eq!(var.storage_class, StorageClass::Function),
+        ref op => panic!("unexpected instruction {:?}", op),
+    }
+    for func in module.function_definitions.clone() {
+        find_control_flow(func).unwrap();
+    }
+    let raw = lower_module(module.clone());
+    assert_eq!(raw.bound, bound);
+    assert_eq!(validate(raw).unwrap(), module);
+    let module = promote_variables(module).unwrap();
+    let module = eliminate_dead_code(module).unwrap();
+    assert_eq!(module.function_definitions[0].blocks.len(), 6);
+}
+
+#[test]
+fn control_flow_loop_break_arms() {
+    use spv::logical::*;
+
+    // A loop whose body is a selection with both arms leaving the loop, so
+    // the merge of the selection and the continue target are unreachable
+    let mut builder = Builder::new(AddressingModel::Logical, MemoryModel::Glsl450);
+    builder.capability(Capability::Shader);
+    let void = builder.type_void();
+    let main_type = builder.type_function(void.clone(), vec![]);
+    let condition = builder.constant_bool(true);
+    builder.begin_function(void, main_type, FunctionControl::default());
+    builder.new_block();
+    let header = builder.id();
+    let body = builder.id();
+    let true_arm = builder.id();
+    let false_arm = builder.id();
+    let selection_merge = builder.id();
+    let continue_target = builder.id();
+    let merge = builder.id();
+    builder.branch(header.clone());
+    builder.begin_block(header.clone());
+    builder.loop_merge(merge.clone(), continue_target.clone());
+    builder.branch(body.clone());
+    builder.begin_block(body);
+    builder.selection_merge(selection_merge.clone());
+    builder.branch_conditional(condition, true_arm.clone(), false_arm.clone());
+    builder.begin_block(true_arm);
+    builder.branch(merge.clone());
+    builder.begin_block(false_arm);
+    builder.branch(merge.clone());
+    builder.begin_block(selection_merge);
+    builder.branch(continue_target.clone());
+    builder.begin_block(continue_target);
+    builder.branch(header);
+    builder.begin_block(merge);
+    builder.ret();
+    builder.end_function();
+    let module = builder.finish();
+
+    let chain = find_control_flow(module.function_definitions[0].clone()).unwrap();
+    assert_eq!(chain.to_string(),
+               "%6\nloop %7 {\n    selection %8 {\n        %9\n        break;\n    } \
+                else {\n        %10\n        break;\n    }\n}\n%13\n");
+}
+
+#[test]
+fn generate_glsl_write_multiply() {
+    use spv::logical::*;
+    use backend::generate_glsl;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let glsl = generate_glsl(&module, "main").unwrap();
+    assert!(glsl.starts_with("#version 450\n"));
+    assert!(glsl.contains("layout(local_size_x = 32, local_size_y = 32, local_size_z = 32) in;"));
+    assert!(glsl.contains("layout(std430, binding = 2) buffer MyBuffer {\n    \
+                           layout(offset = 0) float value[];\n} _25;"));
+    assert!(glsl.contains("_16 = gl_GlobalInvocationID.y;"));
+    assert!(glsl.contains("_21 = float(_20);"));
+    assert!(glsl.contains("    float write_value;\n"));
+    assert!(glsl.contains("_25.value[_30] = _31;"));
+    assert!(generate_glsl(&module, "other").is_err());
+}
+
+#[test]
+fn generate_glsl_loop_simple() {
+    use spv::logical::*;
+    use backend::generate_glsl;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let module = promote_variables(validate(raw_module).unwrap()).unwrap();
+    let glsl = generate_glsl(&module, "main").unwrap();
+    assert!(glsl.contains("    _53_phi = 0u;\n    while (true) {\n        _52 = _52_phi;"));
+    assert!(glsl.contains("        if (!(_25)) {\n            break;\n        }"));
+    assert!(glsl.contains("        _34 = _53 + uint(1);"));
+    assert!(glsl.contains("    _38.value[_41] = _52;"));
+}
+
+#[test]
+fn generate_hlsl_write_multiply() {
+    use spv::logical::*;
+    use backend::generate_hlsl;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let hlsl = generate_hlsl(&module, "main").unwrap();
+    assert!(hlsl.starts_with("static uint3 gl_GlobalInvocationID;\n\
+                              RWStructuredBuffer<float> _25 : register(u2);\n"));
+    assert!(hlsl.contains("void main_inner() {\n"));
+    assert!(hlsl.contains("    _25[_30] = _31;\n"));
+    assert!(hlsl.ends_with("[numthreads(32, 32, 32)]\n\
+                            void main(uint3 gl_GlobalInvocationID_in : SV_DispatchThreadID) {\n    \
+                            gl_GlobalInvocationID = gl_GlobalInvocationID_in;\n    \
+                            main_inner();\n}\n"));
+}
+
+#[test]
+fn generate_hlsl_control_attributes() {
+    use spv::logical::*;
+    use backend::generate_hlsl;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let mut module = promote_variables(validate(raw_module).unwrap()).unwrap();
+    for block in &mut module.function_definitions[0].blocks {
+        if let Some(GroupMerge::OpLoopMerge(ref mut op)) = block.merge {
+            op.loop_control.dont_unroll = true;
+        }
+    }
+    let hlsl = generate_hlsl(&module, "main").unwrap();
+    assert!(hlsl.contains("    [loop]\n    while (true) {\n"));
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    for block in &mut module.function_definitions[0].blocks {
+        if let Some(GroupMerge::OpSelectionMerge(ref mut op)) = block.merge {
+            op.selection_control.dont_flatten = true;
+        }
+    }
+    let hlsl = generate_hlsl(&module, "main").unwrap();
+    assert!(hlsl.contains("    [branch]\n    if (_18) {\n"));
+    assert!(hlsl.contains("        _27 = sin(_26);\n"));
+}
+
+#[test]
+fn generate_msl_globals() {
+    use spv::logical::*;
+    use std::collections::HashMap;
+    use backend::{generate_msl, MslArgument, ResourceBinding};
+
+    let mut builder = Builder::new(AddressingModel::Logical, MemoryModel::Glsl450);
+    builder.capability(Capability::Shader);
+    let void = builder.type_void();
+    let main_type = builder.type_function(void.clone(), vec![]);
+    let uint = builder.type_int(32, false);
+    let uvec3 = builder.type_vector(uint.clone(), 3);
+    let store_type = builder.type_function(void.clone(), vec![uint.clone()]);
+    let values = builder.type_runtime_array(uint.clone());
+    let data_type = builder.type_struct(vec![values]);
+    builder.name(data_type.clone(), "Data");
+    builder.member_name(data_type.clone(), 0, "values");
+    builder.decorate(data_type.clone(), Decoration::BufferBlock);
+    builder.member_decorate(data_type.clone(), 0, Decoration::Offset(0));
+    let data_ptr = builder.type_pointer(StorageClass::Uniform, data_type);
+    let uniform_uint_ptr = builder.type_pointer(StorageClass::Uniform, uint.clone());
+    let input_ptr = builder.type_pointer(StorageClass::Input, uvec3);
+    let input_uint_ptr = builder.type_pointer(StorageClass::Input, uint.clone());
+    let workgroup_ptr = builder.type_pointer(StorageClass::Workgroup, uint.clone());
+    let zero = builder.constant_u32(0);
+    let seven = builder.constant_u32(7);
+    let data = builder.variable(data_ptr, StorageClass::Uniform, None);
+    builder.name(data.clone(), "data");
+    builder.decorate(data.clone(), Decoration::DescriptorSet(1));
+    builder.decorate(data.clone(), Decoration::Binding(3));
+    let gid = builder.variable(input_ptr, StorageClass::Input, None);
+    builder.decorate(gid.clone(), Decoration::BuiltIn(BuiltIn::GlobalInvocationId));
+    let shared = builder.variable(workgroup_ptr, StorageClass::Workgroup, None);
+    builder.name(shared.clone(), "shared_value");
+
+    let store = builder.begin_function(void.clone(), store_type, FunctionControl::default());
+    builder.name(store.clone(), "store");
+    let index = builder.function_parameter(uint.clone());
+    builder.name(index.clone(), "index");
+    builder.new_block();
+    let value = builder.load(uint.clone(), shared.clone());
+    let element = builder.access_chain(uniform_uint_ptr, data, vec![zero.clone(), index]);
+    builder.store(element, value);
+    builder.ret();
+    builder.end_function();
+
+    let main = builder.begin_function(void.clone(), main_type, FunctionControl::default());
+    builder.entry_point(ExecutionModel::GlCompute, main.clone(), "main", vec![gid.clone()]);
+    builder.new_block();
+    builder.store(shared, seven);
+    let x = builder.access_chain(input_uint_ptr, gid, vec![zero]);
+    let x = builder.load(uint, x);
+    builder.function_call(void, store, vec![x]);
+    builder.ret();
+    builder.end_function();
+    let module = builder.finish();
+
+    let mut bindings = HashMap::new();
+    bindings.insert(ResourceBinding { set: 1, binding: 3 }, MslArgument::Buffer(2));
+    assert_eq!(generate_msl(&module, "main", &bindings).unwrap(),
+               "#include <metal_stdlib>\n\
+                #include <simd/simd.h>\n\
+                \n\
+                using namespace metal;\n\
+                \n\
+                struct Data {\n    \
+                    uint values[1];\n\
+                };\n\
+                \n\
+                void store(uint index, device Data &data, threadgroup uint &shared_value) {\n    \
+                    uint _21;\n    \
+                    _21 = shared_value;\n    \
+                    data.values[index] = _21;\n    \
+                    return;\n\
+                }\n\
+                \n\
+                kernel void main0(device Data &data [[buffer(2)]], \
+                                  uint3 gl_GlobalInvocationID [[thread_position_in_grid]]) {\n    \
+                    threadgroup uint shared_value;\n    \
+                    uint _26;\n    \
+                    shared_value = 7u;\n    \
+                    _26 = gl_GlobalInvocationID.x;\n    \
+                    store(_26, data, shared_value);\n    \
+                    return;\n\
+                }\n");
+}
+
+
+#[test]
+fn generate_float_equality_nan() {
+    use spv::logical::*;
+    use std::collections::HashMap;
+    use backend::{generate_glsl, generate_hlsl, generate_msl, generate_wgsl};
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    module.globals.push(GroupGlobal::GroupType(GroupType::OpTypeVector(OpTypeVector {
+        result_id: ResultId(62),
+        component_type: OpId(17),
+        component_count: 3,
+    })));
+    macro_rules! compare {
+        ($name: ident, $ty: expr, $id: expr, $a: expr, $b: expr) => {
+            GroupCode::$name($name {
+                result_type: OpId($ty),
+                result_id: ResultId($id),
+                operand1: OpId($a),
+                operand2: OpId($b),
+            })
+        }
+    }
+    let undef = |id| {
+        GroupCode::OpUndef(OpUndef {
+            result_type: OpId(46),
+            result_id: ResultId(id),
+        })
+    };
+    let code = vec![compare!(OpFOrdNotEqual, 17, 60, 26, 27),
+                    compare!(OpFUnordEqual, 17, 61, 26, 27),
+                    undef(63),
+                    undef(64),
+                    compare!(OpFOrdNotEqual, 62, 65, 63, 64),
+                    compare!(OpFUnordEqual, 62, 66, 63, 64)];
+    module.function_definitions[0].blocks[1].code.extend(code);
+
+    let glsl = generate_glsl(&module, "main").unwrap();
+    assert!(glsl.contains("_60 = !isnan(_26) && !isnan(_27) && _26 != _27;"));
+    assert!(glsl.contains("_61 = isnan(_26) || isnan(_27) || _26 == _27;"));
+    assert!(glsl.contains("_65 = not(mix(mix(equal(_63, _64), isnan(_64), isnan(_64)), \
+                           isnan(_63), isnan(_63)));"));
+    assert!(glsl.contains("_66 = mix(mix(equal(_63, _64), isnan(_64), isnan(_64)), \
+                           isnan(_63), isnan(_63));"));
+
+    let hlsl = generate_hlsl(&module, "main").unwrap();
+    let msl = generate_msl(&module, "main", &HashMap::new()).unwrap();
+    for code in &[hlsl, msl] {
+        assert!(code.contains("_60 = !isnan(_26) && !isnan(_27) && _26 != _27;"));
+        assert!(code.contains("_61 = isnan(_26) || isnan(_27) || _26 == _27;"));
+        assert!(code.contains("_65 = !isnan(_63) && !isnan(_64) && _63 != _64;"));
+        assert!(code.contains("_66 = isnan(_63) || isnan(_64) || _63 == _64;"));
+    }
+
+    let wgsl = generate_wgsl(&module, "main").unwrap();
+    assert!(wgsl.contains("_60 = _26 == _26 && _27 == _27 && _26 != _27;"));
+    assert!(wgsl.contains("_61 = _26 != _26 || _27 != _27 || _26 == _27;"));
+    assert!(wgsl.contains("_65 = (_63 == _63) & (_64 == _64) & (_63 != _64);"));
+    assert!(wgsl.contains("_66 = (_63 != _63) | (_64 != _64) | (_63 == _64);"));
+}
+
+#[test]
+fn generate_signed_remainder() {
+    use spv::logical::*;
+    use backend::{generate_glsl, generate_hlsl, generate_wgsl};
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    let undef = |id| {
+        GroupCode::OpUndef(OpUndef {
+            result_type: OpId(37),
+            result_id: ResultId(id),
+        })
+    };
+    let code = vec![undef(60),
+                    undef(61),
+                    GroupCode::OpSRem(OpSRem {
+                        result_type: OpId(37),
+                        result_id: ResultId(62),
+                        operand1: OpId(60),
+                        operand2: OpId(61),
+                    }),
+                    GroupCode::OpSMod(OpSMod {
+                        result_type: OpId(37),
+                        result_id: ResultId(63),
+                        operand1: OpId(60),
+                        operand2: OpId(61),
+                    })];
+    module.function_definitions[0].blocks[1].code.extend(code);
+
+    // % in GLSL is undefined for negative operands
+    let glsl = generate_glsl(&module, "main").unwrap();
+    assert!(glsl.contains("_62 = _60 - _61 * (_60 / _61);"));
+    assert!(glsl.contains("_63 = ((_60 - _61 * (_60 / _61)) + _61) - \
+                           _61 * (((_60 - _61 * (_60 / _61)) + _61) / _61);"));
+
+    let hlsl = generate_hlsl(&module, "main").unwrap();
+    let wgsl = generate_wgsl(&module, "main").unwrap();
+    for code in &[hlsl, wgsl] {
+        assert!(code.contains("_62 = _60 % _61;"));
+        assert!(code.contains("_63 = ((_60 % _61) + _61) % _61;"));
+    }
+}
+
+#[test]
+fn generate_wgsl_write_multiply() {
+    use spv::logical::*;
+    use backend::generate_wgsl;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let wgsl = generate_wgsl(&module, "main").unwrap();
+    assert!(wgsl.contains("var<private> gl_GlobalInvocationID: vec3<u32>;\n\
+                           @group(0) @binding(2) var<storage, read_write> _25: MyBuffer;\n"));
+    assert!(wgsl.contains("fn main_inner() {\n    var write_value: f32;\n"));
+    assert!(wgsl.ends_with("@compute @workgroup_size(32, 32, 32)\n\
+                            fn main(@builtin(global_invocation_id) \
+                            gl_GlobalInvocationID_in: vec3<u32>) {\n    \
+                            gl_GlobalInvocationID = gl_GlobalInvocationID_in;\n    \
+                            main_inner();\n}\n"));
+}
+
+#[test]
+fn generate_wgsl_loop_continuing() {
+    use spv::logical::*;
+    use backend::generate_wgsl;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let module = promote_variables(validate(raw_module).unwrap()).unwrap();
+    let wgsl = generate_wgsl(&module, "main").unwrap();
+    assert!(wgsl.contains("    loop {\n        \
+                               _52 = _52_phi;\n        \
+                               _53 = _53_phi;\n        \
+                               _23 = gl_GlobalInvocationID.y;\n        \
+                               _25 = _53 < _23;\n        \
+                               if (!(_25)) {\n            \
+                                   break;\n        \
+                               }\n        \
+                               _28 = gl_GlobalInvocationID.z;\n        \
+                               _30 = _52 * _28;\n        \
+                               continuing {\n            \
+                                   _34 = _53 + u32(1i);\n            \
+                                   _52_phi = _30;\n            \
+                                   _53_phi = _34;\n        \
+                               }\n    \
+                           }\n"));
+}
+
+#[test]
+fn generate_wgsl_64_bit() {
+    use spv::logical::*;
+    use backend::{generate_wgsl, BackendError, Type};
+
+    let mut builder = Builder::new(AddressingModel::Logical, MemoryModel::Glsl450);
+    builder.capability(Capability::Shader);
+    builder.capability(Capability::Int64);
+    let void = builder.type_void();
+    let main_type = builder.type_function(void.clone(), vec![]);
+    let ulong = builder.type_int(64, false);
+    let private_ptr = builder.type_pointer(StorageClass::Private, ulong.clone());
+    let counter = builder.variable(private_ptr, StorageClass::Private, None);
+    let main = builder.begin_function(void, main_type, FunctionControl::default());
+    builder.entry_point(ExecutionModel::GlCompute, main.clone(), "main", vec![]);
+    builder.execution_mode(main, ExecutionMode::LocalSize(1, 1, 1));
+    builder.new_block();
+    builder.load(ulong, counter);
+    builder.ret();
+    builder.end_function();
+    let module = builder.finish();
+    match generate_wgsl(&module, "main") {
+        Err(BackendError::UnrepresentableType(Type::Int(64, false))) => {}
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn interpret_write_multiply() {
+    use spv::logical::*;
+    use byteorder::{LittleEndian, ByteOrder};
+    use backend::ResourceBinding;
+    use interpreter::Interpreter;
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let mut interpreter = Interpreter::new(&module, "main").unwrap();
+    assert_eq!(interpreter.local_size(), (32, 32, 32));
+    let binding = ResourceBinding { set: 0, binding: 2 };
+    interpreter.bind_buffer(binding, vec![0; 32 * 4]);
+    interpreter.dispatch(1, 1, 1).unwrap();
+    // The last invocation to write each element has y and z of 31
+    let buffer = interpreter.buffer(binding).unwrap();
+    for index in 0..32 {
+        assert_eq!(LittleEndian::read_f32(&buffer[index * 4..]), 961.0);
+    }
+}
+
+#[test]
+fn interpret_loop_simple() {
+    use spv::logical::*;
+    use byteorder::{LittleEndian, ByteOrder};
+    use backend::ResourceBinding;
+    use interpreter::{Interpreter, InterpretError};
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let mut module = promote_variables(validate(raw_module).unwrap()).unwrap();
+    // A smaller workgroup keeps the number of loop iterations down
+    module.annotations.retain(|op| match *op {
+        GroupAnnotation::OpDecorate(ref op) => {
+            op.decoration != Decoration::BuiltIn(BuiltIn::WorkgroupSize)
+        }
+        _ => true,
+    });
+    module.execution_modes[0].mode = ExecutionMode::LocalSize(32, 4, 4);
+    let mut interpreter = Interpreter::new(&module, "main").unwrap();
+    let binding = ResourceBinding { set: 0, binding: 2 };
+    match interpreter.dispatch(1, 1, 1) {
+        Err(InterpretError::UnboundResource(unbound)) => assert_eq!(unbound, binding),
+        result => panic!("{:?}", result),
+    }
+    interpreter.bind_buffer(binding, vec![0; 32 * 4]);
+    interpreter.dispatch(1, 1, 1).unwrap();
+    // Each element is z to the power of y, from the last invocation to write it,
+    // which has y and z of 3
+    let buffer = interpreter.buffer(binding).unwrap();
+    for index in 0..32 {
+        assert_eq!(LittleEndian::read_u32(&buffer[index * 4..]), 27);
+    }
+}
+
+#[test]
+fn interpret_debugger() {
+    use spv::logical::*;
+    use spv::types::OpId;
+    use backend::ResourceBinding;
+    use interpreter::{Interpreter, Value, Debugger, Breakpoint, TraceEvent};
+
+    let raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let mut interpreter = Interpreter::new(&module, "main").unwrap();
+    interpreter.bind_buffer(ResourceBinding { set: 0, binding: 2 }, vec![0; 32 * 4]);
+    let breakpoints = vec![Breakpoint::Block(OpId(5)), Breakpoint::Result(OpId(31))];
+    let mut debugger = Debugger::new([3, 2, 5], breakpoints);
+    interpreter.dispatch_observed(1, 1, 1, &mut debugger).unwrap();
+
+    let stops = debugger.stops();
+    assert_eq!(stops.len(), 2);
+    assert_eq!(stops[0].breakpoint, Breakpoint::Block(OpId(5)));
+    // The variable is not declared until the first instruction of the block
+    assert_eq!(stops[0].value("write_value"), None);
+    assert_eq!(stops[1].breakpoint, Breakpoint::Result(OpId(31)));
+    assert_eq!(stops[1].value("write_value"), Some(&Value::Float(10.0, 32)));
+    let id = Value::Composite(vec![Value::Int(3, 32), Value::Int(2, 32), Value::Int(5, 32)]);
+    assert_eq!(stops[1].value("gl_GlobalInvocationID"), Some(&id));
+
+    let events = debugger.events();
+    assert_eq!(events[0], TraceEvent::Block(None, OpId(5)));
+    assert_eq!(events.len(), 14);
+    let steps = events[stops[1].event..]
+        .iter()
+        .map(|event| event.to_string().trim().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(steps,
+               vec!["%31 = OpLoad %6 %8 ; 10.0",
+                    "%33 = OpAccessChain %32 %25 %27 %30 ; pointer",
+                    "OpStore %33 %31"]);
+}
+
+#[test]
+fn interpret_coverage() {
+    use spv::logical::*;
+    use spv::types::OpId;
+    use backend::ResourceBinding;
+    use interpreter::{Interpreter, Coverage, Arm};
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    // Every invocation of a single row takes the true arm of the selection
+    module.annotations.retain(|op| match *op {
+        GroupAnnotation::OpDecorate(ref op) => {
+            op.decoration != Decoration::BuiltIn(BuiltIn::WorkgroupSize)
+        }
+        _ => true,
+    });
+    module.execution_modes[0].mode = ExecutionMode::LocalSize(32, 1, 1);
+    let mut interpreter = Interpreter::new(&module, "main").unwrap();
+    interpreter.bind_buffer(ResourceBinding { set: 0, binding: 2 }, vec![0; 64 * 4]);
+    let mut coverage = Coverage::new();
+    interpreter.dispatch_observed(2, 1, 1, &mut coverage).unwrap();
+
+    let report = coverage.report(&module).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].blocks,
+               vec![(OpId(5), 64), (OpId(19), 64), (OpId(28), 0), (OpId(20), 64)]);
+    assert_eq!(report[0].branches, vec![(OpId(5), 64, 0)]);
+    assert_eq!(report[0].untaken_arms, vec![Arm::False(OpId(5))]);
+    assert_eq!(report[0].to_string(),
+               "%4: 3/4 blocks, 1/2 branch directions\n    never took false arm of %5\n");
+}
+
+#[test]
+fn interpret_coverage_lcov() {
+    use spv::logical::*;
+    use backend::ResourceBinding;
+    use interpreter::{Interpreter, Coverage};
+
+    let raw_module = read(include_bytes!("cond_trig_lines.spv")).unwrap();
+    let mut module = validate(raw_module).unwrap();
+    module.annotations.retain(|op| match *op {
+        GroupAnnotation::OpDecorate(ref op) => {
+            op.decoration != Decoration::BuiltIn(BuiltIn::WorkgroupSize)
+        }
+        _ => true,
+    });
+    module.execution_modes[0].mode = ExecutionMode::LocalSize(32, 1, 1);
+    let mut interpreter = Interpreter::new(&module, "main").unwrap();
+    interpreter.bind_buffer(ResourceBinding { set: 0, binding: 2 }, vec![0; 64 * 4]);
+    let mut coverage = Coverage::new();
+    interpreter.dispatch_observed(2, 1, 1, &mut coverage).unwrap();
+
+    assert_eq!(coverage.lcov(&module),
+               "TN:\nSF:cond_trig.comp\nFN:8,main\nFNDA:64,main\nFNF:1\nFNH:1\n\
+                BRDA:8,5,0,64\nBRDA:8,5,1,0\nBRF:2\nBRH:1\n\
+                DA:8,64\nDA:9,64\nDA:11,0\nDA:13,64\nLF:4\nLH:3\nend_of_record\n");
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    assert_eq!(Coverage::new().lcov(&module), "");
+}
+
+#[test]
+fn compact_ids_undefined() {
+    use spv::operands::Operands;
+    let mut raw_module = read(include_bytes!("noop.spv")).unwrap();
+    for inst in &mut raw_module.instructions {
+        if let Some(result_id) = inst.result_id_mut() {
+            result_id.0 = result_id.0 * 3 + 7;
+        }
+        if let Some(result_type) = inst.result_type_mut() {
+            result_type.0 = result_type.0 * 3 + 7;
+        }
+        for operand in inst.operands_mut() {
+            operand.0 = operand.0 * 3 + 7;
+        }
+    }
+    // %11 sits between the defined ids %10 and %13 but is never defined
+    raw_module.instructions.insert(0, Core::OpName(OpName {
+        target: OpId(11),
+        name: "undefined".into(),
+    }));
+    raw_module.bound = 38;
+
+    let compacted = compact_ids(raw_module, IdOrder::Preserve);
+    assert_eq!(compacted.bound, 11);
+    assert_eq!(compacted.instructions[0], Core::OpName(OpName {
+        target: OpId(10),
+        name: "undefined".into(),
+    }));
+    // The defined ids keep their relative order
+    let defined = compacted.instructions
+        .iter()
+        .filter_map(|inst| inst.result_id())
+        .map(|id| id.0)
+        .collect::<Vec<_>>();
+    assert_eq!(defined, vec![1, 2, 3, 6, 7, 8, 9, 4, 5]);
+}
+
+#[test]
+fn diff_write_multiply() {
+    let old = read(include_bytes!("write_multiply.spv")).unwrap();
+    let mut new = compact_ids(old.clone(), IdOrder::FirstDefinition);
+    for inst in &mut new.instructions {
+        let replacement = match *inst {
+            Core::OpIMul(ref op) => {
+                Core::OpIAdd(OpIAdd {
+                    result_type: op.result_type.clone(),
+                    result_id: op.result_id.clone(),
+                    operand1: op.operand1.clone(),
+                    operand2: op.operand2.clone(),
+                })
+            }
+            Core::OpDecorate(ref op) if op.decoration == Decoration::Binding(2) => {
+                Core::OpDecorate(OpDecorate {
+                    target: op.target.clone(),
+                    decoration: Decoration::Binding(3),
+                })
+            }
+            _ => continue,
+        };
+        *inst = replacement;
+    }
+
+    // Ids of the old module are printed as their matching ids in the new module
+    let diff = diff_modules(&old, &new);
+    assert_eq!(diff.sections.len(), 2);
+    assert_eq!(diff.to_string(),
+               "@@ module @@\n\
+                -               OpDecorate %16 Binding 2\n\
+                +               OpDecorate %16 Binding 3\n\
+                @@ function main @@\n\
+                -         %34 = OpIMul %6 %31 %33\n\
+                +         %34 = OpIAdd %6 %31 %33\n");
+}
+
+#[test]
+fn diff_many_edits() {
+    let new = read(include_bytes!("write_multiply.spv")).unwrap();
+    let mut old = new.clone();
+    let multiply = old.instructions
+        .iter()
+        .position(|inst| match *inst {
+            Core::OpIMul(_) => true,
+            _ => false,
+        })
+        .unwrap();
+    for _ in 0..2000 {
+        old.instructions.insert(multiply, Core::OpNop(OpNop));
+    }
+
+    // Past the limit on edits the nops are still reported one by one
+    let diff = diff_modules(&old, &new);
+    assert_eq!(diff.sections.len(), 1);
+    assert_eq!(diff.sections[0].changes.len(), 2000);
+    for change in &diff.sections[0].changes {
+        assert_eq!(*change, Change::Removed(Core::OpNop(OpNop)));
+    }
+}
+
+#[test]
+fn disassemble_friendly_names() {
+    let mut raw_module = read(include_bytes!("write_multiply.spv")).unwrap();
+    for inst in &mut raw_module.instructions {
+        if let Core::OpName(ref mut op) = *inst {
+            if op.name == "test" {
+                op.name = "MyBuffer".into();
+            } else if op.name == "Block" {
+                op.name = "%8 \"Block\"".into();
+            }
+        }
+    }
+    let lines = format!("{}", raw_module).lines().map(String::from).collect::<Vec<_>>();
+    assert_eq!(lines[17], "               OpName %37 \"%8 \\\"Block\\\"\"");
+    let disassembly = format!("{}", Friendly(&raw_module));
+    let lines = disassembly.lines().collect::<Vec<_>>();
+    assert_eq!(lines[8],
+               "               OpEntryPoint GLCompute %main \"main\" %gl_GlobalInvocationID");
+    assert_eq!(lines[16], "               OpName %25 \"\"");
+    assert_eq!(lines[17], "               OpName %_8__Block_ \"%8 \\\"Block\\\"\"");
+    assert_eq!(lines[18], "               OpMemberName %_8__Block_ 0 \"value\"");
+    assert_eq!(lines[19], "               OpName %MyBuffer_0 \"MyBuffer\"");
+    assert_eq!(lines[22], "               OpMemberDecorate %MyBuffer 0 Offset 0 ; value");
+    assert_eq!(lines[35], "     %v3uint = OpTypeVector %uint 3");
+    assert_eq!(lines[38], "     %uint_1 = OpConstant %uint 1");
+    assert_eq!(lines[46], "      %int_0 = OpConstant %int 0");
+    assert_eq!(lines[62], "         %20 = OpIMul %uint %16 %19");
+
+    let names = FriendlyNames::new(&raw_module);
+    assert_eq!(names.instruction(&raw_module.instructions[17]),
+               "               OpMemberDecorate %MyBuffer 0 Offset 0 ; value");
+}
+
+#[test]
+fn disassemble_options() {
+    const SPV: &[u8] = include_bytes!("write_multiply.spv");
+    let raw_module = read(SPV).unwrap();
+    let dis = |binary: Option<&[u8]>, options: DisassemblyOptions| {
+        let mut out = Vec::new();
+        disassemble(&raw_module, binary, &options, &mut out)
+            .map(|_| String::from_utf8(out).unwrap())
+    };
+
+    let options = DisassemblyOptions { friendly_names: true, ..Default::default() };
+    assert_eq!(dis(None, options).unwrap(), format!("{}", Friendly(&raw_module)));
+
+    let options = DisassemblyOptions { header: false, align_ids: false, ..Default::default() };
+    let text = dis(None, options).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "OpCapability Shader");
+    assert_eq!(lines[1], "%1 = OpExtInstImport \"GLSL.std.450\"");
+
+    let options = DisassemblyOptions { byte_offsets: true, raw_words: true, ..Default::default() };
+    match dis(None, options) {
+        Err(DisassemblyError::MissingBinary) => {}
+        result => panic!("{:?}", result),
+    }
+    match dis(Some(&SPV[..SPV.len() - 4]), options) {
+        Err(DisassemblyError::BinaryMismatch) => {}
+        result => panic!("{:?}", result),
+    }
+    let text = dis(Some(SPV), options).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[5], "               OpCapability Shader ; 0x00000014 ; 00020011 00000001");
+
+    let options = DisassemblyOptions { color: true, ..Default::default() };
+    let text = dis(None, options).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[38],
+               "         \x1b[33m%13\x1b[0m = \x1b[34mOpConstant\x1b[0m \x1b[33m%9\x1b[0m \
+                \x1b[32m1\x1b[0m");
+}
+
+#[test]
+fn generate_pseudo_code_loop_simple() {
+    use spv::logical::*;
+    use backend::generate_pseudo_code;
+
+    let raw_module = read(include_bytes!("loop_simple.spv")).unwrap();
+    let module = validate(raw_module).unwrap();
+    let code = generate_pseudo_code(&module, "main").unwrap();
+    assert_eq!(code,
+               "void main() {\n    \
+                    uint write_value;\n    \
+                    uint i;\n    \
+                    write_value = 1;\n    \
+                    i = 0;\n    \
+                    while (i < gl_GlobalInvocationID.y) {\n        \
+                        write_value = write_value * gl_GlobalInvocationID.z;\n        \
+                        i += 1;\n    \
+                    }\n    \
+                    value[gl_GlobalInvocationID.x] = write_value;\n\
+                }\n");
+}
+
+#[test]
+fn generate_pseudo_code_nest_if() {
+    use spv::logical::*;
+    use backend::generate_pseudo_code;
+
+    let raw_module = read(include_bytes!("nest_if.spv")).unwrap();
+    let module = promote_variables(validate(raw_module).unwrap()).unwrap();
+    let code = generate_pseudo_code(&module, "main").unwrap();
+    assert!(code.contains("    if (gl_GlobalInvocationID.y % 2 == 0) {\n        \
+                           _28 = bitcast<int>(gl_GlobalInvocationID.y) - 128;\n"));
+    assert!(code.contains("        } else {\n            _77 = _44 - 1;\n        }\n"));
+    assert!(code.ends_with("    value[gl_GlobalInvocationID.x] = _76;\n}\n"));
+}
+
+#[test]
+fn generate_pseudo_code_phi_argument() {
+    use spv::logical::*;
+    use backend::generate_pseudo_code;
+
+    let mut builder = Builder::new(AddressingModel::Logical, MemoryModel::Glsl450);
+    builder.capability(Capability::Shader);
+    let void = builder.type_void();
+    let main_type = builder.type_function(void.clone(), vec![]);
+    let uint = builder.type_int(32, false);
+    let bool_type = builder.type_bool();
+    let ptr_type = builder.type_pointer(StorageClass::Private, uint.clone());
+    let zero = builder.constant_u32(0);
+    let one = builder.constant_u32(1);
+    let ten = builder.constant_u32(10);
+    let value = builder.variable(ptr_type, StorageClass::Private, None);
+    builder.name(value.clone(), "value");
+
+    let main = builder.begin_function(void.clone(), main_type, FunctionControl::default());
+    builder.entry_point(ExecutionModel::GlCompute, main, "main", vec![]);
+    let entry = builder.new_block();
+    let x = builder.load(uint.clone(), value.clone());
+    builder.name(x.clone(), "x");
+    let a = builder.iadd(uint.clone(), x.clone(), one);
+    let small = builder.uless_than(bool_type, x, ten);
+    let then = builder.id();
+    let merge = builder.id();
+    builder.selection_merge(merge.clone());
+    builder.branch_conditional(small, then.clone(), merge.clone());
+    builder.begin_block(then.clone());
+    builder.branch(merge.clone());
+    builder.begin_block(merge);
+    let result = builder.phi(uint, vec![(a, entry), (zero, then)]);
+    builder.name(result.clone(), "result");
+    builder.store(value, result);
+    builder.ret();
+    builder.end_function();
+    let module = builder.finish();
+
+    let code = generate_pseudo_code(&module, "main").unwrap();
+    assert_eq!(code,
+               "void main() {\n    \
+                    uint result;\n    \
+                    uint x = value;\n    \
+                    if (x < 10) {\n        \
+                        result = 0;\n    \
+                    } else {\n        \
+                        result = x + 1;\n    \
+                    }\n    \
+                    value = result;\n\
+                }\n");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use spv::logical::*;
+    use spv::ExtInstBox;
+
+    let raw_module = read(include_bytes!("cond_trig.spv")).unwrap();
+    let json = serde_json::to_string(&raw_module).unwrap();
+    assert!(json.contains("\"instruction\":{\"GLSL.std.450\":{\"Sin\":{\"x\":"));
+    assert_eq!(serde_json::from_str::<RawModule>(&json).unwrap(), raw_module);
+
+    let module = validate(raw_module).unwrap();
+    let json = serde_json::to_string(&module).unwrap();
+    assert_eq!(serde_json::from_str::<LogicalModule>(&json).unwrap(), module);
+
+    let chain = find_control_flow(module.function_definitions[0].clone()).unwrap();
+    let json = serde_json::to_string(&chain).unwrap();
+    assert_eq!(serde_json::from_str::<ControlFlowChain>(&json).unwrap(), chain);
+
+    assert!(serde_json::from_str::<ExtInstBox>("{\"Unknown\":{\"Sin\":{\"x\":5}}}").is_err());
+}
+
 macro_rules! def_test {
     ($name: ident) => {
         mod $name {
@@ -114,6 +1818,14 @@ macro_rules! def_test {
             use super::read;
             use spv::logical::validate;
             use spv::logical::find_control_flow;
+            use spv::logical::find_def_use;
+            use spv::logical::eliminate_dead_code;
+            use spv::logical::fold_constants;
+            use spv::logical::promote_variables;
+            use spv::logical::eliminate_common_subexpressions;
+            use spv::logical::lower_module;
+            use spv::operands::Operands;
+            use spv::raw::{compact_ids, diff_modules, IdOrder};
 
             const SPV: &'static [u8] = include_bytes!(concat!(stringify!($name), ".spv"));
             const DIS: &'static str = include_str!(concat!(stringify!($name), ".dis"));
@@ -135,6 +1847,17 @@ macro_rules! def_test {
                 module.unwrap();
             }
 
+            #[test]
+            fn lower() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module.clone()).unwrap();
+                let lowered = lower_module(module.clone());
+                assert_eq!(lowered.instructions, raw_module.instructions);
+                assert_eq!(lowered.generator, raw_module.generator);
+                assert!(lowered.bound <= raw_module.bound);
+                assert_eq!(validate(lowered).unwrap(), module);
+            }
+
             #[test]
             fn cfa() {
                 let raw_module = read(SPV).expect("Failed to load spv");
@@ -145,6 +1868,116 @@ macro_rules! def_test {
                     chain_res.unwrap();
                 }
             }
+
+            #[test]
+            fn def_use() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module).unwrap();
+                let def_use = find_def_use(&module).unwrap();
+                for func in &module.function_definitions {
+                    for block in &func.blocks {
+                        for op in &block.code {
+                            for id in op.operands() {
+                                assert!(def_use.get_definition(id).is_some());
+                            }
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn compact() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let preserved = compact_ids(raw_module.clone(), IdOrder::Preserve);
+                assert!(preserved.bound <= raw_module.bound);
+                validate(preserved).unwrap();
+
+                let compacted = compact_ids(raw_module.clone(), IdOrder::FirstDefinition);
+                let mut sparse = raw_module;
+                for inst in &mut sparse.instructions {
+                    if let Some(result_id) = inst.result_id_mut() {
+                        result_id.0 = result_id.0 * 3 + 7;
+                    }
+                    if let Some(result_type) = inst.result_type_mut() {
+                        result_type.0 = result_type.0 * 3 + 7;
+                    }
+                    for operand in inst.operands_mut() {
+                        operand.0 = operand.0 * 3 + 7;
+                    }
+                }
+                sparse.bound = sparse.bound * 3 + 7;
+                assert_eq!(compact_ids(sparse, IdOrder::FirstDefinition), compacted);
+                validate(compacted).unwrap();
+            }
+
+            #[test]
+            fn diff() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let compacted = compact_ids(raw_module.clone(), IdOrder::FirstDefinition);
+                let diff = diff_modules(&raw_module, &compacted);
+                assert!(diff.is_empty(), "{}", diff);
+            }
+
+            #[test]
+            fn dce() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module).unwrap();
+                let module = eliminate_dead_code(module).unwrap();
+                assert_eq!(module.entry_points.len(), 1);
+                assert_eq!(module.function_definitions.len(), 1);
+                let def_use = find_def_use(&module).unwrap();
+                for op in &module.annotations {
+                    for id in op.operands() {
+                        assert!(def_use.get_definition(id).is_some());
+                    }
+                }
+                for func in module.function_definitions {
+                    find_control_flow(func).unwrap();
+                }
+            }
+
+            #[test]
+            fn fold() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module).unwrap();
+                let module = fold_constants(module.clone()).unwrap();
+                assert_eq!(fold_constants(module.clone()).unwrap(), module);
+                for func in module.function_definitions {
+                    find_control_flow(func).unwrap();
+                }
+            }
+
+            #[test]
+            fn cse() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module).unwrap();
+                let module = promote_variables(module).unwrap();
+                let module = eliminate_common_subexpressions(module);
+                assert_eq!(eliminate_common_subexpressions(module.clone()), module);
+                for func in module.function_definitions {
+                    find_control_flow(func).unwrap();
+                }
+            }
+
+            #[test]
+            fn mem2reg() {
+                let raw_module = read(SPV).expect("Failed to load spv");
+                let module = validate(raw_module).unwrap();
+                let module = promote_variables(module).unwrap();
+                let def_use = find_def_use(&module).unwrap();
+                for func in &module.function_definitions {
+                    for block in &func.blocks {
+                        for op in &block.code {
+                            for id in op.operands() {
+                                assert!(def_use.get_definition(id).is_some());
+                            }
+                        }
+                    }
+                }
+                for func in module.function_definitions {
+                    find_control_flow(func).unwrap();
+                }
+            }
         }
     }
 }
@@ -152,5 +1985,7 @@ macro_rules! def_test {
 def_test!(noop);
 def_test!(write_multiply);
 def_test!(cond_trig);
+def_test!(cond_trig_lines);
 def_test!(nest_if);
 def_test!(loop_simple);
+def_test!(spec_constants);